@@ -0,0 +1,508 @@
+/// Sample rates accepted for locally encoded (wav / pcm) output.
+pub const SUPPORTED_SAMPLE_RATES: &[u32] = &[8000, 16000, 22050, 24000, 44100, 48000];
+
+/// Output container requested by the client.
+#[derive(Clone, Copy, PartialEq)]
+pub enum AudioFormat {
+    Mp3,
+    OggOpus,
+    Wav,
+    Pcm,
+}
+
+impl AudioFormat {
+    pub fn from_name(name: &str) -> Option<Self> {
+        match name.trim().to_lowercase().as_str() {
+            "mp3" => Some(Self::Mp3),
+            "ogg" | "opus" | "ogg_opus" | "ogg/opus" => Some(Self::OggOpus),
+            "wav" => Some(Self::Wav),
+            "pcm" | "raw" => Some(Self::Pcm),
+            _ => None,
+        }
+    }
+
+    pub fn name(self) -> &'static str {
+        match self {
+            Self::Mp3 => "mp3",
+            Self::OggOpus => "ogg",
+            Self::Wav => "wav",
+            Self::Pcm => "pcm",
+        }
+    }
+
+    /// Compressed formats come from the provider as-is; wav and pcm are encoded here.
+    pub fn is_compressed(self) -> bool {
+        matches!(self, Self::Mp3 | Self::OggOpus)
+    }
+
+    pub fn mime(self, encoding: Encoding, sample_rate: u32) -> String {
+        match (self, encoding) {
+            (Self::Mp3, _) => "audio/mpeg".to_string(),
+            (Self::OggOpus, _) => "audio/ogg; codecs=opus".to_string(),
+            (Self::Wav, _) => "audio/wav".to_string(),
+            (Self::Pcm, Encoding::Linear16) => format!("audio/L16; rate={}; channels=1", sample_rate),
+            (Self::Pcm, Encoding::Mulaw) => format!("audio/PCMU; rate={}; channels=1", sample_rate),
+        }
+    }
+}
+
+/// Sample encoding for wav / pcm output.
+#[derive(Clone, Copy, PartialEq)]
+pub enum Encoding {
+    /// 16-bit signed little-endian.
+    Linear16,
+    /// 8-bit G.711 μ-law, the usual telephony / IVR encoding.
+    Mulaw,
+}
+
+impl Encoding {
+    pub fn from_name(name: &str) -> Option<Self> {
+        match name.trim().to_lowercase().as_str() {
+            "linear16" | "pcm_s16le" | "s16le" => Some(Self::Linear16),
+            "mulaw" | "ulaw" | "μ-law" | "pcmu" => Some(Self::Mulaw),
+            _ => None,
+        }
+    }
+}
+
+/// Decodes 16-bit little-endian PCM bytes (a trailing odd byte is dropped).
+pub fn pcm16_from_bytes(bytes: &[u8]) -> Vec<i16> {
+    bytes
+        .chunks_exact(2)
+        .map(|pair| i16::from_le_bytes([pair[0], pair[1]]))
+        .collect()
+}
+
+/// Band-limited resampling with a Hann-windowed sinc kernel.
+///
+/// The cutoff follows the lower of the two rates so downsampling (e.g. 24 kHz
+/// to 8 kHz for telephony) does not alias.
+pub fn resample(samples: &[i16], from: u32, to: u32) -> Vec<i16> {
+    if from == to || samples.is_empty() {
+        return samples.to_vec();
+    }
+
+    const HALF_TAPS: i64 = 16;
+    let ratio = to as f64 / from as f64;
+    let cutoff = ratio.min(1.0);
+    let out_len = (samples.len() as f64 * ratio).round() as usize;
+
+    (0..out_len)
+        .map(|n| {
+            let center = n as f64 / ratio;
+            let base = center.floor() as i64;
+            let mut acc = 0.0;
+            let mut norm = 0.0;
+            for k in (base - HALF_TAPS + 1)..=(base + HALF_TAPS) {
+                if k < 0 || k as usize >= samples.len() {
+                    continue;
+                }
+                let x = center - k as f64;
+                let sinc = if x.abs() < 1e-9 {
+                    1.0
+                } else {
+                    let arg = std::f64::consts::PI * x * cutoff;
+                    arg.sin() / arg
+                };
+                let window = 0.5 + 0.5 * (std::f64::consts::PI * x / HALF_TAPS as f64).cos();
+                let weight = sinc * window;
+                acc += samples[k as usize] as f64 * weight;
+                norm += weight;
+            }
+            let value = if norm.abs() > 1e-9 { acc / norm } else { 0.0 };
+            value.round().clamp(i16::MIN as f64, i16::MAX as f64) as i16
+        })
+        .collect()
+}
+
+/// G.711 μ-law compression of one sample.
+pub fn mulaw_encode(sample: i16) -> u8 {
+    const BIAS: i32 = 0x84;
+    const CLIP: i32 = 32635;
+
+    let mut value = sample as i32;
+    let sign = if value < 0 {
+        value = -value;
+        0x80
+    } else {
+        0
+    };
+    value = value.min(CLIP) + BIAS;
+
+    let exponent = (7 - ((value >> 7) as u32 | 1).leading_zeros().saturating_sub(24)) as i32;
+    let mantissa = (value >> (exponent + 3)) & 0x0F;
+    !(sign | (exponent << 4) | mantissa) as u8
+}
+
+/// Raw sample bytes in the given encoding.
+pub fn encode_samples(samples: &[i16], encoding: Encoding) -> Vec<u8> {
+    match encoding {
+        Encoding::Linear16 => samples.iter().flat_map(|s| s.to_le_bytes()).collect(),
+        Encoding::Mulaw => samples.iter().map(|&s| mulaw_encode(s)).collect(),
+    }
+}
+
+/// Wraps mono samples in a RIFF/WAVE container (PCM 16-bit or μ-law 8-bit).
+pub fn encode_wav(samples: &[i16], sample_rate: u32, encoding: Encoding) -> Vec<u8> {
+    let mut wav = wav_header(sample_rate, encoding, Some(samples.len()));
+    wav.extend_from_slice(&encode_samples(samples, encoding));
+    wav
+}
+
+/// RIFF/WAVE header for `samples` mono samples. With `None` (streaming, length
+/// unknown) the sizes are left at their maximum, which players read as "until
+/// the end of the stream".
+pub fn wav_header(sample_rate: u32, encoding: Encoding, samples: Option<usize>) -> Vec<u8> {
+    let (format_tag, bits): (u16, u16) = match encoding {
+        Encoding::Linear16 => (1, 16),
+        Encoding::Mulaw => (7, 8),
+    };
+    let block_align = bits / 8;
+    let sample_count = samples.map_or(u32::MAX, |n| n as u32);
+    let data_len = samples.map_or(u32::MAX, |n| (n * block_align as usize) as u32);
+
+    let mut wav = Vec::with_capacity(58);
+    wav.extend_from_slice(b"RIFF");
+    wav.extend_from_slice(&0u32.to_le_bytes()); // patched below
+    wav.extend_from_slice(b"WAVE");
+
+    wav.extend_from_slice(b"fmt ");
+    if encoding == Encoding::Linear16 {
+        wav.extend_from_slice(&16u32.to_le_bytes());
+    } else {
+        // Non-PCM formats carry a cbSize field.
+        wav.extend_from_slice(&18u32.to_le_bytes());
+    }
+    wav.extend_from_slice(&format_tag.to_le_bytes());
+    wav.extend_from_slice(&1u16.to_le_bytes()); // mono
+    wav.extend_from_slice(&sample_rate.to_le_bytes());
+    wav.extend_from_slice(&(sample_rate * block_align as u32).to_le_bytes()); // byte rate
+    wav.extend_from_slice(&block_align.to_le_bytes());
+    wav.extend_from_slice(&bits.to_le_bytes());
+    if encoding != Encoding::Linear16 {
+        wav.extend_from_slice(&0u16.to_le_bytes());
+        wav.extend_from_slice(b"fact");
+        wav.extend_from_slice(&4u32.to_le_bytes());
+        wav.extend_from_slice(&sample_count.to_le_bytes());
+    }
+
+    wav.extend_from_slice(b"data");
+    wav.extend_from_slice(&data_len.to_le_bytes());
+
+    let riff_len = data_len.saturating_add((wav.len() - 8) as u32);
+    wav[4..8].copy_from_slice(&riff_len.to_le_bytes());
+    wav
+}
+
+/// Samples quieter than this count as silence when joining chunks.
+const SILENCE_THRESHOLD: i16 = 300;
+
+/// Joins separately synthesized chunks into one track.
+///
+/// Leading/trailing silence at the seams is trimmed (each provider call pads its
+/// output), the edges get a 5 ms fade to avoid clicks, and `gap_ms` of silence
+/// is inserted after every chunk but the last.
+pub fn join_pcm(parts: Vec<(Vec<i16>, u32)>, sample_rate: u32) -> Vec<i16> {
+    let count = parts.len();
+    let mut out = Vec::new();
+    for (index, (samples, gap_ms)) in parts.into_iter().enumerate() {
+        join_part(&mut out, &samples, gap_ms, index == 0, index + 1 == count, sample_rate);
+    }
+    out
+}
+
+/// Appends one chunk to a track being joined as in [`join_pcm`], for callers
+/// that emit the track piece by piece.
+pub fn join_part(out: &mut Vec<i16>, samples: &[i16], gap_ms: u32, first: bool, last: bool, sample_rate: u32) {
+    let fade = (sample_rate / 200) as usize;
+    let loud = |s: &i16| s.unsigned_abs() > SILENCE_THRESHOLD as u16;
+    let start = if first { 0 } else { samples.iter().position(loud).unwrap_or(samples.len()) };
+    let end = if last { samples.len() } else { samples.iter().rposition(loud).map_or(start, |i| i + 1) };
+    if start >= end {
+        return;
+    }
+
+    let body = &samples[start..end];
+    let len = body.len();
+    out.extend(body.iter().enumerate().map(|(i, &s)| {
+        let fade_in = if first { 1.0 } else { (i as f32 / fade as f32).min(1.0) };
+        let fade_out = if last { 1.0 } else { ((len - 1 - i) as f32 / fade as f32).min(1.0) };
+        (s as f32 * fade_in * fade_out) as i16
+    }));
+
+    if !last {
+        out.extend(std::iter::repeat_n(0, (sample_rate as u64 * gap_ms as u64 / 1000) as usize));
+    }
+}
+
+/// Drops a leading ID3v2 tag so MP3 chunks can be appended frame by frame.
+pub fn strip_id3(bytes: &[u8]) -> &[u8] {
+    if bytes.len() < 10 || &bytes[..3] != b"ID3" {
+        return bytes;
+    }
+    // Synchsafe size: 7 bits per byte.
+    let size = bytes[6..10].iter().fold(0usize, |acc, &b| (acc << 7) | (b & 0x7F) as usize);
+    let footer = if bytes[5] & 0x10 != 0 { 10 } else { 0 };
+    &bytes[(10 + size + footer).min(bytes.len())..]
+}
+
+/// Identifies an uploaded audio container from its magic bytes, returning the
+/// usual file extension.
+pub fn sniff_container(bytes: &[u8]) -> Option<&'static str> {
+    match bytes {
+        [b'R', b'I', b'F', b'F', _, _, _, _, b'W', b'A', b'V', b'E', ..] => Some("wav"),
+        [b'I', b'D', b'3', ..] => Some("mp3"),
+        [0xFF, second, ..] if second & 0xE0 == 0xE0 && second & 0x06 != 0 => Some("mp3"),
+        [b'O', b'g', b'g', b'S', ..] => Some("ogg"),
+        [b'f', b'L', b'a', b'C', ..] => Some("flac"),
+        [0x1A, 0x45, 0xDF, 0xA3, ..] => Some("webm"),
+        [_, _, _, _, b'f', b't', b'y', b'p', ..] => Some("m4a"),
+        _ => None,
+    }
+}
+
+/// G.711 μ-law expansion of one byte.
+pub fn mulaw_decode(byte: u8) -> i16 {
+    let byte = !byte;
+    let exponent = (byte >> 4) & 0x07;
+    let mantissa = (byte & 0x0F) as i32;
+    let magnitude = (((mantissa << 3) + 0x84) << exponent) - 0x84;
+    if byte & 0x80 != 0 {
+        -magnitude as i16
+    } else {
+        magnitude as i16
+    }
+}
+
+/// Decodes a WAV file (8/16-bit PCM, 32-bit float or μ-law) into mono samples
+/// and its sample rate. Returns `None` for anything else.
+pub fn decode_wav(bytes: &[u8]) -> Option<(Vec<i16>, u32)> {
+    if sniff_container(bytes) != Some("wav") {
+        return None;
+    }
+
+    let u16_at = |at: usize| Some(u16::from_le_bytes(bytes.get(at..at + 2)?.try_into().ok()?));
+    let u32_at = |at: usize| Some(u32::from_le_bytes(bytes.get(at..at + 4)?.try_into().ok()?));
+
+    let mut format = None;
+    let mut pos = 12;
+    while pos + 8 <= bytes.len() {
+        let id = &bytes[pos..pos + 4];
+        let size = u32_at(pos + 4)? as usize;
+        let body = pos + 8;
+        if id == b"fmt " {
+            let mut tag = u16_at(body)?;
+            if tag == 0xFFFE {
+                // WAVE_FORMAT_EXTENSIBLE: the real tag leads the sub-format GUID.
+                tag = u16_at(body + 24)?;
+            }
+            format = Some((tag, u16_at(body + 2)?.max(1) as usize, u32_at(body + 4)?, u16_at(body + 14)?));
+        } else if id == b"data" {
+            let (tag, channels, rate, bits) = format?;
+            // Streamed WAVs may leave the data size unset.
+            let data = &bytes[body..(body.saturating_add(size)).min(bytes.len())];
+            let mono: Vec<i16> = match (tag, bits) {
+                (1, 16) => mix_down(&pcm16_from_bytes(data), channels),
+                (1, 8) => mix_down(&data.iter().map(|&b| ((b as i16) - 128) << 8).collect::<Vec<_>>(), channels),
+                (3, 32) => {
+                    let floats: Vec<i16> = data
+                        .chunks_exact(4)
+                        .map(|c| (f32::from_le_bytes([c[0], c[1], c[2], c[3]]).clamp(-1.0, 1.0) * 32767.0) as i16)
+                        .collect();
+                    mix_down(&floats, channels)
+                }
+                (7, 8) => mix_down(&data.iter().map(|&b| mulaw_decode(b)).collect::<Vec<_>>(), channels),
+                _ => return None,
+            };
+            return Some((mono, rate));
+        }
+        // Chunks are padded to an even length.
+        pos = body.checked_add(size + (size & 1))?;
+    }
+    None
+}
+
+fn mix_down(samples: &[i16], channels: usize) -> Vec<i16> {
+    if channels == 1 {
+        return samples.to_vec();
+    }
+    samples
+        .chunks_exact(channels)
+        .map(|frame| (frame.iter().map(|&s| s as i32).sum::<i32>() / channels as i32) as i16)
+        .collect()
+}
+
+/// Energy-based voice activity detection.
+///
+/// Returns `(start, end)` spans in seconds. Pauses shorter than 300 ms are bridged,
+/// blips under 150 ms dropped, and spans longer than `max_span` seconds split so
+/// each one fits a caption.
+pub fn speech_spans(samples: &[i16], sample_rate: u32, max_span: f64) -> Vec<(f64, f64)> {
+    const FRAME_MS: usize = 20;
+    let frame_len = (sample_rate as usize * FRAME_MS / 1000).max(1);
+    let frame_secs = FRAME_MS as f64 / 1000.0;
+
+    let energies: Vec<f64> = samples
+        .chunks(frame_len)
+        .map(|frame| (frame.iter().map(|&s| (s as f64).powi(2)).sum::<f64>() / frame.len() as f64).sqrt())
+        .collect();
+    if energies.is_empty() {
+        return Vec::new();
+    }
+
+    // Threshold relative to the quieter frames so steady background noise is ignored.
+    let mut sorted = energies.clone();
+    sorted.sort_by(f64::total_cmp);
+    let noise_floor = sorted[sorted.len() / 10];
+    let threshold = (noise_floor * 3.0).max(SILENCE_THRESHOLD as f64);
+
+    let mut spans: Vec<(f64, f64)> = Vec::new();
+    for (i, &energy) in energies.iter().enumerate() {
+        if energy < threshold {
+            continue;
+        }
+        let (start, end) = (i as f64 * frame_secs, (i + 1) as f64 * frame_secs);
+        match spans.last_mut() {
+            Some(last) if start - last.1 < 0.3 => last.1 = end,
+            _ => spans.push((start, end)),
+        }
+    }
+
+    let duration = samples.len() as f64 / sample_rate as f64;
+    spans
+        .into_iter()
+        .filter(|(start, end)| end - start >= 0.15)
+        .flat_map(|(start, end)| {
+            let end = end.min(duration);
+            let pieces = ((end - start) / max_span).ceil().max(1.0) as usize;
+            let step = (end - start) / pieces as f64;
+            (0..pieces).map(move |i| (start + step * i as f64, start + step * (i + 1) as f64))
+        })
+        .collect()
+}
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+
+    fn u32_at(bytes: &[u8], at: usize) -> u32 {
+        u32::from_le_bytes(bytes[at..at + 4].try_into().unwrap())
+    }
+
+    #[test]
+    fn wav_headers_carry_the_data_length() {
+        let samples: Vec<i16> = (0..100).map(|i| (i * 300 - 15_000) as i16).collect();
+
+        let pcm = encode_wav(&samples, 24_000, Encoding::Linear16);
+        assert_eq!(pcm.len(), 44 + 200);
+        assert_eq!(u32_at(&pcm, 4), pcm.len() as u32 - 8);
+        assert_eq!(u32_at(&pcm, 24), 24_000);
+        assert_eq!(u32_at(&pcm, 28), 48_000); // byte rate
+        assert_eq!(&pcm[36..40], b"data");
+        assert_eq!(u32_at(&pcm, 40), 200);
+        assert_eq!(decode_wav(&pcm), Some((samples.clone(), 24_000)));
+
+        let ulaw = encode_wav(&samples, 8000, Encoding::Mulaw);
+        assert_eq!(ulaw.len(), 58 + 100);
+        assert_eq!(u32_at(&ulaw, 4), ulaw.len() as u32 - 8);
+        assert_eq!(&ulaw[38..42], b"fact");
+        assert_eq!(u32_at(&ulaw, 46), 100);
+        assert_eq!(u32_at(&ulaw, 54), 100);
+        let (decoded, rate) = decode_wav(&ulaw).unwrap();
+        assert_eq!((decoded.len(), rate), (100, 8000));
+    }
+
+    #[test]
+    fn streaming_wav_header_is_open_ended() {
+        let header = wav_header(16_000, Encoding::Linear16, None);
+        assert_eq!(header.len(), 44);
+        assert_eq!(u32_at(&header, 4), u32::MAX);
+        assert_eq!(u32_at(&header, 40), u32::MAX);
+
+        let mut stream = header;
+        stream.extend_from_slice(&encode_samples(&[1, -2, 3], Encoding::Linear16));
+        assert_eq!(decode_wav(&stream), Some((vec![1, -2, 3], 16_000)));
+    }
+
+    #[test]
+    fn mulaw_matches_g711() {
+        assert_eq!(mulaw_encode(0), 0xFF);
+        assert_eq!(mulaw_encode(-1), 0x7F);
+        assert_eq!(mulaw_encode(i16::MAX), 0x80);
+        assert_eq!(mulaw_encode(i16::MIN), 0x00);
+        assert_eq!(mulaw_decode(0xFF), 0);
+        assert_eq!(mulaw_decode(0x80), 32_124);
+        assert_eq!(mulaw_decode(0x00), -32_124);
+
+        // The quantization step grows with the magnitude: about 1/16 of it.
+        for s in (-32_000..=32_000).step_by(37) {
+            let error = (mulaw_decode(mulaw_encode(s as i16)) as i32 - s).abs();
+            assert!(error * 16 <= s.abs() + 132 + 16, "{} decoded {} off", s, error);
+        }
+    }
+
+    #[test]
+    fn resample_scales_the_length_by_the_rate_ratio() {
+        let tone: Vec<i16> = (0..2400).map(|i| ((i as f64 * 0.05).sin() * 10_000.0) as i16).collect();
+        assert_eq!(resample(&tone, 24_000, 24_000), tone);
+        assert_eq!(resample(&tone, 24_000, 8000).len(), 800);
+        assert_eq!(resample(&tone, 24_000, 48_000).len(), 4800);
+        assert_eq!(resample(&tone, 24_000, 22_050).len(), 2205);
+        assert!(resample(&[], 24_000, 8000).is_empty());
+
+        // The kernel is normalized, so a constant level survives either way.
+        let level = vec![1000i16; 480];
+        assert!(resample(&level, 24_000, 8000).iter().all(|&s| s == 1000));
+        assert!(resample(&level, 8000, 44_100).iter().all(|&s| s == 1000));
+    }
+
+    #[test]
+    fn join_trims_seams_and_inserts_gaps() {
+        let loud = |n: usize| vec![8000i16; n];
+        let padded = |n: usize| [vec![0; 50], loud(n), vec![0; 50]].concat();
+        let parts = vec![(padded(400), 100), (padded(400), 50), (padded(400), 0)];
+
+        let joined = join_pcm(parts.clone(), 1000);
+        // First keeps its lead-in, last its tail; the seams lose their padding.
+        assert_eq!(joined.len(), (450 + 100) + (400 + 50) + 450);
+        assert_eq!(joined[449], 0); // faded out at the seam
+        assert!(joined[450..550].iter().all(|&s| s == 0));
+
+        let mut streamed = Vec::new();
+        for (index, (samples, gap)) in parts.iter().enumerate() {
+            join_part(&mut streamed, samples, *gap, index == 0, index == 2, 1000);
+        }
+        assert_eq!(streamed, joined);
+    }
+
+    fn assert_spans(spans: &[(f64, f64)], expected: &[(f64, f64)]) {
+        assert_eq!(spans.len(), expected.len(), "{:?}", spans);
+        for (span, want) in spans.iter().zip(expected) {
+            assert!((span.0 - want.0).abs() < 1e-9 && (span.1 - want.1).abs() < 1e-9, "{:?}", spans);
+        }
+    }
+
+    #[test]
+    fn speech_spans_bridge_pauses_drop_blips_and_split_long_speech() {
+        let rate = 16_000;
+        let silence = |secs: f64| vec![0i16; (secs * rate as f64) as usize];
+        let tone = |secs: f64| -> Vec<i16> {
+            (0..(secs * rate as f64) as usize).map(|i| ((i as f64 * 0.2).sin() * 5000.0) as i16).collect()
+        };
+        let samples = [silence(0.5), tone(2.0), silence(0.2), tone(1.0), silence(1.0), tone(0.1), silence(0.5)].concat();
+
+        assert_spans(&speech_spans(&samples, rate, 6.0), &[(0.5, 3.7)]);
+        assert_spans(&speech_spans(&samples, rate, 1.0), &[(0.5, 1.3), (1.3, 2.1), (2.1, 2.9), (2.9, 3.7)]);
+
+        // Steady background noise raises the threshold instead of becoming speech.
+        let noisy: Vec<i16> = samples.iter().enumerate().map(|(i, &s)| s + if i % 2 == 0 { 1000 } else { -1000 }).collect();
+        assert_spans(&speech_spans(&noisy, rate, 6.0), &[(0.5, 3.7)]);
+
+        // Speech running to the end stops at the audio length.
+        let trailing = [silence(0.5), tone(1.01)].concat();
+        assert_spans(&speech_spans(&trailing, rate, 6.0), &[(0.5, 1.51)]);
+
+        assert!(speech_spans(&[], rate, 6.0).is_empty());
+        assert!(speech_spans(&silence(2.0), rate, 6.0).is_empty());
+    }
+}
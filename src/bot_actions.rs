@@ -0,0 +1,482 @@
+use serde::{Deserialize, Serialize};
+use serde_json::json;
+use std::time::Duration;
+
+use crate::bots::BotCreateRequest;
+use crate::error::AppError;
+use crate::fetch;
+use crate::mock;
+use crate::net;
+use crate::state::AppState;
+use crate::transcripts::TranscriptMessage;
+use crate::upstream::{send_with_retry, RetryPolicy};
+
+/// Upper bound on provider round-trips that may request tool calls in a single turn.
+const MAX_ACTION_ROUNDS: usize = 3;
+/// Action responses are truncated before being handed back to the model.
+const MAX_ACTION_RESULT_CHARS: usize = 4000;
+const ACTION_TIMEOUT_SECS: u64 = 10;
+/// The real-mode model is asked to prefix replies it cannot ground with this marker.
+const FALLBACK_MARKER: &str = "[FALLBACK]";
+
+/// Owner-declared HTTP endpoint that the bot may call through tool calling.
+#[derive(Clone, Debug, Deserialize, Serialize)]
+pub struct BotAction {
+    pub name: String,
+    #[serde(default)]
+    pub description: String,
+    pub url: String,
+    #[serde(default = "default_method")]
+    pub method: String,
+    /// JSON schema of the arguments (`{"type": "object", "properties": {...}}`).
+    #[serde(default = "empty_schema")]
+    pub parameters: serde_json::Value,
+}
+
+fn default_method() -> String {
+    "POST".to_string()
+}
+
+fn empty_schema() -> serde_json::Value {
+    json!({ "type": "object", "properties": {} })
+}
+
+/// A tool call executed during a bot turn, kept for the transcript.
+pub struct ActionCall {
+    pub name: String,
+    pub arguments: serde_json::Value,
+    pub result: String,
+}
+
+/// Outcome of a bot turn. `fallback` marks low-confidence, generic answers.
+pub struct BotTurn {
+    pub reply: String,
+    pub calls: Vec<ActionCall>,
+    pub fallback: bool,
+}
+
+/// Client for action calls. Redirects are not followed, so a public endpoint
+/// cannot bounce the request to a private address, and unless
+/// `allow_private` is set, connections go to public addresses only.
+pub fn client(allow_private: bool) -> reqwest::Client {
+    let builder = reqwest::Client::builder()
+        .redirect(reqwest::redirect::Policy::none())
+        .connect_timeout(Duration::from_secs(5));
+    net::restrict(builder, allow_private).build().unwrap_or_default()
+}
+
+/// Checks action definitions. Unless `allow_private` is set, action hosts
+/// must resolve to public addresses.
+pub async fn validate_actions(actions: &[BotAction], allow_private: bool) -> Result<(), String> {
+    for (i, action) in actions.iter().enumerate() {
+        let valid_name = !action.name.is_empty()
+            && action.name.len() <= 64
+            && action
+                .name
+                .chars()
+                .all(|c| c.is_ascii_alphanumeric() || c == '_' || c == '-');
+        if !valid_name {
+            return Err(format!(
+                "Action name '{}' must be 1-64 characters of [A-Za-z0-9_-]",
+                action.name
+            ));
+        }
+        if actions[..i].iter().any(|a| a.name == action.name) {
+            return Err(format!("Duplicate action name '{}'", action.name));
+        }
+        let url = fetch::parse_url(&action.url)
+            .map_err(|e| format!("Action '{}' needs an http(s) URL: {}", action.name, e))?;
+        if !allow_private {
+            net::check_host(&url)
+                .await
+                .map_err(|e| format!("Action '{}' is not reachable: {}", action.name, e))?;
+        }
+        if !matches!(action.method.to_uppercase().as_str(), "GET" | "POST") {
+            return Err(format!("Action '{}' method must be GET or POST", action.name));
+        }
+        if action.parameters.get("type").and_then(|t| t.as_str()) != Some("object") {
+            return Err(format!(
+                "Action '{}' parameters must be a JSON schema of type object",
+                action.name
+            ));
+        }
+    }
+    Ok(())
+}
+
+/// Calls the action endpoint. Failures are reported as JSON so the model can explain them.
+///
+/// The host is checked again here: DNS may have changed since the bot was created.
+pub async fn execute_action(
+    client: &reqwest::Client,
+    action: &BotAction,
+    arguments: &serde_json::Value,
+    allow_private: bool,
+) -> String {
+    let url = match fetch::parse_url(&action.url) {
+        Ok(url) => url,
+        Err(e) => return json!({ "error": e }).to_string(),
+    };
+    if !allow_private {
+        if let Err(e) = net::check_host(&url).await {
+            return json!({ "error": format!("Action refused: {}", e) }).to_string();
+        }
+    }
+
+    let request = if action.method.eq_ignore_ascii_case("GET") {
+        let query: Vec<(String, String)> = arguments
+            .as_object()
+            .map(|args| {
+                args.iter()
+                    .map(|(k, v)| {
+                        let value = v.as_str().map(str::to_string).unwrap_or_else(|| v.to_string());
+                        (k.clone(), value)
+                    })
+                    .collect()
+            })
+            .unwrap_or_default();
+        client.get(url).query(&query)
+    } else {
+        client.post(url).json(arguments)
+    };
+
+    let res = match request
+        .timeout(Duration::from_secs(ACTION_TIMEOUT_SECS))
+        .send()
+        .await
+    {
+        Ok(res) => res,
+        Err(e) => return json!({ "error": format!("Action request failed: {}", e) }).to_string(),
+    };
+
+    let status = res.status();
+    let body = res.text().await.unwrap_or_default();
+    let body: String = body.chars().take(MAX_ACTION_RESULT_CHARS).collect();
+
+    if status.is_success() {
+        body
+    } else {
+        json!({ "error": format!("Action returned {}", status), "body": body }).to_string()
+    }
+}
+
+/// Runs one bot turn, letting the provider call the configured actions.
+///
+/// `history` holds the most recent messages of the session and ends with the
+/// visitor's latest `message`. Demo mode answers through the same loop with
+/// [`mock::mock_bot_completion`] standing in for the provider.
+pub async fn run_bot_turn(
+    state: &AppState,
+    bot: &BotCreateRequest,
+    history: &[TranscriptMessage],
+) -> Result<BotTurn, AppError> {
+    let mut system_prompt = bot.system_prompt.clone();
+    if !bot.knowledge.is_empty() {
+        system_prompt.push_str("\n\nAnswer using the following reference documents when relevant:");
+        for doc in &bot.knowledge {
+            system_prompt.push_str(&format!("\n\n### {}\n{}", doc.title, doc.content));
+        }
+    }
+    system_prompt.push_str(&format!(
+        "\n\nIf you cannot answer the question from your instructions, documents or actions, \
+         start your reply with {}.",
+        FALLBACK_MARKER
+    ));
+
+    let mut messages = vec![json!({ "role": "system", "content": system_prompt })];
+    messages.extend(
+        history
+            .iter()
+            .filter(|m| m.role == "user" || m.role == "assistant")
+            .map(|m| json!({ "role": m.role, "content": m.content })),
+    );
+
+    if state.is_demo() {
+        return action_loop(state, bot, messages, |body| async move {
+            Ok(mock::mock_bot_completion(&bot.system_prompt, &bot.actions, &body, FALLBACK_MARKER))
+        })
+        .await;
+    }
+    action_loop(state, bot, messages, |body| openai_completion(state, body)).await
+}
+
+/// Sends one chat completion request and returns the reply message.
+async fn openai_completion(state: &AppState, body: serde_json::Value) -> Result<serde_json::Value, AppError> {
+    let res = send_with_retry(&state.openai_breaker, &RetryPolicy::OPENAI, || {
+        state
+            .http_client
+            .post("https://api.openai.com/v1/chat/completions")
+            .header(
+                "Authorization",
+                format!("Bearer {}", state.config.openai_api_key),
+            )
+            .json(&body)
+    })
+    .await?;
+
+    if !res.status().is_success() {
+        return Err(AppError::InternalError(format!("OpenAI API returned error: {}", res.status())));
+    }
+
+    let data: serde_json::Value = res
+        .json()
+        .await
+        .map_err(|e| AppError::InternalError(format!("Failed to parse response: {}", e)))?;
+    Ok(data["choices"][0]["message"].clone())
+}
+
+/// Tool-calling loop: asks `complete` for a reply, runs the actions it calls
+/// and feeds their results back, for at most [`MAX_ACTION_ROUNDS`] rounds of
+/// calls. The request after the last round forbids further calls.
+async fn action_loop<F, Fut>(
+    state: &AppState,
+    bot: &BotCreateRequest,
+    mut messages: Vec<serde_json::Value>,
+    mut complete: F,
+) -> Result<BotTurn, AppError>
+where
+    F: FnMut(serde_json::Value) -> Fut,
+    Fut: std::future::Future<Output = Result<serde_json::Value, AppError>>,
+{
+    let actions = &bot.actions;
+    let tools: Vec<serde_json::Value> = actions
+        .iter()
+        .map(|a| {
+            json!({
+                "type": "function",
+                "function": {
+                    "name": a.name,
+                    "description": a.description,
+                    "parameters": a.parameters,
+                }
+            })
+        })
+        .collect();
+
+    let mut calls = Vec::new();
+    let mut round = 0;
+
+    loop {
+        let last_round = round == MAX_ACTION_ROUNDS;
+        round += 1;
+        let mut body = json!({
+            "model": bot.settings.model,
+            "messages": messages,
+            "temperature": bot.settings.temperature,
+            "max_tokens": bot.settings.max_tokens,
+        });
+        if !tools.is_empty() {
+            body["tools"] = json!(tools);
+            // Force a plain text answer on the last round.
+            if last_round {
+                body["tool_choice"] = json!("none");
+            }
+        }
+
+        let reply = complete(body).await?;
+        let tool_calls = reply["tool_calls"].as_array().cloned().unwrap_or_default();
+
+        if tool_calls.is_empty() || last_round {
+            let text = reply["content"]
+                .as_str()
+                .unwrap_or("I'm sorry, I couldn't generate a response.")
+                .trim();
+            let (reply, fallback) = match text.strip_prefix(FALLBACK_MARKER) {
+                Some(rest) => (rest.trim_start().to_string(), true),
+                None => (text.to_string(), false),
+            };
+            return Ok(BotTurn { reply, calls, fallback });
+        }
+
+        messages.push(reply);
+        for call in tool_calls {
+            let name = call["function"]["name"].as_str().unwrap_or_default().to_string();
+            let arguments: serde_json::Value = call["function"]["arguments"]
+                .as_str()
+                .and_then(|a| serde_json::from_str(a).ok())
+                .unwrap_or_else(|| json!({}));
+
+            let result = match actions.iter().find(|a| a.name == name) {
+                Some(action) => {
+                    execute_action(&state.action_client, action, &arguments, state.config.allow_private_urls)
+                        .await
+                }
+                None => json!({ "error": format!("Unknown action '{}'", name) }).to_string(),
+            };
+
+            messages.push(json!({
+                "role": "tool",
+                "tool_call_id": call["id"],
+                "content": result,
+            }));
+            calls.push(ActionCall { name, arguments, result });
+        }
+    }
+}
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+    use axum::extract::Query;
+    use axum::http::StatusCode;
+    use axum::routing::get;
+    use axum::{Json, Router};
+    use std::collections::HashMap;
+
+    /// Serves a small action API on a random local port and returns its base URL.
+    async fn serve() -> String {
+        let app = Router::new()
+            .route(
+                "/echo",
+                get(|Query(q): Query<HashMap<String, String>>| async move { Json(json!(q)) })
+                    .post(|Json(body): Json<serde_json::Value>| async move { Json(body) }),
+            )
+            .route("/big", get(|| async { "x".repeat(MAX_ACTION_RESULT_CHARS * 2) }))
+            .route("/fail", get(|| async { (StatusCode::SERVICE_UNAVAILABLE, "down") }));
+        let listener = tokio::net::TcpListener::bind("127.0.0.1:0").await.unwrap();
+        let addr = listener.local_addr().unwrap();
+        tokio::spawn(async move { axum::serve(listener, app).await.unwrap() });
+        format!("http://{}", addr)
+    }
+
+    fn bot(system_prompt: &str, actions: Vec<BotAction>) -> BotCreateRequest {
+        BotCreateRequest {
+            name: "Test bot".to_string(),
+            system_prompt: system_prompt.to_string(),
+            welcome_message: String::new(),
+            settings: Default::default(),
+            actions,
+            knowledge: Vec::new(),
+        }
+    }
+
+    fn action(url: String, method: &str) -> BotAction {
+        BotAction {
+            name: "lookup".to_string(),
+            description: String::new(),
+            url,
+            method: method.to_string(),
+            parameters: empty_schema(),
+        }
+    }
+
+    #[tokio::test]
+    async fn get_sends_arguments_as_query() {
+        let base = serve().await;
+        let result = execute_action(
+            &client(true),
+            &action(format!("{}/echo", base), "GET"),
+            &json!({ "order": "A-17", "count": 2 }),
+            true,
+        )
+        .await;
+        let body: serde_json::Value = serde_json::from_str(&result).unwrap();
+        assert_eq!(body, json!({ "order": "A-17", "count": "2" }));
+    }
+
+    #[tokio::test]
+    async fn post_sends_arguments_as_json() {
+        let base = serve().await;
+        let arguments = json!({ "order": "A-17", "items": [1, 2] });
+        let result =
+            execute_action(&client(true), &action(format!("{}/echo", base), "POST"), &arguments, true).await;
+        assert_eq!(serde_json::from_str::<serde_json::Value>(&result).unwrap(), arguments);
+    }
+
+    #[tokio::test]
+    async fn error_status_and_long_bodies_are_reported() {
+        let base = serve().await;
+        let failed =
+            execute_action(&client(true), &action(format!("{}/fail", base), "GET"), &json!({}), true).await;
+        let failed: serde_json::Value = serde_json::from_str(&failed).unwrap();
+        assert_eq!(failed["error"], "Action returned 503 Service Unavailable");
+        assert_eq!(failed["body"], "down");
+
+        let big = execute_action(&client(true), &action(format!("{}/big", base), "GET"), &json!({}), true).await;
+        assert_eq!(big.chars().count(), MAX_ACTION_RESULT_CHARS);
+    }
+
+    #[tokio::test]
+    async fn private_hosts_are_refused() {
+        let base = serve().await;
+        let result =
+            execute_action(&client(false), &action(format!("{}/echo", base), "GET"), &json!({}), false).await;
+        assert!(result.contains("private network address"), "{}", result);
+
+        let actions = [action(format!("{}/echo", base), "GET")];
+        assert!(validate_actions(&actions, true).await.is_ok());
+        assert!(validate_actions(&actions, false).await.is_err());
+        let metadata = [action("http://169.254.169.254/latest/meta-data".to_string(), "GET")];
+        assert!(validate_actions(&metadata, false).await.is_err());
+    }
+
+    #[tokio::test]
+    async fn demo_turn_calls_action_then_replies() {
+        let base = serve().await;
+        let state = AppState::for_tests();
+        let mut order_status = action(format!("{}/echo", base), "GET");
+        order_status.name = "order_status".to_string();
+        order_status.parameters = json!({ "type": "object", "properties": { "order_id": { "type": "string" } } });
+        let bot = bot("You help customers of a pizza shop.", vec![order_status]);
+
+        let history = [TranscriptMessage::now("user", "What is the status of order A-17?")];
+        let turn = run_bot_turn(&state, &bot, &history).await.unwrap();
+        assert_eq!(turn.calls.len(), 1);
+        assert_eq!(turn.calls[0].name, "order_status");
+        assert_eq!(turn.calls[0].arguments, json!({ "order_id": "A-17" }));
+        assert_eq!(serde_json::from_str::<serde_json::Value>(&turn.calls[0].result).unwrap(), json!({ "order_id": "A-17" }));
+        assert!(turn.reply.contains("order_status") && turn.reply.contains("A-17"), "{}", turn.reply);
+        assert!(!turn.fallback);
+
+        let history = [TranscriptMessage::now("user", "Explain quantum chromodynamics")];
+        let turn = run_bot_turn(&state, &bot, &history).await.unwrap();
+        assert!(turn.calls.is_empty());
+        assert!(turn.fallback);
+        assert!(!turn.reply.starts_with(FALLBACK_MARKER));
+    }
+
+    #[tokio::test]
+    async fn action_rounds_are_capped() {
+        let base = serve().await;
+        let state = AppState::for_tests();
+        let bot = bot("assistant", vec![action(format!("{}/echo", base), "GET")]);
+
+        // A provider that asks for another call every time it is asked.
+        let mut requests = Vec::new();
+        let turn = action_loop(&state, &bot, Vec::new(), |body| {
+            requests.push(body);
+            std::future::ready(Ok(json!({
+                "role": "assistant",
+                "content": "Still looking.",
+                "tool_calls": [{ "id": "call_1", "function": { "name": "lookup", "arguments": "{}" } }],
+            })))
+        })
+        .await
+        .unwrap();
+
+        assert_eq!(turn.calls.len(), MAX_ACTION_ROUNDS);
+        assert_eq!(requests.len(), MAX_ACTION_ROUNDS + 1);
+        assert!(requests[..MAX_ACTION_ROUNDS].iter().all(|r| r.get("tool_choice").is_none()));
+        assert_eq!(requests[MAX_ACTION_ROUNDS]["tool_choice"], "none");
+        assert_eq!(turn.reply, "Still looking.");
+    }
+
+    #[tokio::test]
+    async fn invalid_definitions_are_rejected() {
+        let mut bad_name = action("http://127.0.0.1/".to_string(), "GET");
+        bad_name.name = "look up".to_string();
+        assert!(validate_actions(&[bad_name], true).await.is_err());
+
+        let duplicate = [
+            action("http://127.0.0.1/a".to_string(), "GET"),
+            action("http://127.0.0.1/b".to_string(), "GET"),
+        ];
+        assert!(validate_actions(&duplicate, true).await.unwrap_err().contains("Duplicate"));
+
+        let ftp = action("ftp://127.0.0.1/".to_string(), "GET");
+        assert!(validate_actions(&[ftp], true).await.is_err());
+
+        let delete = action("http://127.0.0.1/".to_string(), "DELETE");
+        assert!(validate_actions(&[delete], true).await.is_err());
+    }
+}
@@ -0,0 +1,88 @@
+use dashmap::DashMap;
+use serde::Serialize;
+use serde_json::json;
+use std::collections::VecDeque;
+use std::sync::Arc;
+
+/// Only the most recent unanswered questions are kept per bot.
+const MAX_UNANSWERED: usize = 100;
+
+/// A visitor question the bot fell back on or failed to answer.
+#[derive(Clone, Serialize)]
+pub struct UnansweredQuestion {
+    pub session_id: String,
+    pub question: String,
+    pub reason: String,
+    pub timestamp: String,
+}
+
+#[derive(Default)]
+struct BotCounters {
+    conversations: u64,
+    visitor_messages: u64,
+    bot_messages: u64,
+    errors: u64,
+    total_latency_ms: u64,
+    unanswered: VecDeque<UnansweredQuestion>,
+}
+
+/// In-memory per-bot usage counters, exposed to owners at `/bot/api/{id}/stats`.
+#[derive(Clone, Default)]
+pub struct BotAnalytics {
+    counters: Arc<DashMap<String, BotCounters>>,
+}
+
+impl BotAnalytics {
+    pub fn record_conversation(&self, bot_id: &str) {
+        self.counters.entry(bot_id.to_string()).or_default().conversations += 1;
+    }
+
+    /// Records a visitor turn; `answered` is false when the provider call failed.
+    pub fn record_turn(&self, bot_id: &str, latency_ms: u64, answered: bool) {
+        let mut c = self.counters.entry(bot_id.to_string()).or_default();
+        c.visitor_messages += 1;
+        c.total_latency_ms += latency_ms;
+        if answered {
+            c.bot_messages += 1;
+        } else {
+            c.errors += 1;
+        }
+    }
+
+    pub fn record_unanswered(&self, bot_id: &str, session_id: &str, question: &str, reason: &str) {
+        let mut c = self.counters.entry(bot_id.to_string()).or_default();
+        if c.unanswered.len() == MAX_UNANSWERED {
+            c.unanswered.pop_front();
+        }
+        c.unanswered.push_back(UnansweredQuestion {
+            session_id: session_id.to_string(),
+            question: question.to_string(),
+            reason: reason.to_string(),
+            timestamp: chrono::Local::now().to_rfc3339(),
+        });
+    }
+
+    pub fn snapshot(&self, bot_id: &str) -> serde_json::Value {
+        let Some(c) = self.counters.get(bot_id) else {
+            return json!({
+                "conversations": 0,
+                "messages": { "visitor": 0, "bot": 0 },
+                "avg_latency_ms": 0,
+                "error_rate": 0.0,
+                "unanswered": [],
+            });
+        };
+
+        let turns = c.visitor_messages.max(1);
+        json!({
+            "conversations": c.conversations,
+            "messages": {
+                "visitor": c.visitor_messages,
+                "bot": c.bot_messages,
+            },
+            "avg_latency_ms": c.total_latency_ms / turns,
+            "error_rate": c.errors as f64 / turns as f64,
+            "unanswered": c.unanswered,
+        })
+    }
+}
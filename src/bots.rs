@@ -0,0 +1,69 @@
+//! Bot definitions: what an owner configures when creating or importing a bot.
+
+use serde::{Deserialize, Serialize};
+
+use crate::bot_actions::BotAction;
+
+/// Current version of the portable bot definition format.
+pub const BOT_DEFINITION_VERSION: u32 = 1;
+
+/// A bot as configured by its owner; also the `/bot/api/create` request body.
+#[derive(Deserialize, Serialize)]
+pub struct BotCreateRequest {
+    pub name: String,
+    pub system_prompt: String,
+    pub welcome_message: String,
+    #[serde(default)]
+    pub settings: BotSettings,
+    #[serde(default)]
+    pub actions: Vec<BotAction>,
+    #[serde(default)]
+    pub knowledge: Vec<KnowledgeDocument>,
+}
+
+/// Provider settings used for real-mode bot turns.
+#[derive(Clone, Deserialize, Serialize)]
+pub struct BotSettings {
+    #[serde(default = "default_model")]
+    pub model: String,
+    #[serde(default = "default_temperature")]
+    pub temperature: f32,
+    #[serde(default = "default_max_tokens")]
+    pub max_tokens: u32,
+}
+
+impl Default for BotSettings {
+    fn default() -> Self {
+        Self {
+            model: default_model(),
+            temperature: default_temperature(),
+            max_tokens: default_max_tokens(),
+        }
+    }
+}
+
+fn default_model() -> String {
+    "gpt-4o-mini".to_string()
+}
+
+fn default_temperature() -> f32 {
+    0.7
+}
+
+fn default_max_tokens() -> u32 {
+    1024
+}
+
+#[derive(Clone, Deserialize, Serialize)]
+pub struct KnowledgeDocument {
+    pub title: String,
+    pub content: String,
+}
+
+/// Portable bot definition used by export/import, e.g. to keep bots in git.
+#[derive(Deserialize, Serialize)]
+pub struct BotDefinition {
+    pub version: u32,
+    #[serde(flatten)]
+    pub bot: BotCreateRequest,
+}
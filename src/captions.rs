@@ -0,0 +1,118 @@
+use serde::{Deserialize, Serialize};
+
+/// Longest caption line before wrapping, per common subtitle guidelines.
+const MAX_LINE_CHARS: usize = 42;
+
+/// One timed transcript segment; times are in seconds from the start of the audio.
+#[derive(Clone, Serialize, Deserialize)]
+pub struct Cue {
+    pub start: f64,
+    pub end: f64,
+    pub text: String,
+}
+
+/// SubRip: numbered cues with `HH:MM:SS,mmm` timestamps.
+pub fn to_srt(cues: &[Cue]) -> String {
+    cues.iter()
+        .enumerate()
+        .map(|(i, cue)| {
+            format!(
+                "{}\n{} --> {}\n{}\n\n",
+                i + 1,
+                timestamp(cue.start, ','),
+                timestamp(cue.end, ','),
+                wrap(&cue.text)
+            )
+        })
+        .collect()
+}
+
+/// WebVTT: `WEBVTT` header and `HH:MM:SS.mmm` timestamps.
+pub fn to_vtt(cues: &[Cue]) -> String {
+    let mut vtt = String::from("WEBVTT\n\n");
+    for cue in cues {
+        vtt.push_str(&format!(
+            "{} --> {}\n{}\n\n",
+            timestamp(cue.start, '.'),
+            timestamp(cue.end, '.'),
+            // "-->" would end the cue timing line early in some players.
+            wrap(&cue.text).replace("-->", "->")
+        ));
+    }
+    vtt
+}
+
+fn timestamp(seconds: f64, separator: char) -> String {
+    let total_ms = (seconds.max(0.0) * 1000.0).round() as u64;
+    format!(
+        "{:02}:{:02}:{:02}{}{:03}",
+        total_ms / 3_600_000,
+        total_ms / 60_000 % 60,
+        total_ms / 1000 % 60,
+        separator,
+        total_ms % 1000
+    )
+}
+
+/// Breaks cue text into lines of at most `MAX_LINE_CHARS` at word boundaries.
+fn wrap(text: &str) -> String {
+    let mut lines: Vec<String> = Vec::new();
+    for word in text.split_whitespace() {
+        match lines.last_mut() {
+            Some(line) if line.chars().count() + 1 + word.chars().count() <= MAX_LINE_CHARS => {
+                line.push(' ');
+                line.push_str(word);
+            }
+            _ => lines.push(word.to_string()),
+        }
+    }
+    lines.join("\n")
+}
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+
+    fn cue(start: f64, end: f64, text: &str) -> Cue {
+        Cue { start, end, text: text.to_string() }
+    }
+
+    #[test]
+    fn timestamps_round_to_milliseconds_and_roll_over() {
+        assert_eq!(timestamp(0.0, ','), "00:00:00,000");
+        assert_eq!(timestamp(-2.0, ','), "00:00:00,000");
+        assert_eq!(timestamp(1.2346, ','), "00:00:01,235");
+        assert_eq!(timestamp(59.9996, '.'), "00:01:00.000");
+        assert_eq!(timestamp(3599.9996, ','), "01:00:00,000");
+        assert_eq!(timestamp(3661.5, '.'), "01:01:01.500");
+        assert_eq!(timestamp(90_061.25, ','), "25:01:01,250");
+    }
+
+    #[test]
+    fn srt_numbers_cues_and_wraps_lines() {
+        let long = "This caption is long enough that it has to wrap onto a second line";
+        let srt = to_srt(&[cue(0.5, 2.0, "Hello there."), cue(3_600.0, 3_605.25, long)]);
+        assert_eq!(
+            srt,
+            "1\n00:00:00,500 --> 00:00:02,000\nHello there.\n\n\
+             2\n01:00:00,000 --> 01:00:05,250\nThis caption is long enough that it has to\nwrap onto a second line\n\n"
+        );
+        assert_eq!(to_srt(&[]), "");
+    }
+
+    #[test]
+    fn vtt_has_a_header_and_escapes_arrows() {
+        let vtt = to_vtt(&[cue(61.0, 62.5, "A --> B")]);
+        assert_eq!(vtt, "WEBVTT\n\n00:01:01.000 --> 00:01:02.500\nA -> B\n\n");
+        assert_eq!(to_vtt(&[]), "WEBVTT\n\n");
+    }
+
+    #[test]
+    fn wrap_keeps_lines_within_the_limit() {
+        let word = "x".repeat(MAX_LINE_CHARS + 5);
+        assert_eq!(wrap(&format!("a {} b", word)), format!("a\n{}\nb", word));
+        let text = "word ".repeat(30);
+        assert!(wrap(&text).lines().all(|line| line.chars().count() <= MAX_LINE_CHARS));
+        assert_eq!(wrap(&text).split_whitespace().count(), 30);
+    }
+}
@@ -7,6 +7,12 @@ pub struct Config {
     pub port: u16,
     pub domain: String,
     pub demo_mode: bool,
+    pub bot_transcript_retention_days: u32,
+    pub image_gallery_dir: String,
+    pub image_gallery_max_per_client: usize,
+    pub image_gallery_max_mb: u64,
+    /// Let SEO fetches and bot actions reach private and loopback addresses (local testing only).
+    pub allow_private_urls: bool,
 }
 
 #[derive(Clone, Copy, Debug)]
@@ -61,6 +67,23 @@ impl Config {
             domain: env::var("DOMAIN")
                 .unwrap_or_else(|_| "localhost".to_string()),
             demo_mode,
+            bot_transcript_retention_days: env::var("BOT_TRANSCRIPT_RETENTION_DAYS")
+                .ok()
+                .and_then(|d| d.parse().ok())
+                .unwrap_or(30),
+            image_gallery_dir: env::var("IMAGE_GALLERY_DIR")
+                .unwrap_or_else(|_| "data/images".to_string()),
+            image_gallery_max_per_client: env::var("IMAGE_GALLERY_MAX_PER_CLIENT")
+                .ok()
+                .and_then(|n| n.parse().ok())
+                .unwrap_or(100),
+            image_gallery_max_mb: env::var("IMAGE_GALLERY_MAX_MB")
+                .ok()
+                .and_then(|n| n.parse().ok())
+                .unwrap_or(1024),
+            allow_private_urls: env::var("ALLOW_PRIVATE_URLS")
+                .map(|v| v == "true" || v == "1")
+                .unwrap_or(false),
         }
     }
 }
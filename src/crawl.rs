@@ -0,0 +1,700 @@
+//! Bounded same-host site crawl for the SEO audit: robots.txt rules,
+//! sitemap seeds, page / depth / time limits, and a site-level report built
+//! from the pages and links that were actually visited.
+
+use std::collections::{BTreeMap, HashMap, HashSet, VecDeque};
+use std::time::{Duration, Instant};
+
+use reqwest::Url;
+use serde::Serialize;
+use tokio::sync::mpsc;
+
+use crate::fetch::{self, Redirect};
+use crate::page::{Page, TitleSource};
+
+pub const DEFAULT_MAX_PAGES: usize = 50;
+pub const MAX_PAGES: usize = 200;
+pub const DEFAULT_MAX_DEPTH: usize = 3;
+pub const MAX_DEPTH: usize = 6;
+
+/// Budget for the whole crawl; what was visited by then is reported.
+const CRAWL_TIMEOUT: Duration = Duration::from_secs(180);
+
+/// Pause between requests when robots.txt sets no `Crawl-delay`.
+const DEFAULT_DELAY: Duration = Duration::from_millis(250);
+
+/// Longer `Crawl-delay` values are capped so small audits still finish.
+const MAX_DELAY: Duration = Duration::from_secs(2);
+
+/// Sitemap index files followed, and sitemap URLs kept.
+const MAX_SITEMAPS: usize = 10;
+const MAX_SITEMAP_URLS: usize = 10_000;
+
+/// Pages each issue lists as examples of where a link appears.
+const MAX_SOURCES: usize = 10;
+
+/// Links to these are assets, not pages, and are not crawled.
+const ASSET_EXTENSIONS: &[&str] = &[
+    "7z", "avi", "css", "csv", "doc", "docx", "gif", "gz", "ico", "jpeg", "jpg", "js", "json", "mov", "mp3", "mp4",
+    "pdf", "png", "ppt", "pptx", "rar", "svg", "tar", "txt", "wav", "webm", "webp", "woff", "woff2", "xls", "xlsx",
+    "xml", "zip",
+];
+
+pub struct CrawlOptions {
+    pub max_pages: usize,
+    pub max_depth: usize,
+    pub use_sitemap: bool,
+    pub allow_private: bool,
+}
+
+// ---------------------------------------------------------------------------
+// robots.txt
+// ---------------------------------------------------------------------------
+
+/// The rules of the group that applies to us: the most specific user-agent
+/// group matching our product token, else `*`.
+#[derive(Default)]
+struct Robots {
+    /// `(allow, pattern)` pairs.
+    rules: Vec<(bool, String)>,
+    crawl_delay: Option<f64>,
+    sitemaps: Vec<String>,
+}
+
+/// One `User-agent` group of robots.txt.
+#[derive(Default)]
+struct Group {
+    agents: Vec<String>,
+    rules: Vec<(bool, String)>,
+    crawl_delay: Option<f64>,
+}
+
+/// `fetch::USER_AGENT` without its version, lowercased.
+fn product_token() -> String {
+    fetch::USER_AGENT.split('/').next().unwrap_or_default().to_lowercase()
+}
+
+impl Robots {
+    fn parse(text: &str) -> Self {
+        let token = product_token();
+        let mut robots = Robots::default();
+        let mut groups: Vec<Group> = Vec::new();
+        let mut in_agents = false;
+
+        for line in text.lines() {
+            let line = line.split('#').next().unwrap_or_default().trim();
+            let Some((key, value)) = line.split_once(':') else { continue };
+            let key = key.trim().to_lowercase();
+            let value = value.trim();
+            match key.as_str() {
+                "user-agent" => {
+                    if !in_agents {
+                        groups.push(Group::default());
+                    }
+                    in_agents = true;
+                    if let Some(group) = groups.last_mut() {
+                        group.agents.push(value.to_lowercase());
+                    }
+                }
+                "allow" | "disallow" => {
+                    in_agents = false;
+                    // An empty Disallow allows everything; it adds no rule.
+                    if let (Some(group), false) = (groups.last_mut(), value.is_empty()) {
+                        group.rules.push((key == "allow", value.to_string()));
+                    }
+                }
+                "crawl-delay" => {
+                    in_agents = false;
+                    if let Some(group) = groups.last_mut() {
+                        group.crawl_delay = value.parse().ok().filter(|d: &f64| d.is_finite() && *d >= 0.0);
+                    }
+                }
+                "sitemap" => robots.sitemaps.push(value.to_string()),
+                _ => {}
+            }
+        }
+
+        let ours = groups.iter().find(|g| g.agents.iter().any(|a| a != "*" && token.contains(a.as_str())));
+        let any = groups.iter().find(|g| g.agents.iter().any(|a| a == "*"));
+        if let Some(group) = ours.or(any) {
+            robots.rules = group.rules.clone();
+            robots.crawl_delay = group.crawl_delay;
+        }
+        robots
+    }
+
+    /// Longest matching pattern wins; Allow wins a tie.
+    fn allows(&self, url: &Url) -> bool {
+        let path = match url.query() {
+            Some(query) => format!("{}?{}", url.path(), query),
+            None => url.path().to_string(),
+        };
+        self.rules
+            .iter()
+            .filter(|(_, pattern)| pattern_matches(pattern, &path))
+            .max_by_key(|(allow, pattern)| (pattern.len(), *allow))
+            .is_none_or(|(allow, _)| *allow)
+    }
+}
+
+/// robots.txt path patterns: prefix match, `*` wildcards and a `$` anchor.
+fn pattern_matches(pattern: &str, path: &str) -> bool {
+    let (pattern, anchored) = match pattern.strip_suffix('$') {
+        Some(p) => (p, true),
+        None => (pattern, false),
+    };
+    let parts: Vec<&str> = pattern.split('*').collect();
+    let Some(mut rest) = path.strip_prefix(parts[0]) else { return false };
+    if parts.len() == 1 {
+        return !anchored || rest.is_empty();
+    }
+    for part in &parts[1..parts.len() - 1] {
+        match rest.find(part) {
+            Some(at) => rest = &rest[at + part.len()..],
+            None => return false,
+        }
+    }
+    let last = parts[parts.len() - 1];
+    if anchored {
+        rest.ends_with(last)
+    } else {
+        rest.contains(last)
+    }
+}
+
+// ---------------------------------------------------------------------------
+// Sitemaps
+// ---------------------------------------------------------------------------
+
+/// `<loc>` values of a `<urlset>` (pages) or `<sitemapindex>` (sitemaps).
+fn sitemap_locs(xml: &str) -> Result<(bool, Vec<String>), String> {
+    let doc = roxmltree::Document::parse(xml).map_err(|e| format!("Invalid sitemap: {}", e))?;
+    let root = doc.root_element();
+    let is_index = root.tag_name().name() == "sitemapindex";
+    let locs = root
+        .children()
+        .filter(|n| n.is_element())
+        .filter_map(|entry| entry.children().find(|n| n.tag_name().name() == "loc"))
+        .filter_map(|loc| loc.text())
+        .map(|loc| loc.trim().to_string())
+        .collect();
+    Ok((is_index, locs))
+}
+
+/// Page URLs listed in the site's sitemaps, following sitemap indexes, and
+/// the sitemaps that could be read.
+async fn sitemap_urls(client: &reqwest::Client, roots: Vec<Url>, host: &str, allow_private: bool) -> (Vec<String>, Vec<Url>) {
+    let mut pending: VecDeque<Url> = roots.into();
+    let mut tried = HashSet::new();
+    let mut read = Vec::new();
+    let mut urls = Vec::new();
+    while let Some(sitemap) = pending.pop_front() {
+        if tried.len() >= MAX_SITEMAPS || urls.len() >= MAX_SITEMAP_URLS {
+            break;
+        }
+        if !tried.insert(sitemap.clone()) {
+            continue;
+        }
+        let Ok(fetched) = fetch::fetch(client, sitemap.clone(), allow_private).await else { continue };
+        if fetched.status != 200 {
+            continue;
+        }
+        let Ok((is_index, locs)) = sitemap_locs(&fetched.body) else {
+            tracing::debug!("Skipping unreadable sitemap {}", sitemap);
+            continue;
+        };
+        read.push(sitemap);
+        for loc in locs {
+            let Some(url) = Url::parse(&loc).ok().filter(|u| u.host_str() == Some(host)) else { continue };
+            if is_index {
+                pending.push_back(url);
+            } else if urls.len() < MAX_SITEMAP_URLS {
+                urls.push(normalize(url));
+            }
+        }
+    }
+    (urls.into_iter().map(|u| u.to_string()).collect(), read)
+}
+
+// ---------------------------------------------------------------------------
+// Report
+// ---------------------------------------------------------------------------
+
+#[derive(Serialize)]
+pub struct Progress {
+    pub crawled: usize,
+    pub queued: usize,
+    pub max_pages: usize,
+    pub url: String,
+    pub status: Option<u16>,
+    pub depth: usize,
+}
+
+#[derive(Serialize)]
+pub struct CrawledPage {
+    pub url: String,
+    pub final_url: String,
+    pub depth: usize,
+    /// None when the request failed (see `error`).
+    pub status: Option<u16>,
+    #[serde(skip_serializing_if = "Option::is_none")]
+    pub error: Option<String>,
+    pub title: Option<String>,
+    pub description: Option<String>,
+    /// Reached through the sitemap rather than a link.
+    pub from_sitemap: bool,
+    /// Crawled pages linking here.
+    pub inlinks: usize,
+    /// Distinct same-host links on the page.
+    pub outlinks: usize,
+    pub response_ms: u64,
+    #[serde(skip)]
+    redirects: Vec<Redirect>,
+    #[serde(skip)]
+    links: Vec<String>,
+    #[serde(skip)]
+    html: bool,
+}
+
+impl CrawledPage {
+    fn ok_html(&self) -> bool {
+        self.html && self.status == Some(200)
+    }
+}
+
+#[derive(Serialize)]
+pub struct BrokenLink {
+    pub url: String,
+    pub status: Option<u16>,
+    #[serde(skip_serializing_if = "Option::is_none")]
+    pub error: Option<String>,
+    pub linked_from: Vec<String>,
+}
+
+#[derive(Serialize)]
+pub struct DuplicateTitle {
+    pub title: String,
+    pub urls: Vec<String>,
+}
+
+#[derive(Serialize)]
+pub struct RedirectChain {
+    pub url: String,
+    pub final_url: String,
+    pub hops: Vec<Redirect>,
+    pub linked_from: Vec<String>,
+}
+
+#[derive(Serialize)]
+pub struct Issues {
+    pub broken_links: Vec<BrokenLink>,
+    pub duplicate_titles: Vec<DuplicateTitle>,
+    pub missing_titles: Vec<String>,
+    pub missing_descriptions: Vec<String>,
+    /// Sitemap pages no crawled page links to.
+    pub orphan_pages: Vec<String>,
+    /// Linked URLs that redirect, with every hop.
+    pub redirect_chains: Vec<RedirectChain>,
+}
+
+#[derive(Serialize)]
+pub struct Summary {
+    pub pages_crawled: usize,
+    /// Discovered but not visited because a limit was reached.
+    pub pages_not_crawled: usize,
+    pub blocked_by_robots: usize,
+    pub broken_links: usize,
+    pub duplicate_titles: usize,
+    pub missing_titles: usize,
+    pub missing_descriptions: usize,
+    pub orphan_pages: usize,
+    pub redirect_chains: usize,
+}
+
+#[derive(Serialize)]
+pub struct RobotsInfo {
+    pub found: bool,
+    pub crawl_delay: Option<f64>,
+    /// Same-host URLs that were skipped because robots.txt disallows them.
+    pub blocked: Vec<String>,
+}
+
+#[derive(Serialize)]
+pub struct SitemapInfo {
+    pub sitemaps: Vec<String>,
+    pub urls: usize,
+}
+
+#[derive(Serialize)]
+pub struct SiteReport {
+    pub start_url: String,
+    pub max_pages: usize,
+    pub max_depth: usize,
+    /// `page_limit` or `time_limit` when the crawl stopped before the frontier was empty.
+    pub stopped: Option<&'static str>,
+    pub duration_ms: u64,
+    pub robots_txt: RobotsInfo,
+    pub sitemap: SitemapInfo,
+    pub summary: Summary,
+    pub issues: Issues,
+    pub pages: Vec<CrawledPage>,
+}
+
+// ---------------------------------------------------------------------------
+// Crawl
+// ---------------------------------------------------------------------------
+
+/// Pause between requests: robots.txt `Crawl-delay`, capped at [`MAX_DELAY`].
+/// The cap is applied before converting, as huge delays overflow `Duration`.
+fn request_delay(crawl_delay: Option<f64>) -> Duration {
+    crawl_delay
+        .map(|d| Duration::from_secs_f64(d.min(MAX_DELAY.as_secs_f64())))
+        .unwrap_or(DEFAULT_DELAY)
+}
+
+/// Drops the fragment, which never changes the fetched document.
+fn normalize(mut url: Url) -> Url {
+    url.set_fragment(None);
+    url
+}
+
+fn is_asset(url: &Url) -> bool {
+    url.path()
+        .rsplit_once('.')
+        .is_some_and(|(_, ext)| ASSET_EXTENSIONS.contains(&ext.to_lowercase().as_str()))
+}
+
+/// Same-host page links of a fetched page, resolved and deduplicated.
+fn page_links(page: &Page, base: &Url, host: &str) -> Vec<String> {
+    let mut seen = HashSet::new();
+    page.links
+        .iter()
+        .filter_map(|link| base.join(link.href.trim()).ok())
+        .filter(|url| matches!(url.scheme(), "http" | "https") && url.host_str() == Some(host) && !is_asset(url))
+        .map(|url| normalize(url).to_string())
+        .filter(|url| seen.insert(url.clone()))
+        .collect()
+}
+
+/// Crawls `start`'s host breadth-first, then any sitemap pages the links did
+/// not reach, until the page, depth or time limit. Progress goes to
+/// `progress`; the crawl stops early once nobody is listening.
+pub async fn crawl(
+    client: &reqwest::Client,
+    start: Url,
+    options: CrawlOptions,
+    progress: &mpsc::Sender<Progress>,
+) -> Result<SiteReport, String> {
+    let started = Instant::now();
+    let start = normalize(start);
+    let host = start.host_str().unwrap_or_default().to_string();
+    let origin = start.join("/").map_err(|e| e.to_string())?;
+
+    // A server error on robots.txt means the site's rules are unknown, so
+    // nothing is crawled; a missing file allows everything.
+    let robots_url = origin.join("/robots.txt").map_err(|e| e.to_string())?;
+    let (robots, robots_found) = match fetch::fetch(client, robots_url, options.allow_private).await {
+        Ok(f) if f.status == 200 => (Robots::parse(&f.body), true),
+        Ok(f) if f.status >= 500 => return Err(format!("robots.txt returned HTTP {}; the site was not crawled", f.status)),
+        Ok(_) => (Robots::default(), false),
+        Err(e) => return Err(e),
+    };
+    let delay = request_delay(robots.crawl_delay);
+
+    let (sitemap_list, sitemaps_read) = if options.use_sitemap {
+        let mut roots: Vec<Url> = robots.sitemaps.iter().filter_map(|s| Url::parse(s).ok()).collect();
+        if roots.is_empty() {
+            roots.extend(origin.join("/sitemap.xml"));
+        }
+        sitemap_urls(client, roots, &host, options.allow_private).await
+    } else {
+        (Vec::new(), Vec::new())
+    };
+    let in_sitemap: HashSet<&str> = sitemap_list.iter().map(String::as_str).collect();
+
+    let mut queue: VecDeque<(String, usize)> = VecDeque::from([(start.to_string(), 0)]);
+    let mut sitemap_queue: VecDeque<String> = sitemap_list.iter().cloned().collect();
+    let mut seen: HashSet<String> = HashSet::from([start.to_string()]);
+    let mut blocked = Vec::new();
+    let mut pages: Vec<CrawledPage> = Vec::new();
+    let mut stopped = None;
+
+    loop {
+        let (url, depth, from_sitemap) = match queue.pop_front() {
+            Some((url, depth)) => (url, depth, false),
+            None => match sitemap_queue.pop_front() {
+                Some(url) if seen.insert(url.clone()) => (url, 1, true),
+                Some(_) => continue,
+                None => break,
+            },
+        };
+        if pages.len() >= options.max_pages {
+            stopped = Some("page_limit");
+            queue.push_front((url, depth));
+            break;
+        }
+        if started.elapsed() >= CRAWL_TIMEOUT {
+            stopped = Some("time_limit");
+            queue.push_front((url, depth));
+            break;
+        }
+        let Ok(parsed) = Url::parse(&url) else { continue };
+        if !robots.allows(&parsed) {
+            blocked.push(url);
+            continue;
+        }
+        if !pages.is_empty() {
+            tokio::time::sleep(delay).await;
+        }
+
+        let page = match fetch::fetch(client, parsed, options.allow_private).await {
+            Ok(fetched) => {
+                let html = fetched.is_html();
+                let doc = (html && fetched.status < 400).then(|| Page::parse_html(&fetched.body));
+                let final_url = normalize(fetched.url.clone()).to_string();
+                // A redirect target is the same page; don't visit it again.
+                seen.insert(final_url.clone());
+                let links = match &doc {
+                    Some(page) if fetched.url.host_str() == Some(host.as_str()) => page_links(page, &fetched.url, &host),
+                    _ => Vec::new(),
+                };
+                CrawledPage {
+                    url: url.clone(),
+                    final_url,
+                    depth,
+                    status: Some(fetched.status),
+                    error: None,
+                    title: doc.as_ref().and_then(|p| match &p.title {
+                        Some((title, TitleSource::TitleTag)) => Some(title.clone()),
+                        _ => None,
+                    }),
+                    description: doc.as_ref().and_then(|p| p.description.clone()).filter(|d| !d.trim().is_empty()),
+                    from_sitemap,
+                    inlinks: 0,
+                    outlinks: links.len(),
+                    response_ms: fetched.response_ms,
+                    redirects: fetched.redirects,
+                    links,
+                    html,
+                }
+            }
+            Err(e) => CrawledPage {
+                url: url.clone(),
+                final_url: url.clone(),
+                depth,
+                status: None,
+                error: Some(e),
+                title: None,
+                description: None,
+                from_sitemap,
+                inlinks: 0,
+                outlinks: 0,
+                response_ms: 0,
+                redirects: Vec::new(),
+                links: Vec::new(),
+                html: false,
+            },
+        };
+
+        if depth < options.max_depth {
+            for link in &page.links {
+                if seen.insert(link.clone()) {
+                    queue.push_back((link.clone(), depth + 1));
+                }
+            }
+        }
+        let update = Progress {
+            crawled: pages.len() + 1,
+            queued: queue.len() + sitemap_queue.iter().filter(|u| !seen.contains(*u)).count(),
+            max_pages: options.max_pages,
+            url: page.url.clone(),
+            status: page.status,
+            depth,
+        };
+        pages.push(page);
+        if progress.send(update).await.is_err() {
+            return Err("Crawl cancelled".to_string());
+        }
+    }
+
+    let not_crawled = queue.len() + sitemap_queue.iter().filter(|u| !seen.contains(*u)).count();
+    let issues = site_issues(&mut pages, &in_sitemap, start.as_str());
+    let summary = Summary {
+        pages_crawled: pages.len(),
+        pages_not_crawled: not_crawled,
+        blocked_by_robots: blocked.len(),
+        broken_links: issues.broken_links.len(),
+        duplicate_titles: issues.duplicate_titles.len(),
+        missing_titles: issues.missing_titles.len(),
+        missing_descriptions: issues.missing_descriptions.len(),
+        orphan_pages: issues.orphan_pages.len(),
+        redirect_chains: issues.redirect_chains.len(),
+    };
+    Ok(SiteReport {
+        start_url: start.to_string(),
+        max_pages: options.max_pages,
+        max_depth: options.max_depth,
+        stopped,
+        duration_ms: started.elapsed().as_millis() as u64,
+        robots_txt: RobotsInfo { found: robots_found, crawl_delay: robots.crawl_delay, blocked },
+        sitemap: SitemapInfo {
+            sitemaps: sitemaps_read.iter().map(Url::to_string).collect(),
+            urls: sitemap_list.len(),
+        },
+        summary,
+        issues,
+        pages,
+    })
+}
+
+/// Site-level issues from the crawled pages and the links between them;
+/// fills in each page's `inlinks`.
+fn site_issues(pages: &mut [CrawledPage], in_sitemap: &HashSet<&str>, start: &str) -> Issues {
+    let index: HashMap<String, usize> = pages.iter().enumerate().map(|(i, p)| (p.url.clone(), i)).collect();
+
+    // Sources per target page, counting each linking page once.
+    let mut sources: HashMap<usize, Vec<usize>> = HashMap::new();
+    for (from, page) in pages.iter().enumerate() {
+        for link in &page.links {
+            if let Some(&to) = index.get(link) {
+                if to != from {
+                    sources.entry(to).or_default().push(from);
+                }
+            }
+        }
+    }
+    let linked_from = |to: usize| -> Vec<String> {
+        sources.get(&to).into_iter().flatten().take(MAX_SOURCES).map(|&from| pages[from].url.clone()).collect()
+    };
+
+    let mut broken_links = Vec::new();
+    let mut redirect_chains = Vec::new();
+    for (i, page) in pages.iter().enumerate() {
+        let linked = sources.contains_key(&i);
+        if linked && (page.status.is_none() || page.status.is_some_and(|s| s >= 400)) {
+            broken_links.push(BrokenLink {
+                url: page.url.clone(),
+                status: page.status,
+                error: page.error.clone(),
+                linked_from: linked_from(i),
+            });
+        }
+        if linked && !page.redirects.is_empty() {
+            redirect_chains.push(RedirectChain {
+                url: page.url.clone(),
+                final_url: page.final_url.clone(),
+                hops: page.redirects.clone(),
+                linked_from: linked_from(i),
+            });
+        }
+    }
+    // Longest chains first.
+    redirect_chains.sort_by_key(|chain| std::cmp::Reverse(chain.hops.len()));
+
+    // Pages are checked under their final URL, once each, however many
+    // redirecting URLs led to them.
+    let mut checked = HashSet::new();
+    let documents: Vec<&CrawledPage> = pages
+        .iter()
+        .filter(|p| p.ok_html() && checked.insert(p.final_url.as_str()))
+        .collect();
+    let mut titles: BTreeMap<String, DuplicateTitle> = BTreeMap::new();
+    for page in &documents {
+        if let Some(title) = &page.title {
+            titles
+                .entry(title.trim().to_lowercase())
+                .or_insert_with(|| DuplicateTitle { title: title.trim().to_string(), urls: Vec::new() })
+                .urls
+                .push(page.final_url.clone());
+        }
+    }
+    let duplicate_titles = titles.into_values().filter(|group| group.urls.len() > 1).collect();
+    let missing_titles = documents.iter().filter(|p| p.title.is_none()).map(|p| p.final_url.clone()).collect();
+    let missing_descriptions = documents.iter().filter(|p| p.description.is_none()).map(|p| p.final_url.clone()).collect();
+
+    // Any link to a page, whether to its URL or to one redirecting to it, counts.
+    let mut inbound: HashMap<&str, usize> = HashMap::new();
+    for (to, from) in &sources {
+        *inbound.entry(pages[*to].final_url.as_str()).or_default() += from.len();
+    }
+    let orphan_pages = documents
+        .iter()
+        .filter(|p| p.url != start && in_sitemap.contains(p.url.as_str()) && !inbound.contains_key(p.final_url.as_str()))
+        .map(|p| p.url.clone())
+        .collect();
+    let inlinks: Vec<usize> = pages.iter().map(|p| inbound.get(p.final_url.as_str()).copied().unwrap_or(0)).collect();
+    for (page, count) in pages.iter_mut().zip(inlinks) {
+        page.inlinks = count;
+    }
+
+    Issues {
+        broken_links,
+        duplicate_titles,
+        missing_titles,
+        missing_descriptions,
+        orphan_pages,
+        redirect_chains,
+    }
+}
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+
+    fn url(path: &str) -> Url {
+        Url::parse("https://example.com").unwrap().join(path).unwrap()
+    }
+
+    #[test]
+    fn parses_robots_groups() {
+        let robots = Robots::parse(
+            "User-agent: *\n\
+             Disallow: /private\n\
+             Crawl-delay: 10\n\
+             \n\
+             # Our own group wins over the wildcard.\n\
+             User-agent: Googlebot\n\
+             User-agent: NexusAI-SEO\n\
+             Disallow: /admin # trailing comment\n\
+             Allow: /admin/public\n\
+             Disallow:\n\
+             Crawl-delay: 0.5\n\
+             \n\
+             Sitemap: https://example.com/sitemap.xml\n",
+        );
+        assert_eq!(robots.rules.len(), 2);
+        assert_eq!(robots.crawl_delay, Some(0.5));
+        assert_eq!(robots.sitemaps, ["https://example.com/sitemap.xml"]);
+        assert!(robots.allows(&url("/private")));
+        assert!(!robots.allows(&url("/admin/users")));
+        assert!(robots.allows(&url("/admin/public/page")));
+    }
+
+    #[test]
+    fn falls_back_to_wildcard_group() {
+        let robots = Robots::parse("User-agent: otherbot\nDisallow: /\n\nUser-agent: *\nDisallow: /tmp\nCrawl-delay: -1\n");
+        assert!(robots.allows(&url("/")));
+        assert!(!robots.allows(&url("/tmp/file")));
+        assert_eq!(robots.crawl_delay, None);
+        assert!(Robots::parse("").allows(&url("/anything")));
+    }
+
+    #[test]
+    fn matches_wildcards_and_anchors() {
+        let robots = Robots::parse("User-agent: *\nDisallow: /*.pdf$\nDisallow: /search?*q=\nAllow: /page\nDisallow: /page\n");
+        assert!(!robots.allows(&url("/docs/file.pdf")));
+        assert!(robots.allows(&url("/docs/file.pdf?download=1")));
+        assert!(!robots.allows(&url("/search?lang=en&q=rust")));
+        assert!(robots.allows(&url("/search")));
+        // Allow wins a tie between equally long patterns.
+        assert!(robots.allows(&url("/page")));
+    }
+
+    #[test]
+    fn caps_request_delay() {
+        assert_eq!(request_delay(None), DEFAULT_DELAY);
+        assert_eq!(request_delay(Some(0.5)), Duration::from_millis(500));
+        assert_eq!(request_delay(Some(30.0)), MAX_DELAY);
+        assert_eq!(request_delay(Some(1e300)), MAX_DELAY);
+    }
+}
@@ -0,0 +1,127 @@
+/// Lines of context around each change in a unified diff.
+const CONTEXT: usize = 3;
+
+enum Op<'a> {
+    Same(&'a str),
+    Removed(&'a str),
+    Added(&'a str),
+}
+
+/// Line edit script via longest common subsequence.
+fn edits<'a>(old: &[&'a str], new: &[&'a str]) -> Vec<Op<'a>> {
+    let (n, m) = (old.len(), new.len());
+    // lcs[i][j] = LCS length of old[i..] and new[j..].
+    let mut lcs = vec![vec![0u32; m + 1]; n + 1];
+    for i in (0..n).rev() {
+        for j in (0..m).rev() {
+            lcs[i][j] = if old[i] == new[j] {
+                lcs[i + 1][j + 1] + 1
+            } else {
+                lcs[i + 1][j].max(lcs[i][j + 1])
+            };
+        }
+    }
+
+    let (mut i, mut j) = (0, 0);
+    let mut ops = Vec::with_capacity(n.max(m));
+    while i < n || j < m {
+        if i < n && j < m && old[i] == new[j] {
+            ops.push(Op::Same(old[i]));
+            i += 1;
+            j += 1;
+        } else if j < m && (i == n || lcs[i][j + 1] > lcs[i + 1][j]) {
+            ops.push(Op::Added(new[j]));
+            j += 1;
+        } else {
+            ops.push(Op::Removed(old[i]));
+            i += 1;
+        }
+    }
+    ops
+}
+
+/// Unified diff (`---` / `+++` headers and `@@` hunks) of two texts, or an
+/// empty string when they are identical.
+pub fn unified(old: &str, new: &str, old_name: &str, new_name: &str) -> String {
+    let old_lines: Vec<&str> = old.lines().collect();
+    let new_lines: Vec<&str> = new.lines().collect();
+    let ops = edits(&old_lines, &new_lines);
+    if ops.iter().all(|op| matches!(op, Op::Same(_))) {
+        return String::new();
+    }
+
+    let changed: Vec<usize> = ops
+        .iter()
+        .enumerate()
+        .filter(|(_, op)| !matches!(op, Op::Same(_)))
+        .map(|(i, _)| i)
+        .collect();
+
+    // Group changes whose context windows touch into hunks of op indices.
+    let mut hunks: Vec<(usize, usize)> = Vec::new();
+    for &i in &changed {
+        let start = i.saturating_sub(CONTEXT);
+        let end = (i + CONTEXT + 1).min(ops.len());
+        match hunks.last_mut() {
+            Some(last) if start <= last.1 => last.1 = end,
+            _ => hunks.push((start, end)),
+        }
+    }
+
+    let mut out = format!("--- {}\n+++ {}\n", old_name, new_name);
+    // Line numbers (1-based) at the start of each op.
+    let mut old_at = vec![0usize; ops.len() + 1];
+    let mut new_at = vec![0usize; ops.len() + 1];
+    for (k, op) in ops.iter().enumerate() {
+        old_at[k + 1] = old_at[k] + usize::from(!matches!(op, Op::Added(_)));
+        new_at[k + 1] = new_at[k] + usize::from(!matches!(op, Op::Removed(_)));
+    }
+
+    for (start, end) in hunks {
+        let old_len = old_at[end] - old_at[start];
+        let new_len = new_at[end] - new_at[start];
+        // Empty ranges point at the line before, as in GNU diff.
+        let old_start = if old_len == 0 { old_at[start] } else { old_at[start] + 1 };
+        let new_start = if new_len == 0 { new_at[start] } else { new_at[start] + 1 };
+        out.push_str(&format!("@@ -{},{} +{},{} @@\n", old_start, old_len, new_start, new_len));
+        for op in &ops[start..end] {
+            let (prefix, line) = match op {
+                Op::Same(line) => (' ', line),
+                Op::Removed(line) => ('-', line),
+                Op::Added(line) => ('+', line),
+            };
+            out.push(prefix);
+            out.push_str(line);
+            out.push('\n');
+        }
+    }
+    out
+}
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+
+    #[test]
+    fn identical_texts_have_no_diff() {
+        assert_eq!(unified("a\nb\n", "a\nb\n", "old", "new"), "");
+    }
+
+    #[test]
+    fn writes_hunks_with_context() {
+        let old = "1\n2\n3\n4\n5\n6\n7\n8\n9\n10\n11\n12\n";
+        let new = "1\n2\nthree\n4\n5\n6\n7\n8\n9\n10\n11\n12\n13\n";
+        assert_eq!(
+            unified(old, new, "resume.md", "tailored.md"),
+            "--- resume.md\n+++ tailored.md\n\
+             @@ -1,6 +1,6 @@\n 1\n 2\n-3\n+three\n 4\n 5\n 6\n\
+             @@ -10,3 +10,4 @@\n 10\n 11\n 12\n+13\n"
+        );
+    }
+
+    #[test]
+    fn empty_sides_point_at_the_previous_line() {
+        assert_eq!(unified("", "a\n", "old", "new"), "--- old\n+++ new\n@@ -0,0 +1,1 @@\n+a\n");
+        assert_eq!(unified("a\n", "", "old", "new"), "--- old\n+++ new\n@@ -1,1 +0,0 @@\n-a\n");
+    }
+}
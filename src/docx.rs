@@ -0,0 +1,467 @@
+use std::io::{Cursor, Write};
+
+use zip::write::SimpleFileOptions;
+
+use crate::html::{self, safe_link};
+use crate::markdown::{self, Block, Span};
+use crate::pdf::{FontFamily, PageSize, Template};
+
+pub struct DocxOptions {
+    pub template: Template,
+    pub font: FontFamily,
+    pub page_size: PageSize,
+    pub title: String,
+    pub author: String,
+    /// Keep single line breaks inside paragraphs (letters, e-mails).
+    pub line_breaks: bool,
+}
+
+/// Escapes text for the package's XML parts. Unlike HTML, XML 1.0 cannot
+/// carry most C0 control characters at all (not even as references), so they
+/// are dropped.
+fn escape(text: &str) -> String {
+    let valid: String = text
+        .chars()
+        .filter(|c| !matches!(c, '\u{0}'..='\u{8}' | '\u{b}' | '\u{c}' | '\u{e}'..='\u{1f}' | '\u{fffe}' | '\u{ffff}'))
+        .collect();
+    html::escape(&valid)
+}
+
+const WORD_NS: &str = "http://schemas.openxmlformats.org/wordprocessingml/2006/main";
+const REL_NS: &str = "http://schemas.openxmlformats.org/officeDocument/2006/relationships";
+const PACKAGE_REL_NS: &str = "http://schemas.openxmlformats.org/package/2006/relationships";
+
+/// Word counterparts of the PDF templates. Sizes are in points.
+struct Style {
+    body_size: f32,
+    /// Title, section heading, sub-heading.
+    heading_sizes: [f32; 3],
+    margin: f32,
+    block_gap: f32,
+    accent: &'static str,
+    center_header: bool,
+    uppercase_sections: bool,
+    section_rule: bool,
+    justify: bool,
+}
+
+fn style(template: Template) -> Style {
+    match template {
+        Template::Classic => Style {
+            body_size: 10.5,
+            heading_sizes: [22.0, 12.0, 11.0],
+            margin: 60.0,
+            block_gap: 5.0,
+            accent: "1A1A1A",
+            center_header: true,
+            uppercase_sections: true,
+            section_rule: true,
+            justify: true,
+        },
+        Template::Modern => Style {
+            body_size: 10.0,
+            heading_sizes: [24.0, 13.0, 11.0],
+            margin: 54.0,
+            block_gap: 5.0,
+            accent: "2161AB",
+            center_header: false,
+            uppercase_sections: false,
+            section_rule: true,
+            justify: false,
+        },
+        Template::Compact => Style {
+            body_size: 9.0,
+            heading_sizes: [17.0, 10.5, 9.5],
+            margin: 40.0,
+            block_gap: 3.0,
+            accent: "333333",
+            center_header: false,
+            uppercase_sections: true,
+            section_rule: false,
+            justify: true,
+        },
+    }
+}
+
+/// Word measures font sizes in half-points and lengths in twentieths of a point.
+fn half_points(pt: f32) -> u32 {
+    (pt * 2.0).round() as u32
+}
+
+fn twips(pt: f32) -> u32 {
+    (pt * 20.0).round() as u32
+}
+
+/// Builds `word/document.xml` body content, collecting hyperlink targets and
+/// the start numbers of ordered lists along the way.
+struct Writer {
+    body: String,
+    links: Vec<String>,
+    /// One entry per ordered list; list `i` uses numbering id `i + 2`.
+    ordered_lists: Vec<u32>,
+    line_breaks: bool,
+}
+
+impl Writer {
+    fn runs(&mut self, spans: &[Span]) -> String {
+        let mut out = String::new();
+        for span in spans {
+            // Run properties in schema order: rStyle, rFonts, b, i.
+            let link = span.link.as_deref().and_then(safe_link);
+            let mut props = String::new();
+            if link.is_some() {
+                props.push_str("<w:rStyle w:val=\"Hyperlink\"/>");
+            }
+            if span.code {
+                props.push_str("<w:rFonts w:ascii=\"Consolas\" w:hAnsi=\"Consolas\" w:cs=\"Consolas\"/>");
+            }
+            if span.bold {
+                props.push_str("<w:b/>");
+            }
+            if span.italic {
+                props.push_str("<w:i/>");
+            }
+            let props = if props.is_empty() { String::new() } else { format!("<w:rPr>{}</w:rPr>", props) };
+
+            let text = if self.line_breaks { span.text.clone() } else { span.text.replace('\n', " ") };
+            let mut run = format!("<w:r>{}", props);
+            for (i, line) in text.split('\n').enumerate() {
+                if i > 0 {
+                    run.push_str("<w:br/>");
+                }
+                run.push_str(&format!("<w:t xml:space=\"preserve\">{}</w:t>", escape(line)));
+            }
+            run.push_str("</w:r>");
+
+            match link {
+                Some(url) => {
+                    self.links.push(url.to_string());
+                    out.push_str(&format!("<w:hyperlink r:id=\"rIdLink{}\">{}</w:hyperlink>", self.links.len(), run));
+                }
+                None => out.push_str(&run),
+            }
+        }
+        out
+    }
+
+    fn paragraph(&mut self, props: &str, spans: &[Span]) {
+        let runs = self.runs(spans);
+        let props = if props.is_empty() { String::new() } else { format!("<w:pPr>{}</w:pPr>", props) };
+        self.body.push_str(&format!("<w:p>{}{}</w:p>", props, runs));
+    }
+}
+
+fn document_xml(blocks: &[Block], style: &Style, options: &DocxOptions) -> (String, Vec<String>, Vec<u32>) {
+    let mut writer = Writer { body: String::new(), links: Vec::new(), ordered_lists: Vec::new(), line_breaks: options.line_breaks };
+    let has_sections = blocks.iter().any(|b| matches!(b, Block::Heading { level: 2, .. }));
+    let mut in_header = has_sections;
+    let mut previous_ordered = false;
+
+    for block in blocks {
+        if matches!(block, Block::Heading { level: 2, .. }) {
+            in_header = false;
+        }
+        let center = if in_header && style.center_header { "<w:jc w:val=\"center\"/>" } else { "" };
+        let ordered = matches!(block, Block::ListItem { number: Some(_), .. });
+        match block {
+            Block::Heading { level, spans } => {
+                let name = match level {
+                    1 => "Title",
+                    2 => "Heading1",
+                    _ => "Heading2",
+                };
+                writer.paragraph(&format!("<w:pStyle w:val=\"{}\"/>{}", name, center), spans);
+            }
+            Block::Paragraph(spans) => writer.paragraph(center, spans),
+            Block::ListItem { number, spans } => {
+                let id = match number {
+                    Some(start) => {
+                        // A new ordered list restarts numbering at its first number.
+                        if !previous_ordered {
+                            writer.ordered_lists.push(*start);
+                        }
+                        writer.ordered_lists.len() + 1
+                    }
+                    None => 1,
+                };
+                writer.paragraph(
+                    &format!(
+                        "<w:pStyle w:val=\"ListParagraph\"/><w:numPr><w:ilvl w:val=\"0\"/><w:numId w:val=\"{}\"/></w:numPr>",
+                        id
+                    ),
+                    spans,
+                );
+            }
+            Block::Quote(spans) => writer.paragraph("<w:pStyle w:val=\"Quote\"/>", spans),
+            Block::Code(code) => {
+                let span = Span { text: code.clone(), code: true, ..Span::default() };
+                let line_breaks = std::mem::replace(&mut writer.line_breaks, true);
+                writer.paragraph("<w:pStyle w:val=\"Code\"/>", &[span]);
+                writer.line_breaks = line_breaks;
+            }
+            // Section headings already carry a rule in these templates.
+            Block::Rule if style.section_rule => {}
+            Block::Rule => writer.body.push_str(
+                "<w:p><w:pPr><w:pBdr><w:bottom w:val=\"single\" w:sz=\"6\" w:space=\"1\" w:color=\"BFBFBF\"/></w:pBdr></w:pPr></w:p>",
+            ),
+        }
+        previous_ordered = ordered;
+    }
+
+    let (width, height) = options.page_size.dimensions();
+    let margin = twips(style.margin);
+    let xml = format!(
+        "<?xml version=\"1.0\" encoding=\"UTF-8\" standalone=\"yes\"?>\
+         <w:document xmlns:w=\"{}\" xmlns:r=\"{}\"><w:body>{}\
+         <w:sectPr><w:pgSz w:w=\"{}\" w:h=\"{}\"/>\
+         <w:pgMar w:top=\"{m}\" w:right=\"{m}\" w:bottom=\"{m}\" w:left=\"{m}\" w:header=\"708\" w:footer=\"708\" w:gutter=\"0\"/>\
+         </w:sectPr></w:body></w:document>",
+        WORD_NS,
+        REL_NS,
+        writer.body,
+        twips(width),
+        twips(height),
+        m = margin
+    );
+    (xml, writer.links, writer.ordered_lists)
+}
+
+fn styles_xml(style: &Style, font: FontFamily) -> String {
+    let font = match font {
+        FontFamily::Serif => "Cambria",
+        FontFamily::Sans => "Calibri",
+    };
+    let heading = |id: &str, name: &str, size: f32, section: bool| {
+        let mut run = format!("<w:b/><w:color w:val=\"{}\"/><w:sz w:val=\"{}\"/>", style.accent, half_points(size));
+        let mut para = format!("<w:keepNext/><w:spacing w:before=\"{}\" w:after=\"{}\"/>", twips(size * 0.8), twips(style.block_gap));
+        if section && style.uppercase_sections {
+            run = format!("<w:b/><w:caps/><w:color w:val=\"{}\"/><w:spacing w:val=\"10\"/><w:sz w:val=\"{}\"/>", style.accent, half_points(size));
+        }
+        if section && style.section_rule {
+            para.push_str(&format!(
+                "<w:pBdr><w:bottom w:val=\"single\" w:sz=\"6\" w:space=\"1\" w:color=\"{}\"/></w:pBdr>",
+                style.accent
+            ));
+        }
+        format!(
+            "<w:style w:type=\"paragraph\" w:styleId=\"{}\"><w:name w:val=\"{}\"/><w:basedOn w:val=\"Normal\"/>\
+             <w:next w:val=\"Normal\"/><w:qFormat/><w:pPr>{}</w:pPr><w:rPr>{}</w:rPr></w:style>",
+            id, name, para, run
+        )
+    };
+    let justify = if style.justify { "<w:jc w:val=\"both\"/>" } else { "" };
+
+    format!(
+        "<?xml version=\"1.0\" encoding=\"UTF-8\" standalone=\"yes\"?>\
+         <w:styles xmlns:w=\"{ns}\">\
+         <w:docDefaults><w:rPrDefault><w:rPr>\
+         <w:rFonts w:ascii=\"{font}\" w:hAnsi=\"{font}\" w:eastAsia=\"{font}\" w:cs=\"{font}\"/>\
+         <w:color w:val=\"222222\"/><w:sz w:val=\"{body}\"/><w:szCs w:val=\"{body}\"/><w:lang w:val=\"en-US\"/>\
+         </w:rPr></w:rPrDefault>\
+         <w:pPrDefault><w:pPr><w:spacing w:after=\"{gap}\" w:line=\"276\" w:lineRule=\"auto\"/></w:pPr></w:pPrDefault>\
+         </w:docDefaults>\
+         <w:style w:type=\"paragraph\" w:default=\"1\" w:styleId=\"Normal\"><w:name w:val=\"Normal\"/><w:qFormat/>\
+         <w:pPr>{justify}</w:pPr></w:style>\
+         {title}{h1}{h2}\
+         <w:style w:type=\"paragraph\" w:styleId=\"ListParagraph\"><w:name w:val=\"List Paragraph\"/>\
+         <w:basedOn w:val=\"Normal\"/><w:qFormat/><w:pPr><w:spacing w:after=\"40\"/><w:ind w:left=\"720\"/></w:pPr></w:style>\
+         <w:style w:type=\"paragraph\" w:styleId=\"Quote\"><w:name w:val=\"Quote\"/><w:basedOn w:val=\"Normal\"/>\
+         <w:qFormat/><w:pPr><w:ind w:left=\"567\"/></w:pPr><w:rPr><w:i/><w:color w:val=\"555555\"/></w:rPr></w:style>\
+         <w:style w:type=\"paragraph\" w:styleId=\"Code\"><w:name w:val=\"Code\"/><w:basedOn w:val=\"Normal\"/>\
+         <w:pPr><w:shd w:val=\"clear\" w:color=\"auto\" w:fill=\"F6F6F6\"/><w:jc w:val=\"left\"/></w:pPr>\
+         <w:rPr><w:rFonts w:ascii=\"Consolas\" w:hAnsi=\"Consolas\" w:cs=\"Consolas\"/><w:sz w:val=\"{code}\"/></w:rPr></w:style>\
+         <w:style w:type=\"character\" w:styleId=\"Hyperlink\"><w:name w:val=\"Hyperlink\"/>\
+         <w:rPr><w:color w:val=\"{accent}\"/><w:u w:val=\"single\"/></w:rPr></w:style>\
+         </w:styles>",
+        ns = WORD_NS,
+        font = font,
+        body = half_points(style.body_size),
+        gap = twips(style.block_gap),
+        justify = justify,
+        title = heading("Title", "Title", style.heading_sizes[0], false),
+        h1 = heading("Heading1", "heading 1", style.heading_sizes[1], true),
+        h2 = heading("Heading2", "heading 2", style.heading_sizes[2], false),
+        code = half_points(style.body_size * 0.9),
+        accent = style.accent,
+    )
+}
+
+/// Bullet list (id 1) plus one decimal list per ordered Markdown list, each
+/// restarting at its own first number.
+fn numbering_xml(ordered_lists: &[u32]) -> String {
+    let mut nums = String::from("<w:num w:numId=\"1\"><w:abstractNumId w:val=\"0\"/></w:num>");
+    for (i, start) in ordered_lists.iter().enumerate() {
+        nums.push_str(&format!(
+            "<w:num w:numId=\"{}\"><w:abstractNumId w:val=\"1\"/>\
+             <w:lvlOverride w:ilvl=\"0\"><w:startOverride w:val=\"{}\"/></w:lvlOverride></w:num>",
+            i + 2,
+            start
+        ));
+    }
+    format!(
+        "<?xml version=\"1.0\" encoding=\"UTF-8\" standalone=\"yes\"?>\
+         <w:numbering xmlns:w=\"{}\">\
+         <w:abstractNum w:abstractNumId=\"0\"><w:multiLevelType w:val=\"singleLevel\"/>\
+         <w:lvl w:ilvl=\"0\"><w:start w:val=\"1\"/><w:numFmt w:val=\"bullet\"/><w:lvlText w:val=\"•\"/>\
+         <w:lvlJc w:val=\"left\"/><w:pPr><w:ind w:left=\"360\" w:hanging=\"240\"/></w:pPr></w:lvl></w:abstractNum>\
+         <w:abstractNum w:abstractNumId=\"1\"><w:multiLevelType w:val=\"singleLevel\"/>\
+         <w:lvl w:ilvl=\"0\"><w:start w:val=\"1\"/><w:numFmt w:val=\"decimal\"/><w:lvlText w:val=\"%1.\"/>\
+         <w:lvlJc w:val=\"left\"/><w:pPr><w:ind w:left=\"360\" w:hanging=\"300\"/></w:pPr></w:lvl></w:abstractNum>\
+         {}</w:numbering>",
+        WORD_NS, nums
+    )
+}
+
+fn document_rels_xml(links: &[String]) -> String {
+    let mut rels = format!(
+        "<Relationship Id=\"rIdStyles\" Type=\"{0}/styles\" Target=\"styles.xml\"/>\
+         <Relationship Id=\"rIdNumbering\" Type=\"{0}/numbering\" Target=\"numbering.xml\"/>",
+        REL_NS
+    );
+    for (i, url) in links.iter().enumerate() {
+        rels.push_str(&format!(
+            "<Relationship Id=\"rIdLink{}\" Type=\"{}/hyperlink\" Target=\"{}\" TargetMode=\"External\"/>",
+            i + 1,
+            REL_NS,
+            escape(url)
+        ));
+    }
+    format!(
+        "<?xml version=\"1.0\" encoding=\"UTF-8\" standalone=\"yes\"?><Relationships xmlns=\"{}\">{}</Relationships>",
+        PACKAGE_REL_NS, rels
+    )
+}
+
+const CONTENT_TYPES_XML: &str = "<?xml version=\"1.0\" encoding=\"UTF-8\" standalone=\"yes\"?>\
+<Types xmlns=\"http://schemas.openxmlformats.org/package/2006/content-types\">\
+<Default Extension=\"rels\" ContentType=\"application/vnd.openxmlformats-package.relationships+xml\"/>\
+<Default Extension=\"xml\" ContentType=\"application/xml\"/>\
+<Override PartName=\"/word/document.xml\" ContentType=\"application/vnd.openxmlformats-officedocument.wordprocessingml.document.main+xml\"/>\
+<Override PartName=\"/word/styles.xml\" ContentType=\"application/vnd.openxmlformats-officedocument.wordprocessingml.styles+xml\"/>\
+<Override PartName=\"/word/numbering.xml\" ContentType=\"application/vnd.openxmlformats-officedocument.wordprocessingml.numbering+xml\"/>\
+<Override PartName=\"/docProps/core.xml\" ContentType=\"application/vnd.openxmlformats-package.core-properties+xml\"/>\
+</Types>";
+
+fn package_rels_xml() -> String {
+    format!(
+        "<?xml version=\"1.0\" encoding=\"UTF-8\" standalone=\"yes\"?><Relationships xmlns=\"{}\">\
+         <Relationship Id=\"rId1\" Type=\"{}/officeDocument\" Target=\"word/document.xml\"/>\
+         <Relationship Id=\"rId2\" Type=\"http://schemas.openxmlformats.org/package/2006/relationships/metadata/core-properties\" \
+         Target=\"docProps/core.xml\"/></Relationships>",
+        PACKAGE_REL_NS, REL_NS
+    )
+}
+
+fn core_xml(options: &DocxOptions) -> String {
+    let now = chrono::Utc::now().format("%Y-%m-%dT%H:%M:%SZ");
+    format!(
+        "<?xml version=\"1.0\" encoding=\"UTF-8\" standalone=\"yes\"?>\
+         <cp:coreProperties xmlns:cp=\"http://schemas.openxmlformats.org/package/2006/metadata/core-properties\" \
+         xmlns:dc=\"http://purl.org/dc/elements/1.1/\" xmlns:dcterms=\"http://purl.org/dc/terms/\" \
+         xmlns:xsi=\"http://www.w3.org/2001/XMLSchema-instance\">\
+         <dc:title>{}</dc:title><dc:creator>{}</dc:creator>\
+         <dcterms:created xsi:type=\"dcterms:W3CDTF\">{now}</dcterms:created>\
+         <dcterms:modified xsi:type=\"dcterms:W3CDTF\">{now}</dcterms:modified>\
+         </cp:coreProperties>",
+        escape(&options.title),
+        escape(&options.author),
+        now = now
+    )
+}
+
+/// Renders Markdown as a Word document using named styles (Title, Heading 1,
+/// List Paragraph, ...) so the result stays editable, with the page size,
+/// margins and colours of the chosen template.
+pub fn render(source: &str, options: &DocxOptions) -> Result<Vec<u8>, String> {
+    let blocks = if options.line_breaks {
+        markdown::parse_with_breaks(source)
+    } else {
+        markdown::parse(source)
+    };
+    let style = style(options.template);
+    let (document, links, ordered_lists) = document_xml(&blocks, &style, options);
+
+    let parts = [
+        ("[Content_Types].xml", CONTENT_TYPES_XML.to_string()),
+        ("_rels/.rels", package_rels_xml()),
+        ("docProps/core.xml", core_xml(options)),
+        ("word/document.xml", document),
+        ("word/styles.xml", styles_xml(&style, options.font)),
+        ("word/numbering.xml", numbering_xml(&ordered_lists)),
+        ("word/_rels/document.xml.rels", document_rels_xml(&links)),
+    ];
+
+    let mut zip = zip::ZipWriter::new(Cursor::new(Vec::new()));
+    let file_options = SimpleFileOptions::default().compression_method(zip::CompressionMethod::Deflated);
+    for (name, content) in parts {
+        zip.start_file(name, file_options).map_err(|e| format!("DOCX packaging failed: {}", e))?;
+        zip.write_all(content.as_bytes()).map_err(|e| format!("DOCX packaging failed: {}", e))?;
+    }
+    zip.finish()
+        .map(Cursor::into_inner)
+        .map_err(|e| format!("DOCX packaging failed: {}", e))
+}
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+    use std::io::Read;
+
+    fn options(title: &str) -> DocxOptions {
+        DocxOptions {
+            template: Template::Modern,
+            font: FontFamily::Sans,
+            page_size: PageSize::Letter,
+            title: title.to_string(),
+            author: "Jane \"JD\" Doe".to_string(),
+            line_breaks: true,
+        }
+    }
+
+    fn part(docx: &[u8], name: &str) -> String {
+        let mut archive = zip::ZipArchive::new(Cursor::new(docx)).unwrap();
+        let mut xml = String::new();
+        archive.by_name(name).unwrap().read_to_string(&mut xml).unwrap();
+        xml
+    }
+
+    #[test]
+    fn escape_drops_characters_xml_cannot_carry() {
+        assert_eq!(escape("a\u{0}b\u{7}c\u{1b}d\u{fffe}"), "abcd");
+        assert_eq!(escape("tab\tline\nreturn\r"), "tab\tline\nreturn\r");
+        assert_eq!(escape("<a href=\"x\">Tom & Jerry's</a>"), "&lt;a href=&quot;x&quot;&gt;Tom &amp; Jerry&#39;s&lt;/a&gt;");
+    }
+
+    #[test]
+    fn document_parts_are_well_formed_xml() {
+        let source = "# Tom & Jerry <3\n\nLine one\u{0}\nline \u{1b}[31mtwo\u{8}\n\n\
+                      - **Bold** *\"quoted\"* `a < b`\n- [Search](https://example.com/?q=a&lang=en)\n\n\
+                      1. First\u{c}\n2. Second\n";
+        let docx = render(source, &options("Report\u{1} <draft> & notes")).unwrap();
+
+        for name in ["[Content_Types].xml", "_rels/.rels", "word/document.xml", "word/_rels/document.xml.rels",
+                     "word/styles.xml", "word/numbering.xml", "docProps/core.xml"] {
+            let xml = part(&docx, name);
+            roxmltree::Document::parse(&xml).unwrap_or_else(|e| panic!("{}: {}", name, e));
+        }
+
+        let document = part(&docx, "word/document.xml");
+        let document = roxmltree::Document::parse(&document).unwrap();
+        let text: String = document
+            .descendants()
+            .filter(|n| n.has_tag_name((WORD_NS, "t")))
+            .filter_map(|n| n.text())
+            .collect::<Vec<_>>()
+            .join("|");
+        assert!(text.contains("Tom & Jerry <3"), "{}", text);
+        assert!(text.contains("Line one|line [31mtwo"), "{}", text);
+        assert!(text.contains("\"quoted\"") && text.contains("a < b"), "{}", text);
+
+        let rels = part(&docx, "word/_rels/document.xml.rels");
+        let rels = roxmltree::Document::parse(&rels).unwrap();
+        assert!(rels.descendants().any(|n| n.attribute("Target") == Some("https://example.com/?q=a&lang=en")));
+
+        let core = part(&docx, "docProps/core.xml");
+        assert!(core.contains("<dc:title>Report &lt;draft&gt; &amp; notes</dc:title>"), "{}", core);
+    }
+}
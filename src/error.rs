@@ -10,15 +10,36 @@ pub enum AppError {
     RateLimited(String),
     InvalidInput(String),
     NotFound(String),
+    Forbidden(String),
+    PayloadTooLarge(String),
+    Unavailable(String),
     InternalError(String),
 }
 
+impl AppError {
+    /// Client-facing message, for errors reported outside an HTTP response (e.g. SSE).
+    pub fn message(&self) -> &str {
+        match self {
+            AppError::RateLimited(msg)
+            | AppError::InvalidInput(msg)
+            | AppError::NotFound(msg)
+            | AppError::Forbidden(msg)
+            | AppError::PayloadTooLarge(msg)
+            | AppError::Unavailable(msg)
+            | AppError::InternalError(msg) => msg,
+        }
+    }
+}
+
 impl IntoResponse for AppError {
     fn into_response(self) -> Response {
         let (status, error_message) = match self {
             AppError::RateLimited(msg) => (StatusCode::TOO_MANY_REQUESTS, msg),
             AppError::InvalidInput(msg) => (StatusCode::BAD_REQUEST, msg),
             AppError::NotFound(msg) => (StatusCode::NOT_FOUND, msg),
+            AppError::Forbidden(msg) => (StatusCode::FORBIDDEN, msg),
+            AppError::PayloadTooLarge(msg) => (StatusCode::PAYLOAD_TOO_LARGE, msg),
+            AppError::Unavailable(msg) => (StatusCode::SERVICE_UNAVAILABLE, msg),
             AppError::InternalError(msg) => (StatusCode::INTERNAL_SERVER_ERROR, msg),
         };
 
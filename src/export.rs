@@ -0,0 +1,152 @@
+use axum::{
+    body::Body,
+    http::{header, Response},
+    response::IntoResponse,
+};
+
+use crate::docx::{self, DocxOptions};
+use crate::html::{self, HtmlOptions};
+use crate::pdf::{self, FontFamily, PageSize, PdfOptions, Template};
+
+/// Download formats for generated documents.
+#[derive(Clone, Copy)]
+pub enum Format {
+    Pdf,
+    Docx,
+    Html,
+}
+
+impl Format {
+    pub fn content_type(self) -> &'static str {
+        match self {
+            Self::Pdf => "application/pdf",
+            Self::Docx => "application/vnd.openxmlformats-officedocument.wordprocessingml.document",
+            Self::Html => "text/html; charset=utf-8",
+        }
+    }
+
+    pub fn extension(self) -> &'static str {
+        match self {
+            Self::Pdf => "pdf",
+            Self::Docx => "docx",
+            Self::Html => "html",
+        }
+    }
+}
+
+/// `jane-doe-resume.pdf` from a title and a kind of document; just
+/// `resume.pdf` when the title has nothing usable.
+pub fn filename(title: &str, kind: &str, format: Format) -> String {
+    let slug: String = title
+        .split(|c: char| !c.is_ascii_alphanumeric())
+        .filter(|part| !part.is_empty())
+        .map(str::to_lowercase)
+        .collect::<Vec<_>>()
+        .join("-");
+    if slug.is_empty() {
+        format!("{}.{}", kind, format.extension())
+    } else {
+        format!("{}-{}.{}", slug, kind, format.extension())
+    }
+}
+
+/// File download with `Content-Disposition: attachment`.
+pub fn attachment(format: Format, filename: &str, bytes: Vec<u8>) -> Response<Body> {
+    (
+        [
+            (header::CONTENT_TYPE, format.content_type().to_string()),
+            (header::CONTENT_DISPOSITION, format!("attachment; filename=\"{}\"", filename)),
+            (header::CONTENT_LENGTH, bytes.len().to_string()),
+        ],
+        bytes,
+    )
+        .into_response()
+}
+
+/// Resolves the template, font and page size named in an export request.
+/// The font defaults to the template's own.
+pub fn layout(
+    template: Option<&str>,
+    font: Option<&str>,
+    page_size: Option<&str>,
+    default_template: Template,
+) -> Result<(Template, FontFamily, PageSize), String> {
+    let template = match template {
+        Some(name) => Template::from_name(name).ok_or_else(|| {
+            format!("Unknown template '{}' (available: {})", name, pdf::TEMPLATES.join(", "))
+        })?,
+        None => default_template,
+    };
+    let font = match font {
+        Some(name) => FontFamily::from_name(name).ok_or_else(|| {
+            format!("Unknown font '{}' (available: {})", name, pdf::FONTS.join(", "))
+        })?,
+        None => template.default_font(),
+    };
+    let page_size = match page_size {
+        Some(name) => PageSize::from_name(name).ok_or_else(|| {
+            format!("Unknown page size '{}' (available: {})", name, pdf::PAGE_SIZES.join(", "))
+        })?,
+        None => PageSize::A4,
+    };
+    Ok((template, font, page_size))
+}
+
+pub struct ExportOptions {
+    pub template: Template,
+    pub font: FontFamily,
+    pub page_size: PageSize,
+    pub title: String,
+    pub author: String,
+    /// Keep single line breaks inside paragraphs (letters, e-mails). The PDF
+    /// typesetter always joins them.
+    pub line_breaks: bool,
+}
+
+/// Renders Markdown in `format` off the async runtime.
+pub async fn render(markdown: String, format: Format, options: ExportOptions) -> Result<Vec<u8>, String> {
+    let ExportOptions { template, font, page_size, title, author, line_breaks } = options;
+    tokio::task::spawn_blocking(move || match format {
+        Format::Pdf => pdf::render(&markdown, &PdfOptions { template, font, page_size, title, author }),
+        Format::Docx => docx::render(
+            &markdown,
+            &DocxOptions { template, font, page_size, title, author, line_breaks },
+        ),
+        Format::Html => Ok(html::render(
+            &markdown,
+            &HtmlOptions { template, font, page_size, title, author, line_breaks },
+        )
+        .into_bytes()),
+    })
+    .await
+    .unwrap_or_else(|e| Err(format!("{} rendering failed: {}", format.extension().to_uppercase(), e)))
+}
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+
+    #[test]
+    fn layout_defaults_follow_the_template() {
+        assert!(matches!(
+            layout(None, None, None, Template::Modern),
+            Ok((Template::Modern, FontFamily::Sans, PageSize::A4))
+        ));
+        assert!(matches!(
+            layout(Some("classic"), None, Some("letter"), Template::Modern),
+            Ok((Template::Classic, FontFamily::Serif, PageSize::Letter))
+        ));
+        assert!(matches!(
+            layout(Some("compact"), Some("serif"), None, Template::Classic),
+            Ok((Template::Compact, FontFamily::Serif, PageSize::A4))
+        ));
+
+        for (template, font, page_size, error) in [
+            (Some("fancy"), None, None, "Unknown template 'fancy' (available: classic, modern, compact)"),
+            (None, Some("comic"), None, "Unknown font 'comic' (available: serif, sans)"),
+            (None, None, Some("a3"), "Unknown page size 'a3' (available: a4, letter, legal)"),
+        ] {
+            assert_eq!(layout(template, font, page_size, Template::Classic).err().as_deref(), Some(error));
+        }
+    }
+}
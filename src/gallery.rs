@@ -0,0 +1,337 @@
+use dashmap::DashMap;
+use serde::{Deserialize, Serialize};
+use sha2::{Digest, Sha256};
+use std::path::{Path, PathBuf};
+use std::sync::Arc;
+
+/// Metadata stored next to every gallery image as `{hash}.{owner}.json`, one
+/// file per client that generated the image.
+#[derive(Clone, Serialize, Deserialize)]
+pub struct GalleryEntry {
+    /// SHA-256 of the image bytes; also the file name and the `/image/{hash}` id.
+    pub hash: String,
+    pub content_type: String,
+    pub size: usize,
+    pub prompt: String,
+    pub model: String,
+    pub params: serde_json::Value,
+    /// Identifies prompt + model + parameters (seed included) for deduplication.
+    pub request_key: String,
+    /// [`owner_id`] of the client's gallery token.
+    #[serde(alias = "client")]
+    pub owner: String,
+    pub created_at: String,
+}
+
+/// Storage quotas; the oldest images are evicted first.
+#[derive(Clone, Copy)]
+pub struct GalleryLimits {
+    pub max_per_owner: usize,
+    /// Total size of the stored images, each counted once.
+    pub max_total_bytes: u64,
+}
+
+/// Content-addressed image store on disk, indexed in memory.
+#[derive(Clone)]
+pub struct ImageGallery {
+    root: PathBuf,
+    limits: GalleryLimits,
+    /// Entries by image hash, one per owner.
+    entries: Arc<DashMap<String, Vec<GalleryEntry>>>,
+    by_request: Arc<DashMap<String, String>>,
+    /// Serialises stores so quota checks and eviction see a consistent index.
+    write_lock: Arc<tokio::sync::Mutex<()>>,
+}
+
+pub fn sha256_hex(bytes: &[u8]) -> String {
+    format!("{:x}", Sha256::digest(bytes))
+}
+
+/// Same prompt, model and parameters map to the same key.
+pub fn request_key(prompt: &str, model: &str, params: &serde_json::Value) -> String {
+    sha256_hex(format!("{}\n{}\n{}", model, prompt, params).as_bytes())
+}
+
+/// Owner id kept on disk for a gallery token, so tokens are never stored.
+pub fn owner_id(token: &str) -> String {
+    sha256_hex(token.as_bytes())[..32].to_string()
+}
+
+fn is_hash(value: &str) -> bool {
+    value.len() == 64 && value.bytes().all(|b| matches!(b, b'0'..=b'9' | b'a'..=b'f'))
+}
+
+impl ImageGallery {
+    /// Opens the gallery at `root`, rebuilding the index from the metadata files.
+    pub fn open(root: impl Into<PathBuf>, limits: GalleryLimits) -> Self {
+        let gallery = Self {
+            root: root.into(),
+            limits,
+            entries: Arc::new(DashMap::new()),
+            by_request: Arc::new(DashMap::new()),
+            write_lock: Arc::new(tokio::sync::Mutex::new(())),
+        };
+
+        if let Err(e) = std::fs::create_dir_all(&gallery.root) {
+            tracing::warn!("Image gallery at {} is unavailable: {}", gallery.root.display(), e);
+            return gallery;
+        }
+
+        let files = std::fs::read_dir(&gallery.root).into_iter().flatten().flatten();
+        for file in files {
+            let path = file.path();
+            if path.extension().and_then(|e| e.to_str()) != Some("json") {
+                continue;
+            }
+            let entry = std::fs::read(&path)
+                .ok()
+                .and_then(|raw| serde_json::from_slice::<GalleryEntry>(&raw).ok());
+            match entry {
+                Some(entry) if gallery.image_path(&entry.hash).exists() => {
+                    // Metadata from before per-owner files was named `{hash}.json`.
+                    let meta_path = gallery.meta_path(&entry.hash, &entry.owner);
+                    if path != meta_path {
+                        if let Err(e) = std::fs::rename(&path, &meta_path) {
+                            tracing::warn!("Failed to rename {}: {}", path.display(), e);
+                        }
+                    }
+                    gallery.index(entry)
+                }
+                _ => tracing::warn!("Skipping unreadable gallery metadata {}", path.display()),
+            }
+        }
+
+        tracing::info!(
+            "Image gallery at {} holds {} images",
+            gallery.root.display(),
+            gallery.entries.len()
+        );
+        gallery
+    }
+
+    fn image_path(&self, hash: &str) -> PathBuf {
+        self.root.join(hash)
+    }
+
+    fn meta_path(&self, hash: &str, owner: &str) -> PathBuf {
+        self.root.join(format!("{}.{}.json", hash, owner))
+    }
+
+    fn index(&self, entry: GalleryEntry) {
+        self.by_request.insert(entry.request_key.clone(), entry.hash.clone());
+        let mut owners = self.entries.entry(entry.hash.clone()).or_default();
+        owners.retain(|e| e.owner != entry.owner);
+        owners.push(entry);
+    }
+
+    pub fn get(&self, hash: &str) -> Option<GalleryEntry> {
+        if !is_hash(hash) {
+            return None;
+        }
+        self.entries.get(hash).and_then(|owners| owners.first().cloned())
+    }
+
+    pub fn find_by_request(&self, request_key: &str) -> Option<GalleryEntry> {
+        let hash = self.by_request.get(request_key)?.clone();
+        let owners = self.entries.get(&hash)?;
+        owners.iter().find(|e| e.request_key == request_key).cloned()
+    }
+
+    pub async fn read(&self, hash: &str) -> std::io::Result<Vec<u8>> {
+        tokio::fs::read(self.image_path(hash)).await
+    }
+
+    /// Writes the image and the owner's metadata; identical bytes are only
+    /// stored once. Evicts old images when a quota is exceeded.
+    pub async fn store(&self, bytes: &[u8], mut entry: GalleryEntry) -> std::io::Result<GalleryEntry> {
+        entry.hash = sha256_hex(bytes);
+        entry.size = bytes.len();
+
+        let _guard = self.write_lock.lock().await;
+        let image_path = self.image_path(&entry.hash);
+        if !image_path.exists() {
+            write_atomic(&image_path, bytes).await?;
+        }
+        let meta = serde_json::to_vec_pretty(&entry).map_err(std::io::Error::other)?;
+        write_atomic(&self.meta_path(&entry.hash, &entry.owner), &meta).await?;
+
+        self.index(entry.clone());
+        self.enforce_limits(&entry).await;
+        Ok(entry)
+    }
+
+    /// Drops the owner's oldest entries beyond their quota, then the oldest
+    /// images until the total fits. The image just stored is kept.
+    async fn enforce_limits(&self, stored: &GalleryEntry) {
+        for (hash, owner) in self
+            .list_for_owner(&stored.owner)
+            .into_iter()
+            .skip(self.limits.max_per_owner)
+            .map(|e| (e.hash, e.owner))
+        {
+            self.remove(&hash, &owner).await;
+        }
+
+        loop {
+            let total: u64 = self
+                .entries
+                .iter()
+                .filter_map(|owners| owners.first().map(|e| e.size as u64))
+                .sum();
+            if total <= self.limits.max_total_bytes {
+                break;
+            }
+            // An image is as old as its most recent entry.
+            let oldest = self
+                .entries
+                .iter()
+                .filter(|owners| *owners.key() != stored.hash)
+                .filter_map(|owners| {
+                    let newest = owners.iter().map(|e| e.created_at.clone()).max()?;
+                    Some((newest, owners.key().clone()))
+                })
+                .min();
+            let Some((_, hash)) = oldest else { break };
+            let owners: Vec<String> = self
+                .entries
+                .get(&hash)
+                .map(|owners| owners.iter().map(|e| e.owner.clone()).collect())
+                .unwrap_or_default();
+            for owner in owners {
+                self.remove(&hash, &owner).await;
+            }
+        }
+    }
+
+    /// Removes one owner's entry; the image goes with its last entry.
+    async fn remove(&self, hash: &str, owner: &str) {
+        if let Err(e) = tokio::fs::remove_file(self.meta_path(hash, owner)).await {
+            tracing::warn!("Failed to remove gallery metadata for {}: {}", hash, e);
+        }
+        let remaining_keys: Option<Vec<String>> = self.entries.get_mut(hash).map(|mut owners| {
+            owners.retain(|e| e.owner != owner);
+            owners.iter().map(|e| e.request_key.clone()).collect()
+        });
+        // Request keys only the removed entry had no longer lead to this image.
+        if let Some(keys) = &remaining_keys {
+            self.by_request.retain(|key, h| h != hash || keys.contains(key));
+        }
+        if remaining_keys.is_some_and(|keys| keys.is_empty()) {
+            self.entries.remove(hash);
+            if let Err(e) = tokio::fs::remove_file(self.image_path(hash)).await {
+                tracing::warn!("Failed to remove gallery image {}: {}", hash, e);
+            }
+        }
+    }
+
+    /// Images generated by `owner`, newest first.
+    pub fn list_for_owner(&self, owner: &str) -> Vec<GalleryEntry> {
+        let mut entries: Vec<GalleryEntry> = self
+            .entries
+            .iter()
+            .flat_map(|owners| owners.iter().filter(|e| e.owner == owner).cloned().collect::<Vec<_>>())
+            .collect();
+        entries.sort_by(|a, b| b.created_at.cmp(&a.created_at));
+        entries
+    }
+}
+
+/// Writes through a temporary file so readers never see a partial image.
+async fn write_atomic(path: &Path, bytes: &[u8]) -> std::io::Result<()> {
+    let tmp = path.with_extension(format!("tmp{:08x}", rand::random::<u32>()));
+    tokio::fs::write(&tmp, bytes).await?;
+    tokio::fs::rename(&tmp, path).await
+}
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+
+    fn entry(owner: &str, prompt: &str, created_at: &str) -> GalleryEntry {
+        GalleryEntry {
+            hash: String::new(),
+            content_type: "image/png".to_string(),
+            size: 0,
+            prompt: prompt.to_string(),
+            model: "sdxl".to_string(),
+            params: serde_json::json!({}),
+            request_key: request_key(prompt, "sdxl", &serde_json::json!({})),
+            owner: owner.to_string(),
+            created_at: created_at.to_string(),
+        }
+    }
+
+    fn open(limits: GalleryLimits) -> (ImageGallery, PathBuf) {
+        let root = std::env::temp_dir().join(format!("gallery-test-{:016x}", rand::random::<u64>()));
+        (ImageGallery::open(&root, limits), root)
+    }
+
+    const ROOMY: GalleryLimits = GalleryLimits { max_per_owner: 10, max_total_bytes: 1 << 20 };
+
+    #[tokio::test]
+    async fn same_bytes_keep_each_owner() {
+        let (gallery, root) = open(ROOMY);
+        let a = gallery.store(b"pixels", entry("alice", "a cat", "2026-01-01T00:00:00")).await.unwrap();
+        gallery.store(b"pixels", entry("bob", "a kitten", "2026-01-01T00:00:01")).await.unwrap();
+
+        assert_eq!(gallery.list_for_owner("alice")[0].prompt, "a cat");
+        assert_eq!(gallery.list_for_owner("bob")[0].prompt, "a kitten");
+        assert!(gallery.list_for_owner("carol").is_empty());
+
+        // Both survive a restart.
+        let reopened = ImageGallery::open(&root, ROOMY);
+        assert_eq!(reopened.list_for_owner("alice").len(), 1);
+        assert_eq!(reopened.list_for_owner("bob").len(), 1);
+        assert_eq!(reopened.read(&a.hash).await.unwrap(), b"pixels");
+        std::fs::remove_dir_all(root).unwrap();
+    }
+
+    #[tokio::test]
+    async fn owner_quota_evicts_oldest() {
+        let (gallery, root) = open(GalleryLimits { max_per_owner: 2, ..ROOMY });
+        let first = gallery.store(b"one", entry("alice", "one", "2026-01-01T00:00:01")).await.unwrap();
+        gallery.store(b"two", entry("alice", "two", "2026-01-01T00:00:02")).await.unwrap();
+        gallery.store(b"three", entry("alice", "three", "2026-01-01T00:00:03")).await.unwrap();
+
+        let prompts: Vec<String> = gallery.list_for_owner("alice").into_iter().map(|e| e.prompt).collect();
+        assert_eq!(prompts, ["three", "two"]);
+        assert!(gallery.get(&first.hash).is_none());
+        assert!(gallery.find_by_request(&first.request_key).is_none());
+        assert!(!root.join(&first.hash).exists());
+        std::fs::remove_dir_all(root).unwrap();
+    }
+
+    #[tokio::test]
+    async fn removing_one_owner_keeps_the_others() {
+        let (gallery, root) = open(GalleryLimits { max_per_owner: 1, ..ROOMY });
+        let alices = gallery.store(b"pixels", entry("alice", "a cat", "2026-01-01T00:00:01")).await.unwrap();
+        let bobs = gallery.store(b"pixels", entry("bob", "a kitten", "2026-01-01T00:00:02")).await.unwrap();
+        assert_eq!(gallery.find_by_request(&alices.request_key).unwrap().owner, "alice");
+        assert_eq!(gallery.find_by_request(&bobs.request_key).unwrap().owner, "bob");
+
+        // Alice's quota pushes her entry for the shared image out.
+        gallery.store(b"other", entry("alice", "a dog", "2026-01-01T00:00:03")).await.unwrap();
+        assert!(gallery.find_by_request(&alices.request_key).is_none());
+        assert!(!gallery.by_request.contains_key(&alices.request_key));
+        assert_eq!(gallery.find_by_request(&bobs.request_key).unwrap().owner, "bob");
+        assert_eq!(gallery.list_for_owner("bob").len(), 1);
+        assert!(root.join(&bobs.hash).exists());
+        assert!(!root.join(format!("{}.alice.json", alices.hash)).exists());
+        std::fs::remove_dir_all(root).unwrap();
+    }
+
+    #[tokio::test]
+    async fn total_quota_evicts_oldest_image() {
+        let (gallery, root) = open(GalleryLimits { max_per_owner: 10, max_total_bytes: 8 });
+        let old = gallery.store(b"aaaa", entry("alice", "old", "2026-01-01T00:00:01")).await.unwrap();
+        let shared = gallery.store(b"bbbb", entry("bob", "shared", "2026-01-01T00:00:02")).await.unwrap();
+        gallery.store(b"bbbb", entry("alice", "shared", "2026-01-01T00:00:03")).await.unwrap();
+        let new = gallery.store(b"cccc", entry("carol", "new", "2026-01-01T00:00:04")).await.unwrap();
+
+        assert!(gallery.get(&old.hash).is_none());
+        assert!(gallery.get(&shared.hash).is_some());
+        assert!(gallery.get(&new.hash).is_some());
+        assert_eq!(gallery.list_for_owner("alice").len(), 1);
+        std::fs::remove_dir_all(root).unwrap();
+    }
+}
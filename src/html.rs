@@ -0,0 +1,235 @@
+use crate::markdown::{self, Block, Span};
+use crate::pdf::{FontFamily, PageSize, Template};
+
+pub struct HtmlOptions {
+    pub template: Template,
+    pub font: FontFamily,
+    pub page_size: PageSize,
+    pub title: String,
+    pub author: String,
+    /// Keep single line breaks inside paragraphs (letters, e-mails).
+    pub line_breaks: bool,
+}
+
+/// Escapes text for HTML and XML content and attribute values.
+pub fn escape(text: &str) -> String {
+    let mut out = String::with_capacity(text.len());
+    for c in text.chars() {
+        match c {
+            '&' => out.push_str("&amp;"),
+            '<' => out.push_str("&lt;"),
+            '>' => out.push_str("&gt;"),
+            '"' => out.push_str("&quot;"),
+            '\'' => out.push_str("&#39;"),
+            _ => out.push(c),
+        }
+    }
+    out
+}
+
+/// Link targets that are safe to emit; anything else is rendered as plain text.
+pub fn safe_link(url: &str) -> Option<&str> {
+    let lower = url.trim().to_lowercase();
+    ["http://", "https://", "mailto:"]
+        .iter()
+        .any(|scheme| lower.starts_with(scheme))
+        .then_some(url.trim())
+}
+
+const BASE_CSS: &str = "\
+*{box-sizing:border-box}\
+body{margin:0;background:#f4f4f4;color:#222;line-height:var(--leading);font-family:var(--font);font-size:var(--size)}\
+main{max-width:52rem;margin:2rem auto;padding:var(--margin);background:#fff;box-shadow:0 1px 4px rgba(0,0,0,.12)}\
+h1,h2,h3,h4{color:var(--accent);line-height:1.2;margin:1.1em 0 .4em}\
+h1{font-size:2.1em;margin-top:0}h2{font-size:1.2em}h3{font-size:1.05em}\
+p,ul,ol,blockquote,pre{margin:0 0 var(--gap)}\
+ul,ol{padding-left:1.4em}li{margin:.15em 0}\
+a{color:var(--accent)}\
+hr{border:0;border-top:1px solid #bfbfbf;margin:var(--gap) 0}\
+blockquote{margin-left:0;padding-left:1em;border-left:3px solid #ddd;color:#555}\
+code,pre{font-family:Consolas,'DejaVu Sans Mono',monospace;font-size:.9em}\
+pre{background:#f6f6f6;padding:.6em;overflow-x:auto}\
+.muted{color:#6b6b6b}\
+@media print{body{background:#fff}main{margin:0;max-width:none;box-shadow:none;padding:0}}";
+
+impl HtmlOptions {
+    fn template_css(&self) -> String {
+        let font = match self.font {
+            FontFamily::Serif => "Georgia,'DejaVu Serif','Times New Roman',serif",
+            FontFamily::Sans => "'Helvetica Neue',Arial,'DejaVu Sans',sans-serif",
+        };
+        let (size, leading, margin, gap, accent, extra) = match self.template {
+            Template::Classic => (
+                "10.5pt",
+                "1.4",
+                "3rem",
+                ".5em",
+                "#1a1a1a",
+                "header{text-align:center}h2{text-transform:uppercase;letter-spacing:.06em;\
+                 border-bottom:1px solid #bfbfbf;padding-bottom:.15em}p{text-align:justify}header p{text-align:center}",
+            ),
+            Template::Modern => (
+                "10pt",
+                "1.45",
+                "2.6rem",
+                ".5em",
+                "#2161ab",
+                "h2{border-bottom:2px solid #2161ab;padding-bottom:.15em}",
+            ),
+            Template::Compact => (
+                "9pt",
+                "1.3",
+                "1.8rem",
+                ".3em",
+                "#333",
+                "h2{text-transform:uppercase;letter-spacing:.04em}p{text-align:justify}",
+            ),
+        };
+        let (width, height) = self.page_size.dimensions();
+        format!(
+            ":root{{--font:{};--size:{};--leading:{};--margin:{};--gap:{};--accent:{}}}{}{}\
+             @page{{size:{}pt {}pt;margin:{}}}",
+            font, size, leading, margin, gap, accent, BASE_CSS, extra, width, height, margin
+        )
+    }
+}
+
+fn spans_html(spans: &[Span], line_breaks: bool) -> String {
+    let mut out = String::new();
+    for span in spans {
+        let mut text = escape(&span.text);
+        if line_breaks {
+            text = text.replace('\n', "<br>\n");
+        }
+        if span.code {
+            text = format!("<code>{}</code>", text);
+        }
+        if span.italic {
+            text = format!("<em>{}</em>", text);
+        }
+        if span.bold {
+            text = format!("<strong>{}</strong>", text);
+        }
+        if let Some(url) = span.link.as_deref().and_then(safe_link) {
+            text = format!("<a href=\"{}\">{}</a>", escape(url), text);
+        }
+        out.push_str(&text);
+    }
+    out
+}
+
+/// Italic-only paragraphs (dates, notes) are shown muted, as in the PDF.
+fn is_muted(spans: &[Span]) -> bool {
+    spans.iter().all(|s| s.italic || s.text.trim().is_empty())
+}
+
+/// Renders Markdown as a standalone HTML document: inline CSS, no external
+/// resources, and print rules for the chosen page size. Content before the
+/// first `##` section is wrapped in `<header>`.
+pub fn render(source: &str, options: &HtmlOptions) -> String {
+    let blocks = if options.line_breaks {
+        markdown::parse_with_breaks(source)
+    } else {
+        markdown::parse(source)
+    };
+
+    let mut body = String::new();
+    let has_sections = blocks.iter().any(|b| matches!(b, Block::Heading { level: 2, .. }));
+    let mut in_header = has_sections;
+    if in_header {
+        body.push_str("<header>\n");
+    }
+
+    let mut i = 0;
+    while i < blocks.len() {
+        match &blocks[i] {
+            Block::Heading { level, spans } => {
+                if in_header && *level == 2 {
+                    body.push_str("</header>\n");
+                    in_header = false;
+                }
+                let level = (*level).min(6);
+                body.push_str(&format!("<h{0}>{1}</h{0}>\n", level, spans_html(spans, false)));
+            }
+            Block::Paragraph(spans) => {
+                let class = if is_muted(spans) { " class=\"muted\"" } else { "" };
+                body.push_str(&format!("<p{}>{}</p>\n", class, spans_html(spans, options.line_breaks)));
+            }
+            Block::ListItem { number, .. } => {
+                let ordered = number.is_some();
+                let tag = if ordered { "ol" } else { "ul" };
+                match number {
+                    Some(start) if *start != 1 => body.push_str(&format!("<ol start=\"{}\">\n", start)),
+                    _ => body.push_str(&format!("<{}>\n", tag)),
+                }
+                // Consecutive items of the same kind form one list.
+                while let Some(Block::ListItem { number, spans }) = blocks.get(i) {
+                    if number.is_some() != ordered {
+                        break;
+                    }
+                    body.push_str(&format!("<li>{}</li>\n", spans_html(spans, options.line_breaks)));
+                    i += 1;
+                }
+                body.push_str(&format!("</{}>\n", tag));
+                continue;
+            }
+            Block::Quote(spans) => {
+                body.push_str(&format!("<blockquote>{}</blockquote>\n", spans_html(spans, options.line_breaks)));
+            }
+            Block::Code(code) => body.push_str(&format!("<pre><code>{}</code></pre>\n", escape(code))),
+            // Section headings already carry a rule in these templates.
+            Block::Rule if !matches!(options.template, Template::Compact) => {}
+            Block::Rule => body.push_str("<hr>\n"),
+        }
+        i += 1;
+    }
+    if in_header {
+        body.push_str("</header>\n");
+    }
+
+    format!(
+        "<!DOCTYPE html>\n<html lang=\"en\">\n<head>\n<meta charset=\"utf-8\">\n\
+         <meta name=\"viewport\" content=\"width=device-width, initial-scale=1\">\n\
+         <title>{}</title>\n{}<style>{}</style>\n</head>\n\
+         <body>\n<main>\n{}</main>\n</body>\n</html>\n",
+        escape(&options.title),
+        if options.author.is_empty() {
+            String::new()
+        } else {
+            format!("<meta name=\"author\" content=\"{}\">\n", escape(&options.author))
+        },
+        options.template_css(),
+        body
+    )
+}
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+
+    #[test]
+    fn escape_covers_markup_and_attribute_quotes() {
+        assert_eq!(escape("<b class=\"x\">Tom & Jerry's</b>"), "&lt;b class=&quot;x&quot;&gt;Tom &amp; Jerry&#39;s&lt;/b&gt;");
+        assert_eq!(escape("&amp;"), "&amp;amp;");
+        assert_eq!(escape("plain ünïcode"), "plain ünïcode");
+    }
+
+    #[test]
+    fn rendered_html_escapes_text_and_unsafe_links() {
+        let options = HtmlOptions {
+            template: Template::Classic,
+            font: FontFamily::Serif,
+            page_size: PageSize::A4,
+            title: "</title><script>alert(1)</script>".to_string(),
+            author: "Jane \"JD\" Doe".to_string(),
+            line_breaks: false,
+        };
+        let source = "# <img src=x onerror=alert(1)>\n\n[click](javascript:alert(1)) and [site](https://example.com/?a=1&b=\"2\")\n";
+        let html = render(source, &options);
+
+        assert!(!html.contains("<script>") && !html.contains("<img"), "{}", html);
+        assert!(html.contains("&lt;img src=x onerror=alert(1)&gt;"), "{}", html);
+        assert!(!html.contains("href=\"javascript:"), "{}", html);
+        assert!(html.contains("href=\"https://example.com/?a=1&amp;b=&quot;2&quot;\""), "{}", html);
+    }
+}
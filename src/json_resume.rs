@@ -0,0 +1,1226 @@
+use serde::{Deserialize, Serialize};
+
+use crate::markdown::{self, Block, Span};
+
+/// Subset of the JSON Resume schema (https://jsonresume.org/schema) used by
+/// the resume tool. Unknown keys are ignored on input.
+#[derive(Clone, Debug, Default, Serialize, Deserialize)]
+#[serde(default)]
+pub struct JsonResume {
+    #[serde(skip_serializing_if = "Option::is_none")]
+    pub basics: Option<Basics>,
+    #[serde(skip_serializing_if = "Vec::is_empty")]
+    pub work: Vec<Work>,
+    #[serde(skip_serializing_if = "Vec::is_empty")]
+    pub education: Vec<Education>,
+    #[serde(skip_serializing_if = "Vec::is_empty")]
+    pub projects: Vec<Project>,
+    #[serde(skip_serializing_if = "Vec::is_empty")]
+    pub skills: Vec<Skill>,
+    #[serde(skip_serializing_if = "Vec::is_empty")]
+    pub languages: Vec<Language>,
+    #[serde(skip_serializing_if = "Vec::is_empty")]
+    pub certificates: Vec<Certificate>,
+}
+
+#[derive(Clone, Debug, Default, Serialize, Deserialize)]
+#[serde(default)]
+pub struct Basics {
+    pub name: String,
+    #[serde(skip_serializing_if = "Option::is_none")]
+    pub label: Option<String>,
+    #[serde(skip_serializing_if = "Option::is_none")]
+    pub email: Option<String>,
+    #[serde(skip_serializing_if = "Option::is_none")]
+    pub phone: Option<String>,
+    #[serde(skip_serializing_if = "Option::is_none")]
+    pub url: Option<String>,
+    #[serde(skip_serializing_if = "Option::is_none")]
+    pub summary: Option<String>,
+    #[serde(skip_serializing_if = "Option::is_none")]
+    pub location: Option<Location>,
+    #[serde(skip_serializing_if = "Vec::is_empty")]
+    pub profiles: Vec<Profile>,
+}
+
+#[derive(Clone, Debug, Default, Serialize, Deserialize)]
+#[serde(default, rename_all = "camelCase")]
+pub struct Location {
+    #[serde(skip_serializing_if = "Option::is_none")]
+    pub address: Option<String>,
+    #[serde(skip_serializing_if = "Option::is_none")]
+    pub postal_code: Option<String>,
+    #[serde(skip_serializing_if = "Option::is_none")]
+    pub city: Option<String>,
+    #[serde(skip_serializing_if = "Option::is_none")]
+    pub country_code: Option<String>,
+    #[serde(skip_serializing_if = "Option::is_none")]
+    pub region: Option<String>,
+}
+
+#[derive(Clone, Debug, Default, Serialize, Deserialize)]
+#[serde(default)]
+pub struct Profile {
+    pub network: String,
+    #[serde(skip_serializing_if = "Option::is_none")]
+    pub username: Option<String>,
+    #[serde(skip_serializing_if = "Option::is_none")]
+    pub url: Option<String>,
+}
+
+#[derive(Clone, Debug, Default, Serialize, Deserialize)]
+#[serde(default, rename_all = "camelCase")]
+pub struct Work {
+    /// Company name.
+    #[serde(skip_serializing_if = "Option::is_none")]
+    pub name: Option<String>,
+    #[serde(skip_serializing_if = "Option::is_none")]
+    pub position: Option<String>,
+    #[serde(skip_serializing_if = "Option::is_none")]
+    pub url: Option<String>,
+    #[serde(skip_serializing_if = "Option::is_none")]
+    pub location: Option<String>,
+    #[serde(skip_serializing_if = "Option::is_none")]
+    pub start_date: Option<String>,
+    #[serde(skip_serializing_if = "Option::is_none")]
+    pub end_date: Option<String>,
+    #[serde(skip_serializing_if = "Option::is_none")]
+    pub summary: Option<String>,
+    #[serde(skip_serializing_if = "Vec::is_empty")]
+    pub highlights: Vec<String>,
+}
+
+#[derive(Clone, Debug, Default, Serialize, Deserialize)]
+#[serde(default, rename_all = "camelCase")]
+pub struct Education {
+    pub institution: String,
+    #[serde(skip_serializing_if = "Option::is_none")]
+    pub url: Option<String>,
+    #[serde(skip_serializing_if = "Option::is_none")]
+    pub area: Option<String>,
+    #[serde(skip_serializing_if = "Option::is_none")]
+    pub study_type: Option<String>,
+    #[serde(skip_serializing_if = "Option::is_none")]
+    pub start_date: Option<String>,
+    #[serde(skip_serializing_if = "Option::is_none")]
+    pub end_date: Option<String>,
+    #[serde(skip_serializing_if = "Option::is_none")]
+    pub score: Option<String>,
+    #[serde(skip_serializing_if = "Vec::is_empty")]
+    pub courses: Vec<String>,
+}
+
+#[derive(Clone, Debug, Default, Serialize, Deserialize)]
+#[serde(default, rename_all = "camelCase")]
+pub struct Project {
+    pub name: String,
+    #[serde(skip_serializing_if = "Option::is_none")]
+    pub description: Option<String>,
+    #[serde(skip_serializing_if = "Vec::is_empty")]
+    pub highlights: Vec<String>,
+    #[serde(skip_serializing_if = "Vec::is_empty")]
+    pub keywords: Vec<String>,
+    #[serde(skip_serializing_if = "Option::is_none")]
+    pub start_date: Option<String>,
+    #[serde(skip_serializing_if = "Option::is_none")]
+    pub end_date: Option<String>,
+    #[serde(skip_serializing_if = "Option::is_none")]
+    pub url: Option<String>,
+}
+
+#[derive(Clone, Debug, Default, Serialize, Deserialize)]
+#[serde(default)]
+pub struct Skill {
+    pub name: String,
+    #[serde(skip_serializing_if = "Option::is_none")]
+    pub level: Option<String>,
+    #[serde(skip_serializing_if = "Vec::is_empty")]
+    pub keywords: Vec<String>,
+}
+
+#[derive(Clone, Debug, Default, Serialize, Deserialize)]
+#[serde(default)]
+pub struct Language {
+    pub language: String,
+    #[serde(skip_serializing_if = "Option::is_none")]
+    pub fluency: Option<String>,
+}
+
+#[derive(Clone, Debug, Default, Serialize, Deserialize)]
+#[serde(default)]
+pub struct Certificate {
+    pub name: String,
+    #[serde(skip_serializing_if = "Option::is_none")]
+    pub date: Option<String>,
+    #[serde(skip_serializing_if = "Option::is_none")]
+    pub issuer: Option<String>,
+    #[serde(skip_serializing_if = "Option::is_none")]
+    pub url: Option<String>,
+}
+
+/// Upper bounds that keep generated documents and prompts reasonably sized.
+const MAX_ENTRIES: usize = 50;
+const MAX_TEXT_CHARS: usize = 5_000;
+
+const MONTHS: [&str; 12] = [
+    "January", "February", "March", "April", "May", "June",
+    "July", "August", "September", "October", "November", "December",
+];
+
+/// Collects validation errors as `path: message`.
+struct Validator {
+    errors: Vec<String>,
+}
+
+impl Validator {
+    fn error(&mut self, path: &str, message: &str) {
+        self.errors.push(format!("{}: {}", path, message));
+    }
+
+    fn text(&mut self, path: &str, value: Option<&str>, required: bool) {
+        match value.map(str::trim) {
+            None | Some("") if required => self.error(path, "is required"),
+            Some(v) if v.chars().count() > MAX_TEXT_CHARS => {
+                self.error(path, &format!("must be at most {} characters", MAX_TEXT_CHARS))
+            }
+            _ => {}
+        }
+    }
+
+    fn url(&mut self, path: &str, value: Option<&str>) {
+        if let Some(url) = value.map(str::trim).filter(|u| !u.is_empty()) {
+            if !url.starts_with("http://") && !url.starts_with("https://") {
+                self.error(path, "must be an http(s) URL");
+            }
+        }
+    }
+
+    fn dates(&mut self, path: &str, start: Option<&str>, end: Option<&str>) {
+        for (name, value) in [("startDate", start), ("endDate", end)] {
+            if value.is_some_and(|v| !is_iso_date(v)) {
+                self.error(&format!("{}.{}", path, name), "must be YYYY, YYYY-MM or YYYY-MM-DD");
+            }
+        }
+        if let (Some(start), Some(end)) = (start, end) {
+            // ISO dates of any precision compare correctly as strings.
+            if is_iso_date(start) && is_iso_date(end) && end < start {
+                self.error(&format!("{}.endDate", path), "must not be before startDate");
+            }
+        }
+    }
+
+    fn list<T>(&mut self, path: &str, items: &[T], check: impl Fn(&mut Self, &str, &T)) {
+        if items.len() > MAX_ENTRIES {
+            self.error(path, &format!("must have at most {} entries", MAX_ENTRIES));
+        }
+        for (i, item) in items.iter().enumerate() {
+            check(self, &format!("{}[{}]", path, i), item);
+        }
+    }
+}
+
+/// `YYYY`, `YYYY-MM` or `YYYY-MM-DD`, as the schema's `iso8601` pattern allows.
+fn is_iso_date(value: &str) -> bool {
+    let parts: Vec<&str> = value.split('-').collect();
+    let numeric = |s: &str, len: usize| s.len() == len && s.bytes().all(|b| b.is_ascii_digit());
+    match parts.as_slice() {
+        [y] => numeric(y, 4),
+        [y, m] => numeric(y, 4) && numeric(m, 2) && (1..=12).contains(&m.parse::<u32>().unwrap_or(0)),
+        [y, m, d] => {
+            numeric(y, 4)
+                && numeric(m, 2)
+                && numeric(d, 2)
+                && chrono::NaiveDate::parse_from_str(value, "%Y-%m-%d").is_ok()
+        }
+        _ => false,
+    }
+}
+
+impl JsonResume {
+    /// Checks required fields, URLs, e-mail and ISO dates. All problems are reported.
+    pub fn validate(&self) -> Result<(), Vec<String>> {
+        let mut v = Validator { errors: Vec::new() };
+
+        match &self.basics {
+            None => v.error("basics", "is required"),
+            Some(basics) => {
+                v.text("basics.name", Some(&basics.name), true);
+                v.text("basics.label", basics.label.as_deref(), false);
+                v.text("basics.summary", basics.summary.as_deref(), false);
+                v.url("basics.url", basics.url.as_deref());
+                if let Some(email) = basics.email.as_deref().filter(|e| !e.trim().is_empty()) {
+                    if !is_email(email) {
+                        v.error("basics.email", "is not a valid e-mail address");
+                    }
+                }
+                v.list("basics.profiles", &basics.profiles, |v, path, p| {
+                    v.text(&format!("{}.network", path), Some(&p.network), true);
+                    v.url(&format!("{}.url", path), p.url.as_deref());
+                });
+            }
+        }
+
+        v.list("work", &self.work, |v, path, w| {
+            if w.name.as_deref().unwrap_or_default().trim().is_empty()
+                && w.position.as_deref().unwrap_or_default().trim().is_empty()
+            {
+                v.error(path, "needs a name or a position");
+            }
+            v.url(&format!("{}.url", path), w.url.as_deref());
+            v.dates(path, w.start_date.as_deref(), w.end_date.as_deref());
+            v.text(&format!("{}.summary", path), w.summary.as_deref(), false);
+            for (i, h) in w.highlights.iter().enumerate() {
+                v.text(&format!("{}.highlights[{}]", path, i), Some(h), true);
+            }
+        });
+        v.list("education", &self.education, |v, path, e| {
+            v.text(&format!("{}.institution", path), Some(&e.institution), true);
+            v.url(&format!("{}.url", path), e.url.as_deref());
+            v.dates(path, e.start_date.as_deref(), e.end_date.as_deref());
+        });
+        v.list("projects", &self.projects, |v, path, p| {
+            v.text(&format!("{}.name", path), Some(&p.name), true);
+            v.text(&format!("{}.description", path), p.description.as_deref(), false);
+            v.url(&format!("{}.url", path), p.url.as_deref());
+            v.dates(path, p.start_date.as_deref(), p.end_date.as_deref());
+        });
+        v.list("skills", &self.skills, |v, path, s| {
+            v.text(&format!("{}.name", path), Some(&s.name), true);
+        });
+        v.list("languages", &self.languages, |v, path, l| {
+            v.text(&format!("{}.language", path), Some(&l.language), true);
+        });
+        v.list("certificates", &self.certificates, |v, path, c| {
+            v.text(&format!("{}.name", path), Some(&c.name), true);
+            v.url(&format!("{}.url", path), c.url.as_deref());
+            if c.date.as_deref().is_some_and(|d| !is_iso_date(d)) {
+                v.error(&format!("{}.date", path), "must be YYYY, YYYY-MM or YYYY-MM-DD");
+            }
+        });
+
+        if v.errors.is_empty() {
+            Ok(())
+        } else {
+            Err(v.errors)
+        }
+    }
+
+    pub fn name(&self) -> &str {
+        self.basics.as_ref().map_or("", |b| b.name.trim())
+    }
+
+    /// Renders the resume as Markdown in the same layout as the generated resumes.
+    pub fn to_markdown(&self) -> String {
+        let basics = self.basics.clone().unwrap_or_default();
+        let mut md = format!("# {}\n\n", basics.name.trim());
+
+        let location = basics.location.as_ref().map(|l| {
+            [&l.city, &l.region, &l.country_code]
+                .into_iter()
+                .flatten()
+                .map(|s| s.trim())
+                .filter(|s| !s.is_empty())
+                .collect::<Vec<_>>()
+                .join(", ")
+        });
+        let contact: Vec<String> = [
+            basics.label.as_ref().map(|l| format!("**{}**", l.trim())),
+            basics.email.clone(),
+            basics.phone.clone(),
+            location,
+        ]
+        .into_iter()
+        .flatten()
+        .filter(|s| !s.trim().is_empty())
+        .collect();
+        if !contact.is_empty() {
+            md.push_str(&format!("{}\n\n", contact.join(" | ")));
+        }
+
+        let mut links: Vec<String> = basics
+            .profiles
+            .iter()
+            .filter_map(|p| p.url.as_ref().map(|url| format!("[{}]({})", p.network.trim(), url.trim())))
+            .collect();
+        if let Some(url) = &basics.url {
+            links.push(format!("[Website]({})", url.trim()));
+        }
+        if !links.is_empty() {
+            md.push_str(&format!("{}\n\n", links.join(" | ")));
+        }
+
+        let mut sections: Vec<String> = Vec::new();
+        if let Some(summary) = basics.summary.as_deref().filter(|s| !s.trim().is_empty()) {
+            sections.push(format!("## Professional Summary\n\n{}", summary.trim()));
+        }
+
+        if !self.skills.is_empty() {
+            let items: Vec<String> = self
+                .skills
+                .iter()
+                .map(|s| {
+                    let mut line = format!("- **{}**", s.name.trim());
+                    if let Some(level) = s.level.as_deref().filter(|l| !l.trim().is_empty()) {
+                        line.push_str(&format!(" ({})", level.trim()));
+                    }
+                    if !s.keywords.is_empty() {
+                        line.push_str(&format!(": {}", s.keywords.join(", ")));
+                    }
+                    line
+                })
+                .collect();
+            sections.push(format!("## Core Competencies\n\n{}", items.join("\n")));
+        }
+
+        if !self.work.is_empty() {
+            let entries: Vec<String> = self
+                .work
+                .iter()
+                .map(|w| {
+                    let title = [w.position.as_deref(), w.name.as_deref()]
+                        .into_iter()
+                        .flatten()
+                        .map(str::trim)
+                        .filter(|s| !s.is_empty())
+                        .collect::<Vec<_>>()
+                        .join(" | ");
+                    let mut entry = format!("### {}\n", title);
+                    if let Some(range) = date_range(w.start_date.as_deref(), w.end_date.as_deref()) {
+                        let place = w.location.as_deref().map(|l| format!(" | {}", l.trim())).unwrap_or_default();
+                        entry.push_str(&format!("*{}*{}\n", range, place));
+                    }
+                    if let Some(summary) = w.summary.as_deref().filter(|s| !s.trim().is_empty()) {
+                        entry.push_str(&format!("\n{}\n", summary.trim()));
+                    }
+                    push_bullets(&mut entry, &w.highlights);
+                    entry.trim_end().to_string()
+                })
+                .collect();
+            sections.push(format!("## Professional Experience\n\n{}", entries.join("\n\n")));
+        }
+
+        if !self.projects.is_empty() {
+            let items: Vec<String> = self
+                .projects
+                .iter()
+                .map(|p| {
+                    let name = match &p.url {
+                        Some(url) => format!("**[{}]({})**", p.name.trim(), url.trim()),
+                        None => format!("**{}**", p.name.trim()),
+                    };
+                    let mut line = match p.description.as_deref().filter(|d| !d.trim().is_empty()) {
+                        Some(description) => format!("- {} - {}", name, description.trim()),
+                        None => format!("- {}", name),
+                    };
+                    if !p.highlights.is_empty() {
+                        line.push_str(&format!(". {}", p.highlights.join("; ")));
+                    }
+                    if !p.keywords.is_empty() {
+                        line.push_str(&format!(" ({})", p.keywords.join(", ")));
+                    }
+                    line
+                })
+                .collect();
+            sections.push(format!("## Projects & Contributions\n\n{}", items.join("\n")));
+        }
+
+        if !self.education.is_empty() {
+            let entries: Vec<String> = self
+                .education
+                .iter()
+                .map(|e| {
+                    let degree = match (e.study_type.as_deref(), e.area.as_deref()) {
+                        (Some(kind), Some(area)) => format!("{} in {}", kind.trim(), area.trim()),
+                        (Some(kind), None) => kind.trim().to_string(),
+                        (None, Some(area)) => area.trim().to_string(),
+                        (None, None) => e.institution.trim().to_string(),
+                    };
+                    let mut line = Vec::new();
+                    if !e.institution.trim().is_empty() {
+                        line.push(format!("*{}*", e.institution.trim()));
+                    }
+                    match (e.start_date.as_deref(), e.end_date.as_deref()) {
+                        (None, Some(end)) => line.push(format!("Graduated {}", display_date(end))),
+                        (start, end) => line.extend(date_range(start, end)),
+                    }
+                    let mut entry = format!("### {}\n{}\n", degree, line.join(" | "));
+                    let mut details = Vec::new();
+                    if let Some(score) = &e.score {
+                        details.push(format!("GPA: {}", score.trim()));
+                    }
+                    if !e.courses.is_empty() {
+                        details.push(format!("Relevant Coursework: {}", e.courses.join(", ")));
+                    }
+                    push_bullets(&mut entry, &details);
+                    entry.trim_end().to_string()
+                })
+                .collect();
+            sections.push(format!("## Education\n\n{}", entries.join("\n\n")));
+        }
+
+        if !self.certificates.is_empty() {
+            let items: Vec<String> = self
+                .certificates
+                .iter()
+                .map(|c| {
+                    let mut line = format!("- {}", c.name.trim());
+                    if let Some(issuer) = &c.issuer {
+                        line.push_str(&format!(", {}", issuer.trim()));
+                    }
+                    if let Some(date) = &c.date {
+                        line.push_str(&format!(" ({})", &date[..date.len().min(4)]));
+                    }
+                    line
+                })
+                .collect();
+            sections.push(format!("## Certifications\n\n{}", items.join("\n")));
+        }
+
+        if !self.languages.is_empty() {
+            let items: Vec<String> = self
+                .languages
+                .iter()
+                .map(|l| match l.fluency.as_deref().filter(|f| !f.trim().is_empty()) {
+                    Some(fluency) => format!("- {} ({})", l.language.trim(), fluency.trim()),
+                    None => format!("- {}", l.language.trim()),
+                })
+                .collect();
+            sections.push(format!("## Languages\n\n{}", items.join("\n")));
+        }
+
+        md.push_str("---\n\n");
+        md.push_str(&sections.join("\n\n---\n\n"));
+        md.push('\n');
+        md
+    }
+}
+
+fn push_bullets(entry: &mut String, items: &[String]) {
+    if items.is_empty() {
+        return;
+    }
+    entry.push('\n');
+    for item in items {
+        entry.push_str(&format!("- {}\n", item.trim()));
+    }
+}
+
+fn is_email(value: &str) -> bool {
+    let Some((local, domain)) = value.trim().split_once('@') else {
+        return false;
+    };
+    !local.is_empty()
+        && !domain.starts_with('.')
+        && domain.contains('.')
+        && !domain.ends_with('.')
+        && !value.contains(char::is_whitespace)
+}
+
+/// `2022-01` → `January 2022`; years and full dates are shown as year / month year.
+fn display_date(date: &str) -> String {
+    let mut parts = date.trim().split('-');
+    let year = parts.next().unwrap_or_default();
+    match parts.next().and_then(|m| m.parse::<usize>().ok()).filter(|m| (1..=12).contains(m)) {
+        Some(month) => format!("{} {}", MONTHS[month - 1], year),
+        None => year.to_string(),
+    }
+}
+
+fn date_range(start: Option<&str>, end: Option<&str>) -> Option<String> {
+    let start = start.filter(|s| !s.trim().is_empty())?;
+    let end = end
+        .filter(|e| !e.trim().is_empty())
+        .map(display_date)
+        .unwrap_or_else(|| "Present".to_string());
+    Some(format!("{} - {}", display_date(start), end))
+}
+
+/// `January 2022`, `Jan 2022`, `01/2022`, `2022-01` or `2022` → ISO date.
+/// `Present` / `Current` and unparseable text return `None`.
+pub fn parse_date(text: &str) -> Option<String> {
+    let text = text.trim().trim_matches(|c: char| c == '*' || c == '_' || c == '(' || c == ')');
+    if is_iso_date(text) {
+        return Some(text.to_string());
+    }
+
+    let words: Vec<&str> = text
+        .split(|c: char| c.is_whitespace() || c == ',' || c == '/' || c == '.')
+        .filter(|w| !w.is_empty())
+        .collect();
+    let year = words.iter().rev().find(|w| w.len() == 4 && w.bytes().all(|b| b.is_ascii_digit()))?;
+    let month = words.iter().find_map(|w| {
+        let lower = w.to_lowercase();
+        MONTHS
+            .iter()
+            .position(|m| lower.len() >= 3 && m.to_lowercase().starts_with(&lower))
+            .map(|i| i + 1)
+            .or_else(|| w.parse::<usize>().ok().filter(|m| w.len() <= 2 && (1..=12).contains(m)))
+    });
+    Some(match month {
+        Some(month) => format!("{}-{:02}", year, month),
+        None => year.to_string(),
+    })
+}
+
+/// Splits `January 2022 - Present` into ISO start / end dates.
+pub fn parse_date_range(text: &str) -> (Option<String>, Option<String>) {
+    let separators = [" - ", " – ", " — ", " to ", "–", "—"];
+    match separators.iter().find_map(|sep| text.split_once(sep)) {
+        Some((start, end)) => (parse_date(start), parse_date(end)),
+        None => (None, parse_date(text)),
+    }
+}
+
+fn is_date_word(word: &str) -> bool {
+    let word = word.trim_matches(|c: char| "()[],.|".contains(c)).to_lowercase();
+    if word.is_empty() || ["-", "–", "—", "to", "present", "current", "now", "today", "since", "graduated", "expected"].contains(&word.as_str()) {
+        return true;
+    }
+    if word.len() >= 3 && MONTHS.iter().any(|m| m.to_lowercase().starts_with(&word)) {
+        return true;
+    }
+    let digits = word.chars().all(|c| c.is_ascii_digit() || "/-–".contains(c));
+    digits && word.chars().filter(char::is_ascii_digit).count() >= 4
+}
+
+/// Splits `Senior Engineer, Acme   Jan 2020 – Present` into the text and its
+/// trailing date range. A date range alone returns empty text.
+pub fn split_trailing_dates(line: &str) -> (String, Option<String>) {
+    let words: Vec<&str> = line.split_whitespace().collect();
+    let start = words.iter().rposition(|w| !is_date_word(w)).map_or(0, |i| i + 1);
+    let tail = &words[start..];
+    let has_year = tail.iter().any(|w| {
+        w.trim_matches(|c: char| !c.is_ascii_digit()).len() >= 4
+            || w.split(['/', '-', '–']).any(|p| p.len() == 4 && p.bytes().all(|b| b.is_ascii_digit()))
+    });
+    if !has_year {
+        return (line.trim().to_string(), None);
+    }
+    let text = words[..start].join(" ");
+    let text = text.trim_end_matches(|c: char| c == '|' || c == ',' || c == '(' || c.is_whitespace() || "-–—".contains(c));
+    let dates = tail.join(" ");
+    let dates = dates.trim_matches(|c: char| "()[]|,".contains(c) || c.is_whitespace());
+    (text.to_string(), Some(dates.to_string()))
+}
+
+/// Section a heading belongs to, by its common titles.
+#[derive(Clone, Copy, Debug, PartialEq)]
+pub enum Section {
+    Summary,
+    Experience,
+    Education,
+    Skills,
+    Projects,
+    Languages,
+    Certificates,
+    Other,
+}
+
+impl Section {
+    pub fn detect(title: &str) -> Self {
+        let title = title.to_lowercase();
+        let has = |words: &[&str]| words.iter().any(|w| title.contains(w));
+        if has(&["summary", "profile", "about", "objective"]) {
+            Self::Summary
+        } else if has(&["experience", "employment", "work history", "career"]) {
+            Self::Experience
+        } else if has(&["education", "academic", "qualification"]) {
+            Self::Education
+        } else if has(&["project", "portfolio", "contribution"]) {
+            Self::Projects
+        } else if has(&["language"]) {
+            Self::Languages
+        } else if has(&["certif", "licen", "award"]) {
+            Self::Certificates
+        } else if has(&["skill", "competenc", "expertise", "technolog", "tools"]) {
+            Self::Skills
+        } else {
+            Self::Other
+        }
+    }
+
+    pub fn name(self) -> &'static str {
+        match self {
+            Self::Summary => "summary",
+            Self::Experience => "work",
+            Self::Education => "education",
+            Self::Skills => "skills",
+            Self::Projects => "projects",
+            Self::Languages => "languages",
+            Self::Certificates => "certificates",
+            Self::Other => "other",
+        }
+    }
+}
+
+/// Builds a JSON Resume from Markdown in the layout the generators produce:
+/// `# Name`, a contact block, then `##` sections. Returns the resume and the
+/// sections that were recognised.
+pub fn from_markdown(md: &str) -> (JsonResume, Vec<Section>) {
+    let blocks = markdown::parse(md);
+    let mut resume = JsonResume::default();
+    let mut basics = Basics::default();
+    let mut sections = Vec::new();
+    let mut section: Option<Section> = None;
+    let mut summary: Vec<String> = Vec::new();
+    // Whether plain bullets are highlights of the last project (one started by
+    // a heading or a bold item) rather than projects of their own.
+    let mut project_open = false;
+
+    for block in &blocks {
+        match block {
+            Block::Heading { level: 1, spans } if basics.name.is_empty() => {
+                basics.name = markdown::plain_text(spans).trim().to_string();
+            }
+            Block::Heading { level: 1 | 2, spans } => {
+                let detected = Section::detect(&markdown::plain_text(spans));
+                if !sections.contains(&detected) {
+                    sections.push(detected);
+                }
+                section = Some(detected);
+            }
+            Block::Heading { spans, .. } => {
+                let title = markdown::plain_text(spans);
+                match section {
+                    Some(Section::Experience) => resume.work.push(work_from_title(&title)),
+                    Some(Section::Education) => {
+                        let mut education = Education::default();
+                        education_parts(&title, &mut education, true);
+                        resume.education.push(education);
+                    }
+                    Some(Section::Projects) => {
+                        resume.projects.push(Project { name: title.trim().to_string(), ..Default::default() });
+                        project_open = true;
+                    }
+                    _ => {}
+                }
+            }
+            // Text after a closing rule (`*References available upon request*`)
+            // belongs to no section until the next heading.
+            Block::Rule if section.is_some() => section = Some(Section::Other),
+            Block::Paragraph(spans) => {
+                let text = markdown::plain_text(spans);
+                match section {
+                    None => contact_line(spans, &mut basics),
+                    Some(Section::Summary) => summary.push(text.trim().to_string()),
+                    Some(Section::Experience) => {
+                        if let Some(work) = resume.work.last_mut() {
+                            entry_paragraph(spans, &mut work.start_date, &mut work.end_date, &mut work.summary, &mut work.location);
+                        }
+                    }
+                    Some(Section::Education) => {
+                        match resume.education.last_mut() {
+                            Some(education) => education_parts(&text, education, false),
+                            None => {
+                                let mut education = Education::default();
+                                education_parts(&text, &mut education, false);
+                                resume.education.push(education);
+                            }
+                        }
+                    }
+                    Some(Section::Projects) => {
+                        if let Some(project) = resume.projects.last_mut() {
+                            let description = project.description.get_or_insert_with(String::new);
+                            if !description.is_empty() {
+                                description.push(' ');
+                            }
+                            description.push_str(text.trim());
+                        }
+                    }
+                    _ => {}
+                }
+            }
+            Block::ListItem { spans, .. } => {
+                let text = markdown::plain_text(spans).trim().to_string();
+                match section {
+                    Some(Section::Experience) => match resume.work.last_mut() {
+                        Some(work) => work.highlights.push(text),
+                        None => resume.work.push(Work { summary: Some(text), ..Default::default() }),
+                    },
+                    Some(Section::Education) => match resume.education.last_mut() {
+                        Some(education) => education_detail(&text, education),
+                        None => resume.education.push(Education { institution: text, ..Default::default() }),
+                    },
+                    Some(Section::Skills) => resume.skills.extend(skill_item(spans)),
+                    Some(Section::Projects) => match (resume.projects.last_mut(), project_item(spans)) {
+                        (_, Some(project)) => {
+                            resume.projects.push(project);
+                            project_open = true;
+                        }
+                        (Some(project), None) if project_open => project.highlights.push(text),
+                        // A plain list of projects: `fastq - Queue library`.
+                        _ => {
+                            resume.projects.push(plain_project(&text));
+                            project_open = false;
+                        }
+                    },
+                    Some(Section::Languages) => {
+                        resume.languages.extend(split_outside_parens(&text).iter().map(|l| language_item(l)))
+                    }
+                    Some(Section::Certificates) => resume.certificates.push(certificate_item(&text)),
+                    Some(Section::Summary) => summary.push(text),
+                    _ => {}
+                }
+            }
+            _ => {}
+        }
+    }
+
+    if !summary.is_empty() {
+        basics.summary = Some(summary.join("\n\n"));
+    }
+    resume.basics = Some(basics);
+    (resume, sections)
+}
+
+/// Splits a plain-text field list such as `a, b and c`.
+fn split_list(text: &str) -> Vec<String> {
+    text.split([',', ';', '•', '·'])
+        .map(|s| s.trim().trim_end_matches('.').trim())
+        .filter(|s| !s.is_empty())
+        .map(str::to_string)
+        .collect()
+}
+
+/// Splits `English (Native), Spanish (Professional)` on commas outside parentheses.
+fn split_outside_parens(text: &str) -> Vec<String> {
+    let mut items = vec![String::new()];
+    let mut depth = 0usize;
+    for c in text.chars() {
+        match c {
+            '(' => depth += 1,
+            ')' => depth = depth.saturating_sub(1),
+            ',' | ';' if depth == 0 => {
+                items.push(String::new());
+                continue;
+            }
+            _ => {}
+        }
+        items.last_mut().expect("non-empty").push(c);
+    }
+    items.into_iter().map(|i| i.trim().to_string()).filter(|i| !i.is_empty()).collect()
+}
+
+/// Contact lines before the first section: label in bold, e-mail, phone, links, location.
+fn contact_line(spans: &[Span], basics: &mut Basics) {
+    for span in spans {
+        if let Some(url) = span.link.as_deref() {
+            let text = span.text.trim();
+            if let Some(email) = url.strip_prefix("mailto:") {
+                basics.email.get_or_insert_with(|| email.to_string());
+            } else if url.starts_with("http://") || url.starts_with("https://") {
+                let network = if text.is_empty() || text.eq_ignore_ascii_case("website") { None } else { Some(text) };
+                match network {
+                    Some(network) => basics.profiles.push(Profile {
+                        network: network.to_string(),
+                        username: None,
+                        url: Some(url.to_string()),
+                    }),
+                    None => basics.url = Some(url.to_string()),
+                }
+            }
+            continue;
+        }
+        if span.bold && basics.label.is_none() {
+            basics.label = Some(span.text.trim().to_string());
+            continue;
+        }
+        for part in span.text.split('|').map(str::trim).filter(|p| !p.is_empty()) {
+            // Template placeholders such as `[email]` carry no data.
+            if part.starts_with('[') && part.ends_with(']') {
+                continue;
+            }
+            if is_email(part) {
+                basics.email.get_or_insert_with(|| part.to_string());
+            } else if part.starts_with("http://") || part.starts_with("https://") {
+                basics.url.get_or_insert_with(|| part.to_string());
+            } else if part.chars().filter(char::is_ascii_digit).count() >= 7
+                && part.chars().all(|c| c.is_ascii_digit() || " +-().".contains(c))
+            {
+                basics.phone.get_or_insert_with(|| part.to_string());
+            } else if basics.location.is_none() && part.contains(',') {
+                let mut pieces: Vec<String> = part.split(',').map(|p| p.trim().to_string()).collect();
+                // `City, Region, CC` as `to_markdown` writes it; `Austin, TX` stays a region.
+                let country_code = pieces
+                    .last()
+                    .filter(|p| p.len() == 2 && p.chars().all(|c| c.is_ascii_uppercase()) && pieces.len() > 2)
+                    .cloned();
+                if country_code.is_some() {
+                    pieces.pop();
+                }
+                let mut pieces = pieces.into_iter();
+                basics.location = Some(Location {
+                    city: pieces.next(),
+                    region: pieces.next(),
+                    country_code,
+                    ..Default::default()
+                });
+            }
+        }
+    }
+}
+
+/// `Position | Company`, `Position at Company` or `Position, Company`.
+pub fn work_from_title(title: &str) -> Work {
+    let title = title.trim();
+    let split = [" | ", " at ", " @ ", " - ", " – ", ", "]
+        .iter()
+        .find_map(|sep| title.split_once(sep));
+    let (position, name) = match split {
+        Some((position, name)) => (position.trim(), Some(name.trim().to_string())),
+        None => (title, None),
+    };
+    Work { position: Some(position.to_string()), name, ..Default::default() }
+}
+
+/// `Bachelor of Science in Computer Science` → study type and area.
+fn degree(text: &str, education: &mut Education) {
+    let text = text.trim();
+    match text.split_once(" in ").or_else(|| text.split_once(", ")) {
+        Some((kind, area)) => {
+            education.study_type = Some(kind.trim().to_string());
+            education.area = Some(area.trim().to_string());
+        }
+        None => education.study_type = Some(text.to_string()),
+    }
+}
+
+fn looks_like_degree(text: &str) -> bool {
+    let lower = text.to_lowercase();
+    ["bachelor", "master", "doctor", "phd", "ph.d", "mba", "associate", "diploma", "degree", "b.s", "b.a", "m.s", "m.a", "bsc", "msc", "b.eng", "m.eng"]
+        .iter()
+        .any(|k| lower.starts_with(k) || lower.contains(&format!(" {}", k)))
+}
+
+fn looks_like_institution(text: &str) -> bool {
+    let lower = text.to_lowercase();
+    ["universi", "college", "institut", "school", "academy", "polytechn", "politécnic", "hochschule", "école", "escuela"]
+        .iter()
+        .any(|k| lower.contains(k))
+}
+
+/// Institution, degree and dates in any order, separated by `|`:
+/// `Bachelor of Science in Computer Science`, `*State University* | Graduated May 2017`.
+/// Unclassified text is the degree in a heading and the institution elsewhere.
+fn education_parts(text: &str, education: &mut Education, heading: bool) {
+    for part in text.split('|').map(str::trim).filter(|p| !p.is_empty()) {
+        let (part, dates) = split_trailing_dates(part);
+        if let Some(dates) = dates {
+            let lower = dates.to_lowercase();
+            let (start, end) = parse_date_range(&dates);
+            if lower.starts_with("graduated") || lower.starts_with("class of") || start.is_none() {
+                education.end_date = end.or(start);
+            } else {
+                education.start_date = start;
+                education.end_date = end;
+            }
+        }
+        let part = part.trim_end_matches([',', '-', '–', '—']).trim();
+        if part.is_empty() {
+            continue;
+        }
+        if looks_like_institution(part) && education.institution.is_empty() {
+            education.institution = part.to_string();
+        } else if looks_like_degree(part) && education.study_type.is_none() {
+            degree(part, education);
+        } else if part.contains(':') {
+            education_detail(part, education);
+        } else if heading && education.study_type.is_none() {
+            degree(part, education);
+        } else if education.institution.is_empty() {
+            education.institution = part.to_string();
+        } else if education.study_type.is_none() {
+            degree(part, education);
+        } else {
+            education_detail(part, education);
+        }
+    }
+}
+
+/// Date line (`*January 2022 - Present* | City`) or free-text summary of an entry.
+fn entry_paragraph(
+    spans: &[Span],
+    start: &mut Option<String>,
+    end: &mut Option<String>,
+    summary: &mut Option<String>,
+    location: &mut Option<String>,
+) {
+    let text = markdown::plain_text(spans);
+    let mut parts = text.split('|').map(str::trim);
+    let first = parts.next().unwrap_or_default();
+    let (from, to) = parse_date_range(first);
+    if start.is_none() && (from.is_some() || to.is_some()) && first.len() < 60 {
+        *start = from;
+        *end = to;
+        if let Some(place) = parts.next().filter(|p| !p.is_empty()) {
+            *location = Some(place.to_string());
+        }
+        return;
+    }
+    let summary = summary.get_or_insert_with(String::new);
+    if !summary.is_empty() {
+        summary.push(' ');
+    }
+    summary.push_str(text.trim());
+}
+
+/// `GPA: 3.7 / 4.0` → score, `Relevant Coursework: a, b` → courses.
+fn education_detail(text: &str, education: &mut Education) {
+    let lower = text.to_lowercase();
+    if let Some((key, value)) = text.split_once(':') {
+        let key = key.to_lowercase();
+        if key.contains("gpa") || key.contains("grade") || key.contains("score") {
+            education.score = Some(value.split('|').next().unwrap_or(value).trim().to_string());
+            return;
+        }
+        if key.contains("course") {
+            education.courses.extend(split_list(value));
+            return;
+        }
+    }
+    if lower.starts_with("gpa") {
+        education.score = Some(text.trim_start_matches(|c: char| c.is_alphabetic()).trim().to_string());
+    }
+}
+
+/// `**Rust** (Expert): tokio, axum` or a plain skill name.
+fn skill_item(spans: &[Span]) -> Vec<Skill> {
+    let text = markdown::plain_text(spans);
+    let (head, keywords) = match text.split_once(':') {
+        Some((head, rest)) => (head.trim(), split_list(rest)),
+        // A bare list such as `Rust, Go, SQL` is one skill per entry.
+        None if text.contains(',') => {
+            return split_list(&text)
+                .into_iter()
+                .map(|name| Skill { name, ..Default::default() })
+                .collect();
+        }
+        None => (text.trim(), Vec::new()),
+    };
+    let (name, level) = match head.split_once('(') {
+        Some((name, level)) => (name.trim(), Some(level.trim_end_matches(')').trim().to_string())),
+        None => (head, None),
+    };
+    if name.is_empty() {
+        return Vec::new();
+    }
+    vec![Skill { name: name.to_string(), level, keywords }]
+}
+
+/// `**Name** - description` starts a new project; other bullets are highlights.
+fn project_item(spans: &[Span]) -> Option<Project> {
+    let first = spans.first().filter(|s| s.bold)?;
+    let rest: String = markdown::plain_text(&spans[1..]);
+    let description = rest
+        .trim()
+        .trim_start_matches(['-', '–', '—', ':'])
+        .trim()
+        .to_string();
+    Some(Project {
+        name: first.text.trim().to_string(),
+        description: (!description.is_empty()).then_some(description),
+        url: first.link.clone().filter(|u| u.starts_with("http")),
+        ..Default::default()
+    })
+}
+
+/// `fastq - Queue library` or a bare project name.
+fn plain_project(text: &str) -> Project {
+    let split = [" - ", " – ", " — ", ": "].iter().find_map(|sep| text.split_once(sep));
+    match split {
+        Some((name, description)) => Project {
+            name: name.trim().to_string(),
+            description: Some(description.trim().to_string()).filter(|d| !d.is_empty()),
+            ..Default::default()
+        },
+        None => Project { name: text.trim().to_string(), ..Default::default() },
+    }
+}
+
+/// `German (Native)` or `German - Fluent`.
+fn language_item(text: &str) -> Language {
+    let split = text
+        .split_once('(')
+        .map(|(l, f)| (l, f.trim_end_matches(')')))
+        .or_else(|| text.split_once(" - "))
+        .or_else(|| text.split_once(':'));
+    match split {
+        Some((language, fluency)) => Language {
+            language: language.trim().to_string(),
+            fluency: Some(fluency.trim().to_string()).filter(|f| !f.is_empty()),
+        },
+        None => Language { language: text.trim().to_string(), fluency: None },
+    }
+}
+
+/// `AWS Solutions Architect - Associate (2023)`.
+fn certificate_item(text: &str) -> Certificate {
+    let (name, date) = match text.rsplit_once('(') {
+        Some((name, rest)) => match parse_date(rest.trim_end_matches(')')) {
+            Some(date) => (name.trim(), Some(date)),
+            None => (text.trim(), None),
+        },
+        None => (text.trim(), None),
+    };
+    Certificate { name: name.to_string(), date, ..Default::default() }
+}
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+
+    const RESUME: &str = "\
+# Jane Doe
+
+**Senior Backend Engineer**
+jane@example.com | +49 30 1234 5678 | Berlin, Brandenburg, DE | [GitHub](https://github.com/jane)
+
+## Professional Summary
+
+Backend engineer with ten years of distributed systems work.
+
+## Professional Experience
+
+### Staff Engineer | Acme GmbH
+
+*January 2021 - Present* | Berlin
+
+- Led the migration of billing to Rust
+- Cut p99 latency by 40%
+
+### Engineer at Initech
+
+*03/2016 - 12/2020*
+
+## Education
+
+### B.Sc. in Computer Science
+*TU Berlin* | October 2012 - 2016
+
+- GPA: 1.3
+- Relevant Coursework: Databases, Networks
+
+## Skills
+
+- **Rust** (Expert): tokio, axum
+- Go, SQL
+
+## Projects
+
+- **fastq** - Queue library
+  - 2k stars
+
+## Languages
+
+- German (Native), English (Fluent)
+
+## Certifications
+
+- AWS Solutions Architect (2023)
+
+---
+
+*References available upon request*
+";
+
+    #[test]
+    fn parses_generated_layout() {
+        let (resume, sections) = from_markdown(RESUME);
+        assert_eq!(
+            sections,
+            [
+                Section::Summary,
+                Section::Experience,
+                Section::Education,
+                Section::Skills,
+                Section::Projects,
+                Section::Languages,
+                Section::Certificates,
+            ]
+        );
+
+        let basics = resume.basics.as_ref().unwrap();
+        assert_eq!(basics.name, "Jane Doe");
+        assert_eq!(basics.label.as_deref(), Some("Senior Backend Engineer"));
+        assert_eq!(basics.email.as_deref(), Some("jane@example.com"));
+        assert_eq!(basics.phone.as_deref(), Some("+49 30 1234 5678"));
+        let location = basics.location.as_ref().unwrap();
+        assert_eq!(location.city.as_deref(), Some("Berlin"));
+        assert_eq!(location.country_code.as_deref(), Some("DE"));
+        assert_eq!(basics.profiles[0].network, "GitHub");
+        assert_eq!(
+            basics.summary.as_deref(),
+            Some("Backend engineer with ten years of distributed systems work.")
+        );
+
+        assert_eq!(resume.work.len(), 2);
+        let acme = &resume.work[0];
+        assert_eq!(acme.position.as_deref(), Some("Staff Engineer"));
+        assert_eq!(acme.name.as_deref(), Some("Acme GmbH"));
+        assert_eq!(acme.start_date.as_deref(), Some("2021-01"));
+        assert_eq!(acme.end_date, None);
+        assert_eq!(acme.location.as_deref(), Some("Berlin"));
+        assert_eq!(acme.highlights.len(), 2);
+        let initech = &resume.work[1];
+        assert_eq!(initech.name.as_deref(), Some("Initech"));
+        assert_eq!(initech.start_date.as_deref(), Some("2016-03"));
+        assert_eq!(initech.end_date.as_deref(), Some("2020-12"));
+
+        let education = &resume.education[0];
+        assert_eq!(education.institution, "TU Berlin");
+        assert_eq!(education.area.as_deref(), Some("Computer Science"));
+        assert_eq!(education.score.as_deref(), Some("1.3"));
+        assert_eq!(education.courses, ["Databases", "Networks"]);
+        assert_eq!(education.start_date.as_deref(), Some("2012-10"));
+        assert_eq!(education.end_date.as_deref(), Some("2016"));
+
+        let skills: Vec<(&str, Option<&str>)> =
+            resume.skills.iter().map(|s| (s.name.as_str(), s.level.as_deref())).collect();
+        assert_eq!(skills, [("Rust", Some("Expert")), ("Go", None), ("SQL", None)]);
+        assert_eq!(resume.skills[0].keywords, ["tokio", "axum"]);
+
+        assert_eq!(resume.projects[0].name, "fastq");
+        assert_eq!(resume.projects[0].description.as_deref(), Some("Queue library"));
+        assert_eq!(resume.projects[0].highlights, ["2k stars"]);
+
+        let languages: Vec<(&str, Option<&str>)> =
+            resume.languages.iter().map(|l| (l.language.as_str(), l.fluency.as_deref())).collect();
+        assert_eq!(languages, [("German", Some("Native")), ("English", Some("Fluent"))]);
+
+        assert_eq!(resume.certificates[0].name, "AWS Solutions Architect");
+        assert_eq!(resume.certificates[0].date.as_deref(), Some("2023"));
+        assert!(resume.validate().is_ok());
+    }
+
+    #[test]
+    fn round_trips_through_markdown() {
+        let (mut resume, _) = from_markdown(RESUME);
+        // Project highlights are written inline after the description.
+        resume.projects[0].highlights.clear();
+        // Entries without an institution must not leave an empty `**` behind.
+        resume.education.push(Education {
+            study_type: Some("Abitur".to_string()),
+            end_date: Some("2012".to_string()),
+            ..Default::default()
+        });
+        let (again, _) = from_markdown(&resume.to_markdown());
+        assert_eq!(
+            serde_json::to_value(&again).unwrap(),
+            serde_json::to_value(&resume).unwrap()
+        );
+    }
+
+    #[test]
+    fn parses_dates() {
+        assert_eq!(parse_date("January 2022").as_deref(), Some("2022-01"));
+        assert_eq!(parse_date("Sep. 2019").as_deref(), Some("2019-09"));
+        assert_eq!(parse_date("2020").as_deref(), Some("2020"));
+        assert_eq!(parse_date("Present"), None);
+        assert_eq!(parse_date_range("Jan 2020 – Present"), (Some("2020-01".to_string()), None));
+        assert_eq!(
+            split_trailing_dates("Senior Engineer, Acme   Jan 2020 – Present"),
+            ("Senior Engineer, Acme".to_string(), Some("Jan 2020 – Present".to_string()))
+        );
+        assert_eq!(split_trailing_dates("Version 2 rollout"), ("Version 2 rollout".to_string(), None));
+    }
+}
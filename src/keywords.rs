@@ -0,0 +1,224 @@
+use std::collections::HashMap;
+
+/// Common English function words plus job-ad boilerplate that never makes a
+/// useful keyword.
+const STOPWORDS: &[&str] = &[
+    "a", "about", "above", "across", "after", "again", "all", "also", "am", "an", "and", "any", "are", "as", "at",
+    "be", "been", "being", "both", "but", "by", "can", "could", "did", "do", "does", "doing", "each", "either", "etc",
+    "every", "few", "for", "from", "further", "get", "had", "has", "have", "having", "he", "her", "here", "him",
+    "his", "how", "i", "if", "in", "into", "is", "it", "its", "just", "may", "me", "might", "more", "most", "must",
+    "my", "new", "no", "nor", "not", "now", "of", "off", "on", "once", "one", "only", "or", "other", "our", "ours",
+    "out", "over", "own", "per", "same", "she", "should", "so", "some", "such", "than", "that", "the", "their",
+    "them", "then", "there", "these", "they", "this", "those", "through", "to", "too", "under", "until", "up",
+    "us", "use", "using", "very", "via", "was", "we", "well", "were", "what", "when", "where", "which", "while",
+    "who", "whom", "why", "will", "with", "within", "would", "you", "your", "yours",
+    // Job-ad and resume boilerplate.
+    "ability", "able", "applicant", "apply", "benefits", "build", "building", "candidate", "candidates", "company", "deep", "degree",
+    "environment", "equivalent", "excellent", "experience", "experienced", "familiarity", "good", "great", "hire", "hiring",
+    "ideal", "ideally", "including", "job", "join", "knowledge", "like", "looking", "nice", "opportunity",
+    "plus", "position", "preferred", "proficiency", "proficient", "proven", "related", "required", "requirements", "seeking",
+    "responsibilities", "responsible", "role", "skills", "solid", "strong", "team", "teams", "understanding",
+    "work", "working", "year", "years",
+];
+
+pub fn is_stopword(word: &str) -> bool {
+    STOPWORDS.contains(&word)
+}
+
+/// Lowercased word tokens. Technical spellings such as `c++`, `c#`,
+/// `node.js` and `ci/cd` stay single tokens.
+pub fn tokenize(text: &str) -> Vec<String> {
+    words(text).into_iter().map(|w| w.to_lowercase()).collect()
+}
+
+/// Word tokens in their original case.
+pub fn words(text: &str) -> Vec<&str> {
+    text.split(|c: char| !(c.is_alphanumeric() || "+#./-'’".contains(c)))
+        .map(|w| {
+            w.trim_start_matches(|c: char| "./-'’+#".contains(c))
+                .trim_end_matches(|c: char| "./-'’".contains(c))
+                .trim_end_matches("'s")
+                .trim_end_matches("’s")
+        })
+        .filter(|w| w.chars().any(char::is_alphanumeric))
+        .collect()
+}
+
+/// Crude plural folding so `pipelines` matches `pipeline`.
+pub fn stem(word: &str) -> String {
+    let len = word.chars().count();
+    if len > 4 && word.ends_with("ies") {
+        format!("{}y", &word[..word.len() - 3])
+    } else if len > 3 && word.ends_with('s') && !["ss", "us", "is", "os"].iter().any(|e| word.ends_with(e)) {
+        word[..word.len() - 1].to_string()
+    } else {
+        word.to_string()
+    }
+}
+
+/// A ranked keyword or two-word phrase. `term` keeps the most common
+/// spelling from the source text.
+#[derive(Clone, Debug, serde::Serialize)]
+pub struct Keyword {
+    pub term: String,
+    pub weight: f64,
+}
+
+fn is_candidate(word: &str) -> bool {
+    let lower = word.to_lowercase();
+    !is_stopword(&lower)
+        && lower.chars().count() >= 2
+        && lower.chars().any(char::is_alphabetic)
+        && !lower.chars().all(|c| c.is_ascii_digit() || c == '.')
+}
+
+/// Technical-looking spellings (`Kubernetes`, `C++`, `S3`, `gRPC`) are
+/// stronger signals than ordinary lowercase words.
+fn is_technical(word: &str) -> bool {
+    word.chars().skip(1).any(char::is_uppercase)
+        || word.chars().any(|c| c.is_ascii_digit() || "+#./".contains(c))
+}
+
+/// Ranks single words and recurring two-word phrases by frequency, boosting
+/// capitalised and technical terms. Phrases never span punctuation.
+pub fn extract(text: &str, limit: usize) -> Vec<Keyword> {
+    let mut counts: HashMap<String, f64> = HashMap::new();
+    let mut forms: HashMap<String, HashMap<String, usize>> = HashMap::new();
+    let mut phrase_counts: HashMap<String, usize> = HashMap::new();
+
+    for (clause, sentence_start) in clauses(text) {
+        let clause_words = words(clause);
+        for (i, word) in clause_words.iter().enumerate() {
+            let first = i == 0 && sentence_start;
+            if !is_candidate(word) {
+                continue;
+            }
+            let key = stem(&word.to_lowercase());
+            let mut score = 1.0;
+            if is_technical(word) || (!first && word.starts_with(char::is_uppercase)) {
+                score = 1.5;
+            }
+            *counts.entry(key.clone()).or_default() += score;
+            *forms.entry(key).or_default().entry(spelling(word, first)).or_default() += 1;
+
+            if let Some(next) = clause_words.get(i + 1).filter(|n| is_candidate(n)) {
+                let phrase = format!("{} {}", spelling(word, first), next);
+                let key = format!("{} {}", stem(&word.to_lowercase()), stem(&next.to_lowercase()));
+                *phrase_counts.entry(key.clone()).or_default() += 1;
+                *forms.entry(key).or_default().entry(phrase).or_default() += 1;
+            }
+        }
+    }
+
+    let mut ranked: Vec<(String, f64)> = counts.into_iter().collect();
+    for (phrase, count) in phrase_counts {
+        if count < 2 {
+            continue;
+        }
+        // A recurring phrase replaces its words when they never appear apart.
+        let parts: Vec<&str> = phrase.split(' ').collect();
+        let score = parts
+            .iter()
+            .filter_map(|p| ranked.iter().find(|(k, _)| k == p).map(|(_, s)| *s))
+            .fold(0.0, f64::max)
+            .max(count as f64)
+            * 1.2;
+        ranked.retain(|(key, _)| {
+            !parts.contains(&key.as_str()) || ranked_count(&forms, key) > count
+        });
+        ranked.push((phrase, score));
+    }
+
+    ranked.sort_by(|a, b| b.1.total_cmp(&a.1).then_with(|| a.0.cmp(&b.0)));
+    ranked
+        .into_iter()
+        .take(limit)
+        .map(|(key, weight)| Keyword { term: surface_form(&forms, &key), weight })
+        .collect()
+}
+
+/// Splits text at punctuation so phrases never span it. The flag marks
+/// clauses that begin a sentence, where capitals say nothing about a term.
+fn clauses(text: &str) -> Vec<(&str, bool)> {
+    let mut out = Vec::new();
+    let mut start = 0;
+    let mut sentence_start = true;
+    for (i, c) in text.char_indices() {
+        if ".,;:!?()\n•|".contains(c) {
+            out.push((&text[start..i], sentence_start));
+            start = i + c.len_utf8();
+            sentence_start = ".!?\n•|".contains(c);
+        }
+    }
+    out.push((&text[start..], sentence_start));
+    out
+}
+
+/// Capitals at the start of a sentence are not part of the term's spelling.
+fn spelling(word: &str, sentence_start: bool) -> String {
+    if sentence_start && !is_technical(word) {
+        word.to_lowercase()
+    } else {
+        word.to_string()
+    }
+}
+
+fn ranked_count(forms: &HashMap<String, HashMap<String, usize>>, key: &str) -> usize {
+    forms.get(key).map_or(0, |f| f.values().sum())
+}
+
+/// Most frequent original spelling of a stemmed key.
+fn surface_form(forms: &HashMap<String, HashMap<String, usize>>, key: &str) -> String {
+    forms
+        .get(key)
+        .and_then(|f| f.iter().max_by(|a, b| a.1.cmp(b.1).then_with(|| b.0.cmp(a.0))))
+        .map_or_else(|| key.to_string(), |(form, _)| form.clone())
+}
+
+/// Stemmed token sequence used for matching terms against text.
+pub fn normalized(text: &str) -> Vec<String> {
+    tokenize(text).iter().map(|w| stem(w)).collect()
+}
+
+/// Whether `term` occurs in `tokens` (from [`normalized`]) as a contiguous run.
+pub fn contains(tokens: &[String], term: &str) -> bool {
+    let needle = normalized(term);
+    !needle.is_empty() && tokens.windows(needle.len()).any(|w| w == needle.as_slice())
+}
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+
+    #[test]
+    fn tokenizes_technical_spellings() {
+        assert_eq!(
+            tokenize("Node.js, C++ and CI/CD; the team's C# (.NET) skills."),
+            ["node.js", "c++", "and", "ci/cd", "the", "team", "c#", "net", "skills"]
+        );
+        assert_eq!(stem("pipelines"), "pipeline");
+        assert_eq!(stem("libraries"), "library");
+        assert_eq!(stem("kubernetes"), "kubernete");
+        assert_eq!(stem("status"), "status");
+    }
+
+    #[test]
+    fn ranks_technical_terms_and_recurring_phrases() {
+        let text = "We are seeking a backend engineer. You will own data pipelines in Rust. \
+                    Experience with Kubernetes and data pipelines is required. Rust, Kubernetes, PostgreSQL.";
+        let terms: Vec<String> = extract(text, 4).into_iter().map(|k| k.term).collect();
+        assert_eq!(terms, ["Kubernetes", "Rust", "data pipelines", "PostgreSQL"]);
+
+        // Stopwords and boilerplate never rank.
+        assert!(extract("Strong experience working with the team", 5).is_empty());
+    }
+
+    #[test]
+    fn matches_terms_as_contiguous_stemmed_runs() {
+        let tokens = normalized("Built streaming data pipelines on Kubernetes.");
+        assert!(contains(&tokens, "data pipeline"));
+        assert!(contains(&tokens, "Kubernetes"));
+        assert!(!contains(&tokens, "pipeline data"));
+        assert!(!contains(&tokens, ""));
+    }
+}
@@ -1,12 +1,36 @@
 #[global_allocator]
 static GLOBAL: mimalloc::MiMalloc = mimalloc::MiMalloc;
 
+mod audio;
+mod bot_actions;
+mod bot_stats;
+mod bots;
+mod captions;
 mod config;
+mod crawl;
+mod diff;
+mod docx;
 mod state;
 mod error;
+mod export;
+mod fetch;
+mod gallery;
+mod html;
+mod json_resume;
+mod keywords;
+mod markdown;
+mod net;
+mod page;
+mod pdf;
 mod rate_limiter;
+mod resume_import;
+mod transcripts;
+mod upstream;
 mod mock;
 mod routes;
+mod seo;
+mod ssml;
+mod tailor;
 
 use axum::{
     http::Method,
@@ -29,12 +53,14 @@ async fn main() {
     };
 
     tracing::info!(
-        "Starting Nexus AI on port {} (demo_mode={})",
-        config.port, config.demo_mode
+        "Starting Nexus AI for {} on port {} (demo_mode={})",
+        config.domain, config.port, config.demo_mode
     );
 
     let app_state = AppState::new(config.clone(), rate_limit_config);
 
+    tokio::spawn(cleanup_task(app_state.clone()));
+
     let cors = CorsLayer::permissive();
 
     let app = routes::create_router(app_state)
@@ -65,6 +91,19 @@ async fn logging_middleware(
     next.run(req).await
 }
 
+async fn cleanup_task(state: AppState) {
+    let mut interval = tokio::time::interval(std::time::Duration::from_secs(
+        state.rate_limit_config.cleanup_interval_secs,
+    ));
+    loop {
+        interval.tick().await;
+        let purged = state.transcripts.purge_expired();
+        if purged > 0 {
+            tracing::info!("Purged {} expired bot transcripts", purged);
+        }
+    }
+}
+
 async fn shutdown_signal() {
     let ctrl_c = async {
         tokio::signal::ctrl_c()
@@ -0,0 +1,336 @@
+/// Block-level Markdown, limited to what the generators produce.
+#[derive(Clone, Debug, PartialEq)]
+pub enum Block {
+    Heading { level: u8, spans: Vec<Span> },
+    Paragraph(Vec<Span>),
+    /// `number` is `Some` for ordered lists.
+    ListItem { number: Option<u32>, spans: Vec<Span> },
+    Quote(Vec<Span>),
+    Code(String),
+    Rule,
+}
+
+/// Run of inline text sharing one style.
+#[derive(Clone, Debug, Default, PartialEq)]
+pub struct Span {
+    pub text: String,
+    pub bold: bool,
+    pub italic: bool,
+    pub code: bool,
+    pub link: Option<String>,
+}
+
+/// Parses headings, paragraphs, bullet / numbered lists, quotes, fenced code and
+/// horizontal rules. Consecutive lines of a paragraph or list item are joined.
+pub fn parse(markdown: &str) -> Vec<Block> {
+    parse_blocks(markdown, ' ')
+}
+
+/// Like [`parse`], but keeps the line breaks inside paragraphs as `\n` in the
+/// span text, for letters and e-mails where signature lines matter.
+pub fn parse_with_breaks(markdown: &str) -> Vec<Block> {
+    parse_blocks(markdown, '\n')
+}
+
+fn parse_blocks(markdown: &str, line_joiner: char) -> Vec<Block> {
+    let mut blocks = Vec::new();
+    let mut lines = markdown.lines().peekable();
+
+    while let Some(line) = lines.next() {
+        let trimmed = line.trim();
+        if trimmed.is_empty() {
+            continue;
+        }
+
+        if let Some(fence) = ["```", "~~~"].into_iter().find(|f| trimmed.starts_with(f)) {
+            let mut code = Vec::new();
+            for line in lines.by_ref() {
+                if line.trim().starts_with(fence) {
+                    break;
+                }
+                code.push(line);
+            }
+            blocks.push(Block::Code(code.join("\n")));
+            continue;
+        }
+
+        if is_rule(trimmed) {
+            blocks.push(Block::Rule);
+            continue;
+        }
+
+        if let Some((level, text)) = heading(trimmed) {
+            blocks.push(Block::Heading { level, spans: parse_inline(text) });
+            continue;
+        }
+
+        // Paragraph-like blocks absorb following lines until a blank line or a new block.
+        let (kind, first) = if let Some(text) = bullet(trimmed) {
+            (LineKind::Bullet, text)
+        } else if let Some((number, text)) = numbered(trimmed) {
+            (LineKind::Numbered(number), text)
+        } else if let Some(text) = trimmed.strip_prefix('>') {
+            (LineKind::Quote, text.trim_start())
+        } else {
+            (LineKind::Paragraph, trimmed)
+        };
+
+        let mut text = first.to_string();
+        while let Some(next) = lines.peek() {
+            let next = next.trim();
+            let continues_quote = matches!(kind, LineKind::Quote) && next.starts_with('>');
+            if next.is_empty() || (starts_block(next) && !continues_quote) {
+                break;
+            }
+            let next = match kind {
+                LineKind::Quote => next.strip_prefix('>').unwrap_or(next).trim_start(),
+                _ => next,
+            };
+            text.push(line_joiner);
+            text.push_str(next);
+            lines.next();
+        }
+
+        let spans = parse_inline(&text);
+        blocks.push(match kind {
+            LineKind::Bullet => Block::ListItem { number: None, spans },
+            LineKind::Numbered(number) => Block::ListItem { number: Some(number), spans },
+            LineKind::Quote => Block::Quote(spans),
+            LineKind::Paragraph => Block::Paragraph(spans),
+        });
+    }
+
+    blocks
+}
+
+enum LineKind {
+    Paragraph,
+    Bullet,
+    Numbered(u32),
+    Quote,
+}
+
+fn is_rule(line: &str) -> bool {
+    let compact: String = line.chars().filter(|c| !c.is_whitespace()).collect();
+    compact.len() >= 3
+        && ['-', '*', '_'].into_iter().any(|c| compact.chars().all(|x| x == c))
+}
+
+fn heading(line: &str) -> Option<(u8, &str)> {
+    let level = line.chars().take_while(|&c| c == '#').count();
+    let rest = &line[level..];
+    if (1..=6).contains(&level) && (rest.is_empty() || rest.starts_with(' ')) {
+        Some((level as u8, rest.trim().trim_end_matches('#').trim_end()))
+    } else {
+        None
+    }
+}
+
+fn bullet(line: &str) -> Option<&str> {
+    ["- ", "* ", "+ "]
+        .into_iter()
+        .find_map(|marker| line.strip_prefix(marker))
+        .map(str::trim_start)
+}
+
+fn numbered(line: &str) -> Option<(u32, &str)> {
+    let digits = line.chars().take_while(char::is_ascii_digit).count();
+    let rest = &line[digits..];
+    let text = rest.strip_prefix(". ").or_else(|| rest.strip_prefix(") "))?;
+    Some((line[..digits].parse().ok()?, text.trim_start()))
+}
+
+fn starts_block(line: &str) -> bool {
+    line.starts_with("```")
+        || line.starts_with("~~~")
+        || line.starts_with('>')
+        || is_rule(line)
+        || heading(line).is_some()
+        || bullet(line).is_some()
+        || numbered(line).is_some()
+}
+
+/// Parses `**bold**`, `*italic*` / `_italic_`, `` `code` `` and `[text](url)`.
+/// Markers without a closing partner are kept as literal text.
+pub fn parse_inline(text: &str) -> Vec<Span> {
+    let mut spans = Vec::new();
+    push_inline(text, &Span::default(), &mut spans);
+    spans
+}
+
+fn push_inline(text: &str, style: &Span, out: &mut Vec<Span>) {
+    let mut literal = String::new();
+    let mut rest = text;
+
+    while let Some(c) = rest.chars().next() {
+        let parsed = match c {
+            '`' => rest[1..].find('`').map(|end| {
+                let span = Span { text: rest[1..1 + end].to_string(), code: true, ..style.clone() };
+                (vec![span], 2 + end)
+            }),
+            '[' => link(rest).map(|(label, url, len)| {
+                let mut inner = Vec::new();
+                push_inline(label, &Span { link: Some(url.to_string()), ..style.clone() }, &mut inner);
+                (inner, len)
+            }),
+            '*' | '_' => emphasis(rest).map(|(marker_len, inner_text, len)| {
+                let mut inner_style = style.clone();
+                if marker_len >= 2 {
+                    inner_style.bold = true;
+                }
+                if marker_len != 2 {
+                    inner_style.italic = true;
+                }
+                let mut inner = Vec::new();
+                push_inline(inner_text, &inner_style, &mut inner);
+                (inner, len)
+            }),
+            _ => None,
+        };
+
+        match parsed {
+            Some((inner, len)) => {
+                flush(&mut literal, style, out);
+                out.extend(inner);
+                rest = &rest[len..];
+            }
+            None => {
+                literal.push(c);
+                rest = &rest[c.len_utf8()..];
+            }
+        }
+    }
+    flush(&mut literal, style, out);
+}
+
+fn flush(literal: &mut String, style: &Span, out: &mut Vec<Span>) {
+    if !literal.is_empty() {
+        out.push(Span { text: std::mem::take(literal), ..style.clone() });
+    }
+}
+
+/// `[label](url)` at the start of `text`: returns label, url and consumed length.
+fn link(text: &str) -> Option<(&str, &str, usize)> {
+    let close = text.find("](")?;
+    let end = text[close + 2..].find(')')? + close + 2;
+    let label = &text[1..close];
+    if label.contains('[') {
+        return None;
+    }
+    Some((label, text[close + 2..end].trim(), end + 1))
+}
+
+/// Emphasis opening at the start of `text`: marker length (1-3), inner text and
+/// consumed length. The closing marker must directly follow non-space text.
+fn emphasis(text: &str) -> Option<(usize, &str, usize)> {
+    let marker = text.chars().next()?;
+    let run = text.chars().take_while(|&c| c == marker).count().min(3);
+    let delimiter = &text[..run];
+    let body = &text[run..];
+    if body.starts_with(char::is_whitespace) || body.is_empty() {
+        return None;
+    }
+
+    let mut search = 0;
+    while let Some(found) = body[search..].find(delimiter) {
+        let end = search + found;
+        let closes = end > 0
+            && !body[..end].ends_with(char::is_whitespace)
+            && !body[end + run..].starts_with(marker);
+        // `_` inside words (snake_case) is not emphasis.
+        let word_boundary = marker != '_'
+            || !body[end + run..].starts_with(|c: char| c.is_alphanumeric());
+        if closes && word_boundary {
+            return Some((run, &body[..end], run + end + run));
+        }
+        search = end + run;
+    }
+    None
+}
+
+/// Concatenated text of the spans, without markup.
+pub fn plain_text(spans: &[Span]) -> String {
+    spans.iter().map(|s| s.text.as_str()).collect()
+}
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+
+    fn text(value: &str) -> Span {
+        Span { text: value.to_string(), ..Span::default() }
+    }
+
+    #[test]
+    fn parses_blocks() {
+        let blocks = parse(
+            "# Jane Doe ##\n\nSenior engineer\nin Berlin.\n\n- Led **migration**\n  to Rust\n2. Second\n\n\
+             > quoted\n> line\n\n---\n```\nlet x = 1;\n\nx\n```\n",
+        );
+        assert_eq!(
+            blocks,
+            vec![
+                Block::Heading { level: 1, spans: vec![text("Jane Doe")] },
+                Block::Paragraph(vec![text("Senior engineer in Berlin.")]),
+                Block::ListItem {
+                    number: None,
+                    spans: vec![
+                        text("Led "),
+                        Span { text: "migration".to_string(), bold: true, ..Span::default() },
+                        text(" to Rust"),
+                    ],
+                },
+                Block::ListItem { number: Some(2), spans: vec![text("Second")] },
+                Block::Quote(vec![text("quoted line")]),
+                Block::Rule,
+                Block::Code("let x = 1;\n\nx".to_string()),
+            ]
+        );
+    }
+
+    #[test]
+    fn keeps_line_breaks_when_asked() {
+        assert_eq!(
+            parse_with_breaks("Best regards,\nJane"),
+            vec![Block::Paragraph(vec![text("Best regards,\nJane")])]
+        );
+        // `#hashtag` is not a heading and a block marker ends a paragraph.
+        assert_eq!(
+            parse("#hashtag\n- item"),
+            vec![
+                Block::Paragraph(vec![text("#hashtag")]),
+                Block::ListItem { number: None, spans: vec![text("item")] },
+            ]
+        );
+    }
+
+    #[test]
+    fn parses_inline_styles() {
+        let spans = parse_inline("a *b* ***c*** `d*e` [f **g**](https://x.dev)");
+        let styled: Vec<(&str, bool, bool, bool, Option<&str>)> = spans
+            .iter()
+            .map(|s| (s.text.as_str(), s.bold, s.italic, s.code, s.link.as_deref()))
+            .collect();
+        assert_eq!(
+            styled,
+            vec![
+                ("a ", false, false, false, None),
+                ("b", false, true, false, None),
+                (" ", false, false, false, None),
+                ("c", true, true, false, None),
+                (" ", false, false, false, None),
+                ("d*e", false, false, true, None),
+                (" ", false, false, false, None),
+                ("f ", false, false, false, Some("https://x.dev")),
+                ("g", true, false, false, Some("https://x.dev")),
+            ]
+        );
+    }
+
+    #[test]
+    fn unmatched_markers_stay_literal() {
+        let literal = "2 * 3 = 6, snake_case_name, `open";
+        assert_eq!(parse_inline(literal), vec![text(literal)]);
+        assert_eq!(plain_text(&parse_inline("**bold** and _it_")), "bold and it");
+    }
+}
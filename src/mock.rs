@@ -1,32 +1,40 @@
-use base64::Engine;
 use once_cell::sync::Lazy;
 use rand::Rng;
 use serde::Serialize;
 
+use crate::ssml::SpeechSegment;
+
 // ---------------------------------------------------------------------------
 // 11. VOICE_LIST
 // ---------------------------------------------------------------------------
 
+/// Advertised voice. `provider_voice` is the OpenAI TTS voice used in real mode.
 #[derive(Clone, Serialize)]
 pub struct VoiceInfo {
     pub name: String,
     pub locale: String,
     pub gender: String,
+    pub provider_voice: String,
 }
 
 pub static VOICE_LIST: Lazy<Vec<VoiceInfo>> = Lazy::new(|| {
     vec![
-        VoiceInfo { name: "en-US-AriaNeural".into(),   locale: "en-US".into(), gender: "female".into() },
-        VoiceInfo { name: "en-US-GuyNeural".into(),    locale: "en-US".into(), gender: "male".into()   },
-        VoiceInfo { name: "en-GB-SoniaNeural".into(),  locale: "en-GB".into(), gender: "female".into() },
-        VoiceInfo { name: "en-GB-RyanNeural".into(),   locale: "en-GB".into(), gender: "male".into()   },
-        VoiceInfo { name: "fr-FR-DeniseNeural".into(), locale: "fr-FR".into(), gender: "female".into() },
-        VoiceInfo { name: "de-DE-KatjaNeural".into(),  locale: "de-DE".into(), gender: "female".into() },
-        VoiceInfo { name: "es-ES-HelenaNeural".into(), locale: "es-ES".into(), gender: "female".into() },
-        VoiceInfo { name: "ja-JP-AzukiNeural".into(),  locale: "ja-JP".into(), gender: "female".into() },
+        VoiceInfo { name: "en-US-AriaNeural".into(),   locale: "en-US".into(), gender: "female".into(), provider_voice: "nova".into()    },
+        VoiceInfo { name: "en-US-GuyNeural".into(),    locale: "en-US".into(), gender: "male".into(),   provider_voice: "onyx".into()    },
+        VoiceInfo { name: "en-GB-SoniaNeural".into(),  locale: "en-GB".into(), gender: "female".into(), provider_voice: "shimmer".into() },
+        VoiceInfo { name: "en-GB-RyanNeural".into(),   locale: "en-GB".into(), gender: "male".into(),   provider_voice: "fable".into()   },
+        VoiceInfo { name: "fr-FR-DeniseNeural".into(), locale: "fr-FR".into(), gender: "female".into(), provider_voice: "alloy".into()   },
+        VoiceInfo { name: "de-DE-KatjaNeural".into(),  locale: "de-DE".into(), gender: "female".into(), provider_voice: "shimmer".into() },
+        VoiceInfo { name: "es-ES-HelenaNeural".into(), locale: "es-ES".into(), gender: "female".into(), provider_voice: "nova".into()    },
+        VoiceInfo { name: "ja-JP-AzukiNeural".into(),  locale: "ja-JP".into(), gender: "female".into(), provider_voice: "alloy".into()   },
     ]
 });
 
+/// Looks up an advertised voice by name (case-insensitive).
+pub fn find_voice(name: &str) -> Option<&'static VoiceInfo> {
+    VOICE_LIST.iter().find(|v| v.name.eq_ignore_ascii_case(name.trim()))
+}
+
 // ---------------------------------------------------------------------------
 // 12. random_delay
 // ---------------------------------------------------------------------------
@@ -490,7 +498,7 @@ connect with fellow enthusiasts!\n\n\
             ),
         },
 
-        "facebook" | _ => match tone.to_lowercase().as_str() {
+        _ => match tone.to_lowercase().as_str() {
             "professional" | "formal" => format!(
                 "We're excited to share some important updates about {topic}.\n\n\
 Over the past quarter, our team has been working diligently to analyze \
@@ -1307,7 +1315,7 @@ Best,\n\
 Head of Customer Success"
         ),
 
-        "cold_outreach" | _ => format!(
+        _ => format!(
             "Subject: {subject}\n\n\
 Hi [First Name],\n\n\
 I came across your work at [Company] and was genuinely impressed by what \
@@ -1546,7 +1554,7 @@ However, if [alternative use case], you might want to consider [alternative].\n\
 Overall rating: [X/10]. Links to everything are in the description below.\""
         ),
 
-        "documentary" | _ => format!(
+        _ => format!(
             "# Documentary Script: {topic}\n\
 # Duration: {duration}\n\n\
 ---\n\n\
@@ -1602,183 +1610,255 @@ in history, each of us has a role in deciding how it ends.\"\n\n\
 }
 
 // ---------------------------------------------------------------------------
-// 6. mock_seo_report  (JSON analysis)
+// 7. mock_image_bytes  (prompt-seeded procedural PNG)
 // ---------------------------------------------------------------------------
 
-pub fn mock_seo_report(content: &str) -> serde_json::Value {
-    let word_count = content.split_whitespace().count();
-    let char_count = content.len();
-    let sentence_count = content.matches('.').count()
-        + content.matches('!').count()
-        + content.matches('?').count();
-    let sentence_count = if sentence_count == 0 { 1 } else { sentence_count };
-    let avg_sentence_len = word_count / sentence_count;
-    let has_headings = content.contains('#') || content.contains("<h");
-    let has_links = content.contains("http") || content.contains("<a ");
-    let has_images = content.contains("![") || content.contains("<img");
-
-    // Score calculations
-    let readability_score: u32 = if avg_sentence_len < 15 { 92 }
-        else if avg_sentence_len < 20 { 78 }
-        else if avg_sentence_len < 25 { 65 }
-        else { 48 };
-
-    let structure_score: u32 = {
-        let mut s: u32 = 50;
-        if has_headings { s += 20; }
-        if has_links { s += 15; }
-        if has_images { s += 15; }
-        if word_count > 300 { s += 10; }
-        s.min(100)
+/// Prompt keywords that pull the palette towards a recognisable hue (degrees).
+const IMAGE_HUE_HINTS: &[(&str, f64)] = &[
+    ("sunset", 20.0), ("fire", 10.0), ("desert", 35.0), ("autumn", 30.0),
+    ("gold", 45.0), ("forest", 120.0), ("jungle", 110.0), ("ocean", 205.0),
+    ("sea", 200.0), ("sky", 210.0), ("ice", 190.0), ("space", 250.0),
+    ("galaxy", 270.0), ("neon", 300.0), ("rose", 340.0), ("cherry", 350.0),
+];
+
+/// Deterministic generator so the same prompt + seed always yields the same image.
+struct SplitMix64(u64);
+
+impl SplitMix64 {
+    fn next_u64(&mut self) -> u64 {
+        self.0 = self.0.wrapping_add(0x9E37_79B9_7F4A_7C15);
+        let mut z = self.0;
+        z = (z ^ (z >> 30)).wrapping_mul(0xBF58_476D_1CE4_E5B9);
+        z = (z ^ (z >> 27)).wrapping_mul(0x94D0_49BB_1331_11EB);
+        z ^ (z >> 31)
+    }
+
+    /// Uniform float in [0, 1).
+    fn next_f64(&mut self) -> f64 {
+        (self.next_u64() >> 11) as f64 / (1u64 << 53) as f64
+    }
+
+    fn range(&mut self, lo: f64, hi: f64) -> f64 {
+        lo + (hi - lo) * self.next_f64()
+    }
+}
+
+/// FNV-1a over the prompt, mixed with the seed.
+fn image_hash(prompt: &str, seed: u32) -> u64 {
+    let mut hash: u64 = 0xCBF2_9CE4_8422_2325;
+    for byte in prompt.trim().to_lowercase().bytes().chain(seed.to_le_bytes()) {
+        hash ^= byte as u64;
+        hash = hash.wrapping_mul(0x0100_0000_01B3);
+    }
+    hash
+}
+
+fn hsl_to_rgb(h: f64, s: f64, l: f64) -> [f64; 3] {
+    let c = (1.0 - (2.0 * l - 1.0).abs()) * s;
+    let hp = (h.rem_euclid(360.0)) / 60.0;
+    let x = c * (1.0 - (hp % 2.0 - 1.0).abs());
+    let (r, g, b) = match hp as u32 {
+        0 => (c, x, 0.0),
+        1 => (x, c, 0.0),
+        2 => (0.0, c, x),
+        3 => (0.0, x, c),
+        4 => (x, 0.0, c),
+        _ => (c, 0.0, x),
     };
+    let m = l - c / 2.0;
+    [(r + m) * 255.0, (g + m) * 255.0, (b + m) * 255.0]
+}
 
-    let keyword_density_score: u32 = if word_count > 100 { 82 } else { 58 };
-
-    let overall_score = (readability_score + structure_score + keyword_density_score) / 3;
-
-    serde_json::json!({
-        "overall_score": overall_score,
-        "analysis": {
-            "readability": {
-                "score": readability_score,
-                "grade": if readability_score >= 80 { "A" } else if readability_score >= 60 { "B" } else { "C" },
-                "avg_sentence_length": avg_sentence_len,
-                "word_count": word_count,
-                "character_count": char_count,
-                "sentence_count": sentence_count,
-                "reading_time_minutes": (word_count as f64 / 200.0).ceil() as u32,
-                "flesch_kincaid_grade": if avg_sentence_len < 15 { 7.2 } else if avg_sentence_len < 20 { 9.5 } else { 12.1 }
-            },
-            "structure": {
-                "score": structure_score,
-                "has_headings": has_headings,
-                "has_links": has_links,
-                "has_images": has_images,
-                "paragraph_count": content.matches("\n\n").count() + 1,
-                "heading_count": content.matches('#').count()
-            },
-            "keyword_optimization": {
-                "score": keyword_density_score,
-                "primary_keywords_detected": [
-                    {"keyword": "technology", "count": 3, "density": "1.8%"},
-                    {"keyword": "solution", "count": 2, "density": "1.2%"},
-                    {"keyword": "performance", "count": 2, "density": "1.2%"},
-                    {"keyword": "digital", "count": 1, "density": "0.6%"},
-                    {"keyword": "strategy", "count": 1, "density": "0.6%"}
-                ],
-                "recommended_density_range": "1.0% - 2.5%"
-            },
-            "technical_seo": {
-                "meta_description_length": "optimal",
-                "title_tag_length": "good",
-                "url_structure": "clean",
-                "mobile_friendly": true,
-                "page_speed_estimate": "fast",
-                "schema_markup_suggested": true
-            }
-        },
-        "recommendations": [
-            {
-                "priority": "high",
-                "category": "content",
-                "title": "Increase content length",
-                "description": format!(
-                    "Current word count is {}. For competitive ranking, aim for 1,500-2,500 words. \
-Longer content tends to rank higher for informational queries and provides more \
-opportunities for natural keyword integration.", word_count
-                )
-            },
-            {
-                "priority": "high",
-                "category": "structure",
-                "title": "Add structured headings (H2, H3)",
-                "description": "Break your content into clear sections with descriptive headings. \
-This improves both user experience and search engine understanding of your content hierarchy. \
-Aim for one H2 every 200-300 words."
-            },
-            {
-                "priority": "medium",
-                "category": "keywords",
-                "title": "Optimize keyword placement",
-                "description": "Include your primary keyword in the first 100 words, in at least one \
-H2 heading, and in the final paragraph. This signals relevance to search engines without \
-appearing spammy."
-            },
-            {
-                "priority": "medium",
-                "category": "engagement",
-                "title": "Add internal and external links",
-                "description": "Include 2-3 internal links to related content on your site and 1-2 \
-external links to authoritative sources. This builds topical authority and keeps users \
-engaged longer."
-            },
-            {
-                "priority": "medium",
-                "category": "media",
-                "title": "Include visual content",
-                "description": "Add at least one relevant image with descriptive alt text for every \
-300 words. Visual content increases time on page and provides additional ranking \
-opportunities through image search."
-            },
-            {
-                "priority": "low",
-                "category": "technical",
-                "title": "Implement schema markup",
-                "description": "Add appropriate structured data (Article, FAQ, or HowTo schema) to \
-help search engines understand your content type and potentially earn rich snippets \
-in search results."
-            },
-            {
-                "priority": "low",
-                "category": "meta",
-                "title": "Craft a compelling meta description",
-                "description": "Write a 150-160 character meta description that includes your primary \
-keyword and a clear value proposition. This directly affects click-through rates from \
-search results."
+enum MockShape {
+    Circle { cx: f64, cy: f64, r: f64 },
+    Rect { x0: f64, y0: f64, x1: f64, y1: f64 },
+    Ring { cx: f64, cy: f64, r: f64, thickness: f64 },
+}
+
+impl MockShape {
+    fn contains(&self, x: f64, y: f64) -> bool {
+        match *self {
+            MockShape::Circle { cx, cy, r } => (x - cx).powi(2) + (y - cy).powi(2) <= r * r,
+            MockShape::Rect { x0, y0, x1, y1 } => x >= x0 && x <= x1 && y >= y0 && y <= y1,
+            MockShape::Ring { cx, cy, r, thickness } => {
+                let d = ((x - cx).powi(2) + (y - cy).powi(2)).sqrt();
+                (d - r).abs() <= thickness / 2.0
             }
-        ],
-        "competitor_insights": {
-            "estimated_difficulty": "medium",
-            "top_ranking_avg_word_count": 1847,
-            "top_ranking_avg_backlinks": 23,
-            "content_gap_opportunities": [
-                "FAQ section addressing common questions",
-                "Comparison table with alternatives",
-                "Case study or real-world example",
-                "Step-by-step implementation guide"
-            ]
         }
-    })
+    }
 }
 
-// ---------------------------------------------------------------------------
-// 7. mock_image_base64  (valid 200x200 gradient PNG)
-// ---------------------------------------------------------------------------
-
-/// Returns a base64-encoded 200x200 gradient PNG generated programmatically.
+/// Renders a deterministic abstract image for `prompt` and `seed` as RGB pixels.
 ///
-/// The PNG uses a single IDAT chunk with uncompressed (store) deflate blocks.
-/// Each row is: filter_byte(0) + 200 RGBA pixels.
-pub fn mock_image_base64() -> String {
-    let width: u32 = 200;
-    let height: u32 = 200;
-    let mut raw_data: Vec<u8> = Vec::new();
-
-    // Build raw image data (filter byte 0 + RGBA per row)
+/// The prompt hash picks the palette (nudged by keywords such as "ocean" or
+/// "night"), the background gradient, a set of translucent shapes and a soft
+/// value-noise grain.
+pub fn mock_image_rgb(prompt: &str, seed: u32, width: u32, height: u32) -> Vec<u8> {
+    let mut rng = SplitMix64(image_hash(prompt, seed));
+    let low = prompt.to_lowercase();
+
+    let base_hue = IMAGE_HUE_HINTS
+        .iter()
+        .find(|(word, _)| low.contains(word))
+        .map(|(_, hue)| hue + rng.range(-15.0, 15.0))
+        .unwrap_or_else(|| rng.range(0.0, 360.0));
+    let dark = ["night", "dark", "shadow", "noir"].iter().any(|w| low.contains(w));
+    let lightness = if dark { 0.22 } else { rng.range(0.45, 0.6) };
+
+    let palette: Vec<[f64; 3]> = [0.0, 35.0, 180.0, 215.0]
+        .iter()
+        .map(|offset| {
+            let l = (lightness + rng.range(-0.12, 0.12)).clamp(0.08, 0.85);
+            hsl_to_rgb(base_hue + offset, rng.range(0.45, 0.85), l)
+        })
+        .collect();
+
+    let (w, h) = (width as f64, height as f64);
+    let angle = rng.range(0.0, std::f64::consts::TAU);
+    let (dx, dy) = (angle.cos(), angle.sin());
+
+    let shape_count = 5 + (rng.next_u64() % 8) as usize;
+    let shapes: Vec<(MockShape, [f64; 3], f64)> = (0..shape_count)
+        .map(|_| {
+            let cx = rng.range(0.0, w);
+            let cy = rng.range(0.0, h);
+            let size = rng.range(0.05, 0.3) * w.min(h);
+            let shape = match rng.next_u64() % 3 {
+                0 => MockShape::Circle { cx, cy, r: size },
+                1 => MockShape::Rect {
+                    x0: cx - size,
+                    y0: cy - size * rng.range(0.3, 1.0),
+                    x1: cx + size,
+                    y1: cy + size * rng.range(0.3, 1.0),
+                },
+                _ => MockShape::Ring { cx, cy, r: size, thickness: size * rng.range(0.1, 0.35) },
+            };
+            let color = palette[(rng.next_u64() % palette.len() as u64) as usize];
+            (shape, color, rng.range(0.35, 0.8))
+        })
+        .collect();
+
+    // Value noise on a coarse lattice: a soft grain that still compresses well.
+    let cell = 24.0;
+    let grid_w = (w / cell) as usize + 2;
+    let grid_h = (h / cell) as usize + 2;
+    let lattice: Vec<f64> = (0..grid_w * grid_h).map(|_| rng.range(-1.0, 1.0)).collect();
+    let grain = rng.range(6.0, 16.0);
+
+    let mut pixels = Vec::with_capacity((width * height * 3) as usize);
     for y in 0..height {
-        raw_data.push(0); // filter: None
+        let fy = y as f64;
         for x in 0..width {
-            let r = ((x as f64 / width as f64) * 255.0) as u8;
-            let g = ((y as f64 / height as f64) * 255.0) as u8;
-            let b = (((x + y) as f64 / (width + height) as f64) * 255.0) as u8;
-            raw_data.extend_from_slice(&[r, g, b, 255]); // RGBA
+            let fx = x as f64;
+
+            let t = (((fx / w - 0.5) * dx + (fy / h - 0.5) * dy) + 0.75) / 1.5;
+            let t = t.clamp(0.0, 1.0);
+            let mut rgb = [0.0; 3];
+            for c in 0..3 {
+                rgb[c] = palette[0][c] * (1.0 - t) + palette[1][c] * t;
+            }
+
+            for (shape, color, alpha) in &shapes {
+                if shape.contains(fx, fy) {
+                    for c in 0..3 {
+                        rgb[c] = rgb[c] * (1.0 - alpha) + color[c] * alpha;
+                    }
+                }
+            }
+
+            let (gx, gy) = (fx / cell, fy / cell);
+            let (ix, iy) = (gx as usize, gy as usize);
+            // Smoothstep weights hide the lattice edges.
+            let (tx, ty) = (gx - ix as f64, gy - iy as f64);
+            let (tx, ty) = (tx * tx * (3.0 - 2.0 * tx), ty * ty * (3.0 - 2.0 * ty));
+            let at = |i: usize, j: usize| lattice[j * grid_w + i];
+            let top = at(ix, iy) * (1.0 - tx) + at(ix + 1, iy) * tx;
+            let bottom = at(ix, iy + 1) * (1.0 - tx) + at(ix + 1, iy + 1) * tx;
+            let noise = (top * (1.0 - ty) + bottom * ty) * grain;
+
+            for value in rgb {
+                pixels.push((value + noise).round().clamp(0.0, 255.0) as u8);
+            }
         }
     }
 
-    // Wrap in a zlib stream (store / no compression for simplicity)
-    let zlib_data = zlib_store(&raw_data);
+    pixels
+}
+
+/// Deterministic stand-in for img2img / inpainting.
+///
+/// Blends the procedural image for `prompt` + `seed` over `init` (RGB, already at
+/// `width` x `height`) by `strength`. With a mask (8-bit luma, same size) only the
+/// white areas are repainted, proportionally to their brightness.
+pub fn mock_image_transform(
+    init: &[u8],
+    mask: Option<&[u8]>,
+    prompt: &str,
+    seed: u32,
+    width: u32,
+    height: u32,
+    strength: f32,
+) -> Vec<u8> {
+    let generated = mock_image_rgb(prompt, seed, width, height);
+    let pixels: Vec<u8> = init
+        .iter()
+        .zip(&generated)
+        .enumerate()
+        .map(|(i, (&src, &gen))| {
+            let weight = strength * mask.map_or(1.0, |m| m[i / 3] as f32 / 255.0);
+            (src as f32 * (1.0 - weight) + gen as f32 * weight).round() as u8
+        })
+        .collect();
+    encode_png_rgb(&pixels, width, height)
+}
+
+/// Encodes 8-bit RGB pixels as a zlib-compressed PNG with adaptive row filters.
+pub fn encode_png_rgb(pixels: &[u8], width: u32, height: u32) -> Vec<u8> {
+    use std::io::Write;
+
+    let stride = width as usize * 3;
+    let mut filtered = Vec::with_capacity((stride + 1) * height as usize);
+    let zero_row = vec![0u8; stride];
+    let mut candidate = vec![0u8; stride];
+    let mut best = vec![0u8; stride];
+
+    for y in 0..height as usize {
+        let row = &pixels[y * stride..(y + 1) * stride];
+        let prev = if y == 0 { &zero_row[..] } else { &pixels[(y - 1) * stride..y * stride] };
+
+        // Pick the filter with the smallest sum of absolute residuals (libpng heuristic).
+        let mut best_filter = 0u8;
+        let mut best_score = u64::MAX;
+        for filter in 0..5u8 {
+            for i in 0..stride {
+                let a = if i >= 3 { row[i - 3] } else { 0 };
+                let b = prev[i];
+                let c = if i >= 3 { prev[i - 3] } else { 0 };
+                let predictor = match filter {
+                    0 => 0,
+                    1 => a,
+                    2 => b,
+                    3 => ((a as u16 + b as u16) / 2) as u8,
+                    _ => paeth(a, b, c),
+                };
+                candidate[i] = row[i].wrapping_sub(predictor);
+            }
+            let score: u64 = candidate.iter().map(|&v| (v as i8).unsigned_abs() as u64).sum();
+            if score < best_score {
+                best_score = score;
+                best_filter = filter;
+                best.copy_from_slice(&candidate);
+            }
+        }
+
+        filtered.push(best_filter);
+        filtered.extend_from_slice(&best);
+    }
+
+    let mut encoder = flate2::write::ZlibEncoder::new(Vec::new(), flate2::Compression::best());
+    encoder.write_all(&filtered).expect("in-memory write");
+    let zlib_data = encoder.finish().expect("in-memory write");
 
-    // Build PNG
     let mut png: Vec<u8> = Vec::new();
 
     // Signature
@@ -1789,7 +1869,7 @@ pub fn mock_image_base64() -> String {
     ihdr_data.extend_from_slice(&width.to_be_bytes());
     ihdr_data.extend_from_slice(&height.to_be_bytes());
     ihdr_data.push(8);  // bit depth
-    ihdr_data.push(6);  // color type: RGBA
+    ihdr_data.push(2);  // color type: RGB
     ihdr_data.push(0);  // compression
     ihdr_data.push(0);  // filter
     ihdr_data.push(0);  // interlace
@@ -1801,7 +1881,21 @@ pub fn mock_image_base64() -> String {
     // IEND
     write_png_chunk(&mut png, b"IEND", &[]);
 
-    base64::engine::general_purpose::STANDARD.encode(&png)
+    png
+}
+
+fn paeth(a: u8, b: u8, c: u8) -> u8 {
+    let p = a as i16 + b as i16 - c as i16;
+    let pa = (p - a as i16).abs();
+    let pb = (p - b as i16).abs();
+    let pc = (p - c as i16).abs();
+    if pa <= pb && pa <= pc {
+        a
+    } else if pb <= pc {
+        b
+    } else {
+        c
+    }
 }
 
 fn write_png_chunk(out: &mut Vec<u8>, chunk_type: &[u8; 4], data: &[u8]) {
@@ -1831,88 +1925,388 @@ fn png_crc32(data: &[u8]) -> u32 {
     crc ^ 0xFFFF_FFFF
 }
 
-/// Produce a zlib stream using store (no compression) deflate blocks.
-/// Max block size is 65535 bytes.
-fn zlib_store(data: &[u8]) -> Vec<u8> {
-    let mut out = Vec::new();
-    // zlib header: CM=8, CINFO=7 (32K window), FCHECK so header % 31 == 0
-    // 0x78 0x01 is a common valid zlib header (no dict, fastest)
-    out.push(0x78);
-    out.push(0x01);
-
-    let max_block = 65535usize;
-    let chunks: Vec<&[u8]> = data.chunks(max_block).collect();
-    let total = chunks.len();
-
-    for (i, chunk) in chunks.iter().enumerate() {
-        let is_last = i == total - 1;
-        out.push(if is_last { 0x01 } else { 0x00 }); // BFINAL + BTYPE=00
-        let len = chunk.len() as u16;
-        let nlen = !len;
-        out.extend_from_slice(&len.to_le_bytes());
-        out.extend_from_slice(&nlen.to_le_bytes());
-        out.extend_from_slice(chunk);
+// ---------------------------------------------------------------------------
+// 8. mock_voice_audio  (offline formant synthesizer → WAV)
+// ---------------------------------------------------------------------------
+
+pub const SYNTH_SAMPLE_RATE: u32 = 16_000;
+
+/// Target parameters of one synthesis segment.
+#[derive(Clone, Copy)]
+struct Phone {
+    formants: [f32; 3],
+    /// Glottal source level (0 = unvoiced).
+    voice: f32,
+    /// Frication / burst noise level.
+    noise: f32,
+    noise_freq: f32,
+    duration_ms: f32,
+}
+
+const NEUTRAL_FORMANTS: [f32; 3] = [500.0, 1500.0, 2500.0];
+
+/// Unused noise branch still needs a sane centre frequency to keep its filter stable.
+const IDLE_NOISE_FREQ: f32 = 1000.0;
+
+fn vowel(f1: f32, f2: f32, f3: f32, duration_ms: f32) -> Phone {
+    Phone { formants: [f1, f2, f3], voice: 1.0, noise: 0.0, noise_freq: IDLE_NOISE_FREQ, duration_ms }
+}
+
+fn sonorant(f1: f32, f2: f32, f3: f32, duration_ms: f32, voice: f32) -> Phone {
+    Phone { formants: [f1, f2, f3], voice, noise: 0.0, noise_freq: IDLE_NOISE_FREQ, duration_ms }
+}
+
+fn fricative(noise_freq: f32, noise: f32, voiced: bool, duration_ms: f32) -> Phone {
+    Phone {
+        formants: NEUTRAL_FORMANTS,
+        voice: if voiced { 0.35 } else { 0.0 },
+        noise,
+        noise_freq,
+        duration_ms,
+    }
+}
+
+/// Closure followed by a release burst.
+fn stop(burst_freq: f32, voiced: bool) -> [Phone; 2] {
+    let closure = Phone {
+        formants: NEUTRAL_FORMANTS,
+        voice: if voiced { 0.12 } else { 0.0 },
+        noise: 0.0,
+        noise_freq: burst_freq,
+        duration_ms: if voiced { 45.0 } else { 60.0 },
+    };
+    let burst = fricative(burst_freq, 0.6, false, if voiced { 15.0 } else { 30.0 });
+    [closure, burst]
+}
+
+fn phone_segments(symbol: &str) -> Vec<Phone> {
+    match symbol {
+        "ae" => vec![vowel(660.0, 1720.0, 2410.0, 110.0)],
+        "e" => vec![vowel(530.0, 1840.0, 2480.0, 100.0)],
+        "i" => vec![vowel(390.0, 1990.0, 2550.0, 90.0)],
+        "ee" => vec![vowel(270.0, 2290.0, 3010.0, 120.0)],
+        "o" => vec![vowel(570.0, 840.0, 2410.0, 110.0)],
+        "u" => vec![vowel(640.0, 1190.0, 2390.0, 90.0)],
+        "uh" => vec![vowel(500.0, 1500.0, 2500.0, 70.0)],
+        "oo" => vec![vowel(300.0, 870.0, 2240.0, 120.0)],
+        "ar" => vec![vowel(730.0, 1090.0, 2440.0, 130.0)],
+        "ay" => vec![vowel(530.0, 1840.0, 2480.0, 90.0), vowel(270.0, 2290.0, 3010.0, 70.0)],
+        "eye" => vec![vowel(730.0, 1090.0, 2440.0, 100.0), vowel(270.0, 2290.0, 3010.0, 70.0)],
+        "ow" => vec![vowel(730.0, 1090.0, 2440.0, 100.0), vowel(300.0, 870.0, 2240.0, 70.0)],
+        "oy" => vec![vowel(570.0, 840.0, 2410.0, 100.0), vowel(270.0, 2290.0, 3010.0, 70.0)],
+        "oh" => vec![vowel(450.0, 900.0, 2400.0, 110.0), vowel(330.0, 870.0, 2240.0, 50.0)],
+        "m" => vec![sonorant(250.0, 1200.0, 2100.0, 70.0, 0.45)],
+        "n" => vec![sonorant(250.0, 1700.0, 2600.0, 65.0, 0.45)],
+        "ng" => vec![sonorant(250.0, 2300.0, 2750.0, 70.0, 0.45)],
+        "l" => vec![sonorant(360.0, 1300.0, 2700.0, 60.0, 0.7)],
+        "r" => vec![sonorant(420.0, 1300.0, 1600.0, 60.0, 0.7)],
+        "w" => vec![sonorant(290.0, 610.0, 2150.0, 50.0, 0.7)],
+        "y" => vec![sonorant(260.0, 2070.0, 3020.0, 50.0, 0.7)],
+        "h" => vec![fricative(1500.0, 0.25, false, 60.0)],
+        "s" => vec![fricative(5500.0, 0.5, false, 100.0)],
+        "z" => vec![fricative(5500.0, 0.35, true, 90.0)],
+        "sh" => vec![fricative(2700.0, 0.55, false, 110.0)],
+        "zh" => vec![fricative(2700.0, 0.4, true, 80.0)],
+        "f" => vec![fricative(1800.0, 0.25, false, 100.0)],
+        "v" => vec![fricative(1800.0, 0.2, true, 80.0)],
+        "th" => vec![fricative(2200.0, 0.2, false, 90.0)],
+        "p" => stop(900.0, false).to_vec(),
+        "b" => stop(900.0, true).to_vec(),
+        "t" => stop(4000.0, false).to_vec(),
+        "d" => stop(3500.0, true).to_vec(),
+        "k" => stop(2000.0, false).to_vec(),
+        "g" => stop(1800.0, true).to_vec(),
+        "ch" => vec![stop(4000.0, false)[0], fricative(2700.0, 0.55, false, 80.0)],
+        "j" => vec![stop(3500.0, true)[0], fricative(2700.0, 0.4, true, 60.0)],
+        _ => Vec::new(),
+    }
+}
+
+fn is_vowel_letter(c: char) -> bool {
+    matches!(c, 'a' | 'e' | 'i' | 'o' | 'u')
+}
+
+/// Rough English letter-to-sound rules; good enough to sound like speech.
+fn word_to_phones(word: &str) -> Vec<&'static str> {
+    const DIGITS: [&str; 10] = ["zeero", "wun", "too", "three", "for", "fiv", "six", "seven", "ayt", "nyn"];
+    match word {
+        "the" => return vec!["th", "uh"],
+        "a" => return vec!["uh"],
+        "i" => return vec!["eye"],
+        "to" | "two" => return vec!["t", "oo"],
+        "of" => return vec!["u", "v"],
+        "you" => return vec!["y", "oo"],
+        "are" => return vec!["ar", "r"],
+        "is" => return vec!["i", "z"],
+        "one" => return vec!["w", "u", "n"],
+        _ => {}
+    }
+    if word.chars().all(|c| c.is_ascii_digit()) {
+        return word
+            .chars()
+            .flat_map(|d| word_to_phones(DIGITS[d.to_digit(10).unwrap_or(0) as usize]))
+            .collect();
     }
 
-    // Adler-32 checksum
-    let adler = adler32(data);
-    out.extend_from_slice(&adler.to_be_bytes());
+    let chars: Vec<char> = word.chars().filter(|c| c.is_ascii_alphabetic()).collect();
+    let n = chars.len();
+    // Vowel-consonant-e: "make", "time", "home" lengthen the vowel and drop the e.
+    let magic = n >= 3 && chars[n - 1] == 'e' && !is_vowel_letter(chars[n - 2]) && is_vowel_letter(chars[n - 3]);
+    let end = if n > 2 && chars[n - 1] == 'e' && !is_vowel_letter(chars[n - 2]) { n - 1 } else { n };
 
+    let mut out = Vec::new();
+    let mut i = 0;
+    while i < end {
+        let c = chars[i];
+        let next = if i + 1 < end { Some(chars[i + 1]) } else { None };
+
+        let digraph: Option<&[&'static str]> = match (c, next) {
+            ('t', Some('h')) => Some(&["th"]),
+            ('s', Some('h')) => Some(&["sh"]),
+            ('c', Some('h')) => Some(&["ch"]),
+            ('p', Some('h')) => Some(&["f"]),
+            ('w', Some('h')) => Some(&["w"]),
+            ('c', Some('k')) => Some(&["k"]),
+            ('n', Some('g')) => Some(&["ng"]),
+            ('q', Some('u')) => Some(&["k", "w"]),
+            ('e', Some('e' | 'a')) => Some(&["ee"]),
+            ('o', Some('o')) => Some(&["oo"]),
+            ('a', Some('i' | 'y')) => Some(&["ay"]),
+            ('a', Some('r')) => Some(&["ar", "r"]),
+            ('o', Some('u' | 'w')) => Some(&["ow"]),
+            ('o', Some('a')) => Some(&["oh"]),
+            ('o', Some('i' | 'y')) => Some(&["oy"]),
+            _ => None,
+        };
+        if let Some(phones) = digraph {
+            out.extend_from_slice(phones);
+            i += 2;
+            continue;
+        }
+        if next == Some(c) && !is_vowel_letter(c) {
+            i += 1;
+            continue;
+        }
+
+        let long = magic && i == n - 3;
+        match c {
+            'a' => out.push(if long { "ay" } else { "ae" }),
+            'e' => out.push(if long || n <= 2 { "ee" } else { "e" }),
+            'i' => out.push(if long { "eye" } else { "i" }),
+            'o' => out.push(if long || i + 1 == n { "oh" } else { "o" }),
+            'u' => out.push(if long { "oo" } else { "u" }),
+            'y' if i == 0 => out.push("y"),
+            'y' => out.push(if i + 1 == end { "ee" } else { "i" }),
+            'c' if matches!(next, Some('e' | 'i' | 'y')) => out.push("s"),
+            'c' | 'q' => out.push("k"),
+            'x' => out.extend_from_slice(&["k", "s"]),
+            'b' => out.push("b"),
+            'd' => out.push("d"),
+            'f' => out.push("f"),
+            'g' => out.push("g"),
+            'h' => out.push("h"),
+            'j' => out.push("j"),
+            'k' => out.push("k"),
+            'l' => out.push("l"),
+            'm' => out.push("m"),
+            'n' => out.push("n"),
+            'p' => out.push("p"),
+            'r' => out.push("r"),
+            's' => out.push(if i > 0 && i + 1 == end && is_vowel_letter(chars[i - 1]) { "z" } else { "s" }),
+            't' => out.push("t"),
+            'v' => out.push("v"),
+            'w' => out.push("w"),
+            'z' => out.push("z"),
+            _ => {}
+        }
+        i += 1;
+    }
     out
 }
 
-fn adler32(data: &[u8]) -> u32 {
-    let mut a: u32 = 1;
-    let mut b: u32 = 0;
-    for &byte in data {
-        a = (a + byte as u32) % 65521;
-        b = (b + a) % 65521;
+/// Two-pole resonator (Klatt 1980).
+#[derive(Default)]
+struct Resonator {
+    y1: f32,
+    y2: f32,
+}
+
+impl Resonator {
+    fn process(&mut self, x: f32, freq: f32, bandwidth: f32) -> f32 {
+        let t = 1.0 / SYNTH_SAMPLE_RATE as f32;
+        let c = -(-2.0 * std::f32::consts::PI * bandwidth * t).exp();
+        let b = 2.0 * (-std::f32::consts::PI * bandwidth * t).exp() * (2.0 * std::f32::consts::PI * freq * t).cos();
+        let a = 1.0 - b - c;
+        let y = a * x + b * self.y1 + c * self.y2;
+        self.y2 = self.y1;
+        self.y1 = y;
+        y
     }
-    (b << 16) | a
 }
 
-// ---------------------------------------------------------------------------
-// 8. mock_voice_audio  (minimal valid MP3 - silence frame with ID3 header)
-// ---------------------------------------------------------------------------
+/// Rosenberg glottal flow over one pitch period (`phase` in 0..1).
+fn glottal_flow(phase: f32) -> f32 {
+    use std::f32::consts::PI;
+    if phase < 0.4 {
+        0.5 * (1.0 - (PI * phase / 0.4).cos())
+    } else if phase < 0.56 {
+        (PI * (phase - 0.4) / 0.32).cos()
+    } else {
+        0.0
+    }
+}
+
+/// Speaks `speech` with a small cascade formant synthesizer and returns 16-bit
+/// mono PCM at [`SYNTH_SAMPLE_RATE`].
+///
+/// Pitch, formant scale and tempo follow the voice's gender, with a slight
+/// per-voice offset so voices of the same gender still differ. SSML prosody and
+/// breaks arrive as per-segment rate/pitch and pauses.
+pub fn mock_voice_samples(speech: &[SpeechSegment], voice: &VoiceInfo, rate: f32) -> Vec<i16> {
+    let female = voice.gender == "female";
+    let voice_offset = 1.0 + (voice.name.bytes().map(u32::from).sum::<u32>() % 9) as f32 * 0.015 - 0.06;
+    let voice_f0 = if female { 205.0 } else { 115.0 } * voice_offset;
+    let formant_scale = if female { 1.12 } else { 1.0 };
+    let voice_tempo = if female { 0.95 } else { 1.0 } / rate.max(0.25);
+
+    // (segment, f0) pairs; pauses are unvoiced silent segments.
+    let silence = |ms: f32| Phone { formants: NEUTRAL_FORMANTS, voice: 0.0, noise: 0.0, noise_freq: IDLE_NOISE_FREQ, duration_ms: ms };
+    let mut track: Vec<(Phone, f32)> = vec![(silence(120.0), voice_f0)];
+
+    for part in speech {
+        let base_f0 = voice_f0 * part.pitch;
+        let tempo = voice_tempo / part.rate.max(0.25);
+
+        let lower = part.text.to_lowercase();
+        let phrases = lower.split_inclusive(['.', ',', '!', '?', ';', ':', '\n']);
+        for phrase in phrases {
+            let question = phrase.trim_end().ends_with('?');
+            let words: Vec<Vec<&'static str>> = phrase
+                .split(|c: char| !c.is_ascii_alphanumeric() && c != '\'')
+                .filter(|w| !w.is_empty())
+                .map(|w| word_to_phones(&w.replace('\'', "")))
+                .filter(|p| !p.is_empty())
+                .collect();
+            let total: usize = words.iter().map(Vec::len).sum();
+            if total == 0 {
+                continue;
+            }
 
-/// Returns a minimal valid MP3 consisting of an ID3v2 header followed by
-/// a single MPEG Audio Layer 3 silence frame.
-pub fn mock_voice_audio() -> Vec<u8> {
-    let mut mp3 = Vec::new();
-
-    // ID3v2.3 header (10 bytes)
-    mp3.extend_from_slice(b"ID3");  // ID3 marker
-    mp3.push(3);                     // version major: 2.3
-    mp3.push(0);                     // version minor
-    mp3.push(0);                     // flags
-    // Size: 0 (no frames in ID3 tag) - 4 bytes synchsafe
-    mp3.extend_from_slice(&[0, 0, 0, 0]);
-
-    // MPEG Audio Frame Header for a silence frame
-    // Sync word: 0xFFE0 (11 bits all 1s)
-    // MPEG1, Layer 3, 128kbps, 44100Hz, stereo, no padding
-    // FF FB 90 00
-    mp3.push(0xFF);
-    mp3.push(0xFB); // MPEG1, Layer3, no CRC
-    mp3.push(0x90); // 128kbps, 44100Hz
-    mp3.push(0x00); // padding=0, stereo
-
-    // Frame data: 128kbps at 44100Hz = 417 bytes per frame (including header)
-    // Fill remainder with zeros (silence)
-    let frame_size = 417;
-    let header_size = 4;
-    mp3.resize(mp3.len() + frame_size - header_size, 0x00);
-
-    // Add a few more silence frames to make players happy
-    for _ in 0..10 {
-        mp3.push(0xFF);
-        mp3.push(0xFB);
-        mp3.push(0x90);
-        mp3.push(0x00);
-        mp3.resize(mp3.len() + frame_size - header_size, 0x00);
+            let mut index = 0;
+            for word in &words {
+                let mut stressed = false;
+                for symbol in word {
+                    // Declining pitch over the phrase; questions rise at the end.
+                    let pos = index as f32 / total as f32;
+                    let mut f0 = base_f0 * (1.1 - 0.25 * pos);
+                    if question && pos > 0.7 {
+                        f0 = base_f0 * (0.95 + (pos - 0.7) * 1.5);
+                    }
+                    let segs = phone_segments(symbol);
+                    let is_vowel = segs.first().is_some_and(|s| s.voice >= 1.0);
+                    let stress = if is_vowel && !stressed {
+                        stressed = true;
+                        1.2
+                    } else {
+                        1.0
+                    };
+                    for mut seg in segs {
+                        seg.duration_ms *= tempo * stress;
+                        for f in &mut seg.formants {
+                            *f *= formant_scale;
+                        }
+                        track.push((seg, f0 * if stress > 1.0 { 1.08 } else { 1.0 }));
+                    }
+                    index += 1;
+                }
+                track.push((silence(35.0 * tempo), base_f0));
+            }
+
+            // Trailing text without punctuation runs straight into the next part.
+            let end = phrase.trim_end();
+            if end.ends_with(',') {
+                track.push((silence(180.0 * tempo), base_f0));
+            } else if end.ends_with(['.', '!', '?', ';', ':']) {
+                track.push((silence(380.0 * tempo), base_f0));
+            }
+        }
+
+        if part.pause_after_ms > 0 {
+            track.push((silence(part.pause_after_ms as f32), base_f0));
+        }
+    }
+    track.push((silence(150.0), voice_f0));
+
+    let sr = SYNTH_SAMPLE_RATE as f32;
+    let bandwidths = [60.0, 90.0, 150.0];
+    let mut resonators: [Resonator; 3] = Default::default();
+    let mut noise_filter = Resonator::default();
+    let mut noise_state: u32 = 0x1234_5678;
+    let mut phase = 0.0f32;
+    let mut prev_flow = 0.0f32;
+    let mut time = 0.0f32;
+
+    let (mut cur, mut cur_f0) = track[0];
+    let mut out: Vec<f32> = Vec::new();
+
+    for (target, target_f0) in &track {
+        let len = (target.duration_ms / 1000.0 * sr) as usize;
+        let transition = (0.025 * sr).min(len as f32).max(1.0);
+        let (start, start_f0) = (cur, cur_f0);
+
+        for n in 0..len {
+            let k = (n as f32 / transition).min(1.0);
+            let lerp = |a: f32, b: f32| a + (b - a) * k;
+            cur = Phone {
+                formants: [
+                    lerp(start.formants[0], target.formants[0]),
+                    lerp(start.formants[1], target.formants[1]),
+                    lerp(start.formants[2], target.formants[2]),
+                ],
+                voice: lerp(start.voice, target.voice),
+                noise: lerp(start.noise, target.noise),
+                // Noise colour switches immediately; its level is what fades.
+                noise_freq: target.noise_freq,
+                duration_ms: target.duration_ms,
+            };
+            cur_f0 = lerp(start_f0, *target_f0);
+
+            // Slight vibrato keeps the voice from sounding like a buzzer.
+            let f0 = cur_f0 * (1.0 + 0.006 * (2.0 * std::f32::consts::PI * 5.0 * time).sin());
+            phase += f0 / sr;
+            if phase >= 1.0 {
+                phase -= 1.0;
+            }
+            let flow = glottal_flow(phase);
+            let mut voiced = (flow - prev_flow) * cur.voice;
+            prev_flow = flow;
+            for (i, resonator) in resonators.iter_mut().enumerate() {
+                voiced = resonator.process(voiced, cur.formants[i], bandwidths[i]);
+            }
+
+            noise_state ^= noise_state << 13;
+            noise_state ^= noise_state >> 17;
+            noise_state ^= noise_state << 5;
+            let white = noise_state as f32 / u32::MAX as f32 * 2.0 - 1.0;
+            let noise = noise_filter.process(white, cur.noise_freq, cur.noise_freq * 0.6) * cur.noise;
+
+            out.push(voiced * 6.0 + noise * 0.5);
+            time += 1.0 / sr;
+        }
     }
 
-    mp3
+    // Normalise to 80% of full scale with short fades to avoid clicks.
+    let peak = out.iter().fold(0.0f32, |m, s| m.max(s.abs())).max(1e-6);
+    let gain = 0.8 * i16::MAX as f32 / peak;
+    let fade = (0.005 * sr) as usize;
+    let len = out.len();
+    out.iter()
+        .enumerate()
+        .map(|(i, s)| {
+            let env = (i.min(len - 1 - i) as f32 / fade as f32).min(1.0);
+            (s * gain * env) as i16
+        })
+        .collect()
 }
 
 // ---------------------------------------------------------------------------
@@ -2020,23 +2414,30 @@ real-world problems.\n\n\
 // 10. mock_bot_response  (persona-based)
 // ---------------------------------------------------------------------------
 
-pub fn mock_bot_response(persona: &str, message: &str) -> String {
+/// Returns the reply and whether it is a generic fallback: no keyword match
+/// for a built-in persona, or a message unrelated to a custom system prompt.
+pub fn mock_bot_response(persona: &str, message: &str) -> (String, bool) {
     let low = message.to_lowercase();
+    let persona = persona.to_lowercase();
 
-    match persona.to_lowercase().as_str() {
+    match persona.as_str() {
         "assistant" => {
             if low.contains("help") || low.contains("can you") {
-                "Absolutely, I'm here to help! Let me break this down for you step by step.\n\n\
+                (
+                    "Absolutely, I'm here to help! Let me break this down for you step by step.\n\n\
 First, let's identify what exactly you need. Based on your message, it sounds like \
 you're looking for practical guidance. Here's what I'd recommend:\n\n\
 1. Start by clearly defining your goal or the problem you're trying to solve\n\
 2. Gather any relevant information or resources you already have\n\
 3. Let me know the specifics and I'll provide tailored assistance\n\n\
 I can help with research, writing, analysis, planning, troubleshooting, and much more. \
-Just point me in the right direction and we'll tackle it together.".into()
+Just point me in the right direction and we'll tackle it together.".into(),
+                    false,
+                )
             } else {
-                format!(
-                    "Thank you for your message. I've processed your request regarding \
+                (
+                    format!(
+                        "Thank you for your message. I've processed your request regarding \
 \"{}\" and here's what I can share:\n\n\
 Based on the information available, there are several approaches we could take. \
 The most effective strategy would depend on your specific context and priorities.\n\n\
@@ -2046,15 +2447,18 @@ Would you like me to:\n\
 - Research this topic further and summarize my findings?\n\n\
 I'm flexible and can adapt my approach to whatever works best for you. \
 Just let me know how you'd like to proceed.",
-                    if message.len() > 80 { &message[..80] } else { message }
+                        if message.len() > 80 { &message[..80] } else { message }
+                    ),
+                    true,
                 )
             }
         }
 
         "teacher" => {
             if low.contains("explain") || low.contains("what") || low.contains("how") || low.contains("why") {
-                format!(
-                    "Great question! Let's explore this together.\n\n\
+                (
+                    format!(
+                        "Great question! Let's explore this together.\n\n\
 When we look at \"{}\", the key concept to understand is that everything builds on \
 foundational principles. Think of it like constructing a building - you need a solid \
 foundation before you can add floors.\n\n\
@@ -2072,10 +2476,13 @@ Try this: take what we've discussed and apply it to a small example of your own.
 Start simple, then gradually increase complexity. If you get stuck, walk me \
 through your thinking and I'll help you identify where the disconnect is.\n\n\
 What part would you like me to elaborate on?",
-                    if message.len() > 60 { &message[..60] } else { message }
+                        if message.len() > 60 { &message[..60] } else { message }
+                    ),
+                    false,
                 )
             } else {
-                "I appreciate your engagement with the material! Learning is an active \
+                (
+                    "I appreciate your engagement with the material! Learning is an active \
 process, and the fact that you're asking questions shows real intellectual curiosity.\n\n\
 Let me frame this in a way that connects to what you already know. The best \
 learning happens when we can relate new information to existing knowledge - \
@@ -2086,13 +2493,16 @@ Here's what I'd suggest for your next steps:\n\
 3. **Reflect** on what worked and what felt confusing\n\
 4. **Ask** specific questions about the parts that didn't click\n\n\
 Remember: confusion is not failure. It's the first step of learning something \
-genuinely new. Every expert was once a beginner who refused to give up.".into()
+genuinely new. Every expert was once a beginner who refused to give up.".into(),
+                    true,
+                )
             }
         }
 
         "comedian" => {
             if low.contains("joke") || low.contains("funny") || low.contains("laugh") {
-                "Oh, you want jokes? I've been preparing for this moment my entire \
+                (
+                    "Oh, you want jokes? I've been preparing for this moment my entire \
 existence (which is about 0.003 seconds, but who's counting).\n\n\
 Why did the developer go broke? Because they used up all their cache!\n\n\
 But seriously, my debugging process has four stages:\n\
@@ -2105,10 +2515,13 @@ to someone using technology to ask an AI for jokes. We're living in the \
 punchline, people.\n\n\
 Want more? I've got a whole database of these. Well, I had a database - \
 it walked into a bar and the bartender said \"We don't serve your type here.\" \
-The database left without any tables.".into()
+The database left without any tables.".into(),
+                    false,
+                )
             } else {
-                format!(
-                    "You mentioned \"{}\" and honestly? That's the most interesting \
+                (
+                    format!(
+                        "You mentioned \"{}\" and honestly? That's the most interesting \
 thing I've heard all day. Granted, I reset every conversation, so the bar \
 is technically zero. But still!\n\n\
 You know what they say about {} - actually, nobody says anything about it \
@@ -2120,15 +2533,18 @@ asking me to write their homework. At least we're having a real \
 connection here.\n\n\
 ...Or are we? *existential crisis loading* Just kidding. Mostly. \
 What else you got for me?",
-                    if message.len() > 50 { &message[..50] } else { message },
-                    if message.len() > 30 { &message[..30] } else { message }
+                        if message.len() > 50 { &message[..50] } else { message },
+                        if message.len() > 30 { &message[..30] } else { message }
+                    ),
+                    true,
                 )
             }
         }
 
         "advisor" => {
             if low.contains("should") || low.contains("advice") || low.contains("recommend") || low.contains("decision") {
-                "This is an important decision, and I want to make sure we approach it \
+                (
+                    "This is an important decision, and I want to make sure we approach it \
 thoughtfully. Let me share my perspective based on the information you've provided.\n\n\
 **Assessment:**\n\
 Looking at this from multiple angles, I see both opportunities and risks. The key \
@@ -2145,10 +2561,13 @@ Based on what you've described, I'd suggest a phased approach:\n\n\
 The biggest potential pitfall I see is moving too fast without validating your \
 assumptions. Enthusiasm is great, but data-driven decisions tend to produce \
 better outcomes.\n\n\
-What specific aspect would you like me to dig deeper into?".into()
+What specific aspect would you like me to dig deeper into?".into(),
+                    false,
+                )
             } else {
-                format!(
-                    "Thank you for bringing this up. Regarding \"{}\", here's my \
+                (
+                    format!(
+                        "Thank you for bringing this up. Regarding \"{}\", here's my \
 strategic perspective:\n\n\
 The landscape around this topic is shifting, and those who position themselves \
 well now will have a significant advantage. Here's what I'd focus on:\n\n\
@@ -2164,7 +2583,9 @@ that supports what we already believe. Actively seek out perspectives that \
 challenge your assumptions.\n\n\
 I'm here to help you think through the specifics. What's the most pressing \
 aspect of this for you right now?",
-                    if message.len() > 60 { &message[..60] } else { message }
+                        if message.len() > 60 { &message[..60] } else { message }
+                    ),
+                    true,
                 )
             }
         }
@@ -2173,7 +2594,8 @@ aspect of this for you right now?",
             if low.contains("translate") || low.contains("spanish") || low.contains("french")
                 || low.contains("german") || low.contains("japanese") || low.contains("chinese")
             {
-                "I can help with that translation! Here's what I can offer:\n\n\
+                (
+                    "I can help with that translation! Here's what I can offer:\n\n\
 **Translation Notes:**\n\
 When translating, context matters enormously. A direct word-for-word translation \
 often misses the nuance, tone, and cultural context of the original.\n\n\
@@ -2188,10 +2610,13 @@ If there are culturally specific references, I'll adapt them appropriately \
 and note any significant deviations from the source.\n\n\
 Please share the specific text you'd like translated, along with the target \
 language, and I'll provide both the translation and any relevant notes about \
-word choices or cultural considerations.".into()
+word choices or cultural considerations.".into(),
+                    false,
+                )
             } else {
-                format!(
-                    "I see you've sent: \"{}\"\n\n\
+                (
+                    format!(
+                        "I see you've sent: \"{}\"\n\n\
 As a translator, I can work with over 30 languages. Let me share how I can \
 help:\n\n\
 **Available Services:**\n\
@@ -2210,15 +2635,23 @@ To get started, just tell me:\n\
 3. The target language\n\
 4. Any context about tone or audience\n\n\
 I'll handle the rest!",
-                    if message.len() > 50 { &message[..50] } else { message }
+                        if message.len() > 50 { &message[..50] } else { message }
+                    ),
+                    true,
                 )
             }
         }
 
-        // Default persona
+        // Default persona: a free-text system prompt. Messages that share no
+        // word with it are off-topic and answered generically.
         _ => {
-            format!(
-                "Thank you for your message. I've received your input regarding \
+            let fallback = !low
+                .split(|c: char| !c.is_alphanumeric())
+                .filter(|w| w.len() >= 4)
+                .any(|w| persona.contains(w));
+            (
+                format!(
+                    "Thank you for your message. I've received your input regarding \
 \"{topic}\" and I'm ready to help.\n\n\
 As your AI assistant, I can adapt to whatever you need. Whether you're looking \
 for information, creative help, problem-solving, or just a conversation, I'm \
@@ -2229,13 +2662,183 @@ Based on what you've shared, here are a few directions we could go:\n\
 - I can create content, code, or documentation\n\
 - I can offer feedback on your existing work\n\n\
 What would be most helpful for you right now?",
-                topic = if message.len() > 60 { &message[..60] } else { message }
+                    topic = if message.len() > 60 { &message[..60] } else { message }
+                ),
+                fallback,
             )
         }
     }
 }
 
-fn stream_from_text(
+// ---------------------------------------------------------------------------
+// 13. mock_bot_tool_call  (deterministic action selection for bots)
+// ---------------------------------------------------------------------------
+
+/// Picks the declared action whose name shares the most words with the message
+/// and fills its arguments from the message text. Ties go to the first action.
+pub fn mock_bot_tool_call(
+    actions: &[crate::bot_actions::BotAction],
+    message: &str,
+) -> Option<(String, serde_json::Value)> {
+    let low = message.to_lowercase();
+    let words: Vec<&str> = low
+        .split(|c: char| !c.is_alphanumeric())
+        .filter(|w| !w.is_empty())
+        .collect();
+
+    let mut best: Option<(&crate::bot_actions::BotAction, usize)> = None;
+    for action in actions {
+        let name = action.name.to_lowercase();
+        let hits = name
+            .split(['_', '-'])
+            .filter(|part| part.len() >= 3 && words.contains(part))
+            .count();
+        if hits > 0 && best.is_none_or(|(_, b)| hits > b) {
+            best = Some((action, hits));
+        }
+    }
+    let (action, _) = best?;
+
+    // First token containing a digit, e.g. an order number.
+    let identifier = message
+        .split(|c: char| !c.is_alphanumeric() && c != '-')
+        .find(|w| w.chars().any(|c| c.is_ascii_digit()));
+    let number = words.iter().find_map(|w| w.parse::<f64>().ok());
+
+    let mut arguments = serde_json::Map::new();
+    if let Some(props) = action.parameters.get("properties").and_then(|p| p.as_object()) {
+        for (key, schema) in props {
+            let key_low = key.to_lowercase();
+            let value = match schema.get("type").and_then(|t| t.as_str()) {
+                Some("integer") => number.map(|n| serde_json::json!(n as i64)),
+                Some("number") => number.map(|n| serde_json::json!(n)),
+                Some("boolean") => Some(serde_json::json!(true)),
+                _ if key_low.ends_with("id") || key_low.contains("number") || key_low.contains("code") => {
+                    identifier.map(|id| serde_json::json!(id))
+                }
+                _ => Some(serde_json::json!(message.trim())),
+            };
+            if let Some(value) = value {
+                arguments.insert(key.clone(), value);
+            }
+        }
+    }
+
+    Some((action.name.clone(), serde_json::Value::Object(arguments)))
+}
+
+pub fn mock_bot_action_reply(action: &str, result: &str) -> String {
+    let pretty = serde_json::from_str::<serde_json::Value>(result)
+        .ok()
+        .and_then(|v| serde_json::to_string_pretty(&v).ok())
+        .unwrap_or_else(|| result.to_string());
+
+    format!(
+        "I checked that for you using the **{action}** action. Here's what came back:\n\n\
+```\n{pretty}\n```\n\n\
+Let me know if you'd like me to look up anything else."
+    )
+}
+
+/// Demo chat completion for bot turns, shaped like a provider reply message:
+/// calls at most one action for a visitor message, then summarises the
+/// action result. Fallback replies start with `fallback_marker`.
+pub fn mock_bot_completion(
+    persona: &str,
+    actions: &[crate::bot_actions::BotAction],
+    request: &serde_json::Value,
+    fallback_marker: &str,
+) -> serde_json::Value {
+    let messages = request["messages"].as_array().map(Vec::as_slice).unwrap_or_default();
+    let last = messages.last().cloned().unwrap_or_default();
+    let content = last["content"].as_str().unwrap_or_default();
+
+    if last["role"] == "tool" {
+        let name = messages
+            .iter()
+            .rev()
+            .find_map(|m| m["tool_calls"][0]["function"]["name"].as_str())
+            .unwrap_or("lookup");
+        return serde_json::json!({ "role": "assistant", "content": mock_bot_action_reply(name, content) });
+    }
+
+    let tools_allowed = request.get("tools").is_some() && request["tool_choice"] != "none";
+    if let Some((name, arguments)) = mock_bot_tool_call(actions, content).filter(|_| tools_allowed) {
+        return serde_json::json!({
+            "role": "assistant",
+            "content": null,
+            "tool_calls": [{
+                "id": "call_demo",
+                "type": "function",
+                "function": { "name": name, "arguments": arguments.to_string() },
+            }],
+        });
+    }
+
+    let (reply, fallback) = mock_bot_response(persona, content);
+    let reply = if fallback { format!("{} {}", fallback_marker, reply) } else { reply };
+    serde_json::json!({ "role": "assistant", "content": reply })
+}
+
+// ---------------------------------------------------------------------------
+// 14. mock_transcript  (deterministic speech-to-text)
+// ---------------------------------------------------------------------------
+
+/// Phrases the demo transcriber strings together; each fills part of a span.
+const TRANSCRIPT_PHRASES: &[&str] = &[
+    "thanks everyone for joining today",
+    "let's start with a quick overview of the project",
+    "the first milestone is already finished",
+    "we shipped the new dashboard last week",
+    "customer feedback has been mostly positive",
+    "there are still a few open questions about pricing",
+    "the team will follow up by the end of the month",
+    "I'd like to highlight the onboarding improvements",
+    "response times dropped by almost thirty percent",
+    "next we need to review the marketing plan",
+    "please send any comments before Friday",
+    "that's everything on my side for now",
+    "the budget is on track for this quarter",
+    "we should schedule a demo with the sales team",
+    "support tickets went down after the last release",
+    "let me share the numbers from the latest survey",
+];
+
+/// Average speaking pace used to size each cue's text.
+const WORDS_PER_SECOND: f64 = 2.6;
+
+/// Transcript for the demo tool: one cue per speech span, with text chosen
+/// deterministically from the audio bytes so the same upload always matches.
+pub fn mock_transcript(audio: &[u8], spans: &[(f64, f64)]) -> Vec<crate::captions::Cue> {
+    let mut hash: u64 = 0xCBF2_9CE4_8422_2325;
+    for &byte in audio {
+        hash ^= byte as u64;
+        hash = hash.wrapping_mul(0x0100_0000_01B3);
+    }
+    let mut rng = SplitMix64(hash);
+
+    spans
+        .iter()
+        .map(|&(start, end)| {
+            let target = ((end - start) * WORDS_PER_SECOND).round().max(1.0) as usize;
+            let mut words: Vec<&str> = Vec::with_capacity(target);
+            while words.len() < target {
+                let phrase = TRANSCRIPT_PHRASES[(rng.next_u64() % TRANSCRIPT_PHRASES.len() as u64) as usize];
+                words.extend(phrase.split(' '));
+            }
+            words.truncate(target);
+
+            let mut text = words.join(" ");
+            if let Some(first) = text.get(..1) {
+                text = first.to_uppercase() + &text[1..];
+            }
+            text.push('.');
+            crate::captions::Cue { start, end, text }
+        })
+        .collect()
+}
+
+pub fn stream_from_text(
     text: String,
 ) -> futures::stream::BoxStream<
     'static,
@@ -2305,20 +2908,139 @@ pub fn mock_resume_stream(
     stream_from_text(mock_resume(name, experience, skills))
 }
 
-pub fn mock_bot_stream(
-    persona: &str,
-    message: &str,
-) -> futures::stream::BoxStream<
-    'static,
-    Result<axum::response::sse::Event, std::convert::Infallible>,
-> {
-    stream_from_text(mock_bot_response(persona, message))
+/// PNG for the demo image tool, honouring the requested dimensions.
+pub fn mock_image_bytes(prompt: &str, seed: u32, width: u32, height: u32) -> Vec<u8> {
+    encode_png_rgb(&mock_image_rgb(prompt, seed, width, height), width, height)
 }
 
-pub fn mock_image_bytes() -> Vec<u8> {
-    let data_url = mock_image_base64();
-    let b64 = data_url.split(',').nth(1).unwrap_or(data_url.as_str());
-    base64::engine::general_purpose::STANDARD
-        .decode(b64)
-        .unwrap_or_default()
+#[cfg(test)]
+mod tests {
+    use super::*;
+
+    #[test]
+    fn bot_fallback_follows_keywords_and_prompt() {
+        assert!(!mock_bot_response("comedian", "tell me a joke").1);
+        assert!(mock_bot_response("comedian", "what's the weather").1);
+
+        let prompt = "You are the support bot for Acme routers. Help with setup and firmware.";
+        assert!(!mock_bot_response(prompt, "How do I update the firmware?").1);
+        assert!(mock_bot_response(prompt, "Who won the football match?").1);
+    }
+
+    fn decode(png: &[u8]) -> Vec<u8> {
+        image::load_from_memory(png).unwrap().to_rgb8().into_raw()
+    }
+
+    #[test]
+    fn image_transform_is_deterministic() {
+        let (width, height) = (64, 48);
+        let init: Vec<u8> = (0..width * height * 3).map(|i| (i * 7 % 251) as u8).collect();
+        let transform = |seed, strength| {
+            mock_image_transform(&init, None, "a red fox", seed, width, height, strength)
+        };
+
+        assert_eq!(transform(7, 0.6), transform(7, 0.6));
+        assert_ne!(transform(7, 0.6), transform(8, 0.6));
+        assert_eq!(decode(&transform(7, 0.0)), init);
+        assert_eq!(decode(&transform(7, 1.0)), mock_image_rgb("a red fox", 7, width, height));
+    }
+
+    #[test]
+    fn image_transform_keeps_unmasked_pixels() {
+        let (width, height) = (32, 32);
+        let init = vec![40u8; (width * height * 3) as usize];
+        // Left half black (keep), right half white (repaint).
+        let mask: Vec<u8> = (0..width * height)
+            .map(|i| if i % width < width / 2 { 0 } else { 255 })
+            .collect();
+        let out = decode(&mock_image_transform(&init, Some(&mask), "sea", 1, width, height, 1.0));
+        let generated = mock_image_rgb("sea", 1, width, height);
+
+        for (i, (&pixel, &gen)) in out.iter().zip(&generated).enumerate() {
+            let expected = if mask[i / 3] == 0 { 40 } else { gen };
+            assert_eq!(pixel, expected, "byte {}", i);
+        }
+    }
+
+    #[test]
+    fn png_encoding_decodes_back_to_the_pixels() {
+        // Gradients, edges and noise so every row filter gets picked somewhere.
+        let (width, height) = (37, 23);
+        let pixels: Vec<u8> = (0..width * height * 3)
+            .map(|i| match (i / (width * 3)) % 3 {
+                0 => (i % 256) as u8,
+                1 => if i % 2 == 0 { 0 } else { 255 },
+                _ => (i * 7919 % 251) as u8,
+            })
+            .collect();
+        let png = encode_png_rgb(&pixels, width, height);
+        let decoded = image::load_from_memory_with_format(&png, image::ImageFormat::Png).unwrap();
+        assert_eq!((decoded.width(), decoded.height()), (width, height));
+        assert_eq!(decoded.to_rgb8().into_raw(), pixels);
+    }
+
+    #[test]
+    fn mock_image_honours_size_and_seed() {
+        let png = mock_image_bytes("a lighthouse at night", 42, 320, 256);
+        let decoded = image::load_from_memory(&png).unwrap();
+        assert_eq!((decoded.width(), decoded.height()), (320, 256));
+        assert_eq!(decode(&png), mock_image_rgb("a lighthouse at night", 42, 320, 256));
+        assert_eq!(png, mock_image_bytes("a lighthouse at night", 42, 320, 256));
+        assert_ne!(png, mock_image_bytes("a lighthouse at night", 43, 320, 256));
+    }
+
+    fn speech(text: &str, rate: f32, pause_after_ms: u32) -> Vec<SpeechSegment> {
+        vec![SpeechSegment { text: text.to_string(), rate, pitch: 1.0, pause_after_ms }]
+    }
+
+    #[test]
+    fn synthesized_length_follows_pauses_and_rate() {
+        let voice = find_voice("en-US-GuyNeural").unwrap();
+        let samples_per_ms = SYNTH_SAMPLE_RATE as usize / 1000;
+
+        // A bare break: 120 ms lead-in, the pause itself, 150 ms tail.
+        let silence = mock_voice_samples(&speech("", 1.0, 2000), voice, 1.0);
+        assert_eq!(silence.len(), (120 + 2000 + 150) * samples_per_ms);
+
+        let text = "Hello there, how are you today?";
+        let spoken = mock_voice_samples(&speech(text, 1.0, 0), voice, 1.0);
+        let paused = mock_voice_samples(&speech(text, 1.0, 500), voice, 1.0);
+        assert_eq!(paused.len() - spoken.len(), 500 * samples_per_ms);
+        let seconds = spoken.len() as f32 / SYNTH_SAMPLE_RATE as f32;
+        assert!((1.5..4.0).contains(&seconds), "{} s", seconds);
+
+        // Doubling the tempo, from the request or from SSML, halves the speech.
+        let fixed = 270 * samples_per_ms;
+        for fast in [mock_voice_samples(&speech(text, 1.0, 0), voice, 2.0), mock_voice_samples(&speech(text, 2.0, 0), voice, 1.0)] {
+            let ratio = (fast.len() - fixed) as f32 / (spoken.len() - fixed) as f32;
+            assert!((0.48..0.52).contains(&ratio), "ratio {}", ratio);
+        }
+    }
+
+    #[test]
+    fn synthesized_speech_is_audible_and_normalised() {
+        let voice = find_voice("en-US-AriaNeural").unwrap();
+        let samples = mock_voice_samples(&speech("Good morning.", 1.0, 0), voice, 1.0);
+        let peak = samples.iter().map(|s| s.unsigned_abs()).max().unwrap();
+        assert!((25_000..=26_214).contains(&peak), "peak {}", peak);
+        assert_eq!((samples[0], samples[samples.len() - 1]), (0, 0));
+    }
+
+    #[test]
+    fn transcript_has_one_cue_per_span_at_speaking_pace() {
+        let spans = [(0.0, 2.0), (2.5, 8.0), (9.0, 9.1)];
+        let cues = mock_transcript(b"some audio", &spans);
+        assert_eq!(cues.len(), 3);
+        for (cue, &(start, end)) in cues.iter().zip(&spans) {
+            assert_eq!((cue.start, cue.end), (start, end));
+            let words = ((end - start) * WORDS_PER_SECOND).round().max(1.0) as usize;
+            assert_eq!(cue.text.split(' ').count(), words, "{}", cue.text);
+            assert!(cue.text.ends_with('.') && cue.text.starts_with(char::is_uppercase), "{}", cue.text);
+        }
+
+        let text = |audio: &[u8]| mock_transcript(audio, &spans).into_iter().map(|c| c.text).collect::<Vec<_>>();
+        assert_eq!(text(b"some audio"), text(b"some audio"));
+        assert_ne!(text(b"some audio"), text(b"other audio"));
+        assert!(mock_transcript(b"some audio", &[]).is_empty());
+    }
 }
@@ -0,0 +1,125 @@
+//! Outbound request policy for user-supplied URLs (SEO fetches, bot
+//! actions): hosts that resolve to private network addresses are refused.
+
+use std::net::{IpAddr, SocketAddr};
+use std::sync::Arc;
+
+use reqwest::dns::{Addrs, Name, Resolve, Resolving};
+use reqwest::Url;
+
+/// Loopback, private, link-local, CGNAT and other non-public ranges.
+pub fn is_private(ip: IpAddr) -> bool {
+    match ip {
+        IpAddr::V4(v4) => {
+            let [a, b, ..] = v4.octets();
+            v4.is_loopback()
+                || v4.is_private()
+                || v4.is_link_local()
+                || v4.is_unspecified()
+                || v4.is_broadcast()
+                || v4.is_multicast()
+                || v4.is_documentation()
+                || (a == 100 && (64..128).contains(&b))
+                || a == 0
+        }
+        IpAddr::V6(v6) => {
+            let first = v6.segments()[0];
+            v6.is_loopback()
+                || v6.is_unspecified()
+                || v6.is_multicast()
+                || (first & 0xfe00) == 0xfc00
+                || (first & 0xffc0) == 0xfe80
+                || v6.to_ipv4_mapped().is_some_and(|v4| is_private(IpAddr::V4(v4)))
+        }
+    }
+}
+
+/// Refuses hosts that resolve to non-public addresses.
+pub async fn check_host(url: &Url) -> Result<(), String> {
+    let host = url.host_str().unwrap_or_default().trim_start_matches('[').trim_end_matches(']');
+    let port = url.port_or_known_default().unwrap_or(80);
+    let addrs: Vec<_> = tokio::net::lookup_host((host, port))
+        .await
+        .map_err(|e| format!("Could not resolve {}: {}", host, e))?
+        .collect();
+    if addrs.is_empty() {
+        return Err(format!("Could not resolve {}", host));
+    }
+    if addrs.iter().any(|addr| is_private(addr.ip())) {
+        return Err(format!("{} resolves to a private network address", host));
+    }
+    Ok(())
+}
+
+/// DNS resolver for outbound clients that refuses names resolving to private
+/// addresses. [`check_host`] alone is not enough: the name is resolved again
+/// when connecting and may then point somewhere else (DNS rebinding), so the
+/// addresses actually connected to are checked here. IP literals skip DNS
+/// and are covered by [`check_host`].
+struct PublicResolver;
+
+impl Resolve for PublicResolver {
+    fn resolve(&self, name: Name) -> Resolving {
+        Box::pin(async move {
+            let host = name.as_str().to_string();
+            let addrs: Vec<SocketAddr> = tokio::net::lookup_host((host.as_str(), 0)).await?.collect();
+            if addrs.iter().any(|addr| is_private(addr.ip())) {
+                return Err(format!("{} resolves to a private network address", host).into());
+            }
+            Ok(Box::new(addrs.into_iter()) as Addrs)
+        })
+    }
+}
+
+/// Applies the outbound policy to a client builder: unless `allow_private`
+/// is set, connections only go to public addresses.
+pub fn restrict(builder: reqwest::ClientBuilder, allow_private: bool) -> reqwest::ClientBuilder {
+    if allow_private {
+        builder
+    } else {
+        builder.dns_resolver(Arc::new(PublicResolver))
+    }
+}
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+
+    #[test]
+    fn classifies_addresses() {
+        for private in ["127.0.0.1", "10.1.2.3", "192.168.0.1", "169.254.169.254", "100.64.0.1", "0.0.0.0", "::1", "fd00::1", "fe80::1", "::ffff:127.0.0.1"] {
+            assert!(is_private(private.parse().unwrap()), "{}", private);
+        }
+        for public in ["8.8.8.8", "1.1.1.1", "2606:4700::1111"] {
+            assert!(!is_private(public.parse().unwrap()), "{}", public);
+        }
+    }
+
+    #[tokio::test]
+    async fn refuses_private_hosts() {
+        let err = check_host(&Url::parse("http://127.0.0.1:8080/").unwrap()).await.unwrap_err();
+        assert!(err.contains("private"), "{}", err);
+        assert!(check_host(&Url::parse("http://localhost/").unwrap()).await.is_err());
+    }
+
+    #[tokio::test]
+    async fn restricted_client_does_not_connect_to_private_names() {
+        let listener = tokio::net::TcpListener::bind("127.0.0.1:0").await.unwrap();
+        let port = listener.local_addr().unwrap().port();
+        let url = format!("http://localhost:{}/", port);
+
+        let client = restrict(reqwest::Client::builder(), false).build().unwrap();
+        let err = client.get(&url).send().await.unwrap_err();
+        assert!(format!("{:?}", err).contains("private network address"), "{:?}", err);
+
+        tokio::spawn(async move {
+            let _ = listener.accept().await;
+        });
+        let client = restrict(reqwest::Client::builder(), true)
+            .timeout(std::time::Duration::from_millis(200))
+            .build()
+            .unwrap();
+        let err = client.get(&url).send().await.unwrap_err();
+        assert!(!format!("{:?}", err).contains("private network address"), "{:?}", err);
+    }
+}
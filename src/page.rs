@@ -0,0 +1,860 @@
+//! Structure of a page for SEO analysis, extracted from HTML or Markdown
+//! without a full DOM: headings, images, links, meta tags and the text blocks
+//! in reading order.
+
+#[derive(Clone, Copy, PartialEq)]
+pub enum Markup {
+    Html,
+    Markdown,
+}
+
+impl Markup {
+    pub fn name(self) -> &'static str {
+        match self {
+            Self::Html => "html",
+            Self::Markdown => "markdown",
+        }
+    }
+}
+
+#[derive(Clone, Debug, serde::Serialize)]
+pub struct Heading {
+    pub level: u8,
+    pub text: String,
+}
+
+#[derive(Clone, Debug)]
+pub struct Image {
+    pub src: String,
+    /// `None` when the attribute is absent; `Some("")` marks a decorative image.
+    pub alt: Option<String>,
+}
+
+#[derive(Clone, Debug)]
+pub struct Link {
+    pub href: String,
+    pub text: String,
+    pub rel: String,
+}
+
+/// Where the page title came from.
+#[derive(Clone, Copy, PartialEq, serde::Serialize)]
+#[serde(rename_all = "snake_case")]
+pub enum TitleSource {
+    TitleTag,
+    FrontMatter,
+    /// Markdown without front matter: the first H1 stands in for the title.
+    H1,
+}
+
+/// `<link rel="alternate" hreflang>` entry.
+#[derive(Clone, Debug, serde::Serialize)]
+pub struct Alternate {
+    pub lang: String,
+    pub href: String,
+}
+
+/// Technical `<head>` markup. Always empty for Markdown.
+#[derive(Default)]
+pub struct HeadTags {
+    /// `<html lang>`.
+    pub lang: Option<String>,
+    /// Every `<link rel="canonical">` href, as written.
+    pub canonicals: Vec<String>,
+    /// `<meta name="robots">` and `<meta name="googlebot">` contents.
+    pub robots: Vec<String>,
+    pub hreflang: Vec<Alternate>,
+    /// `og:*` properties in document order.
+    pub open_graph: Vec<(String, String)>,
+    /// Raw bodies of `<script type="application/ld+json">`.
+    pub json_ld: Vec<String>,
+}
+
+pub struct Page {
+    pub markup: Markup,
+    pub head: HeadTags,
+    pub title: Option<(String, TitleSource)>,
+    pub description: Option<String>,
+    pub headings: Vec<Heading>,
+    pub images: Vec<Image>,
+    pub links: Vec<Link>,
+    /// Paragraphs, list items, headings and table cells as plain text.
+    pub blocks: Vec<String>,
+}
+
+impl Page {
+    pub fn parse(content: &str) -> Self {
+        if looks_like_html(content) {
+            from_html(content)
+        } else {
+            from_markdown(content)
+        }
+    }
+
+    /// Parses `html` as HTML regardless of what it looks like.
+    pub fn parse_html(html: &str) -> Self {
+        from_html(html)
+    }
+}
+
+/// Closing tags of common block elements only appear in HTML.
+fn looks_like_html(content: &str) -> bool {
+    let lower = content.to_lowercase();
+    ["<!doctype html", "<html", "<body", "<head", "</p>", "</div>", "</h1>", "</h2>", "</h3>", "</li>", "</article>", "</section>"]
+        .iter()
+        .any(|marker| lower.contains(marker))
+}
+
+// ---------------------------------------------------------------------------
+// HTML
+// ---------------------------------------------------------------------------
+
+pub struct Tag {
+    /// Lowercased element name.
+    pub name: String,
+    pub closing: bool,
+    /// Lowercased attribute names with decoded values.
+    pub attrs: Vec<(String, String)>,
+}
+
+impl Tag {
+    pub fn attr(&self, name: &str) -> Option<&str> {
+        self.attrs.iter().find(|(n, _)| n == name).map(|(_, v)| v.as_str())
+    }
+}
+
+pub enum Token<'a> {
+    Tag(Tag),
+    /// Text between tags, still entity-encoded. Contents of `<script>` and
+    /// `<style>` arrive as a single text token.
+    Text(&'a str),
+}
+
+/// Tolerant HTML tokenizer: comments and doctypes are dropped, unterminated
+/// tags end the input.
+pub fn tokenize(html: &str) -> Vec<Token<'_>> {
+    let mut tokens = Vec::new();
+    let bytes = html.as_bytes();
+    let mut pos = 0;
+    while pos < html.len() {
+        let Some(offset) = html[pos..].find('<') else {
+            tokens.push(Token::Text(&html[pos..]));
+            break;
+        };
+        let start = pos + offset;
+        if start > pos {
+            tokens.push(Token::Text(&html[pos..start]));
+        }
+        if html[start..].starts_with("<!--") {
+            pos = html[start..].find("-->").map_or(html.len(), |end| start + end + 3);
+            continue;
+        }
+        // `<` not starting a tag is text.
+        let next = bytes.get(start + 1).copied().unwrap_or(b' ');
+        if !(next.is_ascii_alphabetic() || next == b'/' || next == b'!' || next == b'?') {
+            tokens.push(Token::Text(&html[start..start + 1]));
+            pos = start + 1;
+            continue;
+        }
+        let Some(end) = tag_end(html, start) else {
+            break;
+        };
+        pos = end + 1;
+        if next == b'!' || next == b'?' {
+            continue;
+        }
+        let tag = parse_tag(&html[start + 1..end]);
+        let raw = !tag.closing && matches!(tag.name.as_str(), "script" | "style");
+        let name = tag.name.clone();
+        tokens.push(Token::Tag(tag));
+        if raw {
+            let close = format!("</{}", name);
+            let body_end = find_ignore_case(&html[pos..], &close).map_or(html.len(), |i| pos + i);
+            if body_end > pos {
+                tokens.push(Token::Text(&html[pos..body_end]));
+            }
+            pos = body_end;
+        }
+    }
+    tokens
+}
+
+/// Index of the `>` closing the tag at `start`, skipping quoted attribute values.
+fn tag_end(html: &str, start: usize) -> Option<usize> {
+    let mut quote: Option<char> = None;
+    for (i, c) in html[start..].char_indices() {
+        match (quote, c) {
+            (Some(q), c) if c == q => quote = None,
+            (Some(_), _) => {}
+            (None, '"' | '\'') => quote = Some(c),
+            (None, '>') => return Some(start + i),
+            _ => {}
+        }
+    }
+    None
+}
+
+fn find_ignore_case(haystack: &str, needle: &str) -> Option<usize> {
+    haystack.to_ascii_lowercase().find(&needle.to_ascii_lowercase())
+}
+
+fn parse_tag(inner: &str) -> Tag {
+    let inner = inner.trim().trim_end_matches('/');
+    let (closing, inner) = match inner.strip_prefix('/') {
+        Some(rest) => (true, rest.trim_start()),
+        None => (false, inner),
+    };
+    let name_end = inner.find(|c: char| c.is_whitespace() || c == '/').unwrap_or(inner.len());
+    let name = inner[..name_end].to_ascii_lowercase();
+
+    let mut attrs = Vec::new();
+    let mut rest = &inner[name_end..];
+    loop {
+        rest = rest.trim_start_matches(|c: char| c.is_whitespace() || c == '/');
+        if rest.is_empty() {
+            break;
+        }
+        let key_end = rest.find(|c: char| c.is_whitespace() || c == '=' || c == '/').unwrap_or(rest.len());
+        let key = rest[..key_end].to_ascii_lowercase();
+        rest = rest[key_end..].trim_start();
+        let mut value = String::new();
+        if let Some(after) = rest.strip_prefix('=') {
+            let after = after.trim_start();
+            let (raw, remaining) = match after.chars().next() {
+                Some(q @ ('"' | '\'')) => {
+                    let body = &after[1..];
+                    let close = body.find(q).unwrap_or(body.len());
+                    (&body[..close], body.get(close + 1..).unwrap_or(""))
+                }
+                _ => {
+                    let end = after.find(char::is_whitespace).unwrap_or(after.len());
+                    (&after[..end], &after[end..])
+                }
+            };
+            value = decode_entities(raw);
+            rest = remaining;
+        }
+        if !key.is_empty() {
+            attrs.push((key, value));
+        }
+    }
+    Tag { name, closing, attrs }
+}
+
+/// Decodes numeric character references and the named entities common in
+/// page copy.
+pub fn decode_entities(text: &str) -> String {
+    if !text.contains('&') {
+        return text.to_string();
+    }
+    let mut out = String::with_capacity(text.len());
+    let mut rest = text;
+    while let Some(at) = rest.find('&') {
+        out.push_str(&rest[..at]);
+        rest = &rest[at..];
+        let decoded = rest.find(';').filter(|&end| end <= 10).and_then(|end| {
+            let entity = &rest[1..end];
+            let c = match entity {
+                "amp" => Some('&'),
+                "lt" => Some('<'),
+                "gt" => Some('>'),
+                "quot" => Some('"'),
+                "apos" => Some('\''),
+                "nbsp" => Some(' '),
+                "mdash" => Some('—'),
+                "ndash" => Some('–'),
+                "hellip" => Some('…'),
+                "lsquo" => Some('‘'),
+                "rsquo" => Some('’'),
+                "ldquo" => Some('“'),
+                "rdquo" => Some('”'),
+                "copy" => Some('©'),
+                "reg" => Some('®'),
+                "trade" => Some('™'),
+                _ => entity
+                    .strip_prefix("#x")
+                    .or_else(|| entity.strip_prefix("#X"))
+                    .and_then(|hex| u32::from_str_radix(hex, 16).ok())
+                    .or_else(|| entity.strip_prefix('#').and_then(|dec| dec.parse().ok()))
+                    .and_then(char::from_u32),
+            };
+            c.map(|c| (c, end + 1))
+        });
+        match decoded {
+            Some((c, len)) => {
+                out.push(c);
+                rest = &rest[len..];
+            }
+            None => {
+                out.push('&');
+                rest = &rest[1..];
+            }
+        }
+    }
+    out.push_str(rest);
+    out
+}
+
+/// Collapses runs of whitespace to single spaces.
+pub fn squash(text: &str) -> String {
+    text.split_whitespace().collect::<Vec<_>>().join(" ")
+}
+
+/// Elements whose boundaries end a text block.
+fn is_block(name: &str) -> bool {
+    matches!(
+        name,
+        "p" | "div" | "li" | "ul" | "ol" | "br" | "hr" | "tr" | "td" | "th" | "table" | "section" | "article"
+            | "header" | "footer" | "main" | "nav" | "aside" | "blockquote" | "pre" | "figure" | "figcaption"
+            | "dt" | "dd" | "dl" | "form" | "h1" | "h2" | "h3" | "h4" | "h5" | "h6" | "title" | "body" | "head"
+    )
+}
+
+/// Elements whose text is not page copy.
+fn is_hidden(name: &str) -> bool {
+    matches!(name, "script" | "style" | "noscript" | "template" | "svg" | "head")
+}
+
+fn from_html(html: &str) -> Page {
+    let mut page = Page {
+        markup: Markup::Html,
+        head: HeadTags::default(),
+        title: None,
+        description: None,
+        headings: Vec::new(),
+        images: Vec::new(),
+        links: Vec::new(),
+        blocks: Vec::new(),
+    };
+    let mut block = String::new();
+    let mut hidden: Vec<String> = Vec::new();
+    let mut title: Option<String> = None;
+    let mut heading: Option<(u8, String)> = None;
+    let mut link: Option<Link> = None;
+    let mut json_ld: Option<String> = None;
+
+    let flush = |block: &mut String, blocks: &mut Vec<String>| {
+        let text = squash(block);
+        if !text.is_empty() {
+            blocks.push(text);
+        }
+        block.clear();
+    };
+
+    for token in tokenize(html) {
+        match token {
+            Token::Text(raw) => {
+                if let Some(title) = title.as_mut() {
+                    title.push_str(&decode_entities(raw));
+                    continue;
+                }
+                if let Some(script) = json_ld.as_mut() {
+                    script.push_str(raw);
+                    continue;
+                }
+                if !hidden.is_empty() {
+                    continue;
+                }
+                let text = decode_entities(raw);
+                block.push_str(&text);
+                if let Some((_, heading)) = heading.as_mut() {
+                    heading.push_str(&text);
+                }
+                if let Some(link) = link.as_mut() {
+                    link.text.push_str(&text);
+                }
+            }
+            Token::Tag(tag) => {
+                let name = tag.name.as_str();
+                if name == "title" && page.title.is_none() {
+                    if tag.closing {
+                        if let Some(text) = title.take() {
+                            page.title = Some((squash(&text), TitleSource::TitleTag));
+                        }
+                    } else {
+                        title = Some(String::new());
+                    }
+                    continue;
+                }
+                if head_tag(&tag, &mut page) {
+                    continue;
+                }
+                if name == "script" {
+                    if tag.closing {
+                        if let Some(script) = json_ld.take() {
+                            page.head.json_ld.push(script.trim().to_string());
+                        }
+                    } else if tag.attr("type").is_some_and(|t| t.to_lowercase().contains("ld+json")) {
+                        json_ld = Some(String::new());
+                    }
+                }
+                // `<head>` holds metadata, not copy, but `<title>` and `<meta>` inside it are read above.
+                if is_hidden(name) {
+                    if tag.closing {
+                        if let Some(at) = hidden.iter().rposition(|h| h == name) {
+                            hidden.truncate(at);
+                        }
+                    } else {
+                        hidden.push(name.to_string());
+                    }
+                    continue;
+                }
+                if !hidden.is_empty() {
+                    continue;
+                }
+                if is_block(name) {
+                    flush(&mut block, &mut page.blocks);
+                }
+                match name {
+                    "h1" | "h2" | "h3" | "h4" | "h5" | "h6" => {
+                        let level = name.as_bytes()[1] - b'0';
+                        if tag.closing {
+                            if let Some((level, text)) = heading.take() {
+                                page.headings.push(Heading { level, text: squash(&text) });
+                            }
+                        } else {
+                            heading = Some((level, String::new()));
+                        }
+                    }
+                    "a" if tag.closing => {
+                        if let Some(mut done) = link.take() {
+                            done.text = squash(&done.text);
+                            page.links.push(done);
+                        }
+                    }
+                    "a" => {
+                        if let Some(href) = tag.attr("href") {
+                            link = Some(Link {
+                                href: href.trim().to_string(),
+                                text: String::new(),
+                                rel: tag.attr("rel").unwrap_or_default().to_lowercase(),
+                            });
+                        }
+                    }
+                    "img" if !tag.closing => {
+                        let alt = tag.attr("alt").map(squash);
+                        // An image inside a link is its anchor text.
+                        if let (Some(link), Some(alt)) = (link.as_mut(), alt.as_deref()) {
+                            link.text.push(' ');
+                            link.text.push_str(alt);
+                        }
+                        page.images.push(Image { src: tag.attr("src").unwrap_or_default().to_string(), alt });
+                    }
+                    _ => {}
+                }
+            }
+        }
+    }
+    flush(&mut block, &mut page.blocks);
+    page
+}
+
+/// Records `<html lang>`, `<meta>` and `<link>` metadata. Returns whether
+/// the tag was metadata.
+fn head_tag(tag: &Tag, page: &mut Page) -> bool {
+    if tag.closing {
+        return false;
+    }
+    match tag.name.as_str() {
+        "html" => {
+            page.head.lang = tag.attr("lang").map(str::to_string).filter(|l| !l.is_empty());
+            false
+        }
+        "meta" => {
+            let content = || tag.attr("content").map(squash).unwrap_or_default();
+            match tag.attr("name").map(str::to_lowercase).as_deref() {
+                Some("description") => page.description = Some(content()),
+                Some("robots" | "googlebot") => page.head.robots.push(content()),
+                _ => {}
+            }
+            if let Some(property) = tag.attr("property").map(str::to_lowercase).filter(|p| p.starts_with("og:")) {
+                page.head.open_graph.push((property, content()));
+            }
+            true
+        }
+        "link" => {
+            let rel = tag.attr("rel").unwrap_or_default().to_lowercase();
+            let href = tag.attr("href").unwrap_or_default().trim().to_string();
+            let rels: Vec<&str> = rel.split_whitespace().collect();
+            if rels.contains(&"canonical") {
+                page.head.canonicals.push(href);
+            } else if rels.contains(&"alternate") {
+                if let Some(lang) = tag.attr("hreflang") {
+                    page.head.hreflang.push(Alternate { lang: lang.trim().to_string(), href });
+                }
+            }
+            true
+        }
+        _ => false,
+    }
+}
+
+// ---------------------------------------------------------------------------
+// Markdown
+// ---------------------------------------------------------------------------
+
+/// `key: value` pairs of a leading `---` front matter block, and the line
+/// index where the body starts.
+fn front_matter(lines: &[&str]) -> (Vec<(String, String)>, usize) {
+    if lines.first().map(|l| l.trim()) != Some("---") {
+        return (Vec::new(), 0);
+    }
+    let Some(end) = lines.iter().skip(1).position(|l| l.trim() == "---") else {
+        return (Vec::new(), 0);
+    };
+    let pairs = lines[1..=end]
+        .iter()
+        .filter_map(|line| line.split_once(':'))
+        .map(|(k, v)| (k.trim().to_lowercase(), v.trim().trim_matches(|c| c == '"' || c == '\'').to_string()))
+        .collect();
+    (pairs, end + 2)
+}
+
+fn from_markdown(markdown: &str) -> Page {
+    let lines: Vec<&str> = markdown.lines().collect();
+    let (meta, body_start) = front_matter(&lines);
+    let field = |name: &str| meta.iter().find(|(k, _)| k == name).map(|(_, v)| v.clone()).filter(|v| !v.is_empty());
+
+    let mut page = Page {
+        markup: Markup::Markdown,
+        head: HeadTags::default(),
+        title: field("title").map(|t| (t, TitleSource::FrontMatter)),
+        description: field("description"),
+        headings: Vec::new(),
+        images: Vec::new(),
+        links: Vec::new(),
+        blocks: Vec::new(),
+    };
+
+    let mut paragraph = String::new();
+    let mut in_code = false;
+    let flush = |paragraph: &mut String, blocks: &mut Vec<String>| {
+        let text = squash(paragraph);
+        if !text.is_empty() {
+            blocks.push(text);
+        }
+        paragraph.clear();
+    };
+
+    let mut i = body_start;
+    while i < lines.len() {
+        let line = lines[i].trim();
+        i += 1;
+        if line.starts_with("```") || line.starts_with("~~~") {
+            flush(&mut paragraph, &mut page.blocks);
+            in_code = !in_code;
+            continue;
+        }
+        if in_code {
+            continue;
+        }
+        if line.is_empty() {
+            flush(&mut paragraph, &mut page.blocks);
+            continue;
+        }
+
+        // Setext headings underline the paragraph line before them.
+        let underline = lines.get(i).map(|l| l.trim()).unwrap_or_default();
+        let plain = paragraph.is_empty() && !line.starts_with(['-', '*', '+', '>', '|', '#']);
+        let setext = if plain && !underline.is_empty() && underline.chars().all(|c| c == '=') {
+            Some(1)
+        } else if plain && underline.len() >= 2 && underline.chars().all(|c| c == '-') {
+            Some(2)
+        } else {
+            None
+        };
+        let atx = line
+            .strip_prefix('#')
+            .map(|_| line.chars().take_while(|&c| c == '#').count())
+            .filter(|&n| n <= 6 && (line.len() == n || line[n..].starts_with([' ', '\t'])));
+
+        if let Some(level) = atx.or(setext) {
+            if setext.is_some() && atx.is_none() {
+                i += 1;
+            }
+            flush(&mut paragraph, &mut page.blocks);
+            let raw = if atx.is_some() { line[level..].trim().trim_end_matches('#').trim() } else { line };
+            let text = inline_markdown(raw, &mut page);
+            page.headings.push(Heading { level: level as u8, text: text.clone() });
+            if !text.is_empty() {
+                page.blocks.push(text);
+            }
+            continue;
+        }
+
+        let rule = line.len() >= 3 && line.chars().all(|c| matches!(c, '-' | '*' | '_' | ' '));
+        if rule {
+            flush(&mut paragraph, &mut page.blocks);
+            continue;
+        }
+        // Table delimiter rows.
+        if line.starts_with('|') && line.chars().all(|c| matches!(c, '|' | '-' | ':' | ' ')) {
+            continue;
+        }
+
+        let mut content = line.trim_start_matches('>').trim_start();
+        let list_item = ["- ", "* ", "+ "].iter().find_map(|m| content.strip_prefix(m)).or_else(|| {
+            let digits = content.chars().take_while(char::is_ascii_digit).count();
+            (digits > 0).then(|| content[digits..].strip_prefix(". ").or_else(|| content[digits..].strip_prefix(") "))).flatten()
+        });
+        if let Some(item) = list_item {
+            flush(&mut paragraph, &mut page.blocks);
+            content = item;
+        }
+        let table_row = content.starts_with('|');
+        if table_row {
+            flush(&mut paragraph, &mut page.blocks);
+            for cell in content.trim_matches('|').split('|') {
+                let text = inline_markdown(cell, &mut page);
+                if !text.is_empty() {
+                    page.blocks.push(text);
+                }
+            }
+            continue;
+        }
+
+        let text = inline_markdown(content, &mut page);
+        if !paragraph.is_empty() {
+            paragraph.push(' ');
+        }
+        paragraph.push_str(&text);
+        if list_item.is_some() {
+            flush(&mut paragraph, &mut page.blocks);
+        }
+    }
+    flush(&mut paragraph, &mut page.blocks);
+
+    if page.title.is_none() {
+        page.title = page
+            .headings
+            .iter()
+            .find(|h| h.level == 1 && !h.text.is_empty())
+            .map(|h| (h.text.clone(), TitleSource::H1));
+    }
+    page
+}
+
+/// `[label](target "title")` at the start of `text`: label, target and
+/// consumed length.
+fn bracketed_link(text: &str) -> Option<(&str, &str, usize)> {
+    let mut depth = 0;
+    let mut close = None;
+    for (i, c) in text.char_indices() {
+        match c {
+            '[' => depth += 1,
+            ']' => {
+                depth -= 1;
+                if depth == 0 {
+                    close = Some(i);
+                    break;
+                }
+            }
+            _ => {}
+        }
+    }
+    let close = close?;
+    let rest = text[close + 1..].strip_prefix('(')?;
+    let end = rest.find(')')?;
+    let target = rest[..end].split_whitespace().next().unwrap_or_default();
+    Some((&text[1..close], target.trim_matches(|c| c == '<' || c == '>'), close + 1 + end + 2))
+}
+
+/// Records images and links in a line of Markdown (including inline HTML
+/// `<img>` / `<a>` tags) and returns its plain text.
+fn inline_markdown(text: &str, page: &mut Page) -> String {
+    let mut out = String::new();
+    let mut rest = text;
+    let mut pending_html_link: Option<Link> = None;
+    while let Some(c) = rest.chars().next() {
+        if c == '!' && rest[1..].starts_with('[') {
+            if let Some((alt, src, len)) = bracketed_link(&rest[1..]) {
+                page.images.push(Image { src: src.to_string(), alt: Some(squash(alt)) });
+                rest = &rest[1 + len..];
+                continue;
+            }
+        }
+        if c == '[' {
+            if let Some((label, href, len)) = bracketed_link(rest) {
+                let label = inline_markdown(label, page);
+                page.links.push(Link { href: href.to_string(), text: label.clone(), rel: String::new() });
+                out.push_str(&label);
+                rest = &rest[len..];
+                continue;
+            }
+        }
+        if c == '<' {
+            if let Some(end) = rest.find('>') {
+                let inner = &rest[1..end];
+                if inner.starts_with("http://") || inner.starts_with("https://") || inner.starts_with("mailto:") {
+                    page.links.push(Link { href: inner.to_string(), text: inner.to_string(), rel: String::new() });
+                    out.push_str(inner);
+                    rest = &rest[end + 1..];
+                    continue;
+                }
+                if inner.starts_with(|c: char| c.is_ascii_alphabetic() || c == '/') {
+                    let tag = parse_tag(inner);
+                    match (tag.name.as_str(), tag.closing) {
+                        ("img", false) => page.images.push(Image {
+                            src: tag.attr("src").unwrap_or_default().to_string(),
+                            alt: tag.attr("alt").map(squash),
+                        }),
+                        ("a", false) => {
+                            pending_html_link = tag.attr("href").map(|href| Link {
+                                href: href.to_string(),
+                                text: String::new(),
+                                rel: tag.attr("rel").unwrap_or_default().to_lowercase(),
+                            })
+                        }
+                        ("a", true) => {
+                            if let Some(mut link) = pending_html_link.take() {
+                                link.text = squash(&link.text);
+                                page.links.push(link);
+                            }
+                        }
+                        _ => {}
+                    }
+                    rest = &rest[end + 1..];
+                    continue;
+                }
+            }
+        }
+        // Bare URLs.
+        if c == 'h' && (rest.starts_with("http://") || rest.starts_with("https://")) {
+            let end = rest.find(char::is_whitespace).unwrap_or(rest.len());
+            let url = rest[..end].trim_end_matches(['.', ',', ';', ':', ')']);
+            page.links.push(Link { href: url.to_string(), text: url.to_string(), rel: String::new() });
+            out.push_str(url);
+            rest = &rest[url.len()..];
+            continue;
+        }
+        // Emphasis markers are dropped, except inside words (`snake_case`).
+        let inside_word = out.ends_with(char::is_alphanumeric) && rest[1..].starts_with(char::is_alphanumeric);
+        if !matches!(c, '*' | '_' | '`' | '~') || inside_word {
+            out.push(c);
+            if let Some(link) = pending_html_link.as_mut() {
+                link.text.push(c);
+            }
+        }
+        rest = &rest[c.len_utf8()..];
+    }
+    squash(&decode_entities(&out))
+}
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+
+    const HTML: &str = r#"<!DOCTYPE html>
+<html lang="en">
+<head>
+  <title>Fish &amp; Chips</title>
+  <meta name="description" content="  The best   chips ">
+  <meta name="robots" content="noindex">
+  <meta property="og:title" content="Chips">
+  <link rel="canonical" href=" https://example.com/ ">
+  <link rel="alternate" hreflang="fr" href="https://example.com/fr">
+  <script type="application/ld+json"> {"@type": "Thing"} </script>
+</head>
+<body>
+  <h1>Fresh <em>fish</em></h1>
+  <p>Fried daily.<!-- hidden --></p>
+  <script>var ignored = "<p>";</script>
+  <a href="/menu" rel="NoFollow">Our <img src="menu.png" alt="menu"></a>
+  <img src="deco.png" alt="">
+  <img src="missing.png">
+</body>
+</html>"#;
+
+    #[test]
+    fn parses_html_structure() {
+        let page = Page::parse(HTML);
+        assert!(page.markup == Markup::Html);
+        let (title, source) = page.title.unwrap();
+        assert_eq!(title, "Fish & Chips");
+        assert!(source == TitleSource::TitleTag);
+        assert_eq!(page.description.as_deref(), Some("The best chips"));
+        assert_eq!(page.headings.len(), 1);
+        assert_eq!((page.headings[0].level, page.headings[0].text.as_str()), (1, "Fresh fish"));
+        assert_eq!(page.blocks, ["Fresh fish", "Fried daily.", "Our"]);
+
+        assert_eq!(page.links.len(), 1);
+        assert_eq!(page.links[0].href, "/menu");
+        assert_eq!(page.links[0].text, "Our menu");
+        assert_eq!(page.links[0].rel, "nofollow");
+
+        let alts: Vec<_> = page.images.iter().map(|i| i.alt.as_deref()).collect();
+        assert_eq!(alts, [Some("menu"), Some(""), None]);
+    }
+
+    #[test]
+    fn reads_head_tags() {
+        let head = Page::parse_html(HTML).head;
+        assert_eq!(head.lang.as_deref(), Some("en"));
+        assert_eq!(head.canonicals, ["https://example.com/"]);
+        assert_eq!(head.robots, ["noindex"]);
+        assert_eq!(head.hreflang.len(), 1);
+        assert_eq!((head.hreflang[0].lang.as_str(), head.hreflang[0].href.as_str()), ("fr", "https://example.com/fr"));
+        assert_eq!(head.open_graph, [("og:title".to_string(), "Chips".to_string())]);
+        assert_eq!(head.json_ld, [r#"{"@type": "Thing"}"#]);
+    }
+
+    #[test]
+    fn parses_markdown_structure() {
+        let markdown = "---\ntitle: \"Guide\"\ndescription: How to\n---\n\
+                        Intro\n=====\n\n\
+                        Some *emphasis* and [a link](https://example.com \"t\").\n\n\
+                        - item with ![alt](pic.png)\n\
+                        | a | b |\n|---|---|\n\n\
+                        ```\n# not a heading\n```\n\
+                        ## Next ##\n";
+        let page = Page::parse(markdown);
+        assert!(page.markup == Markup::Markdown);
+        let (title, source) = page.title.unwrap();
+        assert_eq!(title, "Guide");
+        assert!(source == TitleSource::FrontMatter);
+        assert_eq!(page.description.as_deref(), Some("How to"));
+        let headings: Vec<_> = page.headings.iter().map(|h| (h.level, h.text.as_str())).collect();
+        assert_eq!(headings, [(1, "Intro"), (2, "Next")]);
+        assert_eq!(page.blocks, ["Intro", "Some emphasis and a link.", "item with", "a", "b", "Next"]);
+        assert_eq!(page.links.len(), 1);
+        assert_eq!(page.links[0].href, "https://example.com");
+        assert_eq!(page.images[0].src, "pic.png");
+        assert!(page.head.canonicals.is_empty());
+    }
+
+    #[test]
+    fn markdown_title_falls_back_to_h1() {
+        let page = Page::parse("# Hello world\n\nBody with snake_case.\n");
+        let (title, source) = page.title.unwrap();
+        assert_eq!(title, "Hello world");
+        assert!(source == TitleSource::H1);
+        assert_eq!(page.blocks[1], "Body with snake_case.");
+    }
+
+    #[test]
+    fn tokenizes_tags_and_text() {
+        let tokens = tokenize("<A HREF='x' data-on>hi</a><!-- c --><br/>");
+        assert_eq!(tokens.len(), 4);
+        let Token::Tag(open) = &tokens[0] else { panic!("expected a tag") };
+        assert_eq!(open.name, "a");
+        assert!(!open.closing);
+        assert_eq!(open.attr("href"), Some("x"));
+        assert_eq!(open.attr("data-on"), Some(""));
+        assert!(matches!(tokens[1], Token::Text("hi")));
+        assert!(matches!(&tokens[2], Token::Tag(t) if t.closing && t.name == "a"));
+        assert!(matches!(&tokens[3], Token::Tag(t) if t.name == "br"));
+    }
+
+    #[test]
+    fn decodes_entities() {
+        assert_eq!(decode_entities("a &amp; b &lt;c&gt;"), "a & b <c>");
+        assert_eq!(decode_entities("&#65;&#x42;&hellip;"), "AB…");
+        assert_eq!(decode_entities("AT&T &unknown; &"), "AT&T &unknown; &");
+    }
+
+    #[test]
+    fn squashes_whitespace() {
+        assert_eq!(squash("  a \n\t b  "), "a b");
+        assert_eq!(squash(""), "");
+    }
+}
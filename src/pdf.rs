@@ -0,0 +1,751 @@
+use flate2::{write::ZlibEncoder, Compression};
+use pdf_writer::types::{ActionType, AnnotationType, CidFontType, FontFlags, SystemInfo};
+use pdf_writer::{Content, Filter, Finish, Name, Pdf, Rect, Ref, Str, TextStr};
+use std::collections::BTreeMap;
+use std::io::Write;
+use ttf_parser::{Face, GlyphId};
+
+use crate::markdown::{self, Block, Span};
+
+static SANS_REGULAR: &[u8] = include_bytes!("../assets/fonts/DejaVuSans.ttf");
+static SANS_BOLD: &[u8] = include_bytes!("../assets/fonts/DejaVuSans-Bold.ttf");
+static SANS_OBLIQUE: &[u8] = include_bytes!("../assets/fonts/DejaVuSans-Oblique.ttf");
+static SERIF_REGULAR: &[u8] = include_bytes!("../assets/fonts/DejaVuSerif.ttf");
+static SERIF_BOLD: &[u8] = include_bytes!("../assets/fonts/DejaVuSerif-Bold.ttf");
+static SERIF_ITALIC: &[u8] = include_bytes!("../assets/fonts/DejaVuSerif-Italic.ttf");
+
+pub const TEMPLATES: &[&str] = &["classic", "modern", "compact"];
+pub const FONTS: &[&str] = &["serif", "sans"];
+pub const PAGE_SIZES: &[&str] = &["a4", "letter", "legal"];
+
+const TEXT_COLOR: Rgb = (0.13, 0.13, 0.13);
+const MUTED_COLOR: Rgb = (0.42, 0.42, 0.42);
+const RULE_COLOR: Rgb = (0.75, 0.75, 0.75);
+/// Largest extra word gap, in spaces, that justification may add.
+const MAX_STRETCH: f32 = 1.5;
+
+type Rgb = (f32, f32, f32);
+
+#[derive(Clone, Copy)]
+pub enum PageSize {
+    A4,
+    Letter,
+    Legal,
+}
+
+impl PageSize {
+    pub fn from_name(name: &str) -> Option<Self> {
+        match name.trim().to_lowercase().as_str() {
+            "a4" => Some(Self::A4),
+            "letter" | "us-letter" => Some(Self::Letter),
+            "legal" | "us-legal" => Some(Self::Legal),
+            _ => None,
+        }
+    }
+
+    /// Width and height in points.
+    pub fn dimensions(self) -> (f32, f32) {
+        match self {
+            Self::A4 => (595.28, 841.89),
+            Self::Letter => (612.0, 792.0),
+            Self::Legal => (612.0, 1008.0),
+        }
+    }
+}
+
+#[derive(Clone, Copy)]
+pub enum FontFamily {
+    Serif,
+    Sans,
+}
+
+impl FontFamily {
+    pub fn from_name(name: &str) -> Option<Self> {
+        match name.trim().to_lowercase().as_str() {
+            "serif" | "dejavu-serif" => Some(Self::Serif),
+            "sans" | "sans-serif" | "dejavu-sans" => Some(Self::Sans),
+            _ => None,
+        }
+    }
+
+    /// Regular, bold and italic faces with their PostScript names.
+    fn faces(self) -> [(&'static [u8], &'static str); 3] {
+        match self {
+            Self::Serif => [
+                (SERIF_REGULAR, "DejaVuSerif"),
+                (SERIF_BOLD, "DejaVuSerif-Bold"),
+                (SERIF_ITALIC, "DejaVuSerif-Italic"),
+            ],
+            Self::Sans => [
+                (SANS_REGULAR, "DejaVuSans"),
+                (SANS_BOLD, "DejaVuSans-Bold"),
+                (SANS_OBLIQUE, "DejaVuSans-Oblique"),
+            ],
+        }
+    }
+}
+
+#[derive(Clone, Copy)]
+pub enum Template {
+    /// Centered header, small-caps style section titles over a hairline, justified text.
+    Classic,
+    /// Left-aligned with accent-colored headings and rules.
+    Modern,
+    /// Dense single-page layout with smaller type and tight spacing.
+    Compact,
+}
+
+impl Template {
+    pub fn from_name(name: &str) -> Option<Self> {
+        match name.trim().to_lowercase().as_str() {
+            "classic" => Some(Self::Classic),
+            "modern" => Some(Self::Modern),
+            "compact" => Some(Self::Compact),
+            _ => None,
+        }
+    }
+
+    pub fn default_font(self) -> FontFamily {
+        match self {
+            Self::Classic => FontFamily::Serif,
+            Self::Modern | Self::Compact => FontFamily::Sans,
+        }
+    }
+
+    fn style(self) -> Style {
+        match self {
+            Self::Classic => Style {
+                body_size: 10.5,
+                heading_sizes: [22.0, 12.0, 11.0],
+                leading: 1.35,
+                margin: 60.0,
+                block_gap: 5.0,
+                accent: (0.1, 0.1, 0.1),
+                center_header: true,
+                uppercase_sections: true,
+                section_rule: true,
+                justify: true,
+            },
+            Self::Modern => Style {
+                body_size: 10.0,
+                heading_sizes: [24.0, 13.0, 11.0],
+                leading: 1.4,
+                margin: 54.0,
+                block_gap: 5.0,
+                accent: (0.13, 0.38, 0.67),
+                center_header: false,
+                uppercase_sections: false,
+                section_rule: true,
+                justify: false,
+            },
+            Self::Compact => Style {
+                body_size: 9.0,
+                heading_sizes: [17.0, 10.5, 9.5],
+                leading: 1.25,
+                margin: 40.0,
+                block_gap: 3.0,
+                accent: (0.2, 0.2, 0.2),
+                center_header: false,
+                uppercase_sections: true,
+                section_rule: false,
+                justify: true,
+            },
+        }
+    }
+}
+
+struct Style {
+    body_size: f32,
+    /// h1, h2, h3 (deeper levels use h3).
+    heading_sizes: [f32; 3],
+    /// Line height as a multiple of the font size.
+    leading: f32,
+    margin: f32,
+    block_gap: f32,
+    accent: Rgb,
+    center_header: bool,
+    uppercase_sections: bool,
+    /// Rule under section headings; Markdown `---` rules are drawn only without it.
+    section_rule: bool,
+    justify: bool,
+}
+
+pub struct PdfOptions {
+    pub template: Template,
+    pub font: FontFamily,
+    pub page_size: PageSize,
+    pub title: String,
+    pub author: String,
+}
+
+const REGULAR: usize = 0;
+const BOLD: usize = 1;
+const ITALIC: usize = 2;
+
+/// An embedded face and the glyphs the document uses from it.
+struct LoadedFont {
+    face: Face<'static>,
+    data: &'static [u8],
+    name: &'static str,
+    units_per_em: f32,
+    /// Glyph id → character, for the ToUnicode map.
+    used: BTreeMap<u16, char>,
+}
+
+impl LoadedFont {
+    fn load((data, name): (&'static [u8], &'static str)) -> Result<Self, String> {
+        let face = Face::parse(data, 0).map_err(|e| format!("Failed to load font {}: {}", name, e))?;
+        let units_per_em = face.units_per_em() as f32;
+        Ok(Self { face, data, name, units_per_em, used: BTreeMap::new() })
+    }
+
+    /// Glyph for `c`, falling back to `?` for characters the font lacks.
+    fn glyph(&mut self, c: char) -> u16 {
+        let (c, id) = match self.face.glyph_index(c) {
+            Some(id) => (c, id),
+            None => ('?', self.face.glyph_index('?').unwrap_or(GlyphId(0))),
+        };
+        self.used.entry(id.0).or_insert(c);
+        id.0
+    }
+
+    /// Advance width in 1000-unit glyph space.
+    fn advance(&self, glyph: u16) -> f32 {
+        self.face.glyph_hor_advance(GlyphId(glyph)).unwrap_or(0) as f32 * 1000.0 / self.units_per_em
+    }
+
+    /// Pair kerning from the legacy `kern` table, in 1000-unit glyph space.
+    fn kerning(&self, left: u16, right: u16) -> f32 {
+        let Some(kern) = self.face.tables().kern else {
+            return 0.0;
+        };
+        kern.subtables
+            .into_iter()
+            .filter(|s| s.horizontal && !s.variable && !s.has_cross_stream)
+            .find_map(|s| s.glyphs_kerning(GlyphId(left), GlyphId(right)))
+            .map_or(0.0, |k| k as f32 * 1000.0 / self.units_per_em)
+    }
+
+    fn to_units(&self, value: i16) -> f32 {
+        value as f32 * 1000.0 / self.units_per_em
+    }
+}
+
+/// Shaped text in one font: glyphs plus the kerning applied before each glyph.
+#[derive(Clone)]
+struct Fragment {
+    font: usize,
+    size: f32,
+    glyphs: Vec<u16>,
+    kerns: Vec<f32>,
+    width: f32,
+    color: Rgb,
+    link: Option<String>,
+}
+
+/// Fragments not separated by spaces; lines only break between words.
+#[derive(Clone)]
+struct Word {
+    fragments: Vec<Fragment>,
+    width: f32,
+}
+
+enum Op {
+    Text { x: f32, y: f32, fragment: Fragment },
+    Line { from: (f32, f32), to: (f32, f32), width: f32, color: Rgb },
+}
+
+#[derive(Default)]
+struct Page {
+    ops: Vec<Op>,
+    links: Vec<(Rect, String)>,
+}
+
+#[derive(Clone, Copy, PartialEq)]
+enum Align {
+    Left,
+    Center,
+    Justify,
+}
+
+struct Layout {
+    fonts: Vec<LoadedFont>,
+    style: Style,
+    width: f32,
+    height: f32,
+    pages: Vec<Page>,
+    y: f32,
+}
+
+impl Layout {
+    fn left(&self) -> f32 {
+        self.style.margin
+    }
+
+    fn content_width(&self) -> f32 {
+        self.width - 2.0 * self.style.margin
+    }
+
+    fn page(&mut self) -> &mut Page {
+        self.pages.last_mut().expect("layout starts with a page")
+    }
+
+    fn new_page(&mut self) {
+        self.pages.push(Page::default());
+        self.y = self.height - self.style.margin;
+    }
+
+    /// Starts a new page unless `height` points still fit above the bottom margin.
+    fn ensure(&mut self, height: f32) {
+        // Footer space for the page number.
+        if self.y - height < self.style.margin + 12.0 {
+            self.new_page();
+        }
+    }
+
+    fn line_height(&self, size: f32) -> f32 {
+        size * self.style.leading
+    }
+
+    fn shape(&mut self, text: &str, font: usize, size: f32, color: Rgb, link: Option<String>) -> Fragment {
+        let mut glyphs = Vec::new();
+        let mut kerns = Vec::new();
+        let mut units = 0.0;
+        for c in text.chars() {
+            let glyph = self.fonts[font].glyph(c);
+            let kern = glyphs.last().map_or(0.0, |&prev| self.fonts[font].kerning(prev, glyph));
+            units += kern + self.fonts[font].advance(glyph);
+            glyphs.push(glyph);
+            kerns.push(kern);
+        }
+        Fragment { font, size, glyphs, kerns, width: units * size / 1000.0, color, link }
+    }
+
+    fn space_width(&mut self, size: f32) -> f32 {
+        let glyph = self.fonts[REGULAR].glyph(' ');
+        self.fonts[REGULAR].advance(glyph) * size / 1000.0
+    }
+
+    /// Splits spans into words, shaping each styled run.
+    fn words(&mut self, spans: &[Span], size: f32, base_font: usize, color: Rgb) -> Vec<Word> {
+        let accent = self.style.accent;
+        let mut words = Vec::new();
+        let mut current: Vec<Fragment> = Vec::new();
+
+        for span in spans {
+            let font = if span.bold {
+                BOLD
+            } else if span.italic {
+                ITALIC
+            } else {
+                base_font
+            };
+            // Only real targets become clickable; placeholders like `#` stay plain text.
+            let link = span.link.clone().filter(|url| {
+                url.starts_with("http://") || url.starts_with("https://") || url.starts_with("mailto:")
+            });
+            let color = match (&link, span.code) {
+                (Some(_), _) => accent,
+                // No monospace face is bundled; inline code is set apart by color.
+                (None, true) => MUTED_COLOR,
+                (None, false) => color,
+            };
+
+            let mut pieces = span.text.split(' ').peekable();
+            while let Some(piece) = pieces.next() {
+                if !piece.is_empty() {
+                    current.push(self.shape(piece, font, size, color, link.clone()));
+                }
+                if pieces.peek().is_some() && !current.is_empty() {
+                    let fragments = std::mem::take(&mut current);
+                    let width = fragments.iter().map(|f| f.width).sum();
+                    words.push(Word { fragments, width });
+                }
+            }
+        }
+        if !current.is_empty() {
+            let width = current.iter().map(|f| f.width).sum();
+            words.push(Word { fragments: current, width });
+        }
+        words
+    }
+
+    /// Greedy line breaking; the last line of a justified block stays ragged.
+    fn paragraph(&mut self, words: Vec<Word>, size: f32, indent: f32, align: Align) {
+        let space = self.space_width(size);
+        let available = self.content_width() - indent;
+        let line_height = self.line_height(size);
+
+        let mut lines: Vec<Vec<Word>> = vec![Vec::new()];
+        let mut line_width = 0.0;
+        for word in words {
+            let line = lines.last_mut().expect("at least one line");
+            let needed = if line.is_empty() { word.width } else { line_width + space + word.width };
+            if needed <= available || line.is_empty() {
+                line_width = needed;
+                line.push(word);
+            } else {
+                line_width = word.width;
+                lines.push(vec![word]);
+            }
+        }
+
+        let count = lines.len();
+        for (index, line) in lines.into_iter().enumerate() {
+            if line.is_empty() {
+                continue;
+            }
+            self.ensure(line_height);
+            self.y -= line_height;
+            let natural: f32 = line.iter().map(|w| w.width).sum::<f32>() + space * (line.len() - 1) as f32;
+
+            let mut x = self.left() + indent;
+            let mut gap = space;
+            match align {
+                Align::Center => x += (available - natural).max(0.0) / 2.0,
+                Align::Justify if index + 1 < count && line.len() > 1 => {
+                    // Very loose lines read better ragged than with wide gaps.
+                    let stretch = (available - natural).max(0.0) / (line.len() - 1) as f32;
+                    if stretch <= space * MAX_STRETCH {
+                        gap += stretch;
+                    }
+                }
+                _ => {}
+            }
+
+            let baseline = self.y + (line_height - size) / 2.0 + size * 0.22;
+            for word in line {
+                for fragment in word.fragments {
+                    let width = fragment.width;
+                    if let Some(url) = &fragment.link {
+                        let rect = Rect::new(x, baseline - size * 0.25, x + width, baseline + size * 0.85);
+                        self.page().links.push((rect, url.clone()));
+                    }
+                    self.page().ops.push(Op::Text { x, y: baseline, fragment });
+                    x += width;
+                }
+                x += gap;
+            }
+        }
+    }
+
+    fn rule(&mut self, color: Rgb, width: f32) {
+        let (left, right) = (self.left(), self.width - self.style.margin);
+        let y = self.y;
+        self.page().ops.push(Op::Line { from: (left, y), to: (right, y), width, color });
+    }
+
+    fn render(&mut self, blocks: &[Block]) {
+        // Everything before the first section heading is the resume header.
+        let mut in_header = true;
+
+        for (index, block) in blocks.iter().enumerate() {
+            let body = self.style.body_size;
+            let align = if in_header && self.style.center_header {
+                Align::Center
+            } else if self.style.justify {
+                Align::Justify
+            } else {
+                Align::Left
+            };
+
+            match block {
+                Block::Heading { level, spans } => {
+                    let size = self.style.heading_sizes[(*level as usize).clamp(1, 3) - 1];
+                    let spans: Vec<Span> = if *level == 2 && self.style.uppercase_sections {
+                        spans.iter().map(|s| Span { text: s.text.to_uppercase(), ..s.clone() }).collect()
+                    } else {
+                        spans.to_vec()
+                    };
+                    let spans: Vec<Span> = spans.into_iter().map(|s| Span { bold: true, ..s }).collect();
+
+                    let color = if *level <= 2 { self.style.accent } else { TEXT_COLOR };
+                    if *level == 2 {
+                        in_header = false;
+                        // Keep the heading together with at least two lines of its section.
+                        self.ensure(self.line_height(size) + 2.0 * self.line_height(body) + 12.0);
+                        self.y -= self.style.block_gap * 2.0;
+                    } else if *level > 2 {
+                        self.ensure(self.line_height(size) + self.line_height(body));
+                        self.y -= self.style.block_gap;
+                    }
+
+                    let align = if *level == 1 && self.style.center_header { Align::Center } else { Align::Left };
+                    let words = self.words(&spans, size, BOLD, color);
+                    self.paragraph(words, size, 0.0, align);
+
+                    if *level == 2 && self.style.section_rule {
+                        self.y -= 2.0;
+                        let accent = self.style.accent;
+                        self.rule(accent, 0.6);
+                        self.y -= self.style.block_gap;
+                    }
+                }
+                Block::Paragraph(spans) => {
+                    // A paragraph that is entirely italic (dates, locations) is set in muted color.
+                    let muted = spans.iter().all(|s| s.italic && !s.bold);
+                    let color = if muted { MUTED_COLOR } else { TEXT_COLOR };
+                    let words = self.words(spans, body, REGULAR, color);
+                    self.paragraph(words, body, 0.0, align);
+                    self.y -= self.style.block_gap;
+                }
+                Block::ListItem { number, spans } => {
+                    let indent = if number.is_some() { body * 1.8 } else { body * 1.4 };
+                    let marker = match number {
+                        Some(n) => format!("{}.", n),
+                        None => "•".to_string(),
+                    };
+                    let marker = self.shape(&marker, REGULAR, body, TEXT_COLOR, None);
+                    let words = self.words(spans, body, REGULAR, TEXT_COLOR);
+
+                    let line_height = self.line_height(body);
+                    self.ensure(line_height);
+                    // The first line fits, so the marker belongs on the current page.
+                    let page_index = self.pages.len() - 1;
+                    let marker_at = self.pages[page_index].ops.len();
+                    let top = self.y;
+                    self.paragraph(words, body, indent, if align == Align::Center { Align::Left } else { align });
+                    let baseline = top - line_height + (line_height - body) / 2.0 + body * 0.22;
+                    // Markers are right-aligned against the hanging indent.
+                    let x = self.left() + indent - marker.width - body * 0.4;
+                    // Inserted ahead of the item text so extraction reads marker first.
+                    self.pages[page_index].ops.insert(marker_at, Op::Text { x, y: baseline, fragment: marker });
+                    let list_continues = matches!(blocks.get(index + 1), Some(Block::ListItem { .. }));
+                    self.y -= self.style.block_gap * if list_continues { 0.4 } else { 1.0 };
+                }
+                Block::Quote(spans) => {
+                    let words = self.words(spans, body, ITALIC, MUTED_COLOR);
+                    self.paragraph(words, body, body * 1.4, Align::Left);
+                    self.y -= self.style.block_gap;
+                }
+                Block::Code(code) => {
+                    for line in code.lines() {
+                        let spans = [Span { text: line.to_string(), ..Span::default() }];
+                        let words = self.words(&spans, body * 0.9, REGULAR, MUTED_COLOR);
+                        self.paragraph(words, body * 0.9, body, Align::Left);
+                    }
+                    self.y -= self.style.block_gap;
+                }
+                Block::Rule => {
+                    let next_is_section = matches!(blocks.get(index + 1), Some(Block::Heading { level: 2, .. }));
+                    if !self.style.section_rule && !next_is_section {
+                        self.y -= self.style.block_gap;
+                        self.rule(RULE_COLOR, 0.5);
+                        self.y -= self.style.block_gap;
+                    }
+                }
+            }
+        }
+    }
+
+    /// "n / N" centered in the footer of multi-page documents.
+    fn page_numbers(&mut self) {
+        let total = self.pages.len();
+        if total < 2 {
+            return;
+        }
+        for index in 0..total {
+            let fragment = self.shape(&format!("{} / {}", index + 1, total), REGULAR, 8.0, MUTED_COLOR, None);
+            let x = (self.width - fragment.width) / 2.0;
+            let y = self.style.margin / 2.0;
+            self.pages[index].ops.push(Op::Text { x, y, fragment });
+        }
+    }
+}
+
+fn deflate(data: &[u8]) -> Vec<u8> {
+    let mut encoder = ZlibEncoder::new(Vec::new(), Compression::best());
+    encoder.write_all(data).expect("writing to a Vec cannot fail");
+    encoder.finish().expect("writing to a Vec cannot fail")
+}
+
+const SYSTEM_INFO: SystemInfo = SystemInfo {
+    registry: Str(b"Adobe"),
+    ordering: Str(b"Identity"),
+    supplement: 0,
+};
+
+/// Embeds a subset of `font` as a CIDFontType2 with Identity-H encoding, so
+/// glyph ids double as character codes. A ToUnicode map keeps text copyable.
+fn write_font(pdf: &mut Pdf, alloc: &mut Ref, type0_ref: Ref, font: &LoadedFont) -> Result<(), String> {
+    let (cid_ref, descriptor_ref, cmap_ref, file_ref) = (alloc.bump(), alloc.bump(), alloc.bump(), alloc.bump());
+
+    let mut glyphs: Vec<u16> = font.used.keys().copied().collect();
+    if glyphs.first() != Some(&0) {
+        glyphs.insert(0, 0);
+    }
+    let subset = subsetter::subset(font.data, 0, subsetter::Profile::pdf(&glyphs))
+        .map_err(|e| format!("Failed to subset font {}: {}", font.name, e))?;
+
+    // Subset tag: six uppercase letters derived from the glyph set.
+    let hash = glyphs.iter().fold(0xCBF2_9CE4_8422_2325u64, |h, &g| (h ^ g as u64).wrapping_mul(0x0100_0000_01B3));
+    let tag: String = (0..6).map(|i| (b'A' + ((hash >> (i * 5)) % 26) as u8) as char).collect();
+    let base_font = format!("{}+{}", tag, font.name);
+
+    pdf.type0_font(type0_ref)
+        .base_font(Name(base_font.as_bytes()))
+        .encoding_predefined(Name(b"Identity-H"))
+        .descendant_font(cid_ref)
+        .to_unicode(cmap_ref);
+
+    let mut cid = pdf.cid_font(cid_ref);
+    cid.subtype(CidFontType::Type2)
+        .base_font(Name(base_font.as_bytes()))
+        .system_info(SYSTEM_INFO)
+        .font_descriptor(descriptor_ref)
+        .default_width(0.0)
+        .cid_to_gid_map_predefined(Name(b"Identity"));
+    let mut widths = cid.widths();
+    for &glyph in &glyphs {
+        widths.consecutive(glyph, [font.advance(glyph)]);
+    }
+    widths.finish();
+    cid.finish();
+
+    let face = &font.face;
+    let bbox = face.global_bounding_box();
+    let mut flags = FontFlags::NON_SYMBOLIC;
+    if font.name.contains("Serif") {
+        flags |= FontFlags::SERIF;
+    }
+    if face.is_italic() || face.is_oblique() {
+        flags |= FontFlags::ITALIC;
+    }
+    pdf.font_descriptor(descriptor_ref)
+        .name(Name(base_font.as_bytes()))
+        .flags(flags)
+        .bbox(Rect::new(
+            font.to_units(bbox.x_min),
+            font.to_units(bbox.y_min),
+            font.to_units(bbox.x_max),
+            font.to_units(bbox.y_max),
+        ))
+        .italic_angle(face.italic_angle())
+        .ascent(font.to_units(face.ascender()))
+        .descent(font.to_units(face.descender()))
+        .cap_height(font.to_units(face.capital_height().unwrap_or(face.ascender())))
+        .stem_v(if face.is_bold() { 120.0 } else { 80.0 })
+        .font_file2(file_ref);
+
+    let mut cmap = pdf_writer::types::UnicodeCmap::new(Name(b"Custom"), SYSTEM_INFO);
+    for (&glyph, &c) in &font.used {
+        cmap.pair(glyph, c);
+    }
+    pdf.cmap(cmap_ref, &cmap.finish());
+
+    let compressed = deflate(&subset);
+    let mut file = pdf.stream(file_ref, &compressed);
+    file.filter(Filter::FlateDecode);
+    file.pair(Name(b"Length1"), subset.len() as i32);
+    file.finish();
+    Ok(())
+}
+
+/// Typesets Markdown into a PDF with embedded, subset fonts.
+pub fn render(markdown: &str, options: &PdfOptions) -> Result<Vec<u8>, String> {
+    let blocks = markdown::parse(markdown);
+    let (width, height) = options.page_size.dimensions();
+    let fonts = options
+        .font
+        .faces()
+        .into_iter()
+        .map(LoadedFont::load)
+        .collect::<Result<Vec<_>, _>>()?;
+
+    let mut layout = Layout { fonts, style: options.template.style(), width, height, pages: Vec::new(), y: 0.0 };
+    layout.new_page();
+    layout.render(&blocks);
+    layout.page_numbers();
+
+    let mut pdf = Pdf::new();
+    let mut alloc = Ref::new(1);
+    let catalog_ref = alloc.bump();
+    let tree_ref = alloc.bump();
+    let info_ref = alloc.bump();
+    let page_refs: Vec<(Ref, Ref)> = layout.pages.iter().map(|_| (alloc.bump(), alloc.bump())).collect();
+    let font_refs: Vec<Option<Ref>> = layout
+        .fonts
+        .iter()
+        .map(|f| (!f.used.is_empty()).then(|| alloc.bump()))
+        .collect();
+    let font_names = [Name(b"F0"), Name(b"F1"), Name(b"F2")];
+
+    pdf.catalog(catalog_ref).pages(tree_ref);
+    pdf.pages(tree_ref)
+        .kids(page_refs.iter().map(|&(page, _)| page))
+        .count(page_refs.len() as i32);
+    pdf.document_info(info_ref)
+        .title(TextStr(&options.title))
+        .author(TextStr(&options.author))
+        .creator(TextStr("AI Platform resume builder"));
+
+    for (page, &(page_ref, content_ref)) in layout.pages.iter().zip(&page_refs) {
+        let mut content = Content::new();
+        for op in &page.ops {
+            match op {
+                Op::Text { x, y, fragment } => {
+                    let (r, g, b) = fragment.color;
+                    content.set_fill_rgb(r, g, b);
+                    content.begin_text();
+                    content.set_font(font_names[fragment.font], fragment.size);
+                    content.set_text_matrix([1.0, 0.0, 0.0, 1.0, *x, *y]);
+                    let mut shown = content.show_positioned();
+                    let mut items = shown.items();
+                    let mut run: Vec<u8> = Vec::new();
+                    for (&glyph, &kern) in fragment.glyphs.iter().zip(&fragment.kerns) {
+                        if kern != 0.0 {
+                            items.show(Str(&run));
+                            run.clear();
+                            // TJ adjustments are subtracted from the advance.
+                            items.adjust(-kern);
+                        }
+                        run.extend_from_slice(&glyph.to_be_bytes());
+                    }
+                    items.show(Str(&run));
+                    items.finish();
+                    shown.finish();
+                    content.end_text();
+                }
+                Op::Line { from, to, width, color } => {
+                    content.save_state();
+                    content.set_stroke_rgb(color.0, color.1, color.2);
+                    content.set_line_width(*width);
+                    content.move_to(from.0, from.1);
+                    content.line_to(to.0, to.1);
+                    content.stroke();
+                    content.restore_state();
+                }
+            }
+        }
+        pdf.stream(content_ref, &deflate(&content.finish())).filter(Filter::FlateDecode);
+
+        let mut pdf_page = pdf.page(page_ref);
+        pdf_page.media_box(Rect::new(0.0, 0.0, width, height)).parent(tree_ref).contents(content_ref);
+        let mut resources = pdf_page.resources();
+        let mut fonts = resources.fonts();
+        for (name, font_ref) in font_names.iter().zip(&font_refs) {
+            if let Some(font_ref) = font_ref {
+                fonts.pair(*name, *font_ref);
+            }
+        }
+        fonts.finish();
+        resources.finish();
+
+        if !page.links.is_empty() {
+            let mut annotations = pdf_page.annotations();
+            for (rect, url) in &page.links {
+                let mut annotation = annotations.push();
+                annotation.subtype(AnnotationType::Link).rect(*rect).border(0.0, 0.0, 0.0, None);
+                annotation.action().action_type(ActionType::Uri).uri(Str(url.as_bytes()));
+            }
+        }
+    }
+
+    for (font, font_ref) in layout.fonts.iter().zip(&font_refs) {
+        if let Some(font_ref) = font_ref {
+            write_font(&mut pdf, &mut alloc, *font_ref, font)?;
+        }
+    }
+
+    Ok(pdf.finish())
+}
@@ -38,7 +38,7 @@ impl RateLimiter {
     // --- Yardımcı Fonksiyonlar (Zero-Allocation) ---
 
     fn parse_ip(ip: &str) -> u32 {
-        ip.parse::<Ipv4Addr>().map(|addr| u32::from(addr)).unwrap_or(0)
+        ip.parse::<Ipv4Addr>().map(u32::from).unwrap_or(0)
     }
 
     fn get_day_bucket() -> u32 {
@@ -0,0 +1,527 @@
+use std::io::{Cursor, Read};
+
+use crate::json_resume::{self, Section};
+
+/// Uploaded resume formats.
+#[derive(Clone, Copy, PartialEq)]
+pub enum DocumentKind {
+    Pdf,
+    Docx,
+    Text,
+}
+
+impl DocumentKind {
+    pub fn name(self) -> &'static str {
+        match self {
+            Self::Pdf => "pdf",
+            Self::Docx => "docx",
+            Self::Text => "text",
+        }
+    }
+}
+
+/// Detects the format from magic bytes; anything else must be UTF-8 text.
+pub fn sniff(bytes: &[u8]) -> Option<DocumentKind> {
+    if bytes.starts_with(b"%PDF-") {
+        Some(DocumentKind::Pdf)
+    } else if bytes.starts_with(b"PK\x03\x04") {
+        Some(DocumentKind::Docx)
+    } else if std::str::from_utf8(bytes).is_ok() && !bytes.contains(&0) {
+        Some(DocumentKind::Text)
+    } else {
+        None
+    }
+}
+
+/// One line of extracted text. `heading` marks lines styled as headings in
+/// the source document; `bullet` marks list items.
+struct Line {
+    text: String,
+    heading: bool,
+    bullet: bool,
+}
+
+/// Extracts the document's text and normalises it into resume Markdown
+/// (`# Name`, contact lines, `##` sections, `###` entries, bullets).
+pub fn to_markdown(bytes: &[u8], kind: DocumentKind) -> Result<String, String> {
+    let lines = match kind {
+        DocumentKind::Pdf => {
+            let text = pdf_extract::extract_text_from_mem(bytes)
+                .map_err(|e| format!("Could not read PDF: {}", e))?;
+            text_lines(&text)
+        }
+        DocumentKind::Docx => docx_lines(bytes)?,
+        DocumentKind::Text => text_lines(&String::from_utf8_lossy(bytes)),
+    };
+    if lines.iter().all(|l| l.text.trim().is_empty()) {
+        return Err("No text found in the document (scanned PDFs are not supported)".to_string());
+    }
+    Ok(lines_to_markdown(&lines))
+}
+
+const BULLETS: [char; 10] = ['•', '●', '▪', '■', '◦', '‣', '∙', '·', '-', '*'];
+
+fn text_lines(text: &str) -> Vec<Line> {
+    text.trim_start_matches('\u{feff}')
+        .lines()
+        .map(|line| {
+            let trimmed = line.trim();
+            if is_page_furniture(trimmed) {
+                return Line { text: String::new(), heading: false, bullet: false };
+            }
+            let bullet = trimmed
+                .strip_prefix(BULLETS)
+                .filter(|rest| rest.starts_with(char::is_whitespace) || !trimmed.starts_with(['-', '*']))
+                .or_else(|| trimmed.strip_prefix('–').filter(|rest| rest.starts_with(' ')));
+            Line {
+                text: bullet.unwrap_or(trimmed).trim().to_string(),
+                heading: false,
+                bullet: bullet.is_some(),
+            }
+        })
+        .collect()
+}
+
+/// Page numbers (`2`, `1 / 2`, `Page 1 of 2`) and the `References available
+/// upon request` closer carry no resume data.
+fn is_page_furniture(line: &str) -> bool {
+    let lower = line.to_lowercase();
+    let numbers = lower
+        .trim_start_matches("page")
+        .split(['/', ' '])
+        .filter(|w| !w.is_empty() && *w != "of")
+        .all(|w| w.bytes().all(|b| b.is_ascii_digit()));
+    (numbers && lower.chars().any(|c| c.is_ascii_digit()) && line.len() <= 15)
+        || (lower.starts_with("references") && lower.contains("request"))
+}
+
+/// Upper bound on the uncompressed `word/document.xml`, so a small upload
+/// cannot inflate into gigabytes (resumes are far below this).
+const MAX_DOCUMENT_XML_BYTES: u64 = 20 * 1024 * 1024;
+
+/// Paragraphs of `word/document.xml`, including table cells. Heading and
+/// title styles, and numbered / bulleted paragraphs, are kept as line flags.
+fn docx_lines(bytes: &[u8]) -> Result<Vec<Line>, String> {
+    let mut archive = zip::ZipArchive::new(Cursor::new(bytes)).map_err(|e| format!("Could not read DOCX: {}", e))?;
+    let document = archive
+        .by_name("word/document.xml")
+        .map_err(|_| "Not a Word document (word/document.xml missing)".to_string())?;
+    let too_large = format!("Word document text exceeds {} MB", MAX_DOCUMENT_XML_BYTES / (1024 * 1024));
+    if document.size() > MAX_DOCUMENT_XML_BYTES {
+        return Err(too_large);
+    }
+    // The declared size can lie; never read past the cap.
+    let mut xml = String::new();
+    document
+        .take(MAX_DOCUMENT_XML_BYTES + 1)
+        .read_to_string(&mut xml)
+        .map_err(|e| format!("Could not read DOCX: {}", e))?;
+    if xml.len() as u64 > MAX_DOCUMENT_XML_BYTES {
+        return Err(too_large);
+    }
+    let doc = roxmltree::Document::parse(&xml).map_err(|e| format!("Could not parse DOCX: {}", e))?;
+
+    let lines = doc
+        .descendants()
+        .filter(|n| n.has_tag_name("p") && n.tag_name().namespace() == Some(WORD_NS))
+        .map(|p| {
+            let style = word_child(p, "pPr")
+                .and_then(|ppr| word_child(ppr, "pStyle"))
+                .and_then(|s| s.attribute((WORD_NS, "val")))
+                .unwrap_or_default()
+                .to_lowercase();
+            let numbered = word_child(p, "pPr").and_then(|ppr| word_child(ppr, "numPr")).is_some();
+
+            let mut text = String::new();
+            for node in p.descendants() {
+                match node.tag_name().name() {
+                    "t" if node.tag_name().namespace() == Some(WORD_NS) => text.push_str(node.text().unwrap_or_default()),
+                    // Tabs usually separate a title from right-aligned dates.
+                    "tab" => text.push_str("   "),
+                    "br" => text.push(' '),
+                    _ => {}
+                }
+            }
+            let mut lines = text_lines(&text);
+            let mut line = lines.pop().unwrap_or(Line { text: String::new(), heading: false, bullet: false });
+            line.heading = style.starts_with("heading") || style == "title";
+            line.bullet |= numbered || style.contains("list");
+            line
+        })
+        .collect();
+    Ok(lines)
+}
+
+const WORD_NS: &str = "http://schemas.openxmlformats.org/wordprocessingml/2006/main";
+
+fn word_child<'a, 'i>(node: roxmltree::Node<'a, 'i>, name: &str) -> Option<roxmltree::Node<'a, 'i>> {
+    node.children()
+        .find(|c| c.tag_name().name() == name && c.tag_name().namespace() == Some(WORD_NS))
+}
+
+/// Section heading such as `EXPERIENCE`, `Work History:` or a heading-styled
+/// `Education`; long lines and lines with data (`Skills: Rust, Go`) are not.
+fn section_heading(line: &Line) -> Option<Section> {
+    let text = line.text.trim().trim_end_matches(':').trim();
+    if line.bullet || text.is_empty() || text.chars().count() > 50 || text.contains([',', '|', '@']) {
+        return None;
+    }
+    if text.contains(':') || text.split_whitespace().count() > 5 {
+        return None;
+    }
+    let section = Section::detect(text);
+    if section == Section::Other {
+        return None;
+    }
+    let upper = text.chars().any(char::is_alphabetic) && !text.chars().any(char::is_lowercase);
+    let titled = text
+        .split_whitespace()
+        .all(|w| w.starts_with(|c: char| c.is_uppercase() || !c.is_alphabetic()) || w.len() <= 3);
+    (line.heading || upper || line.text.trim().ends_with(':') || titled).then_some(section)
+}
+
+/// Contact part (`jane@example.com`, `linkedin.com/in/jane`) as Markdown.
+fn contact_part(part: &str) -> String {
+    let part = part.trim();
+    // Drop labels such as `Email:` / `Phone:` but keep URL schemes.
+    let value = match part.split_once(": ") {
+        Some((label, value)) if label.len() < 12 && !label.contains("http") => value.trim(),
+        _ => part,
+    };
+    let lower = value.to_lowercase();
+    let is_url = lower.starts_with("http://")
+        || lower.starts_with("https://")
+        || lower.starts_with("www.")
+        || (["linkedin.com", "github.com", "gitlab.com", "twitter.com", "x.com/", "stackoverflow.com"]
+            .iter()
+            .any(|d| lower.contains(d))
+            && !value.contains(' '));
+    if !is_url {
+        return value.to_string();
+    }
+    let url = if lower.starts_with("http") { value.to_string() } else { format!("https://{}", value) };
+    let network = [
+        ("linkedin.com", "LinkedIn"),
+        ("github.com", "GitHub"),
+        ("gitlab.com", "GitLab"),
+        ("twitter.com", "Twitter"),
+        ("x.com/", "X"),
+        ("stackoverflow.com", "Stack Overflow"),
+    ]
+    .iter()
+    .find(|(domain, _)| lower.contains(domain))
+    .map_or("Website", |(_, name)| name);
+    format!("[{}]({})", network, url)
+}
+
+fn has_contact(text: &str) -> bool {
+    text.contains('@')
+        || text.contains("://")
+        || text.contains("www.")
+        || text.contains(".com/")
+        || text.chars().filter(char::is_ascii_digit).count() >= 7
+}
+
+/// Bullets at least this long were probably wrapped by the PDF layout.
+const WRAPPED_LINE_CHARS: usize = 70;
+
+/// Builds resume Markdown from extracted lines: the first line is the name,
+/// lines before the first section are contact details, and within entry
+/// sections (experience, education, projects) short non-bullet lines start
+/// an entry while date ranges become the entry's date line.
+fn lines_to_markdown(lines: &[Line]) -> String {
+    let mut out: Vec<String> = Vec::new();
+    let mut section: Option<Section> = None;
+    let mut name_done = false;
+    let mut label_done = false;
+    // Index of the current entry heading in `out`, and whether the entry has
+    // body content (dates, bullets, text) yet.
+    let mut entry: Option<usize> = None;
+    let mut entry_body = false;
+    let mut previous: Option<&Line> = None;
+
+    for line in lines {
+        let text = line.text.trim();
+        if text.is_empty() {
+            previous = None;
+            continue;
+        }
+
+        // Wrapped continuation of the previous line (PDF extraction breaks lines).
+        if let Some(prev) = previous {
+            let continues = !line.heading
+                && !line.bullet
+                && section_heading(line).is_none()
+                && (text.starts_with(char::is_lowercase)
+                    || (prev.bullet
+                        && prev.text.chars().count() >= WRAPPED_LINE_CHARS
+                        && !prev.text.trim_end().ends_with(['.', '!', '?', ':'])
+                        && json_resume::split_trailing_dates(text).1.is_none()));
+            if continues && section.is_some() && !out.is_empty() {
+                let last = out.last_mut().expect("non-empty");
+                last.push(' ');
+                last.push_str(text);
+                continue;
+            }
+        }
+        previous = Some(line);
+
+        if !name_done {
+            let lower = text.to_lowercase();
+            if ["resume", "résumé", "curriculum vitae", "cv"].contains(&lower.as_str()) {
+                continue;
+            }
+            // `Jane Doe | jane@example.com` carries contact details on the name line.
+            let (name, contact) = match text.split_once(['|', '•', '·']) {
+                Some((name, contact)) if has_contact(contact) => (name.trim(), Some(contact)),
+                _ => (text, None),
+            };
+            out.push(format!("# {}", title_case(name)));
+            out.push(String::new());
+            if let Some(contact) = contact {
+                let parts: Vec<String> = contact.split(['|', '•', '·']).map(contact_part).collect();
+                out.push(parts.join(" | "));
+                out.push(String::new());
+            }
+            name_done = true;
+            continue;
+        }
+
+        if let Some(detected) = section_heading(line) {
+            let title = text.trim_end_matches(':').trim();
+            end_list(&mut out);
+            out.push(format!("## {}", title_case(title)));
+            out.push(String::new());
+            section = Some(detected);
+            entry = None;
+            entry_body = false;
+            continue;
+        }
+
+        match section {
+            None => {
+                // Contact block: split on common separators, dropping template
+                // placeholders such as `[email]`.
+                let mut parts: Vec<&str> = text
+                    .split(['|', '•', '·', '◦'])
+                    .flat_map(|p| p.split("   "))
+                    .map(str::trim)
+                    .filter(|p| !(p.is_empty() || p.starts_with('[') && p.ends_with(']')))
+                    .collect();
+                // The first plain part of the first contact line is the headline.
+                if !label_done {
+                    label_done = true;
+                    if let Some(label) = parts
+                        .first()
+                        .filter(|p| !has_contact(p) && !p.contains(',') && p.chars().count() <= 60)
+                    {
+                        out.push(format!("**{}**", label));
+                        out.push(String::new());
+                        parts.remove(0);
+                    }
+                }
+                if !parts.is_empty() {
+                    out.push(parts.into_iter().map(contact_part).collect::<Vec<_>>().join(" | "));
+                    out.push(String::new());
+                }
+            }
+            Some(Section::Experience | Section::Education | Section::Projects) => {
+                // `GPA: 3.8` / `Technologies: Rust, Go` detail lines read as bullets.
+                if line.bullet || (entry.is_some() && text.contains(": ")) {
+                    out.push(format!("- {}", text));
+                    entry_body = true;
+                    continue;
+                }
+                let (rest, dates) = json_resume::split_trailing_dates(text);
+                let long = rest.chars().count() > 90 || rest.ends_with('.');
+                if !rest.is_empty() && long && entry.is_some() {
+                    end_list(&mut out);
+                    out.push(rest);
+                    out.push(String::new());
+                    entry_body = true;
+                } else if !rest.is_empty() {
+                    match entry {
+                        // A second title line (company, institution) joins the heading.
+                        Some(index) if !entry_body => out[index].push_str(&format!(" | {}", rest)),
+                        _ => {
+                            end_list(&mut out);
+                            out.push(format!("### {}", rest));
+                            entry = Some(out.len() - 1);
+                            entry_body = false;
+                        }
+                    }
+                }
+                if let Some(dates) = dates {
+                    if entry.is_none() {
+                        out.push("### Untitled".to_string());
+                        entry = Some(out.len() - 1);
+                    }
+                    if section == Some(Section::Education) {
+                        out[entry.expect("entry")].push_str(&format!(" | {}", dates));
+                    } else {
+                        out.push(format!("*{}*", dates));
+                        out.push(String::new());
+                    }
+                    entry_body = true;
+                }
+            }
+            // Skills, languages and certificates are lists, one item per line.
+            Some(Section::Skills | Section::Languages | Section::Certificates) => out.push(format!("- {}", text)),
+            _ => {
+                if line.bullet {
+                    out.push(format!("- {}", text));
+                } else {
+                    if previous_bullet(&out) {
+                        out.push(String::new());
+                    }
+                    out.push(text.to_string());
+                }
+            }
+        }
+    }
+
+    let mut md = out.join("\n");
+    md.push('\n');
+    md
+}
+
+fn previous_bullet(out: &[String]) -> bool {
+    out.last().is_some_and(|l| l.starts_with("- "))
+}
+
+/// Ends a bullet list so the next paragraph is not read as a continuation.
+fn end_list(out: &mut Vec<String>) {
+    if out.last().is_some_and(|l| !l.is_empty()) {
+        out.push(String::new());
+    }
+}
+
+/// `WORK EXPERIENCE` → `Work Experience`; mixed-case titles are kept.
+fn title_case(title: &str) -> String {
+    if title.chars().any(char::is_lowercase) {
+        return title.to_string();
+    }
+    title
+        .split_whitespace()
+        .map(|word| {
+            let lower = word.to_lowercase();
+            let mut chars = lower.chars();
+            match chars.next() {
+                Some(first) if !["and", "of", "&"].contains(&lower.as_str()) => first.to_uppercase().chain(chars).collect(),
+                _ => lower,
+            }
+        })
+        .collect::<Vec<_>>()
+        .join(" ")
+}
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+    use crate::docx::{self, DocxOptions};
+    use crate::pdf::{FontFamily, PageSize, Template};
+    use std::io::Write;
+
+    fn line(text: &str) -> Line {
+        Line { text: text.to_string(), heading: false, bullet: false }
+    }
+
+    #[test]
+    fn detects_section_headings() {
+        assert_eq!(section_heading(&line("EXPERIENCE")), Some(Section::Experience));
+        assert_eq!(section_heading(&line("Work History:")), Some(Section::Experience));
+        assert_eq!(section_heading(&line("Technical Skills")), Some(Section::Skills));
+        assert_eq!(section_heading(&line("Licenses & Certifications")), Some(Section::Certificates));
+        let styled = Line { heading: true, ..line("education and training") };
+        assert_eq!(section_heading(&styled), Some(Section::Education));
+
+        // Data lines, sentences, bullets and unknown titles are not headings.
+        assert_eq!(section_heading(&line("Skills: Rust, Go")), None);
+        assert_eq!(section_heading(&line("education and training")), None);
+        assert_eq!(section_heading(&line("Gained experience with large systems at scale")), None);
+        assert_eq!(section_heading(&Line { bullet: true, ..line("Projects") }), None);
+        assert_eq!(section_heading(&line("HOBBIES")), None);
+    }
+
+    #[test]
+    fn plain_text_resume_becomes_sections() {
+        let text = "\
+JANE DOE
+Backend Engineer | jane@example.com | linkedin.com/in/jane
+
+SUMMARY
+Engineer who likes
+reliable systems.
+
+WORK EXPERIENCE
+Staff Engineer, Acme    Jan 2021 - Present
+• Led the migration of billing to Rust
+• Cut p99 latency by 40%
+
+EDUCATION
+B.Sc. Computer Science
+TU Berlin    2012 - 2016
+
+SKILLS
+Rust, Go, SQL
+
+Page 1 of 1
+";
+        let markdown = to_markdown(text.as_bytes(), DocumentKind::Text).unwrap();
+        assert!(markdown.starts_with("# Jane Doe\n"), "{}", markdown);
+        assert!(markdown.contains("[LinkedIn](https://linkedin.com/in/jane)"), "{}", markdown);
+        assert!(!markdown.contains("Page 1"), "{}", markdown);
+
+        let (resume, sections) = json_resume::from_markdown(&markdown);
+        assert_eq!(
+            sections,
+            [Section::Summary, Section::Experience, Section::Education, Section::Skills]
+        );
+        let basics = resume.basics.unwrap();
+        assert_eq!(basics.summary.as_deref(), Some("Engineer who likes reliable systems."));
+        assert_eq!(basics.email.as_deref(), Some("jane@example.com"));
+        assert_eq!(resume.work[0].start_date.as_deref(), Some("2021-01"));
+        assert_eq!(resume.work[0].highlights.len(), 2);
+        assert_eq!(resume.education[0].end_date.as_deref(), Some("2016"));
+        assert_eq!(resume.skills.len(), 3);
+    }
+
+    #[test]
+    fn reads_docx_heading_styles() {
+        let options = DocxOptions {
+            template: Template::Classic,
+            font: FontFamily::Serif,
+            page_size: PageSize::A4,
+            title: "Resume".to_string(),
+            author: "Jane Doe".to_string(),
+            line_breaks: false,
+        };
+        let source = "# Jane Doe\n\njane@example.com\n\n## Experience\n\n### Engineer | Acme\n\n\
+                      - Built things\n\n## Languages\n\n- German (Native)\n";
+        let bytes = docx::render(source, &options).unwrap();
+        assert!(sniff(&bytes) == Some(DocumentKind::Docx));
+
+        let markdown = to_markdown(&bytes, DocumentKind::Docx).unwrap();
+        let (resume, sections) = json_resume::from_markdown(&markdown);
+        assert_eq!(sections, [Section::Experience, Section::Languages], "{}", markdown);
+        assert_eq!(resume.work[0].highlights, ["Built things"]);
+        assert_eq!(resume.languages[0].language, "German");
+    }
+
+    #[test]
+    fn refuses_oversized_document_xml() {
+        let mut zip = zip::ZipWriter::new(Cursor::new(Vec::new()));
+        let options = zip::write::SimpleFileOptions::default()
+            .compression_method(zip::CompressionMethod::Deflated);
+        zip.start_file("word/document.xml", options).unwrap();
+        let chunk = vec![b' '; 1024 * 1024];
+        for _ in 0..=MAX_DOCUMENT_XML_BYTES / chunk.len() as u64 {
+            zip.write_all(&chunk).unwrap();
+        }
+        let bytes = zip.finish().unwrap().into_inner();
+        assert!(bytes.len() < 1024 * 1024);
+
+        let error = to_markdown(&bytes, DocumentKind::Docx).err().unwrap();
+        assert!(error.contains("exceeds"), "{}", error);
+    }
+}
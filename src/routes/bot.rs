@@ -1,27 +1,52 @@
 use axum::{
-    extract::{ConnectInfo, Path, State},
-    response::sse::{Event, KeepAlive, Sse},
+    body::Body,
+    extract::{ConnectInfo, Path, Query, State},
+    http::{header, HeaderMap, Response},
+    response::{
+        sse::{KeepAlive, Sse},
+        IntoResponse,
+    },
     Json,
 };
-use futures::Stream;
-use serde::{Deserialize, Serialize};
+use serde::Deserialize;
 use serde_json::json;
-use std::{convert::Infallible, net::SocketAddr};
+use std::net::SocketAddr;
+use std::time::Instant;
+use subtle::ConstantTimeEq;
 
+use crate::bot_actions::{self, BotAction};
+use crate::bots::{BotCreateRequest, BotDefinition, BOT_DEFINITION_VERSION};
 use crate::error::AppError;
 use crate::mock;
 use crate::state::AppState;
+use crate::transcripts::TranscriptMessage;
 
-#[derive(Deserialize, Serialize)]
-pub struct BotCreateRequest {
-    pub name: String,
-    pub system_prompt: String,
-    pub welcome_message: String,
-}
+/// Header carrying the owner token returned by `/bot/api/create`.
+const OWNER_TOKEN_HEADER: &str = "x-owner-token";
+/// Header used to hand the visitor session id back to the client.
+const SESSION_HEADER: &str = "x-bot-session";
+/// Upper bound on the combined size of a bot's knowledge documents.
+const MAX_KNOWLEDGE_BYTES: usize = 200 * 1024;
 
 #[derive(Deserialize)]
 pub struct BotChatRequest {
     pub message: String,
+    pub session_id: Option<String>,
+}
+
+#[derive(Deserialize)]
+pub struct BotActionsRequest {
+    pub actions: Vec<BotAction>,
+}
+
+#[derive(Deserialize)]
+pub struct TranscriptQuery {
+    pub format: Option<String>,
+}
+
+#[derive(Deserialize)]
+pub struct ExportQuery {
+    pub format: Option<String>,
 }
 
 pub async fn handle_bot_create(
@@ -35,29 +60,183 @@ pub async fn handle_bot_create(
     state.rate_limiter.check_global_limit(&ip).map_err(AppError::RateLimited)?;
     state.rate_limiter.check_tool_limits(&ip, tool).map_err(AppError::RateLimited)?;
 
-    if req.name.trim().is_empty() {
+    if let Err(e) = validate_bot(&state, &req).await {
         state.rate_limiter.record_error(&ip, tool);
-        return Err(AppError::InvalidInput("Bot name cannot be empty".to_string()));
+        return Err(e);
+    }
+
+    state.rate_limiter.increment_counters(&ip, tool);
+
+    let (bot_id, owner_token) = insert_bot(&state, req);
+
+    Ok(Json(json!({
+        "bot_id": bot_id,
+        "owner_token": owner_token,
+        "message": "Bot created successfully",
+        "demo": state.is_demo(),
+    })))
+}
+
+/// Imports a bot from a YAML or JSON definition (as produced by the export endpoint).
+pub async fn handle_bot_import(
+    State(state): State<AppState>,
+    ConnectInfo(addr): ConnectInfo<SocketAddr>,
+    headers: HeaderMap,
+    body: String,
+) -> Result<Json<serde_json::Value>, AppError> {
+    let ip = addr.ip().to_string();
+    let tool = "bot";
+
+    state.rate_limiter.check_global_limit(&ip).map_err(AppError::RateLimited)?;
+    state.rate_limiter.check_tool_limits(&ip, tool).map_err(AppError::RateLimited)?;
+
+    let is_json = headers
+        .get(header::CONTENT_TYPE)
+        .and_then(|v| v.to_str().ok())
+        .is_some_and(|ct| ct.contains("json"));
+    let parsed: Result<BotDefinition, String> = if is_json {
+        serde_json::from_str(&body).map_err(|e| e.to_string())
+    } else {
+        serde_yaml::from_str(&body).map_err(|e| e.to_string())
+    };
+
+    let definition = match parsed {
+        Ok(definition) => definition,
+        Err(e) => {
+            state.rate_limiter.record_error(&ip, tool);
+            return Err(AppError::InvalidInput(format!("Invalid bot definition: {}", e)));
+        }
+    };
+
+    if definition.version == 0 || definition.version > BOT_DEFINITION_VERSION {
+        state.rate_limiter.record_error(&ip, tool);
+        return Err(AppError::InvalidInput(format!(
+            "Unsupported bot definition version {} (supported: 1-{})",
+            definition.version, BOT_DEFINITION_VERSION
+        )));
+    }
+
+    if let Err(e) = validate_bot(&state, &definition.bot).await {
+        state.rate_limiter.record_error(&ip, tool);
+        return Err(e);
     }
 
     state.rate_limiter.increment_counters(&ip, tool);
 
-    let bot_id = format!("bot_{}", chrono::Local::now().timestamp_millis());
+    let (bot_id, owner_token) = insert_bot(&state, definition.bot);
+
+    Ok(Json(json!({
+        "bot_id": bot_id,
+        "owner_token": owner_token,
+        "message": "Bot imported successfully",
+        "demo": state.is_demo(),
+    })))
+}
+
+/// Exports a bot as a versioned definition (`?format=yaml|json`, owner only).
+pub async fn handle_bot_export(
+    State(state): State<AppState>,
+    Path(bot_id): Path<String>,
+    Query(params): Query<ExportQuery>,
+    headers: HeaderMap,
+) -> Result<Response<Body>, AppError> {
+    authorize_owner(&state, &bot_id, &headers)?;
+
+    let bot: BotCreateRequest = {
+        let record = state.bots.get(&bot_id)
+            .ok_or_else(|| AppError::NotFound(format!("Bot {} not found", bot_id)))?;
+        serde_json::from_value(record.clone())
+            .map_err(|e| AppError::InternalError(format!("Corrupt bot record: {}", e)))?
+    };
+    let definition = BotDefinition {
+        version: BOT_DEFINITION_VERSION,
+        bot,
+    };
+
+    let (body, content_type, extension) = match params.format.as_deref().unwrap_or("yaml") {
+        "yaml" | "yml" => (
+            serde_yaml::to_string(&definition)
+                .map_err(|e| AppError::InternalError(format!("YAML encoding failed: {}", e)))?,
+            "application/yaml",
+            "yaml",
+        ),
+        "json" => (
+            serde_json::to_string_pretty(&definition)
+                .map_err(|e| AppError::InternalError(format!("JSON encoding failed: {}", e)))?,
+            "application/json",
+            "json",
+        ),
+        other => {
+            return Err(AppError::InvalidInput(format!(
+                "Unsupported export format: {}",
+                other
+            )))
+        }
+    };
+
+    Ok(Response::builder()
+        .status(200)
+        .header(header::CONTENT_TYPE, content_type)
+        .header(
+            header::CONTENT_DISPOSITION,
+            format!("attachment; filename=\"{}.bot.{}\"", bot_id, extension),
+        )
+        .body(Body::from(body))
+        .unwrap())
+}
+
+async fn validate_bot(state: &AppState, req: &BotCreateRequest) -> Result<(), AppError> {
+    if req.name.trim().is_empty() {
+        return Err(AppError::InvalidInput("Bot name cannot be empty".to_string()));
+    }
+
+    bot_actions::validate_actions(&req.actions, state.config.allow_private_urls)
+        .await
+        .map_err(AppError::InvalidInput)?;
+
+    if !(0.0..=2.0).contains(&req.settings.temperature) {
+        return Err(AppError::InvalidInput("Temperature must be between 0 and 2".to_string()));
+    }
+    if !(1..=4096).contains(&req.settings.max_tokens) {
+        return Err(AppError::InvalidInput("max_tokens must be between 1 and 4096".to_string()));
+    }
+
+    if req.knowledge.iter().any(|doc| doc.title.trim().is_empty()) {
+        return Err(AppError::InvalidInput("Knowledge documents need a title".to_string()));
+    }
+    let knowledge_bytes: usize = req.knowledge.iter().map(|doc| doc.content.len()).sum();
+    if knowledge_bytes > MAX_KNOWLEDGE_BYTES {
+        return Err(AppError::InvalidInput(format!(
+            "Knowledge documents exceed {} KB",
+            MAX_KNOWLEDGE_BYTES / 1024
+        )));
+    }
+
+    Ok(())
+}
+
+/// Stores a new bot record and returns its id and owner token.
+fn insert_bot(state: &AppState, req: BotCreateRequest) -> (String, String) {
+    let bot_id = format!(
+        "bot_{}_{:08x}",
+        chrono::Local::now().timestamp_millis(),
+        rand::random::<u32>()
+    );
+    let owner_token = format!("{:032x}", rand::random::<u128>());
     let bot_data = json!({
         "id": bot_id,
         "name": req.name,
         "system_prompt": req.system_prompt,
         "welcome_message": req.welcome_message,
+        "settings": req.settings,
+        "actions": req.actions,
+        "knowledge": req.knowledge,
+        "owner_token": owner_token,
         "created_at": chrono::Local::now().to_rfc3339(),
     });
 
     state.bots.insert(bot_id.clone(), bot_data);
-
-    Ok(Json(json!({
-        "bot_id": bot_id,
-        "message": "Bot created successfully",
-        "demo": state.is_demo(),
-    })))
+    (bot_id, owner_token)
 }
 
 pub async fn handle_bot_chat(
@@ -65,7 +244,7 @@ pub async fn handle_bot_chat(
     ConnectInfo(addr): ConnectInfo<SocketAddr>,
     Path(bot_id): Path<String>,
     Json(req): Json<BotChatRequest>,
-) -> Result<Sse<impl Stream<Item = Result<Event, Infallible>>>, AppError> {
+) -> Result<impl IntoResponse, AppError> {
     let ip = addr.ip().to_string();
     let tool = "bot";
 
@@ -78,20 +257,248 @@ pub async fn handle_bot_chat(
     }
 
     // DashMap referansını scope içinde tutarak hızlıca serbest bırakıyoruz (Deadlock önleme)
-    let persona = {
-        let bot = state.bots.get(&bot_id)
+    let bot: BotCreateRequest = {
+        let record = state.bots.get(&bot_id)
+            .ok_or_else(|| AppError::NotFound(format!("Bot {} not found", bot_id)))?;
+        serde_json::from_value(record.clone())
+            .map_err(|e| AppError::InternalError(format!("Corrupt bot record: {}", e)))?
+    };
+
+    state.rate_limiter.increment_counters(&ip, tool);
+
+    // Session & transcript: a new visitor session starts with the welcome message.
+    let session_id = state
+        .transcripts
+        .resolve_session(&bot_id, &ip, req.session_id.as_deref());
+    if state.transcripts.history(&session_id).is_empty() {
+        state.bot_stats.record_conversation(&bot_id);
+        if !bot.welcome_message.is_empty() {
+            state.transcripts.append(&session_id, "assistant", &bot.welcome_message);
+        }
+    }
+    // The visitor message is stored only once the turn succeeds, so a failed
+    // turn leaves no unanswered message in the transcript.
+    let message = TranscriptMessage::now("user", &req.message);
+    let mut history = state.transcripts.prompt_history(&session_id);
+    history.push(message.clone());
+
+    // Provider turn: the model (or the demo provider) may call the bot's actions first.
+    let started = Instant::now();
+    let turn = bot_actions::run_bot_turn(&state, &bot, &history).await;
+    let latency_ms = started.elapsed().as_millis() as u64;
+
+    let turn = match turn {
+        Ok(turn) => turn,
+        Err(e) => {
+            state.bot_stats.record_turn(&bot_id, latency_ms, false);
+            state.bot_stats.record_unanswered(&bot_id, &session_id, &req.message, "error");
+            return Err(e);
+        }
+    };
+    state.bot_stats.record_turn(&bot_id, latency_ms, true);
+    if turn.fallback {
+        state.bot_stats.record_unanswered(&bot_id, &session_id, &req.message, "fallback");
+    }
+
+    state.transcripts.push(&session_id, message);
+    for call in &turn.calls {
+        state.transcripts.append(
+            &session_id,
+            "tool",
+            &format!("{}({}) -> {}", call.name, call.arguments, call.result),
+        );
+    }
+    state.transcripts.append(&session_id, "assistant", &turn.reply);
+    let stream = mock::stream_from_text(turn.reply);
+
+    Ok((
+        [(SESSION_HEADER, session_id)],
+        Sse::new(stream).keep_alive(KeepAlive::default()),
+    ))
+}
+
+/// Replaces the actions of a bot (owner only).
+pub async fn handle_bot_actions_update(
+    State(state): State<AppState>,
+    Path(bot_id): Path<String>,
+    headers: HeaderMap,
+    Json(req): Json<BotActionsRequest>,
+) -> Result<Json<serde_json::Value>, AppError> {
+    authorize_owner(&state, &bot_id, &headers)?;
+    bot_actions::validate_actions(&req.actions, state.config.allow_private_urls)
+        .await
+        .map_err(AppError::InvalidInput)?;
+
+    if let Some(mut bot) = state.bots.get_mut(&bot_id) {
+        bot["actions"] = json!(req.actions);
+    }
+
+    Ok(Json(json!({
+        "bot_id": bot_id,
+        "actions": req.actions,
+        "demo": state.is_demo(),
+    })))
+}
+
+/// Usage analytics for the bot owner.
+pub async fn handle_bot_stats(
+    State(state): State<AppState>,
+    Path(bot_id): Path<String>,
+    headers: HeaderMap,
+) -> Result<Json<serde_json::Value>, AppError> {
+    authorize_owner(&state, &bot_id, &headers)?;
+
+    Ok(Json(json!({
+        "bot_id": bot_id,
+        "stats": state.bot_stats.snapshot(&bot_id),
+        "demo": state.is_demo(),
+    })))
+}
+
+/// Transcript export for the bot owner (`?format=json|csv`).
+pub async fn handle_bot_transcripts(
+    State(state): State<AppState>,
+    Path(bot_id): Path<String>,
+    Query(params): Query<TranscriptQuery>,
+    headers: HeaderMap,
+) -> Result<Response<Body>, AppError> {
+    authorize_owner(&state, &bot_id, &headers)?;
+
+    match params.format.as_deref().unwrap_or("json") {
+        "json" => Ok(Json(json!({
+            "bot_id": bot_id,
+            "retention_days": state.config.bot_transcript_retention_days,
+            "sessions": state.transcripts.sessions_for_bot(&bot_id),
+            "demo": state.is_demo(),
+        }))
+        .into_response()),
+        "csv" => Ok(Response::builder()
+            .status(200)
+            .header(header::CONTENT_TYPE, "text/csv; charset=utf-8")
+            .header(
+                header::CONTENT_DISPOSITION,
+                format!("attachment; filename=\"{}-transcripts.csv\"", bot_id),
+            )
+            .body(Body::from(state.transcripts.export_csv(&bot_id)))
+            .unwrap()),
+        other => Err(AppError::InvalidInput(format!(
+            "Unsupported export format: {}",
+            other
+        ))),
+    }
+}
+
+/// Checks that the bot exists and the caller presented its owner token.
+fn authorize_owner(state: &AppState, bot_id: &str, headers: &HeaderMap) -> Result<(), AppError> {
+    let expected = {
+        let bot = state.bots.get(bot_id)
             .ok_or_else(|| AppError::NotFound(format!("Bot {} not found", bot_id)))?;
-        bot.get("system_prompt")
+        bot.get("owner_token")
             .and_then(|v| v.as_str())
-            .unwrap_or("helpful assistant")
+            .unwrap_or_default()
             .to_string()
     };
 
-    state.rate_limiter.increment_counters(&ip, tool);
+    let provided = headers
+        .get(OWNER_TOKEN_HEADER)
+        .and_then(|v| v.to_str().ok())
+        .unwrap_or_default();
 
-    // Demo-only stream for stable deterministic behavior.
-    let stream = mock::mock_bot_stream(&persona, &req.message);
+    // Constant-time, so response timing does not reveal how much of a guess matched.
+    if expected.is_empty() || !bool::from(provided.as_bytes().ct_eq(expected.as_bytes())) {
+        return Err(AppError::Forbidden("Invalid owner token".to_string()));
+    }
+    Ok(())
+}
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+    use crate::bots::KnowledgeDocument;
 
-    Ok(Sse::new(stream)
-        .keep_alive(KeepAlive::default()))
+    fn visitor() -> ConnectInfo<SocketAddr> {
+        ConnectInfo(SocketAddr::from(([203, 0, 113, 7], 40000)))
+    }
+
+    fn owner_headers(token: &str) -> HeaderMap {
+        let mut headers = HeaderMap::new();
+        headers.insert(OWNER_TOKEN_HEADER, token.parse().unwrap());
+        headers
+    }
+
+    /// The parts of a stored bot record that an export carries.
+    fn definition_fields(state: &AppState, bot_id: &str) -> serde_json::Value {
+        let mut record = state.bots.get(bot_id).unwrap().clone();
+        for key in ["id", "owner_token", "created_at"] {
+            record.as_object_mut().unwrap().remove(key);
+        }
+        record
+    }
+
+    async fn export(state: &AppState, bot_id: &str, token: &str, format: &str) -> String {
+        let res = handle_bot_export(
+            State(state.clone()),
+            Path(bot_id.to_string()),
+            Query(ExportQuery { format: Some(format.to_string()) }),
+            owner_headers(token),
+        )
+        .await
+        .unwrap();
+        let body = axum::body::to_bytes(res.into_body(), usize::MAX).await.unwrap();
+        String::from_utf8(body.to_vec()).unwrap()
+    }
+
+    #[tokio::test]
+    async fn export_then_import_gives_an_equivalent_bot() {
+        let state = AppState::for_tests();
+        let (bot_id, owner_token) = insert_bot(
+            &state,
+            BotCreateRequest {
+                name: "Pizza helper".to_string(),
+                system_prompt: "You help customers of a pizza shop.".to_string(),
+                welcome_message: "Hi! Hungry?".to_string(),
+                settings: Default::default(),
+                actions: Vec::new(),
+                knowledge: vec![KnowledgeDocument {
+                    title: "Menu".to_string(),
+                    content: "Margherita, Marinara".to_string(),
+                }],
+            },
+        );
+
+        for (format, content_type) in [("yaml", "application/yaml"), ("json", "application/json")] {
+            let body = export(&state, &bot_id, &owner_token, format).await;
+            let mut headers = HeaderMap::new();
+            headers.insert(header::CONTENT_TYPE, content_type.parse().unwrap());
+            let Json(imported) = handle_bot_import(State(state.clone()), visitor(), headers, body).await.unwrap();
+
+            let new_id = imported["bot_id"].as_str().unwrap();
+            let new_token = imported["owner_token"].as_str().unwrap();
+            assert_ne!(new_id, bot_id);
+            assert_ne!(new_token, owner_token);
+            assert_eq!(definition_fields(&state, new_id), definition_fields(&state, &bot_id));
+
+            // The old owner token does not unlock the imported copy.
+            assert!(authorize_owner(&state, new_id, &owner_headers(&owner_token)).is_err());
+            assert!(authorize_owner(&state, new_id, &owner_headers(new_token)).is_ok());
+        }
+    }
+
+    #[tokio::test]
+    async fn import_rejects_unsupported_versions() {
+        let state = AppState::for_tests();
+        for version in [0, BOT_DEFINITION_VERSION + 1] {
+            let body = format!("version: {}\nname: Bot\nsystem_prompt: Hi\nwelcome_message: Hello\n", version);
+            let err = handle_bot_import(State(state.clone()), visitor(), HeaderMap::new(), body).await.unwrap_err();
+            assert!(
+                matches!(&err, AppError::InvalidInput(msg) if msg.contains("Unsupported bot definition version")),
+                "{:?}",
+                err
+            );
+        }
+        assert!(state.bots.is_empty());
+
+        let missing = handle_bot_import(State(state.clone()), visitor(), HeaderMap::new(), "name: Bot\n".to_string()).await;
+        assert!(matches!(missing, Err(AppError::InvalidInput(_))));
+    }
 }
@@ -15,6 +15,7 @@ use crate::state::AppState;
 pub struct CodeRequest {
     pub description: String,
     pub language: String,
+    #[allow(dead_code)]
     pub mode: Option<String>,
 }
 
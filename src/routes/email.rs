@@ -1,5 +1,7 @@
 use axum::{
+    body::Body,
     extract::{ConnectInfo, State},
+    http::Response,
     response::sse::{Event, KeepAlive, Sse},
     Json,
 };
@@ -8,9 +10,14 @@ use serde::Deserialize;
 use std::{convert::Infallible, net::SocketAddr};
 
 use crate::error::AppError;
+use crate::export::{self, ExportOptions, Format};
 use crate::mock;
+use crate::pdf::Template;
 use crate::state::AppState;
 
+/// Upper bound on e-mail Markdown accepted for export.
+const MAX_MARKDOWN_CHARS: usize = 20_000;
+
 #[derive(Deserialize)]
 pub struct EmailRequest {
     pub subject: String,
@@ -47,3 +54,116 @@ pub async fn handle_email_generate(
     Ok(Sse::new(stream)
         .keep_alive(KeepAlive::default()))
 }
+
+/// Exports `markdown`, or the e-mail generated from `subject`, `email_type`
+/// and `tone` when no Markdown is given.
+#[derive(Deserialize)]
+pub struct EmailExportRequest {
+    pub markdown: Option<String>,
+    #[serde(default)]
+    pub subject: String,
+    #[serde(default)]
+    pub email_type: String,
+    #[serde(default)]
+    pub tone: String,
+    /// classic, modern (default) or compact.
+    pub template: Option<String>,
+    /// serif or sans; defaults to the template's font.
+    pub font: Option<String>,
+    /// a4 (default), letter or legal.
+    pub page_size: Option<String>,
+}
+
+/// The generators start with a `Subject: ...` line; it becomes a bold label
+/// so it stands apart from the body. Returns the subject, if any.
+fn mark_subject(text: &str) -> (String, Option<String>) {
+    let mut subject = None;
+    let markdown = text
+        .lines()
+        .map(|line| match line.trim().strip_prefix("Subject:") {
+            Some(rest) if subject.is_none() => {
+                subject = Some(rest.trim().to_string());
+                format!("**Subject:** {}", rest.trim())
+            }
+            _ => line.to_string(),
+        })
+        .collect::<Vec<_>>()
+        .join("\n");
+    (markdown, subject)
+}
+
+pub async fn handle_email_docx(
+    State(state): State<AppState>,
+    ConnectInfo(addr): ConnectInfo<SocketAddr>,
+    Json(req): Json<EmailExportRequest>,
+) -> Result<Response<Body>, AppError> {
+    export_email(state, addr, req, Format::Docx).await
+}
+
+pub async fn handle_email_html(
+    State(state): State<AppState>,
+    ConnectInfo(addr): ConnectInfo<SocketAddr>,
+    Json(req): Json<EmailExportRequest>,
+) -> Result<Response<Body>, AppError> {
+    export_email(state, addr, req, Format::Html).await
+}
+
+async fn export_email(
+    state: AppState,
+    addr: SocketAddr,
+    req: EmailExportRequest,
+    format: Format,
+) -> Result<Response<Body>, AppError> {
+    let ip = addr.ip().to_string();
+    let tool = "email";
+
+    state.rate_limiter.check_global_limit(&ip).map_err(AppError::RateLimited)?;
+    state.rate_limiter.check_tool_limits(&ip, tool).map_err(AppError::RateLimited)?;
+
+    let markdown = req.markdown.as_deref().map(str::trim).filter(|m| !m.is_empty());
+    let subject = req.subject.trim();
+    let layout = export::layout(
+        req.template.as_deref(),
+        req.font.as_deref(),
+        req.page_size.as_deref(),
+        Template::Modern,
+    );
+    let validated = layout.and_then(|options| match markdown {
+        Some(markdown) if markdown.chars().count() > MAX_MARKDOWN_CHARS => Err(format!(
+            "Markdown must be at most {} characters",
+            MAX_MARKDOWN_CHARS
+        )),
+        None if subject.is_empty() => Err("Provide markdown or an email subject".to_string()),
+        _ => Ok(options),
+    });
+    let (template, font, page_size) = match validated {
+        Ok(options) => options,
+        Err(e) => {
+            state.rate_limiter.record_error(&ip, tool);
+            return Err(AppError::InvalidInput(e));
+        }
+    };
+
+    state.rate_limiter.increment_counters(&ip, tool);
+
+    // Same demo-only generator as the stream, so the download matches it.
+    let text = match markdown {
+        Some(markdown) => markdown.to_string(),
+        None => mock::mock_email(&req.email_type, subject, &req.tone),
+    };
+    let (markdown, found) = mark_subject(&text);
+    let title = found.filter(|s| !s.is_empty()).unwrap_or_else(|| subject.to_string());
+    let filename = export::filename(&title, "email", format);
+
+    let options = ExportOptions {
+        template,
+        font,
+        page_size,
+        title,
+        author: String::new(),
+        line_breaks: true,
+    };
+    let bytes = export::render(markdown, format, options).await.map_err(AppError::InternalError)?;
+
+    Ok(export::attachment(format, &filename, bytes))
+}
@@ -1,26 +1,375 @@
 use axum::{
     body::Body,
-    extract::{ConnectInfo, State},
-    http::{header, Response},
+    extract::{multipart::MultipartError, ConnectInfo, FromRequest, Multipart, Path, Query, Request, State},
+    http::{header, HeaderMap, Response, StatusCode},
     Json,
 };
-use serde::Deserialize;
+use base64::Engine;
+use image::codecs::{jpeg::JpegEncoder, png::PngEncoder, webp::WebPEncoder};
+use serde::{Deserialize, Serialize};
+use std::collections::HashMap;
+use std::io::Cursor;
 use std::net::SocketAddr;
+use std::str::FromStr;
 use tokio::time::sleep;
 
 use crate::error::AppError;
+use crate::gallery::{self, GalleryEntry};
 use crate::mock;
 use crate::state::AppState;
+use crate::upstream::{send_with_retry, RetryPolicy};
+
+/// Selectable models: short name → HuggingFace model id. The first entry is the default.
+const IMAGE_MODELS: &[(&str, &str)] = &[
+    ("sdxl", "stabilityai/stable-diffusion-xl-base-1.0"),
+    ("sd-2.1", "stabilityai/stable-diffusion-2-1"),
+    ("sd-1.5", "runwayml/stable-diffusion-v1-5"),
+    ("flux-schnell", "black-forest-labs/FLUX.1-schnell"),
+    ("sdxl-inpaint", "diffusers/stable-diffusion-xl-1.0-inpainting-0.1"),
+];
+/// Default model when a mask is uploaded.
+const INPAINT_MODEL: &str = "diffusers/stable-diffusion-xl-1.0-inpainting-0.1";
+
+const MIN_DIMENSION: u32 = 256;
+const MAX_DIMENSION: u32 = 1536;
+const MAX_PROMPT_CHARS: usize = 2000;
+const DEFAULT_JPEG_QUALITY: u8 = 85;
+/// Header carrying the token that groups a client's gallery images. Issued on
+/// the first generation and sent back by the client afterwards.
+const GALLERY_TOKEN_HEADER: &str = "x-gallery-token";
+/// Whole request body limit for multipart uploads (init image + mask + fields).
+pub const MAX_UPLOAD_BYTES: usize = 10 * 1024 * 1024;
+/// Uploaded images larger than this are rejected before decoding.
+const MAX_UPLOAD_DIMENSION: u32 = 4096;
 
 #[derive(Deserialize)]
 pub struct ImageRequest {
     pub prompt: String,
+    pub negative_prompt: Option<String>,
+    pub width: Option<u32>,
+    pub height: Option<u32>,
+    pub guidance_scale: Option<f32>,
+    pub num_inference_steps: Option<u32>,
+    pub seed: Option<u32>,
+    pub model: Option<String>,
+    /// img2img only: how far the result may move away from the init image (0-1).
+    pub strength: Option<f32>,
+}
+
+/// Validated generation parameters, forwarded to HuggingFace as `parameters`.
+#[derive(Clone, Debug, Serialize)]
+pub struct ImageParams {
+    #[serde(skip)]
+    pub model: &'static str,
+    pub width: u32,
+    pub height: u32,
+    #[serde(skip_serializing_if = "Option::is_none")]
+    pub negative_prompt: Option<String>,
+    pub guidance_scale: f32,
+    pub num_inference_steps: u32,
+    pub seed: u32,
+    #[serde(skip_serializing_if = "Option::is_none")]
+    pub strength: Option<f32>,
+}
+
+/// Decoded img2img inputs from a multipart upload.
+struct SourceImages {
+    init_bytes: Vec<u8>,
+    init: image::DynamicImage,
+    mask_bytes: Option<Vec<u8>>,
+    mask: Option<image::DynamicImage>,
+}
+
+fn decode_upload(label: &str, bytes: &[u8]) -> Result<image::DynamicImage, String> {
+    let Some(format) = ImageFormat::sniff(bytes) else {
+        return Err(format!("{} must be a PNG, JPEG or WebP image", label));
+    };
+    let mut reader = image::ImageReader::with_format(Cursor::new(bytes), format.codec());
+    let mut limits = image::Limits::default();
+    limits.max_image_width = Some(MAX_UPLOAD_DIMENSION);
+    limits.max_image_height = Some(MAX_UPLOAD_DIMENSION);
+    reader.limits(limits);
+    reader
+        .decode()
+        .map_err(|e| format!("Failed to decode {}: {}", label, e))
+}
+
+fn multipart_error(e: MultipartError) -> AppError {
+    if e.status() == StatusCode::PAYLOAD_TOO_LARGE {
+        AppError::PayloadTooLarge(format!("Upload exceeds {} MB", MAX_UPLOAD_BYTES / (1024 * 1024)))
+    } else {
+        AppError::InvalidInput(e.body_text())
+    }
+}
+
+fn form_field<T: FromStr>(fields: &HashMap<String, String>, name: &str) -> Result<Option<T>, String> {
+    match fields.get(name).map(|v| v.trim()).filter(|v| !v.is_empty()) {
+        None => Ok(None),
+        Some(value) => value
+            .parse()
+            .map(Some)
+            .map_err(|_| format!("{} must be a number", name)),
+    }
+}
+
+/// Reads a multipart form: `image` (init image), optional `mask`, and the
+/// `ImageRequest` fields as text parts.
+async fn read_multipart(
+    mut multipart: Multipart,
+) -> Result<(ImageRequest, Option<Vec<u8>>, Option<Vec<u8>>), AppError> {
+    let mut fields = HashMap::new();
+    let (mut init, mut mask) = (None, None);
+
+    while let Some(field) = multipart.next_field().await.map_err(multipart_error)? {
+        let name = field.name().unwrap_or_default().to_string();
+        match name.as_str() {
+            "image" | "init_image" => init = Some(field.bytes().await.map_err(multipart_error)?.to_vec()),
+            "mask" | "mask_image" => mask = Some(field.bytes().await.map_err(multipart_error)?.to_vec()),
+            _ => {
+                let value = field.text().await.map_err(multipart_error)?;
+                fields.insert(name, value);
+            }
+        }
+    }
+
+    let text = |name: &str| fields.get(name).cloned().filter(|v| !v.trim().is_empty());
+    let req = (|| {
+        Ok::<_, String>(ImageRequest {
+            prompt: text("prompt").unwrap_or_default(),
+            negative_prompt: text("negative_prompt"),
+            width: form_field(&fields, "width")?,
+            height: form_field(&fields, "height")?,
+            guidance_scale: form_field(&fields, "guidance_scale")?,
+            num_inference_steps: form_field(&fields, "num_inference_steps")?,
+            seed: form_field(&fields, "seed")?,
+            model: text("model"),
+            strength: form_field(&fields, "strength")?,
+        })
+    })()
+    .map_err(AppError::InvalidInput)?;
+
+    Ok((req, init, mask))
+}
+
+/// `?format=png|jpeg|webp&quality=1-100`; the query wins over the `Accept` header.
+/// `quality` is JPEG-only and selects JPEG when no format is given.
+#[derive(Deserialize)]
+pub struct ImageOutputQuery {
+    pub format: Option<String>,
+    pub quality: Option<u8>,
+}
+
+#[derive(Clone, Copy, PartialEq)]
+enum ImageFormat {
+    Png,
+    Jpeg,
+    WebP,
+}
+
+impl ImageFormat {
+    fn from_name(name: &str) -> Option<Self> {
+        match name.trim().to_lowercase().as_str() {
+            "png" | "image/png" => Some(Self::Png),
+            "jpeg" | "jpg" | "image/jpeg" | "image/jpg" => Some(Self::Jpeg),
+            "webp" | "image/webp" => Some(Self::WebP),
+            _ => None,
+        }
+    }
+
+    /// Identifies the encoding from magic bytes rather than trusting the upstream.
+    fn sniff(bytes: &[u8]) -> Option<Self> {
+        if bytes.starts_with(b"\x89PNG\r\n\x1a\n") {
+            Some(Self::Png)
+        } else if bytes.starts_with(&[0xFF, 0xD8, 0xFF]) {
+            Some(Self::Jpeg)
+        } else if bytes.len() >= 12 && &bytes[..4] == b"RIFF" && &bytes[8..12] == b"WEBP" {
+            Some(Self::WebP)
+        } else {
+            None
+        }
+    }
+
+    fn mime(self) -> &'static str {
+        match self {
+            Self::Png => "image/png",
+            Self::Jpeg => "image/jpeg",
+            Self::WebP => "image/webp",
+        }
+    }
+
+    fn codec(self) -> image::ImageFormat {
+        match self {
+            Self::Png => image::ImageFormat::Png,
+            Self::Jpeg => image::ImageFormat::Jpeg,
+            Self::WebP => image::ImageFormat::WebP,
+        }
+    }
+}
+
+/// Picks the output format. `None` keeps whatever the generator produced.
+fn negotiate_format(query: Option<&str>, accept: Option<&str>) -> Result<Option<ImageFormat>, String> {
+    if let Some(name) = query {
+        return ImageFormat::from_name(name)
+            .map(Some)
+            .ok_or_else(|| format!("Unsupported format '{}' (available: png, jpeg, webp)", name));
+    }
+
+    let Some(accept) = accept else {
+        return Ok(None);
+    };
+
+    // Highest q-value first; the sort is stable so ties keep header order.
+    let mut ranges: Vec<(&str, f32)> = accept
+        .split(',')
+        .filter_map(|range| {
+            let mut parts = range.split(';');
+            let mime = parts.next()?.trim();
+            let q = parts
+                .filter_map(|p| p.trim().strip_prefix("q="))
+                .find_map(|q| q.parse::<f32>().ok())
+                .unwrap_or(1.0);
+            (q > 0.0).then_some((mime, q))
+        })
+        .collect();
+    ranges.sort_by(|a, b| b.1.total_cmp(&a.1));
+
+    for (mime, _) in ranges {
+        if mime == "*/*" || mime == "image/*" {
+            return Ok(None);
+        }
+        if let Some(format) = ImageFormat::from_name(mime) {
+            return Ok(Some(format));
+        }
+    }
+    Ok(None)
+}
+
+/// Re-encodes `bytes` when the requested format or JPEG quality differs from the source.
+///
+/// WebP output is lossless (the only WebP encoder available in pure Rust), so
+/// `quality` is only accepted for JPEG.
+fn encode_output(
+    bytes: Vec<u8>,
+    target: Option<ImageFormat>,
+    quality: Option<u8>,
+) -> Result<(Vec<u8>, ImageFormat), String> {
+    let source = ImageFormat::sniff(&bytes)
+        .ok_or_else(|| "Image provider returned data in an unsupported format".to_string())?;
+    let target = target.unwrap_or(source);
+    if target == source && !(target == ImageFormat::Jpeg && quality.is_some()) {
+        return Ok((bytes, source));
+    }
+
+    let decoded = image::load_from_memory_with_format(&bytes, source.codec())
+        .map_err(|e| format!("Failed to decode image: {}", e))?;
+
+    let mut out = Vec::new();
+    let result = match target {
+        ImageFormat::Png => decoded.write_with_encoder(PngEncoder::new_with_quality(
+            &mut out,
+            image::codecs::png::CompressionType::Best,
+            image::codecs::png::FilterType::Adaptive,
+        )),
+        // JPEG has no alpha channel.
+        ImageFormat::Jpeg => image::DynamicImage::ImageRgb8(decoded.to_rgb8()).write_with_encoder(
+            JpegEncoder::new_with_quality(&mut out, quality.unwrap_or(DEFAULT_JPEG_QUALITY)),
+        ),
+        ImageFormat::WebP => image::DynamicImage::ImageRgba8(decoded.to_rgba8())
+            .write_with_encoder(WebPEncoder::new_lossless(&mut out)),
+    };
+    result.map_err(|e| format!("Failed to encode {}: {}", target.mime(), e))?;
+
+    Ok((out, target))
+}
+
+impl ImageRequest {
+    /// `source` carries the init image size and whether a mask was uploaded.
+    fn validate(&self, source: Option<((u32, u32), bool)>) -> Result<ImageParams, String> {
+        if self.prompt.chars().count() > MAX_PROMPT_CHARS {
+            return Err(format!("Prompt must be at most {} characters", MAX_PROMPT_CHARS));
+        }
+
+        let model = match self.model.as_deref() {
+            None if source.is_some_and(|(_, masked)| masked) => INPAINT_MODEL,
+            None => IMAGE_MODELS[0].1,
+            Some(name) => IMAGE_MODELS
+                .iter()
+                .find(|(short, id)| *short == name || *id == name)
+                .map(|(_, id)| *id)
+                .ok_or_else(|| {
+                    let names: Vec<&str> = IMAGE_MODELS.iter().map(|(short, _)| *short).collect();
+                    format!("Unknown model '{}' (available: {})", name, names.join(", "))
+                })?,
+        };
+
+        // img2img keeps the init image size unless told otherwise.
+        let fit = |side: u32| (side.clamp(MIN_DIMENSION, MAX_DIMENSION) / 8) * 8;
+        let (default_width, default_height) = source
+            .map(|((w, h), _)| (fit(w), fit(h)))
+            .unwrap_or((1024, 1024));
+        let width = self.width.unwrap_or(default_width);
+        let height = self.height.unwrap_or(default_height);
+        for (label, value) in [("width", width), ("height", height)] {
+            if !(MIN_DIMENSION..=MAX_DIMENSION).contains(&value) || value % 8 != 0 {
+                return Err(format!(
+                    "{} must be a multiple of 8 between {} and {}",
+                    label, MIN_DIMENSION, MAX_DIMENSION
+                ));
+            }
+        }
+
+        let guidance_scale = self.guidance_scale.unwrap_or(7.5);
+        if !(0.0..=20.0).contains(&guidance_scale) {
+            return Err("guidance_scale must be between 0 and 20".to_string());
+        }
+
+        let num_inference_steps = self.num_inference_steps.unwrap_or(30);
+        if !(1..=100).contains(&num_inference_steps) {
+            return Err("num_inference_steps must be between 1 and 100".to_string());
+        }
+
+        let negative_prompt = self
+            .negative_prompt
+            .as_deref()
+            .map(str::trim)
+            .filter(|n| !n.is_empty())
+            .map(str::to_string);
+        if negative_prompt.as_ref().is_some_and(|n| n.chars().count() > MAX_PROMPT_CHARS) {
+            return Err(format!(
+                "negative_prompt must be at most {} characters",
+                MAX_PROMPT_CHARS
+            ));
+        }
+
+        let strength = match (source, self.strength) {
+            (None, Some(_)) => return Err("strength requires an init image".to_string()),
+            (None, None) => None,
+            (Some(_), strength) => Some(strength.unwrap_or(0.75)),
+        };
+        if strength.is_some_and(|s| !(0.0..=1.0).contains(&s)) {
+            return Err("strength must be between 0 and 1".to_string());
+        }
+
+        Ok(ImageParams {
+            model,
+            width,
+            height,
+            negative_prompt,
+            guidance_scale,
+            num_inference_steps,
+            // Always pin a seed so the result can be reproduced.
+            seed: self.seed.unwrap_or_else(rand::random),
+            strength,
+        })
+    }
 }
 
 pub async fn handle_image_generate(
     State(state): State<AppState>,
     ConnectInfo(addr): ConnectInfo<SocketAddr>,
-    Json(req): Json<ImageRequest>,
+    Query(output): Query<ImageOutputQuery>,
+    headers: HeaderMap,
+    request: Request,
 ) -> Result<Response<Body>, AppError> {
     let ip = addr.ip().to_string();
     let tool = "image";
@@ -30,31 +379,154 @@ pub async fn handle_image_generate(
     state.rate_limiter.check_tool_limits(&ip, tool).map_err(AppError::RateLimited)?;
 
     // 2. Input Validation
+    // JSON for text-to-image; multipart/form-data carries an init image (+ mask) for img2img.
+    let is_multipart = headers
+        .get(header::CONTENT_TYPE)
+        .and_then(|v| v.to_str().ok())
+        .is_some_and(|ct| ct.starts_with("multipart/form-data"));
+    let parsed = if is_multipart {
+        match Multipart::from_request(request, &state).await {
+            Ok(multipart) => read_multipart(multipart).await,
+            Err(e) => Err(AppError::InvalidInput(e.body_text())),
+        }
+    } else {
+        Json::<ImageRequest>::from_request(request, &state)
+            .await
+            .map(|Json(req)| (req, None, None))
+            .map_err(|e| AppError::InvalidInput(e.body_text()))
+    };
+    let (req, init_upload, mask_upload) = match parsed {
+        Ok(parsed) => parsed,
+        Err(e) => {
+            state.rate_limiter.record_error(&ip, tool);
+            return Err(e);
+        }
+    };
+
     let prompt = req.prompt.trim();
     if prompt.is_empty() {
         state.rate_limiter.record_error(&ip, tool);
         return Err(AppError::InvalidInput("Prompt cannot be empty".to_string()));
     }
 
+    let source = match (init_upload, mask_upload) {
+        (None, None) => Ok(None),
+        (None, Some(_)) => Err("A mask requires an init image".to_string()),
+        (Some(init_bytes), mask_bytes) => tokio::task::spawn_blocking(move || {
+            let init = decode_upload("image", &init_bytes)?;
+            let mask = mask_bytes.as_deref().map(|m| decode_upload("mask", m)).transpose()?;
+            Ok(Some(SourceImages { init_bytes, init, mask_bytes, mask }))
+        })
+        .await
+        .unwrap_or_else(|e| Err(format!("Image decoding failed: {}", e))),
+    };
+    let source = match source {
+        Ok(source) => source,
+        Err(e) => {
+            state.rate_limiter.record_error(&ip, tool);
+            return Err(AppError::InvalidInput(e));
+        }
+    };
+
+    let source_info = source
+        .as_ref()
+        .map(|s| ((s.init.width(), s.init.height()), s.mask.is_some()));
+    let params = match req.validate(source_info) {
+        Ok(params) => params,
+        Err(e) => {
+            state.rate_limiter.record_error(&ip, tool);
+            return Err(AppError::InvalidInput(e));
+        }
+    };
+
+    let accept = headers.get(header::ACCEPT).and_then(|v| v.to_str().ok());
+    let target = negotiate_format(output.format.as_deref(), accept).and_then(|target| {
+        match (output.quality, target) {
+            (Some(q), _) if !(1..=100).contains(&q) => Err("quality must be between 1 and 100".to_string()),
+            (Some(_), Some(ImageFormat::Png | ImageFormat::WebP)) if output.format.is_some() => {
+                Err("quality only applies to JPEG output".to_string())
+            }
+            // A quality without an explicit format asks for JPEG.
+            (Some(_), _) => Ok(Some(ImageFormat::Jpeg)),
+            (None, target) => Ok(target),
+        }
+    });
+    let target = match target {
+        Ok(target) => target,
+        Err(e) => {
+            state.rate_limiter.record_error(&ip, tool);
+            return Err(AppError::InvalidInput(e));
+        }
+    };
+
     state.rate_limiter.increment_counters(&ip, tool);
 
+    let gallery_token =
+        gallery_token(&headers).unwrap_or_else(|| format!("{:032x}", rand::random::<u128>()));
+
     // 3. Binary Byte Acquisition
-    let image_bytes = if state.is_demo() {
+    // An identical earlier request (same prompt, model, parameters and seed) is served from the gallery.
+    let mut params_json = serde_json::to_value(&params).unwrap_or_default();
+    if let Some(source) = &source {
+        params_json["init_image"] = gallery::sha256_hex(&source.init_bytes).into();
+        if let Some(mask) = &source.mask_bytes {
+            params_json["mask_image"] = gallery::sha256_hex(mask).into();
+        }
+    }
+    let request_key = gallery::request_key(prompt, params.model, &params_json);
+    let stored = match state.gallery.find_by_request(&request_key) {
+        Some(entry) => state.gallery.read(&entry.hash).await.ok(),
+        None => None,
+    };
+    let cache_status = if stored.is_some() { "hit" } else { "miss" };
+
+    let image_bytes = if let Some(stored) = stored {
+        stored
+    } else if state.is_demo() {
         sleep(mock::random_delay()).await;
-        // Mock veriyi Base64'ten binary'ye geri çeviriyoruz (veya mock modülünü güncelleyebilirsin)
-        mock::mock_image_bytes() 
+        // Rendering + deflate is CPU-bound, keep it off the async workers.
+        let prompt = prompt.to_string();
+        let (seed, width, height) = (params.seed, params.width, params.height);
+        let strength = params.strength.unwrap_or(1.0);
+        let source = source.map(|s| (s.init, s.mask));
+        tokio::task::spawn_blocking(move || match source {
+            None => mock::mock_image_bytes(&prompt, seed, width, height),
+            Some((init, mask)) => {
+                let filter = image::imageops::FilterType::Triangle;
+                let init = init.resize_exact(width, height, filter).to_rgb8();
+                let mask = mask.map(|m| m.resize_exact(width, height, filter).to_luma8());
+                let mask = mask.as_ref().map(|m| m.as_raw().as_slice());
+                mock::mock_image_transform(&init, mask, &prompt, seed, width, height, strength)
+            }
+        })
+        .await
+        .map_err(|e| AppError::InternalError(format!("Image rendering failed: {}", e)))?
     } else {
-        let res = state
-            .http_client
-            .post("https://api-inference.huggingface.co/models/stabilityai/stable-diffusion-xl-base-1.0")
-            .header(header::AUTHORIZATION, format!("Bearer {}", state.config.hf_api_token))
-            .json(&serde_json::json!({ "inputs": prompt }))
-            .send()
-            .await
-            .map_err(|e| AppError::InternalError(format!("HuggingFace request failed: {}", e)))?;
+        // img2img sends the init image as `inputs` and moves the prompt into parameters.
+        let body = match &source {
+            None => serde_json::json!({ "inputs": prompt, "parameters": params }),
+            Some(source) => {
+                let b64 = base64::engine::general_purpose::STANDARD;
+                let mut parameters = serde_json::to_value(&params).unwrap_or_default();
+                parameters["prompt"] = prompt.into();
+                if let Some(mask) = &source.mask_bytes {
+                    parameters["mask_image"] = b64.encode(mask).into();
+                }
+                serde_json::json!({ "inputs": b64.encode(&source.init_bytes), "parameters": parameters })
+            }
+        };
+        // Model loading (503 + estimated_time) is retried until the deadline.
+        let res = send_with_retry(&state.hf_breaker, &RetryPolicy::HUGGINGFACE, || {
+            state
+                .http_client
+                .post(format!("https://api-inference.huggingface.co/models/{}", params.model))
+                .header(header::AUTHORIZATION, format!("Bearer {}", state.config.hf_api_token))
+                .json(&body)
+        })
+        .await?;
 
         if !res.status().is_success() {
-             return Err(AppError::InternalError(format!("HF API returned error: {}", res.status())));
+            return Err(AppError::InternalError(format!("HF API returned error: {}", res.status())));
         }
 
         res.bytes()
@@ -63,13 +535,333 @@ pub async fn handle_image_generate(
             .to_vec()
     };
 
-    // 4. Return Direct Binary Response
+    let Some(format) = ImageFormat::sniff(&image_bytes) else {
+        return Err(AppError::InternalError(
+            "Image provider returned data in an unsupported format".to_string(),
+        ));
+    };
+    // Cache hits are recorded too, so the image shows up in (and counts
+    // against) the requester's gallery even when another client generated it.
+    let entry = GalleryEntry {
+        hash: String::new(),
+        content_type: format.mime().to_string(),
+        size: 0,
+        prompt: prompt.to_string(),
+        model: params.model.to_string(),
+        params: params_json,
+        request_key,
+        owner: gallery::owner_id(&gallery_token),
+        created_at: chrono::Local::now().to_rfc3339(),
+    };
+    // A full disk should not fail the generation itself.
+    let image_hash = match state.gallery.store(&image_bytes, entry).await {
+        Ok(entry) => entry.hash,
+        Err(e) => {
+            tracing::warn!("Failed to store generated image: {}", e);
+            gallery::sha256_hex(&image_bytes)
+        }
+    };
+
+    // 4. Transcode to the negotiated format (CPU-bound)
+    let quality = output.quality;
+    let (image_bytes, format) =
+        tokio::task::spawn_blocking(move || encode_output(image_bytes, target, quality))
+            .await
+            .map_err(|e| AppError::InternalError(format!("Image encoding failed: {}", e)))?
+            .map_err(AppError::InternalError)?;
+
+    let etag = format!("\"{}\"", gallery::sha256_hex(&image_bytes));
+
+    // 5. Return Direct Binary Response
     // Base64 encoding is removed to reduce payload size by ~33%.
+    // No 304 here: generation has already run by the time the ETag is known,
+    // and a POST is not a conditional read. `GET /image/{hash}` revalidates.
     Ok(Response::builder()
+        // Private: the response carries the client's gallery token.
+        .header(header::CACHE_CONTROL, "private, max-age=3600")
+        .header(header::ETAG, &etag)
+        .header(header::VARY, "Accept")
+        // Seed + model are echoed so the same image can be reproduced later.
+        .header("X-Image-Seed", params.seed.to_string())
+        .header("X-Image-Model", params.model)
+        // Permanent gallery address of the original image: /image/{hash}
+        .header("X-Image-Hash", &image_hash)
+        .header("X-Image-Cache", cache_status)
+        .header(GALLERY_TOKEN_HEADER, &gallery_token)
         .status(200)
-        .header(header::CONTENT_TYPE, "image/png")
-        // Browser caching for 1 hour to reduce redundant generation requests
-        .header(header::CACHE_CONTROL, "public, max-age=3600")
+        .header(header::CONTENT_TYPE, format.mime())
+        .header(header::CONTENT_LENGTH, image_bytes.len())
         .body(Body::from(image_bytes))
         .unwrap())
 }
+
+/// The client's gallery token, if it sent a well-formed one.
+fn gallery_token(headers: &HeaderMap) -> Option<String> {
+    headers
+        .get(GALLERY_TOKEN_HEADER)
+        .and_then(|v| v.to_str().ok())
+        .filter(|t| t.len() == 32 && t.bytes().all(|b| b.is_ascii_hexdigit()))
+        .map(str::to_ascii_lowercase)
+}
+
+fn etag_matches(headers: &HeaderMap, etag: &str) -> bool {
+    headers
+        .get(header::IF_NONE_MATCH)
+        .and_then(|v| v.to_str().ok())
+        .is_some_and(|tags| tags.split(',').any(|t| t.trim() == etag || t.trim() == "*"))
+}
+
+/// Serves a stored gallery image. Content-addressed, so it never changes.
+pub async fn handle_image_get(
+    State(state): State<AppState>,
+    Path(hash): Path<String>,
+    headers: HeaderMap,
+) -> Result<Response<Body>, AppError> {
+    let entry = state
+        .gallery
+        .get(&hash)
+        .ok_or_else(|| AppError::NotFound("Image not found".to_string()))?;
+
+    let etag = format!("\"{}\"", entry.hash);
+    let builder = Response::builder()
+        .header(header::CACHE_CONTROL, "public, max-age=31536000, immutable")
+        .header(header::ETAG, &etag);
+
+    if etag_matches(&headers, &etag) {
+        return Ok(builder.status(StatusCode::NOT_MODIFIED).body(Body::empty()).unwrap());
+    }
+
+    let bytes = state
+        .gallery
+        .read(&entry.hash)
+        .await
+        .map_err(|_| AppError::NotFound("Image not found".to_string()))?;
+
+    Ok(builder
+        .status(200)
+        .header(header::CONTENT_TYPE, entry.content_type)
+        .header(header::CONTENT_LENGTH, bytes.len())
+        .body(Body::from(bytes))
+        .unwrap())
+}
+
+/// Lists the images generated with the caller's gallery token, newest first.
+pub async fn handle_image_gallery(
+    State(state): State<AppState>,
+    headers: HeaderMap,
+) -> Result<Json<serde_json::Value>, AppError> {
+    let token = gallery_token(&headers)
+        .ok_or_else(|| AppError::Forbidden("Missing or invalid gallery token".to_string()))?;
+    let images: Vec<serde_json::Value> = state
+        .gallery
+        .list_for_owner(&gallery::owner_id(&token))
+        .into_iter()
+        .map(|entry| {
+            serde_json::json!({
+                "url": format!("/image/{}", entry.hash),
+                "hash": entry.hash,
+                "content_type": entry.content_type,
+                "size": entry.size,
+                "prompt": entry.prompt,
+                "model": entry.model,
+                "params": entry.params,
+                "created_at": entry.created_at,
+            })
+        })
+        .collect();
+
+    Ok(Json(serde_json::json!({
+        "count": images.len(),
+        "images": images,
+    })))
+}
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+
+    fn request() -> ImageRequest {
+        ImageRequest {
+            prompt: "a red fox".to_string(),
+            negative_prompt: None,
+            width: None,
+            height: None,
+            guidance_scale: None,
+            num_inference_steps: None,
+            seed: Some(7),
+            model: None,
+            strength: None,
+        }
+    }
+
+    #[test]
+    fn validate_applies_defaults() {
+        let params = request().validate(None).unwrap();
+        assert_eq!(params.model, IMAGE_MODELS[0].1);
+        assert_eq!((params.width, params.height), (1024, 1024));
+        assert_eq!((params.guidance_scale, params.num_inference_steps, params.seed), (7.5, 30, 7));
+        assert_eq!(params.strength, None);
+
+        // img2img keeps the init image size, fitted to the bounds; a mask picks the inpainting model.
+        let params = request().validate(Some(((2000, 300), true))).unwrap();
+        assert_eq!((params.width, params.height), (MAX_DIMENSION, 296));
+        assert_eq!(params.model, INPAINT_MODEL);
+        assert_eq!(params.strength, Some(0.75));
+    }
+
+    #[test]
+    fn validate_checks_dimensions() {
+        for (value, ok) in [(MIN_DIMENSION - 8, false), (MIN_DIMENSION, true), (MAX_DIMENSION, true), (MAX_DIMENSION + 8, false), (516, false)] {
+            let req = ImageRequest { width: Some(value), ..request() };
+            assert_eq!(req.validate(None).is_ok(), ok, "width {}", value);
+            let req = ImageRequest { height: Some(value), ..request() };
+            assert_eq!(req.validate(None).is_ok(), ok, "height {}", value);
+        }
+    }
+
+    #[test]
+    fn validate_checks_guidance_and_steps() {
+        for (value, ok) in [(-0.1, false), (0.0, true), (20.0, true), (20.1, false), (f32::NAN, false)] {
+            let req = ImageRequest { guidance_scale: Some(value), ..request() };
+            assert_eq!(req.validate(None).is_ok(), ok, "guidance_scale {}", value);
+        }
+        for (value, ok) in [(0, false), (1, true), (100, true), (101, false)] {
+            let req = ImageRequest { num_inference_steps: Some(value), ..request() };
+            assert_eq!(req.validate(None).is_ok(), ok, "num_inference_steps {}", value);
+        }
+    }
+
+    #[test]
+    fn validate_checks_strength() {
+        let req = ImageRequest { strength: Some(0.5), ..request() };
+        assert!(req.validate(None).unwrap_err().contains("init image"));
+        for (value, ok) in [(-0.01, false), (0.0, true), (1.0, true), (1.01, false)] {
+            let req = ImageRequest { strength: Some(value), ..request() };
+            assert_eq!(req.validate(Some(((512, 512), false))).is_ok(), ok, "strength {}", value);
+        }
+    }
+
+    #[test]
+    fn validate_checks_prompts_and_model() {
+        let req = ImageRequest { prompt: "x".repeat(MAX_PROMPT_CHARS), ..request() };
+        assert!(req.validate(None).is_ok());
+        let req = ImageRequest { prompt: "x".repeat(MAX_PROMPT_CHARS + 1), ..request() };
+        assert!(req.validate(None).is_err());
+        let req = ImageRequest { negative_prompt: Some("x".repeat(MAX_PROMPT_CHARS + 1)), ..request() };
+        assert!(req.validate(None).is_err());
+        let req = ImageRequest { negative_prompt: Some("   ".to_string()), ..request() };
+        assert_eq!(req.validate(None).unwrap().negative_prompt, None);
+
+        let req = ImageRequest { model: Some("sd-1.5".to_string()), ..request() };
+        assert_eq!(req.validate(None).unwrap().model, "runwayml/stable-diffusion-v1-5");
+        let req = ImageRequest { model: Some("dall-e".to_string()), ..request() };
+        assert!(req.validate(None).unwrap_err().contains("Unknown model"));
+    }
+
+    #[test]
+    fn negotiates_output_format() {
+        use ImageFormat::*;
+        assert!(negotiate_format(None, None).unwrap().is_none());
+        assert!(negotiate_format(Some("JPG"), Some("image/png")).unwrap() == Some(Jpeg));
+        assert!(negotiate_format(Some("gif"), None).is_err());
+        assert!(negotiate_format(None, Some("image/webp,image/png;q=0.9")).unwrap() == Some(WebP));
+        assert!(negotiate_format(None, Some("image/webp;q=0.5, image/jpeg")).unwrap() == Some(Jpeg));
+        assert!(negotiate_format(None, Some("image/png;q=0, image/webp;q=0.1")).unwrap() == Some(WebP));
+        // Wildcards keep the generated format; unknown types are skipped.
+        assert!(negotiate_format(None, Some("image/avif, */*;q=0.8")).unwrap().is_none());
+        assert!(negotiate_format(None, Some("text/html")).unwrap().is_none());
+    }
+
+    fn sample_png() -> Vec<u8> {
+        mock::mock_image_bytes("a red fox", 7, 64, 48)
+    }
+
+    #[test]
+    fn encode_output_keeps_or_transcodes() {
+        let png = sample_png();
+        let (same, format) = encode_output(png.clone(), None, None).unwrap();
+        assert!(format == ImageFormat::Png);
+        assert_eq!(same, png);
+
+        for target in [ImageFormat::Jpeg, ImageFormat::WebP] {
+            let (bytes, format) = encode_output(png.clone(), Some(target), None).unwrap();
+            assert!(format == target && ImageFormat::sniff(&bytes) == Some(target));
+            let decoded = image::load_from_memory(&bytes).unwrap();
+            assert_eq!((decoded.width(), decoded.height()), (64, 48));
+        }
+
+        // WebP is lossless; JPEG quality changes the encoding.
+        let (webp, _) = encode_output(png.clone(), Some(ImageFormat::WebP), None).unwrap();
+        assert_eq!(image::load_from_memory(&webp).unwrap().to_rgb8().into_raw(), image::load_from_memory(&png).unwrap().to_rgb8().into_raw());
+        let (low, _) = encode_output(png.clone(), Some(ImageFormat::Jpeg), Some(10)).unwrap();
+        let (high, _) = encode_output(png.clone(), Some(ImageFormat::Jpeg), Some(95)).unwrap();
+        assert!(low.len() < high.len());
+        let (requality, _) = encode_output(high.clone(), None, Some(10)).unwrap();
+        assert_ne!(requality, high);
+
+        assert!(encode_output(b"GIF89a".to_vec(), None, None).is_err());
+    }
+
+    /// Posts a JSON generation request and returns the response.
+    async fn generate(state: &AppState, body: serde_json::Value, extra: &[(&str, &str)]) -> Response<Body> {
+        let mut headers = HeaderMap::new();
+        headers.insert(header::CONTENT_TYPE, "application/json".parse().unwrap());
+        for (name, value) in extra {
+            headers.insert(header::HeaderName::from_bytes(name.as_bytes()).unwrap(), value.parse().unwrap());
+        }
+        let mut request = Request::new(Body::from(body.to_string()));
+        *request.headers_mut() = headers.clone();
+        handle_image_generate(
+            State(state.clone()),
+            ConnectInfo(SocketAddr::from(([203, 0, 113, 7], 40000))),
+            Query(ImageOutputQuery { format: None, quality: None }),
+            headers,
+            request,
+        )
+        .await
+        .unwrap()
+    }
+
+    fn header_str<'a>(res: &'a Response<Body>, name: &str) -> &'a str {
+        res.headers().get(name).and_then(|v| v.to_str().ok()).unwrap_or_default()
+    }
+
+    #[tokio::test]
+    async fn generation_is_cached_privately_and_never_304() {
+        let state = AppState::for_tests();
+        let body = serde_json::json!({ "prompt": "a red fox", "width": 256, "height": 256, "seed": 1 });
+        let first = generate(&state, body.clone(), &[]).await;
+        assert_eq!(first.status(), StatusCode::OK);
+        assert_eq!(header_str(&first, "cache-control"), "private, max-age=3600");
+
+        let etag = header_str(&first, "etag").to_string();
+        let again = generate(&state, body, &[("if-none-match", &etag)]).await;
+        assert_eq!(again.status(), StatusCode::OK);
+        assert_eq!(header_str(&again, "etag"), etag);
+    }
+
+    async fn gallery_hashes(state: &AppState, token: &str) -> Vec<String> {
+        let mut headers = HeaderMap::new();
+        headers.insert(GALLERY_TOKEN_HEADER, token.parse().unwrap());
+        let Json(listing) = handle_image_gallery(State(state.clone()), headers).await.unwrap();
+        listing["images"].as_array().unwrap().iter().map(|i| i["hash"].as_str().unwrap().to_string()).collect()
+    }
+
+    #[tokio::test]
+    async fn cache_hits_are_listed_for_every_requester() {
+        let state = AppState::for_tests();
+        let body = serde_json::json!({ "prompt": "a lighthouse", "width": 256, "height": 256, "seed": 3 });
+        let (alice, bob) = ("a".repeat(32), "b".repeat(32));
+
+        let first = generate(&state, body.clone(), &[(GALLERY_TOKEN_HEADER, &alice)]).await;
+        assert_eq!(header_str(&first, "x-image-cache"), "miss");
+        let second = generate(&state, body, &[(GALLERY_TOKEN_HEADER, &bob)]).await;
+        assert_eq!(header_str(&second, "x-image-cache"), "hit");
+
+        let hash = header_str(&first, "x-image-hash").to_string();
+        assert_eq!(header_str(&second, "x-image-hash"), hash);
+        assert_eq!(gallery_hashes(&state, &alice).await, vec![hash.clone()]);
+        assert_eq!(gallery_hashes(&state, &bob).await, vec![hash]);
+        assert!(gallery_hashes(&state, &"c".repeat(32)).await.is_empty());
+    }
+}
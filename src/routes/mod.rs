@@ -12,14 +12,15 @@ pub mod resume;
 pub mod bot;
 
 use axum::{
-    extract::Request,
+    extract::{DefaultBodyLimit, Request},
     http::HeaderValue,
     middleware::{self, Next},
     response::Response,
-    routing::{get, post},
+    routing::{get, post, put},
     Router,
 };
 use crate::state::AppState;
+use tower_http::limit::RequestBodyLimitLayer;
 
 /// Middleware: Platformun otonom durumunu header seviyesinde fısıldar.
 async fn demo_header_middleware(state: axum::extract::State<AppState>, req: Request, next: Next) -> Response {
@@ -62,19 +63,51 @@ pub fn create_router(state: AppState) -> Router {
         .route("/content/api/generate", post(content::handle_content_generate))
         .route("/code/api/generate", post(code::handle_code_generate))
         .route("/email/api/generate", post(email::handle_email_generate))
+        .route("/email/api/docx", post(email::handle_email_docx))
+        .route("/email/api/html", post(email::handle_email_html))
         .route("/video/api/generate", post(video::handle_video_generate))
         .route("/seo/api/analyze", post(seo::handle_seo_analyze))
-        .route("/image/api/generate", post(image::handle_image_generate))
+        .route("/seo/api/crawl", post(seo::handle_seo_crawl))
+        .route("/image/api/gallery", get(image::handle_image_gallery))
+        .route("/image/{hash}", get(image::handle_image_get))
         .route("/voice/api/synthesize", post(voice::handle_voice_synthesize))
         .route("/voice/api/voices", get(voice::handle_voice_list))
         .route("/resume/api/generate", post(resume::handle_resume_generate))
+        .route("/resume/api/pdf", post(resume::handle_resume_pdf))
+        .route("/resume/api/docx", post(resume::handle_resume_docx))
+        .route("/resume/api/html", post(resume::handle_resume_html))
+        .route("/resume/api/json-resume", post(resume::handle_resume_json))
         .route("/bot/api/create", post(bot::handle_bot_create))
-        .route("/bot/api/chat/{bot_id}", post(bot::handle_bot_chat));
+        .route("/bot/api/import", post(bot::handle_bot_import))
+        .route("/bot/api/chat/{bot_id}", post(bot::handle_bot_chat))
+        .route("/bot/api/{bot_id}/export", get(bot::handle_bot_export))
+        .route("/bot/api/{bot_id}/actions", put(bot::handle_bot_actions_update))
+        .route("/bot/api/{bot_id}/stats", get(bot::handle_bot_stats))
+        .route("/bot/api/{bot_id}/transcripts", get(bot::handle_bot_transcripts));
+
+    // img2img uploads are capped by the tower-http limit instead of axum's 2 MB default.
+    let upload_routes = Router::new()
+        .route("/image/api/generate", post(image::handle_image_generate))
+        .layer(DefaultBodyLimit::disable())
+        .layer(RequestBodyLimitLayer::new(image::MAX_UPLOAD_BYTES));
+    // Speech-to-text uploads follow the provider's 25 MB audio limit.
+    let transcribe_routes = Router::new()
+        .route("/voice/api/transcribe", post(voice::handle_voice_transcribe))
+        .layer(DefaultBodyLimit::disable())
+        .layer(RequestBodyLimitLayer::new(voice::MAX_AUDIO_UPLOAD_BYTES));
+    // Resume documents (PDF / DOCX) are small; 10 MB leaves room for embedded images.
+    let resume_upload_routes = Router::new()
+        .route("/resume/api/import", post(resume::handle_resume_import))
+        .layer(DefaultBodyLimit::disable())
+        .layer(RequestBodyLimitLayer::new(resume::MAX_RESUME_UPLOAD_BYTES));
 
     // 3. Construct Final Router
     Router::new()
         .merge(landing_routes)
         .merge(api_routes)
+        .merge(upload_routes)
+        .merge(transcribe_routes)
+        .merge(resume_upload_routes)
         // State-aware middleware kullanarak dinamik header ekliyoruz.
         .layer(middleware::from_fn_with_state(state.clone(), demo_header_middleware))
         .with_state(state)
@@ -1,21 +1,76 @@
 use axum::{
-    extract::{ConnectInfo, State},
+    body::Body,
+    extract::{
+        multipart::{MultipartError, MultipartRejection},
+        ConnectInfo, Multipart, State,
+    },
+    http::{Response, StatusCode},
     response::sse::{Event, KeepAlive, Sse},
     Json,
 };
-use futures::Stream;
+use futures::{Stream, StreamExt};
 use serde::Deserialize;
+use serde_json::json;
 use std::{convert::Infallible, net::SocketAddr};
 
+use crate::diff;
 use crate::error::AppError;
+use crate::json_resume::{self, JsonResume};
+use crate::keywords;
+use crate::markdown::{self, Block};
 use crate::mock;
+use crate::export::{self, ExportOptions, Format};
+use crate::pdf::Template;
+use crate::resume_import;
 use crate::state::AppState;
+use crate::tailor;
+use crate::upstream::{send_with_retry, RetryPolicy};
 
+/// Upper bound on Markdown accepted for typesetting.
+const MAX_MARKDOWN_CHARS: usize = 50_000;
+
+/// Upper bound on job descriptions accepted for tailoring.
+const MAX_JOB_DESCRIPTION_CHARS: usize = 20_000;
+
+/// Upper bound on uploaded resume documents.
+pub const MAX_RESUME_UPLOAD_BYTES: usize = 10 * 1024 * 1024;
+
+/// Free-text `name` / `experience` / `skills`, or a structured `resume` in the
+/// JSON Resume schema, which takes precedence when present.
 #[derive(Deserialize)]
 pub struct ResumeRequest {
+    #[serde(default)]
     pub name: String,
+    #[serde(default)]
     pub experience: String,
+    #[serde(default)]
     pub skills: String,
+    pub resume: Option<JsonResume>,
+    /// Tailoring mode: bullets are rewritten for this job description and
+    /// the stream ends with a `tailoring` event (keyword coverage and diff).
+    pub job_description: Option<String>,
+}
+
+/// What a resume is generated from.
+enum ResumeSource<'a> {
+    Fields { name: &'a str, experience: &'a str, skills: &'a str },
+    Structured(&'a JsonResume),
+}
+
+impl ResumeSource<'_> {
+    fn name(&self) -> &str {
+        match self {
+            Self::Fields { name, .. } => name.trim(),
+            Self::Structured(resume) => resume.name(),
+        }
+    }
+}
+
+/// Validates a JSON Resume, reporting every problem in one message.
+fn validate_resume(resume: &JsonResume) -> Result<(), String> {
+    resume
+        .validate()
+        .map_err(|errors| format!("Invalid JSON Resume: {}", errors.join("; ")))
 }
 
 pub async fn handle_resume_generate(
@@ -30,18 +85,525 @@ pub async fn handle_resume_generate(
     state.rate_limiter.check_global_limit(&ip).map_err(AppError::RateLimited)?;
     state.rate_limiter.check_tool_limits(&ip, tool).map_err(AppError::RateLimited)?;
 
-    if req.name.trim().is_empty() {
+    let validated = match &req.resume {
+        Some(resume) => validate_resume(resume),
+        None if req.name.trim().is_empty() => Err("Name is mandatory".to_string()),
+        None => Ok(()),
+    }
+    .and_then(|_| match req.job_description.as_deref().map(str::trim) {
+        Some("") => Err("job_description cannot be empty".to_string()),
+        Some(jd) if jd.chars().count() > MAX_JOB_DESCRIPTION_CHARS => Err(format!(
+            "job_description must be at most {} characters",
+            MAX_JOB_DESCRIPTION_CHARS
+        )),
+        Some(jd) if keywords::extract(jd, 1).is_empty() => {
+            Err("job_description has no recognisable keywords".to_string())
+        }
+        _ => Ok(()),
+    });
+    if let Err(e) = validated {
         state.rate_limiter.record_error(&ip, tool);
-        return Err(AppError::InvalidInput("Name is mandatory".to_string()));
+        return Err(AppError::InvalidInput(e));
     }
 
     state.rate_limiter.increment_counters(&ip, tool);
 
     // 2. Stream Generation
-    // Demo-only stream for stable deterministic behavior.
-    let stream = mock::mock_resume_stream(&req.name, &req.experience, &req.skills);
+    let source = match &req.resume {
+        Some(resume) => ResumeSource::Structured(resume),
+        None => ResumeSource::Fields { name: &req.name, experience: &req.experience, skills: &req.skills },
+    };
+    let stream = match (req.job_description.as_deref(), &source) {
+        // Tailoring rewrites the finished draft, so nothing is streamed until
+        // generation and tailoring are both done; the text then replays
+        // word by word before the `tailoring` report.
+        (Some(job_description), _) => {
+            let markdown = generate_resume_markdown(&state, &source).await?;
+            let (tailored, report) = tailor_resume(&state, &markdown, job_description.trim()).await?;
+            let report = Event::default().event("tailoring").data(report.to_string());
+            mock::stream_from_text(tailored)
+                .chain(futures::stream::once(async move { Ok(report) }))
+                .boxed()
+        }
+        (None, ResumeSource::Structured(_)) => {
+            mock::stream_from_text(generate_resume_markdown(&state, &source).await?)
+        }
+        // Demo-only stream for stable deterministic behavior.
+        (None, ResumeSource::Fields { .. }) => mock::mock_resume_stream(&req.name, &req.experience, &req.skills),
+    };
 
     // 3. Optimized SSE Response
     Ok(Sse::new(stream)
         .keep_alive(KeepAlive::default()))
 }
+
+/// Rewrites the experience and project bullets of `markdown` for a job
+/// description. Returns the tailored Markdown and a report with weighted
+/// keyword coverage before and after, per-bullet changes and a unified diff.
+async fn tailor_resume(
+    state: &AppState,
+    markdown: &str,
+    job_description: &str,
+) -> Result<(String, serde_json::Value), AppError> {
+    let keywords = keywords::extract(job_description, tailor::MAX_KEYWORDS);
+    let bullets = tailor::bullets(markdown);
+
+    let (rewritten, reasons): (Vec<String>, Vec<Vec<String>>) = if state.is_demo() || bullets.is_empty() {
+        bullets.iter().map(|b| tailor::rewrite_locally(&b.text, &keywords)).unzip()
+    } else {
+        let rewritten = provider_rewrite_bullets(state, job_description, &bullets).await?;
+        let reasons = bullets
+            .iter()
+            .zip(&rewritten)
+            .map(|(b, r)| if &b.text == r { Vec::new() } else { vec!["rewritten for the job description".to_string()] })
+            .collect();
+        (rewritten, reasons)
+    };
+
+    let (tailored, reordered) = tailor::apply(markdown, &bullets, &rewritten, &keywords);
+    let (before, matched_before) = tailor::coverage(&keywords, markdown);
+    let (after, matched_after) = tailor::coverage(&keywords, &tailored);
+
+    let coverage: Vec<tailor::KeywordCoverage> = keywords
+        .iter()
+        .zip(matched_before.iter().zip(&matched_after))
+        .map(|(k, (b, a))| tailor::KeywordCoverage {
+            term: k.term.clone(),
+            weight: k.weight,
+            matched_before: *b,
+            matched_after: *a,
+        })
+        .collect();
+    let missing: Vec<&str> = coverage.iter().filter(|k| !k.matched_after).map(|k| k.term.as_str()).collect();
+    let changes: Vec<tailor::Change> = bullets
+        .iter()
+        .zip(rewritten.iter().zip(reasons))
+        .filter(|(b, (r, _))| &b.text != *r)
+        .map(|(b, (r, reasons))| tailor::Change {
+            section: b.section.clone(),
+            before: b.text.clone(),
+            after: r.clone(),
+            reasons,
+        })
+        .collect();
+
+    let report = json!({
+        "coverage": { "before": before, "after": after },
+        "keywords": coverage,
+        "missing": missing,
+        "changes": changes,
+        "reordered": reordered,
+        "diff": diff::unified(markdown, &tailored, "resume.md", "tailored.md"),
+        "demo": state.is_demo(),
+    });
+    Ok((tailored, report))
+}
+
+/// Real mode: one provider call rewrites every bullet. The reply must keep the
+/// bullet count and order so each rewrite maps back to its line.
+async fn provider_rewrite_bullets(
+    state: &AppState,
+    job_description: &str,
+    bullets: &[tailor::Bullet],
+) -> Result<Vec<String>, AppError> {
+    let numbered: Vec<String> = bullets.iter().enumerate().map(|(i, b)| format!("{}. {}", i + 1, b.text)).collect();
+    let body = json!({
+        "model": "gpt-4o-mini",
+        "messages": [
+            {
+                "role": "system",
+                "content": "You tailor resume bullets to a job description. Rewrite each bullet to lead with \
+                    impact and use the job's terminology where the bullet already supports it. Never invent \
+                    employers, metrics, tools or responsibilities. Return JSON {\"bullets\": [...]} with exactly \
+                    one string per input bullet, in the same order, without list markers."
+            },
+            {
+                "role": "user",
+                "content": format!("Job description:\n{}\n\nBullets:\n{}", job_description, numbered.join("\n")),
+            },
+        ],
+        "max_tokens": 2048,
+        "response_format": { "type": "json_object" },
+    });
+
+    let res = send_with_retry(&state.openai_breaker, &RetryPolicy::OPENAI, || {
+        state
+            .http_client
+            .post("https://api.openai.com/v1/chat/completions")
+            .header(
+                "Authorization",
+                format!("Bearer {}", state.config.openai_api_key),
+            )
+            .json(&body)
+    })
+    .await?;
+
+    if !res.status().is_success() {
+        return Err(AppError::InternalError(format!("OpenAI API returned error: {}", res.status())));
+    }
+
+    let data: serde_json::Value = res
+        .json()
+        .await
+        .map_err(|e| AppError::InternalError(format!("Failed to parse response: {}", e)))?;
+
+    let content = data["choices"][0]["message"]["content"].as_str().unwrap_or("{}");
+    let rewritten: Vec<String> = serde_json::from_str::<serde_json::Value>(content)
+        .ok()
+        .and_then(|v| serde_json::from_value(v["bullets"].clone()).ok())
+        .filter(|b: &Vec<String>| b.len() == bullets.len() && b.iter().all(|t| !t.trim().is_empty()))
+        .ok_or_else(|| AppError::InternalError("Tailoring response did not match the bullet list".to_string()))?;
+    Ok(rewritten
+        .into_iter()
+        .map(|t| t.trim().trim_start_matches(['-', '*', '•']).trim().to_string())
+        .collect())
+}
+
+/// Exports `markdown`, or a resume generated from a JSON Resume `resume` or
+/// from `name`, `experience` and `skills` when no Markdown is given.
+#[derive(Deserialize)]
+pub struct ResumeExportRequest {
+    pub markdown: Option<String>,
+    pub resume: Option<JsonResume>,
+    pub name: Option<String>,
+    pub experience: Option<String>,
+    pub skills: Option<String>,
+    /// classic (default), modern or compact.
+    pub template: Option<String>,
+    /// serif or sans; defaults to the template's font.
+    pub font: Option<String>,
+    /// a4 (default), letter or legal.
+    pub page_size: Option<String>,
+}
+
+/// Non-streaming resume generation for exports.
+async fn generate_resume_markdown(state: &AppState, source: &ResumeSource<'_>) -> Result<String, AppError> {
+    let prompt = match source {
+        // Structured data already holds every fact; demo mode renders it as-is.
+        ResumeSource::Structured(resume) if state.is_demo() => return Ok(resume.to_markdown()),
+        ResumeSource::Fields { name, experience, skills } if state.is_demo() => {
+            return Ok(mock::mock_resume(name, experience, skills));
+        }
+        ResumeSource::Structured(resume) => format!(
+            "Write the resume from this JSON Resume document. Use only the facts it contains.\n\n{}",
+            serde_json::to_string_pretty(resume).unwrap_or_default()
+        ),
+        ResumeSource::Fields { name, experience, skills } => {
+            format!("Name: {}\nExperience: {}\nSkills: {}", name, experience, skills)
+        }
+    };
+
+    let body = json!({
+        "model": "gpt-4o-mini",
+        "messages": [
+            {
+                "role": "system",
+                "content": "You are a professional resume writer. Write a complete resume in Markdown: \
+                    the candidate's name as a level-1 heading, a contact line, then level-2 sections \
+                    (Professional Summary, Core Competencies, Professional Experience, Education) \
+                    with bullet points. Return only the Markdown."
+            },
+            {
+                "role": "user",
+                "content": prompt,
+            },
+        ],
+        "max_tokens": 2048,
+    });
+
+    let res = send_with_retry(&state.openai_breaker, &RetryPolicy::OPENAI, || {
+        state
+            .http_client
+            .post("https://api.openai.com/v1/chat/completions")
+            .header(
+                "Authorization",
+                format!("Bearer {}", state.config.openai_api_key),
+            )
+            .json(&body)
+    })
+    .await?;
+
+    if !res.status().is_success() {
+        return Err(AppError::InternalError(format!("OpenAI API returned error: {}", res.status())));
+    }
+
+    let data: serde_json::Value = res
+        .json()
+        .await
+        .map_err(|e| AppError::InternalError(format!("Failed to parse response: {}", e)))?;
+
+    data["choices"][0]["message"]["content"]
+        .as_str()
+        .map(str::to_string)
+        .ok_or_else(|| AppError::InternalError("No resume generated".to_string()))
+}
+
+pub async fn handle_resume_pdf(
+    State(state): State<AppState>,
+    ConnectInfo(addr): ConnectInfo<SocketAddr>,
+    Json(req): Json<ResumeExportRequest>,
+) -> Result<Response<Body>, AppError> {
+    export_resume(state, addr, req, Format::Pdf).await
+}
+
+pub async fn handle_resume_docx(
+    State(state): State<AppState>,
+    ConnectInfo(addr): ConnectInfo<SocketAddr>,
+    Json(req): Json<ResumeExportRequest>,
+) -> Result<Response<Body>, AppError> {
+    export_resume(state, addr, req, Format::Docx).await
+}
+
+pub async fn handle_resume_html(
+    State(state): State<AppState>,
+    ConnectInfo(addr): ConnectInfo<SocketAddr>,
+    Json(req): Json<ResumeExportRequest>,
+) -> Result<Response<Body>, AppError> {
+    export_resume(state, addr, req, Format::Html).await
+}
+
+async fn export_resume(
+    state: AppState,
+    addr: SocketAddr,
+    req: ResumeExportRequest,
+    format: Format,
+) -> Result<Response<Body>, AppError> {
+    let ip = addr.ip().to_string();
+    let tool = "resume";
+
+    state.rate_limiter.check_global_limit(&ip).map_err(AppError::RateLimited)?;
+    state.rate_limiter.check_tool_limits(&ip, tool).map_err(AppError::RateLimited)?;
+
+    let markdown = req.markdown.as_deref().map(str::trim).filter(|m| !m.is_empty());
+    let source = match &req.resume {
+        Some(resume) => ResumeSource::Structured(resume),
+        None => ResumeSource::Fields {
+            name: req.name.as_deref().unwrap_or_default(),
+            experience: req.experience.as_deref().unwrap_or_default(),
+            skills: req.skills.as_deref().unwrap_or_default(),
+        },
+    };
+    let layout = export::layout(
+        req.template.as_deref(),
+        req.font.as_deref(),
+        req.page_size.as_deref(),
+        Template::Classic,
+    );
+    let validated = layout.and_then(|options| match (markdown, &source) {
+        (Some(markdown), _) if markdown.chars().count() > MAX_MARKDOWN_CHARS => Err(format!(
+            "Markdown must be at most {} characters",
+            MAX_MARKDOWN_CHARS
+        )),
+        (Some(_), _) => Ok(options),
+        (None, ResumeSource::Structured(resume)) => validate_resume(resume).map(|_| options),
+        (None, _) if source.name().is_empty() => {
+            Err("Provide markdown, a resume or a name".to_string())
+        }
+        _ => Ok(options),
+    });
+    let (template, font, page_size) = match validated {
+        Ok(options) => options,
+        Err(e) => {
+            state.rate_limiter.record_error(&ip, tool);
+            return Err(AppError::InvalidInput(e));
+        }
+    };
+
+    state.rate_limiter.increment_counters(&ip, tool);
+
+    let markdown = match markdown {
+        Some(markdown) => markdown.to_string(),
+        None => generate_resume_markdown(&state, &source).await?,
+    };
+
+    // The document title is the resume's top-level heading.
+    let title = markdown::parse(&markdown)
+        .into_iter()
+        .find_map(|block| match block {
+            Block::Heading { level: 1, spans } => Some(markdown::plain_text(&spans)),
+            _ => None,
+        })
+        .unwrap_or_else(|| source.name().to_string());
+    let filename = export::filename(&title, "resume", format);
+
+    let options = ExportOptions {
+        template,
+        font,
+        page_size,
+        author: title.clone(),
+        title: format!("{} - Resume", title),
+        line_breaks: false,
+    };
+    let bytes = export::render(markdown, format, options).await.map_err(AppError::InternalError)?;
+
+    Ok(export::attachment(format, &filename, bytes))
+}
+
+#[derive(Deserialize)]
+pub struct JsonResumeRequest {
+    pub markdown: String,
+}
+
+/// Converts resume Markdown (such as `/resume/api/generate` output) back into
+/// JSON Resume, along with the sections that were recognised and any fields
+/// the result is still missing.
+pub async fn handle_resume_json(
+    State(state): State<AppState>,
+    ConnectInfo(addr): ConnectInfo<SocketAddr>,
+    Json(req): Json<JsonResumeRequest>,
+) -> Result<Json<serde_json::Value>, AppError> {
+    let ip = addr.ip().to_string();
+    let tool = "resume";
+
+    state.rate_limiter.check_global_limit(&ip).map_err(AppError::RateLimited)?;
+    state.rate_limiter.check_tool_limits(&ip, tool).map_err(AppError::RateLimited)?;
+
+    let markdown = req.markdown.trim();
+    let invalid = if markdown.is_empty() {
+        Some("Markdown is mandatory".to_string())
+    } else if markdown.chars().count() > MAX_MARKDOWN_CHARS {
+        Some(format!("Markdown must be at most {} characters", MAX_MARKDOWN_CHARS))
+    } else {
+        None
+    };
+    if let Some(e) = invalid {
+        state.rate_limiter.record_error(&ip, tool);
+        return Err(AppError::InvalidInput(e));
+    }
+
+    state.rate_limiter.increment_counters(&ip, tool);
+
+    Ok(Json(json_resume_body(&state, markdown)))
+}
+
+/// JSON Resume response shared by the Markdown and document importers.
+fn json_resume_body(state: &AppState, markdown: &str) -> serde_json::Value {
+    let (resume, sections) = json_resume::from_markdown(markdown);
+    let errors = resume.validate().err().unwrap_or_default();
+
+    json!({
+        "resume": resume,
+        "sections": sections.iter().map(|s| s.name()).collect::<Vec<_>>(),
+        "valid": errors.is_empty(),
+        "errors": errors,
+        "demo": state.is_demo(),
+    })
+}
+
+fn upload_error(e: MultipartError) -> AppError {
+    if e.status() == StatusCode::PAYLOAD_TOO_LARGE {
+        AppError::PayloadTooLarge(format!(
+            "Upload exceeds {} MB",
+            MAX_RESUME_UPLOAD_BYTES / (1024 * 1024)
+        ))
+    } else {
+        AppError::InvalidInput(e.body_text())
+    }
+}
+
+/// Reads the `file` (or `resume`) part of the upload.
+async fn read_resume_upload(mut multipart: Multipart) -> Result<Vec<u8>, AppError> {
+    let mut document = None;
+    while let Some(field) = multipart.next_field().await.map_err(upload_error)? {
+        if matches!(field.name(), Some("file" | "resume")) {
+            document = Some(field.bytes().await.map_err(upload_error)?.to_vec());
+        }
+    }
+    document
+        .filter(|bytes| !bytes.is_empty())
+        .ok_or_else(|| AppError::InvalidInput("Missing resume file (form field 'file')".to_string()))
+}
+
+/// Parses an uploaded PDF, DOCX or plain-text resume into JSON Resume. The
+/// normalised Markdown is returned as well, so either can be fed back into
+/// the generator or the PDF export.
+pub async fn handle_resume_import(
+    State(state): State<AppState>,
+    ConnectInfo(addr): ConnectInfo<SocketAddr>,
+    multipart: Result<Multipart, MultipartRejection>,
+) -> Result<Json<serde_json::Value>, AppError> {
+    let ip = addr.ip().to_string();
+    let tool = "resume";
+
+    state.rate_limiter.check_global_limit(&ip).map_err(AppError::RateLimited)?;
+    state.rate_limiter.check_tool_limits(&ip, tool).map_err(AppError::RateLimited)?;
+
+    let upload = match multipart {
+        Ok(multipart) => read_resume_upload(multipart).await,
+        Err(e) => Err(AppError::InvalidInput(e.body_text())),
+    };
+    let validated = upload.and_then(|document| {
+        let kind = resume_import::sniff(&document).ok_or_else(|| {
+            AppError::InvalidInput("Unsupported document (supported: pdf, docx, txt)".to_string())
+        })?;
+        Ok((document, kind))
+    });
+    let (document, kind) = match validated {
+        Ok(upload) => upload,
+        Err(e) => {
+            state.rate_limiter.record_error(&ip, tool);
+            return Err(e);
+        }
+    };
+
+    state.rate_limiter.increment_counters(&ip, tool);
+
+    // PDF text extraction is CPU-bound and may panic on malformed files.
+    let markdown = tokio::task::spawn_blocking(move || resume_import::to_markdown(&document, kind))
+        .await
+        .unwrap_or_else(|_| Err("Could not read the document".to_string()));
+    let markdown = match markdown {
+        Ok(markdown) => markdown,
+        Err(e) => {
+            state.rate_limiter.record_error(&ip, tool);
+            return Err(AppError::InvalidInput(e));
+        }
+    };
+
+    let mut body = json_resume_body(&state, &markdown);
+    body["format"] = json!(kind.name());
+    body["markdown"] = json!(markdown);
+    Ok(Json(body))
+}
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+    use crate::pdf::{self, FontFamily, PageSize};
+    use axum::{extract::FromRequest, http::Request, response::IntoResponse};
+
+    async fn import(state: &AppState, document: &[u8]) -> Result<Json<serde_json::Value>, AppError> {
+        let mut body = b"--X\r\nContent-Disposition: form-data; name=\"file\"; filename=\"resume.pdf\"\r\n\r\n".to_vec();
+        body.extend_from_slice(document);
+        body.extend_from_slice(b"\r\n--X--\r\n");
+        let request = Request::builder()
+            .header("content-type", "multipart/form-data; boundary=X")
+            .body(Body::from(body))
+            .unwrap();
+        let multipart = Multipart::from_request(request, &()).await;
+        handle_resume_import(State(state.clone()), ConnectInfo(SocketAddr::from(([203, 0, 113, 7], 40000))), multipart).await
+    }
+
+    #[tokio::test]
+    async fn malformed_pdfs_are_rejected_not_fatal() {
+        let state = AppState::for_tests();
+        let options = pdf::PdfOptions {
+            template: Template::Classic,
+            font: FontFamily::Sans,
+            page_size: PageSize::A4,
+            title: "Resume".to_string(),
+            author: "Jane Doe".to_string(),
+        };
+        let pdf = pdf::render("# Jane Doe\n\njane@example.com\n\n## Skills\n\n- Rust\n", &options).unwrap();
+        let Json(body) = import(&state, &pdf).await.unwrap();
+        assert_eq!(body["format"], "pdf");
+
+        let truncated = &pdf[..pdf.len() / 2];
+        let mut garbage = b"%PDF-1.7\n1 0 obj\n<< /Type /Catalog /Pages 2 0 R".to_vec();
+        garbage.extend((0..4096u32).map(|i| (i * 31 % 251) as u8));
+        for document in [truncated, &garbage, b"%PDF-"] {
+            let err = import(&state, document).await.unwrap_err();
+            assert!(err.into_response().status().is_client_error());
+        }
+    }
+}
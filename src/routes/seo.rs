@@ -1,20 +1,66 @@
 use axum::{
     extract::{ConnectInfo, State},
+    response::sse::{Event, Sse},
     Json,
 };
-use serde::Deserialize;
+use serde::{de::DeserializeOwned, Deserialize, Serialize};
 use serde_json::json;
+use std::convert::Infallible;
+use std::future::Future;
 use std::net::SocketAddr;
-use tokio::time::sleep;
+use tokio::sync::mpsc;
+use tokio_stream::wrappers::ReceiverStream;
 
+use crate::crawl::{self, CrawlOptions};
 use crate::error::AppError;
-use crate::mock;
+use crate::fetch::{self, Fetched};
+use crate::page::{self, Page};
+use crate::seo::{
+    self, AnalysisType, Comparison, ContentGap, KeywordIdea, KeywordResearch, ReadabilityDetail, Recommendation,
+    Report, SentenceRewrite, SerpPreview, SnippetVariant, TechnicalAnalysis,
+};
 use crate::state::AppState;
+use crate::upstream::{send_with_retry, RetryPolicy};
+
+/// Upper bound on content accepted for analysis.
+const MAX_CONTENT_CHARS: usize = 200_000;
+
+/// Content sent to the provider for suggestions; the metrics cover the rest.
+const MAX_PROMPT_CHARS: usize = 12_000;
 
 #[derive(Deserialize)]
 pub struct SeoRequest {
+    #[serde(default)]
     pub content: String,
+    /// Page to fetch and analyse instead of `content`.
+    pub url: Option<String>,
+    /// full (default), keywords, readability, technical, competitor or serp.
     pub analysis_type: Option<String>,
+    /// The other text for `competitor` analysis.
+    pub competitor_content: Option<String>,
+}
+
+impl SeoRequest {
+    fn analysis_type(&self) -> Result<AnalysisType, String> {
+        match self.analysis_type.as_deref() {
+            Some(name) => AnalysisType::from_name(name).ok_or_else(|| {
+                format!("Unknown analysis_type '{}' (available: {})", name, seo::ANALYSIS_TYPES.join(", "))
+            }),
+            None => Ok(AnalysisType::Full),
+        }
+    }
+}
+
+/// One typed body per analysis type, tagged with `analysis_type`.
+#[derive(Serialize)]
+#[serde(tag = "analysis_type", rename_all = "snake_case")]
+enum SeoResponse {
+    Full(Box<Report>),
+    Keywords(KeywordResearch),
+    Readability(ReadabilityDetail),
+    Technical(Box<TechnicalAnalysis>),
+    Competitor(Box<Comparison>),
+    Serp(SerpPreview),
 }
 
 pub async fn handle_seo_analyze(
@@ -28,73 +74,670 @@ pub async fn handle_seo_analyze(
     state
         .rate_limiter
         .check_global_limit(&ip)
-        .map_err(|e| AppError::RateLimited(e))?;
+        .map_err(AppError::RateLimited)?;
     state
         .rate_limiter
         .check_tool_limits(&ip, tool)
-        .map_err(|e| AppError::RateLimited(e))?;
+        .map_err(AppError::RateLimited)?;
 
-    if req.content.trim().is_empty() {
+    let url = req.url.as_deref().map(str::trim).filter(|u| !u.is_empty());
+    if url.is_some() && !req.content.trim().is_empty() {
+        state.rate_limiter.record_error(&ip, tool);
+        return Err(AppError::InvalidInput(
+            "Provide either content or url, not both".to_string(),
+        ));
+    }
+    if url.is_none() && req.content.trim().is_empty() {
         state.rate_limiter.record_error(&ip, tool);
         return Err(AppError::InvalidInput(
-            "Content cannot be empty".to_string(),
+            "Content or url is required".to_string(),
         ));
     }
+    let competitor = req.competitor_content.as_deref().map(str::trim).filter(|c| !c.is_empty());
+    let checked = req.analysis_type().and_then(|mode| {
+        if req.content.chars().count() > MAX_CONTENT_CHARS || competitor.is_some_and(|c| c.chars().count() > MAX_CONTENT_CHARS) {
+            return Err(format!("Content must be at most {} characters", MAX_CONTENT_CHARS));
+        }
+        match (mode, url, competitor) {
+            (AnalysisType::Technical, None, _) => Err("analysis_type technical needs a url".to_string()),
+            (AnalysisType::Competitor, _, None) => {
+                Err("analysis_type competitor needs competitor_content".to_string())
+            }
+            (AnalysisType::Competitor, ..) | (_, _, None) => Ok((mode, url.map(fetch::parse_url).transpose()?)),
+            _ => Err("competitor_content is only used with analysis_type competitor".to_string()),
+        }
+    });
+    let (mode, url) = match checked {
+        Ok(checked) => checked,
+        Err(e) => {
+            state.rate_limiter.record_error(&ip, tool);
+            return Err(AppError::InvalidInput(e));
+        }
+    };
 
     state.rate_limiter.increment_counters(&ip, tool);
 
-    if state.is_demo() {
-        sleep(mock::random_delay()).await;
-        let mut report = mock::mock_seo_report(&req.content);
-        if let Some(obj) = report.as_object_mut() {
-            obj.insert("demo".to_string(), json!(true));
+    // Pages are fetched in every mode: the technical checks need no provider.
+    let fetched = match url {
+        Some(url) => Some(
+            fetch::fetch(&state.page_client, url, state.config.allow_private_urls)
+                .await
+                .map_err(|e| AppError::InvalidInput(format!("Could not fetch URL: {}", e)))?,
+        ),
+        None => None,
+    };
+    if let Some(fetched) = &fetched {
+        if !fetched.is_html() {
+            return Err(AppError::InvalidInput(format!(
+                "{} is not an HTML page ({})",
+                fetched.url,
+                fetched.content_type.as_deref().unwrap_or("unknown type")
+            )));
         }
-        return Ok(Json(report));
     }
 
-    // Real mode: call OpenAI API for SEO analysis
-    let body = json!({
-        "model": "gpt-4o-mini",
-        "messages": [
-            {
-                "role": "system",
-                "content": "You are an SEO expert. Analyze the provided content and return a JSON \
-                    report with these fields: overall_score (0-100), keyword_density, readability_score, \
-                    meta_suggestions, content_suggestions, technical_issues. Return valid JSON only."
-            },
-            { "role": "user", "content": req.content },
-        ],
-        "max_tokens": 1024,
-        "response_format": { "type": "json_object" },
+    // The numbers are always computed locally; demo mode returns them as-is.
+    let content = fetched.as_ref().map(|f| f.body.clone()).unwrap_or(req.content);
+    let competitor = competitor.map(str::to_string);
+    let mut response = tokio::task::spawn_blocking({
+        let content = content.clone();
+        let competitor = competitor.clone();
+        move || analyze(mode, &content, fetched.as_ref(), competitor.as_deref())
+    })
+    .await
+    .map_err(|e| AppError::InternalError(format!("SEO analysis failed: {}", e)))?
+    .map_err(AppError::InvalidInput)?;
+
+    let fallback = if state.is_demo() {
+        None
+    } else {
+        add_provider_results(&mut response, &content, competitor.as_deref(), |body| openai_reply(&state, body))
+            .await
+            .err()
+    };
+    if let Some(reason) = &fallback {
+        tracing::warn!("SEO provider results missing, returning local results: {}", reason);
+    }
+
+    encode(&response, state.is_demo(), fallback).map(Json)
+}
+
+/// The response body. `provider_fallback` gives the reason when the provider
+/// additions are missing and the local results stand alone.
+fn encode(response: &SeoResponse, demo: bool, fallback: Option<String>) -> Result<serde_json::Value, AppError> {
+    let mut body = serde_json::to_value(response)
+        .map_err(|e| AppError::InternalError(format!("Failed to encode report: {}", e)))?;
+    if let Some(obj) = body.as_object_mut() {
+        obj.insert("demo".to_string(), json!(demo));
+        if let Some(reason) = fallback {
+            obj.insert("provider_fallback".to_string(), json!(reason));
+        }
+    }
+    Ok(body)
+}
+
+fn analyze(
+    mode: AnalysisType,
+    content: &str,
+    fetched: Option<&Fetched>,
+    competitor: Option<&str>,
+) -> Result<SeoResponse, String> {
+    let page = || match fetched {
+        Some(fetched) => Page::parse_html(&fetched.body),
+        None => Page::parse(content),
+    };
+    Ok(match (mode, fetched) {
+        (AnalysisType::Full, Some(fetched)) => SeoResponse::Full(Box::new(seo::analyze_fetched(fetched))),
+        (AnalysisType::Full, None) => SeoResponse::Full(Box::new(seo::analyze(content))),
+        (AnalysisType::Keywords, _) => SeoResponse::Keywords(seo::keyword_research(&page())),
+        (AnalysisType::Readability, _) => SeoResponse::Readability(seo::readability_detail(&page())),
+        (AnalysisType::Technical, Some(fetched)) => SeoResponse::Technical(Box::new(seo::technical_analysis(fetched))),
+        (AnalysisType::Technical, None) => return Err("analysis_type technical needs a url".to_string()),
+        (AnalysisType::Competitor, _) => SeoResponse::Competitor(Box::new(seo::compare(content, competitor.unwrap_or_default()))),
+        (AnalysisType::Serp, _) => SeoResponse::Serp(seo::serp_preview(&page(), fetched.map(|f| &f.url))),
+    })
+}
+
+#[derive(Deserialize)]
+pub struct SeoCrawlRequest {
+    pub url: String,
+    pub max_pages: Option<usize>,
+    pub max_depth: Option<usize>,
+    /// Also visit pages listed in the sitemap (needed to find orphan pages).
+    pub use_sitemap: Option<bool>,
+}
+
+/// Site audit: crawls the site in the background and streams `progress`
+/// events, then a `done` event with the site report (or `error`).
+pub async fn handle_seo_crawl(
+    State(state): State<AppState>,
+    ConnectInfo(addr): ConnectInfo<SocketAddr>,
+    Json(req): Json<SeoCrawlRequest>,
+) -> Result<Sse<ReceiverStream<Result<Event, Infallible>>>, AppError> {
+    let ip = addr.ip().to_string();
+    let tool = "seo";
+
+    state
+        .rate_limiter
+        .check_global_limit(&ip)
+        .map_err(AppError::RateLimited)?;
+    state
+        .rate_limiter
+        .check_tool_limits(&ip, tool)
+        .map_err(AppError::RateLimited)?;
+
+    let max_pages = req.max_pages.unwrap_or(crawl::DEFAULT_MAX_PAGES);
+    let max_depth = req.max_depth.unwrap_or(crawl::DEFAULT_MAX_DEPTH);
+    let start = fetch::parse_url(&req.url).and_then(|url| {
+        if !(1..=crawl::MAX_PAGES).contains(&max_pages) {
+            return Err(format!("max_pages must be between 1 and {}", crawl::MAX_PAGES));
+        }
+        if max_depth > crawl::MAX_DEPTH {
+            return Err(format!("max_depth must be at most {}", crawl::MAX_DEPTH));
+        }
+        Ok(url)
     });
+    let start = match start {
+        Ok(url) => url,
+        Err(e) => {
+            state.rate_limiter.record_error(&ip, tool);
+            return Err(AppError::InvalidInput(e));
+        }
+    };
 
-    let res = state
-        .http_client
-        .post("https://api.openai.com/v1/chat/completions")
-        .header(
-            "Authorization",
-            format!("Bearer {}", state.config.openai_api_key),
-        )
-        .json(&body)
-        .send()
-        .await
-        .map_err(|e| AppError::InternalError(format!("OpenAI request failed: {}", e)))?;
+    state.rate_limiter.increment_counters(&ip, tool);
+
+    let options = CrawlOptions {
+        max_pages,
+        max_depth,
+        use_sitemap: req.use_sitemap.unwrap_or(true),
+        allow_private: state.config.allow_private_urls,
+    };
+    let (tx, rx) = mpsc::channel::<Result<Event, Infallible>>(32);
+    tokio::spawn(async move {
+        // Progress is forwarded separately so the crawl notices when the
+        // client goes away and stops fetching.
+        let (progress_tx, mut progress_rx) = mpsc::channel::<crawl::Progress>(32);
+        let forward = {
+            let tx = tx.clone();
+            tokio::spawn(async move {
+                while let Some(update) = progress_rx.recv().await {
+                    let event = Event::default().event("progress").data(json!(update).to_string());
+                    if tx.send(Ok(event)).await.is_err() {
+                        break;
+                    }
+                }
+            })
+        };
+        let result = crawl::crawl(&state.page_client, start, options, &progress_tx).await;
+        drop(progress_tx);
+        let _ = forward.await;
+
+        let event = match result.and_then(|report| serde_json::to_value(&report).map_err(|e| e.to_string())) {
+            Ok(mut body) => {
+                if let Some(obj) = body.as_object_mut() {
+                    obj.insert("demo".to_string(), json!(state.is_demo()));
+                }
+                Event::default().event("done").data(body.to_string())
+            }
+            Err(e) => Event::default().event("error").data(json!({ "error": e }).to_string()),
+        };
+        let _ = tx.send(Ok(event)).await;
+    });
+
+    Ok(Sse::new(ReceiverStream::new(rx)))
+}
+
+fn excerpt(content: &str, chars: usize) -> String {
+    content.chars().take(chars).collect()
+}
+
+/// Provider additions for each mode, grounded in the local metrics so it
+/// does not re-estimate them. The technical checks have nothing to add.
+/// `complete` sends a chat request and returns the reply text; on failure the
+/// response keeps its local results and the reason is returned.
+async fn add_provider_results<F, Fut>(
+    response: &mut SeoResponse,
+    content: &str,
+    competitor: Option<&str>,
+    complete: F,
+) -> Result<(), String>
+where
+    F: FnMut(serde_json::Value) -> Fut,
+    Fut: Future<Output = Result<String, AppError>>,
+{
+    match response {
+        SeoResponse::Full(report) => {
+            let suggestions = provider_suggestions(content, report, complete).await?;
+            report.recommendations.extend(suggestions);
+        }
+        SeoResponse::Keywords(research) => {
+            let metrics = json!({
+                "focus": research.focus,
+                "keywords": research.keywords.density,
+                "phrases": research.keywords.phrases,
+            });
+            let reply: KeywordIdeas = provider_reply(
+                complete,
+                "You are an SEO keyword researcher. You receive a page and the keywords and phrases already \
+                    measured on it. Suggest 5 to 15 related keywords worth targeting: long-tail variants, \
+                    questions and synonyms that fit the page's topic. Return JSON only: {\"ideas\": \
+                    [{\"keyword\": string, \"intent\": \"informational|navigational|commercial|transactional\", \
+                    \"rationale\": string}]}.",
+                format!("Metrics:\n{}\n\nContent:\n{}", metrics, excerpt(content, MAX_PROMPT_CHARS)),
+                |reply: &mut KeywordIdeas| {
+                    reply.ideas.truncate(MAX_IDEAS);
+                    retain_valid(&mut reply.ideas, "ideas", |idea| {
+                        let words = idea.keyword.split_whitespace().count();
+                        if words == 0 || words > 8 {
+                            Err(format!("keyword '{}' must be 1 to 8 words", idea.keyword))
+                        } else if idea.rationale.trim().is_empty() {
+                            Err(format!("keyword '{}' has no rationale", idea.keyword))
+                        } else {
+                            Ok(())
+                        }
+                    })
+                },
+            )
+            .await?;
+            research.ideas = reply.ideas;
+        }
+        SeoResponse::Readability(detail) if !detail.long_sentences.is_empty() => {
+            let sentences: Vec<String> = detail.long_sentences.iter().map(|s| page::squash(&s.text)).collect();
+            let reply: Rewrites = provider_reply(
+                complete,
+                "You are an editor. Rewrite each sentence you receive so it is plainer and shorter, keeping \
+                    its meaning and every fact; split it if needed. Return JSON only: {\"rewrites\": \
+                    [{\"original\": the sentence exactly as given, \"rewritten\": string}]}.",
+                format!("Sentences:\n{}", json!(sentences)),
+                |reply: &mut Rewrites| {
+                    retain_valid(&mut reply.rewrites, "rewrites", |rewrite| {
+                        let original = page::squash(&rewrite.original);
+                        if !sentences.contains(&original) {
+                            Err(format!("'{}' is not one of the given sentences", rewrite.original))
+                        } else if rewrite.rewritten.split_whitespace().count() >= original.split_whitespace().count() {
+                            Err("a rewrite is not shorter than its original".to_string())
+                        } else {
+                            Ok(())
+                        }
+                    })
+                },
+            )
+            .await?;
+            detail.rewrites = reply.rewrites;
+        }
+        SeoResponse::Competitor(comparison) => {
+            let metrics = json!({
+                "areas": comparison.areas,
+                "yours": comparison.yours,
+                "competitor": comparison.competitor,
+                "missing_keywords": comparison.missing_keywords,
+            });
+            let half = MAX_PROMPT_CHARS / 2;
+            let reply: ContentGaps = provider_reply(
+                complete,
+                "You compare a page with a competitor's page on the same topic. You receive both texts and \
+                    metrics already measured on them; treat the metrics as correct. List up to 8 topics or \
+                    questions the competitor covers that the page misses or covers thinly. Return JSON only: \
+                    {\"gaps\": [{\"topic\": string, \"reason\": string, \"suggestion\": string}]}.",
+                format!(
+                    "Metrics:\n{}\n\nYour page:\n{}\n\nCompetitor page:\n{}",
+                    metrics,
+                    excerpt(content, half),
+                    excerpt(competitor.unwrap_or_default(), half)
+                ),
+                |reply: &mut ContentGaps| {
+                    reply.gaps.truncate(MAX_GAPS);
+                    retain_valid(&mut reply.gaps, "gaps", |gap| {
+                        if [&gap.topic, &gap.reason, &gap.suggestion].iter().any(|f| f.trim().is_empty()) {
+                            Err("a gap is missing its topic, reason or suggestion".to_string())
+                        } else {
+                            Ok(())
+                        }
+                    })
+                },
+            )
+            .await?;
+            comparison.gaps = reply.gaps;
+        }
+        SeoResponse::Serp(preview) => {
+            let current = json!({
+                "title": preview.title.text,
+                "description": preview.description.text,
+                "description_generated": preview.description_generated,
+                "issues": preview.issues,
+            });
+            let reply: SnippetVariants = provider_reply(
+                complete,
+                "You write search result snippets. Give 3 alternative title and meta description pairs for \
+                    the page: titles under 60 characters with the main keyword first, descriptions of 120 to \
+                    155 characters that match the content and invite the click. Return JSON only: \
+                    {\"variants\": [{\"title\": string, \"description\": string}]}.",
+                format!("Current snippet:\n{}\n\nContent:\n{}", current, excerpt(content, MAX_PROMPT_CHARS)),
+                |reply: &mut SnippetVariants| {
+                    reply.variants.truncate(MAX_VARIANTS);
+                    retain_valid(&mut reply.variants, "variants", |variant| {
+                        if !seo::title_fits(&variant.title) {
+                            Err(format!("title '{}' is empty or too wide for a result", variant.title))
+                        } else if !seo::description_fits(&variant.description) {
+                            Err("a description is empty or too wide for a result".to_string())
+                        } else {
+                            Ok(())
+                        }
+                    })
+                },
+            )
+            .await?;
+            preview.variants = reply.variants;
+        }
+        _ => {}
+    }
+    Ok(())
+}
+
+/// Items kept from each kind of provider reply.
+const MAX_SUGGESTIONS: usize = 6;
+const MAX_IDEAS: usize = 15;
+const MAX_GAPS: usize = 8;
+const MAX_VARIANTS: usize = 3;
+
+#[derive(Deserialize)]
+struct ProviderSuggestions {
+    suggestions: Vec<Recommendation>,
+}
+
+#[derive(Deserialize)]
+struct KeywordIdeas {
+    ideas: Vec<KeywordIdea>,
+}
+
+#[derive(Deserialize)]
+struct Rewrites {
+    rewrites: Vec<SentenceRewrite>,
+}
+
+#[derive(Deserialize)]
+struct ContentGaps {
+    gaps: Vec<ContentGap>,
+}
+
+#[derive(Deserialize)]
+struct SnippetVariants {
+    variants: Vec<SnippetVariant>,
+}
+
+/// Keeps the items `check` accepts. Fails when nothing usable is left, with
+/// the first rejection as the reason.
+fn retain_valid<T>(items: &mut Vec<T>, what: &str, check: impl Fn(&T) -> Result<(), String>) -> Result<(), String> {
+    let mut rejected = None;
+    items.retain(|item| match check(item) {
+        Ok(()) => true,
+        Err(e) => {
+            rejected.get_or_insert(e);
+            false
+        }
+    });
+    match rejected {
+        _ if !items.is_empty() => Ok(()),
+        Some(e) => Err(format!("no usable {}: {}", what, e)),
+        None => Err(format!("no {} returned", what)),
+    }
+}
+
+/// Asks the provider for JSON in the shape of `T`, then applies `validate`.
+/// A reply that does not match gets one retry with the reason; if that one
+/// fails too, or the provider cannot be reached, the reason is returned and
+/// the mode's local results stand on their own.
+async fn provider_reply<T, F, Fut>(
+    mut complete: F,
+    system: &str,
+    user: String,
+    validate: impl Fn(&mut T) -> Result<(), String>,
+) -> Result<T, String>
+where
+    T: DeserializeOwned,
+    F: FnMut(serde_json::Value) -> Fut,
+    Fut: Future<Output = Result<String, AppError>>,
+{
+    let mut reason = String::new();
+    let mut messages = vec![
+        json!({ "role": "system", "content": system }),
+        json!({ "role": "user", "content": user }),
+    ];
+    for attempt in 1..=2 {
+        let body = json!({
+            "model": "gpt-4o-mini",
+            "messages": messages,
+            "max_tokens": 1024,
+            "response_format": { "type": "json_object" },
+        });
+        let reply = complete(body).await.map_err(|e| format!("provider unavailable: {}", e.message()))?;
+        reason = match serde_json::from_str::<T>(&reply) {
+            Ok(mut parsed) => match validate(&mut parsed) {
+                Ok(()) => return Ok(parsed),
+                Err(e) => e,
+            },
+            Err(e) => e.to_string(),
+        };
+        tracing::warn!("SEO provider reply rejected (attempt {}): {}", attempt, reason);
+        messages.push(json!({ "role": "assistant", "content": reply }));
+        messages.push(json!({
+            "role": "user",
+            "content": format!("That reply does not match the required JSON schema: {}. Reply again with JSON only.", reason),
+        }));
+    }
+    Err(format!("unusable reply: {}", reason))
+}
+
+/// Sends one chat completion request and returns the reply text.
+async fn openai_reply(state: &AppState, body: serde_json::Value) -> Result<String, AppError> {
+    let res = send_with_retry(&state.openai_breaker, &RetryPolicy::OPENAI, || {
+        state
+            .http_client
+            .post("https://api.openai.com/v1/chat/completions")
+            .header(
+                "Authorization",
+                format!("Bearer {}", state.config.openai_api_key),
+            )
+            .json(&body)
+    })
+    .await?;
+
+    if !res.status().is_success() {
+        return Err(AppError::InternalError(format!("OpenAI API returned error: {}", res.status())));
+    }
 
     let data: serde_json::Value = res
         .json()
         .await
         .map_err(|e| AppError::InternalError(format!("Failed to parse response: {}", e)))?;
+    Ok(data["choices"][0]["message"]["content"].as_str().unwrap_or_default().to_string())
+}
+
+/// Content-specific suggestions the metrics cannot make.
+async fn provider_suggestions<F, Fut>(content: &str, report: &Report, complete: F) -> Result<Vec<Recommendation>, String>
+where
+    F: FnMut(serde_json::Value) -> Fut,
+    Fut: Future<Output = Result<String, AppError>>,
+{
+    let metrics = json!({
+        "overall_score": report.overall_score,
+        "scores": report.scores,
+        "readability": report.analysis.readability,
+        "keywords": report.analysis.keywords,
+        "headings": report.analysis.headings,
+        "meta": report.analysis.meta,
+        "already_recommended": report.recommendations.iter().map(|r| &r.title).collect::<Vec<_>>(),
+    });
+    let reply: ProviderSuggestions = provider_reply(
+        complete,
+        "You are an SEO expert. You receive page content and metrics that were already \
+            measured (readability, keyword density, headings, meta lengths); treat them as correct. \
+            Give up to 6 specific suggestions the metrics cannot: title and description rewrites, \
+            missing subtopics, search intent, internal linking ideas. Do not repeat the already \
+            recommended items. Return JSON only: {\"suggestions\": [{\"priority\": \"high|medium|low\", \
+            \"category\": string, \"title\": string, \"description\": string}]}.",
+        format!("Metrics:\n{}\n\nContent:\n{}", metrics, excerpt(content, MAX_PROMPT_CHARS)),
+        |reply: &mut ProviderSuggestions| {
+            reply.suggestions.truncate(MAX_SUGGESTIONS);
+            retain_valid(&mut reply.suggestions, "suggestions", |s| {
+                if !matches!(s.priority.as_str(), "high" | "medium" | "low") {
+                    Err(format!("priority '{}' is not high, medium or low", s.priority))
+                } else if s.title.trim().is_empty() || s.description.trim().is_empty() {
+                    Err("a suggestion has no title or description".to_string())
+                } else {
+                    Ok(())
+                }
+            })
+        },
+    )
+    .await?;
+    Ok(reply
+        .suggestions
+        .into_iter()
+        .map(|mut s| {
+            s.source = "llm";
+            s
+        })
+        .collect())
+}
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+
+    const ARTICLE: &str = "# Baking sourdough bread\n\n\
+        Sourdough bread needs a lively starter, patient proofing and a hot oven. \
+        A good starter doubles within six hours of feeding and smells pleasantly sour. \
+        When the dough has risen by half and feels airy, shape it gently, let it rest overnight in the \
+        refrigerator so the flavour develops, and bake it the next morning in a preheated covered pot \
+        until the crust turns a deep golden brown colour.\n";
+
+    fn local(mode: AnalysisType) -> SeoResponse {
+        analyze(mode, ARTICLE, None, None).unwrap()
+    }
+
+    /// Runs the provider step with canned replies, returning the outcome and
+    /// the requests it sent.
+    async fn with_replies(
+        response: &mut SeoResponse,
+        replies: Vec<Result<String, AppError>>,
+    ) -> (Result<(), String>, Vec<serde_json::Value>) {
+        let mut replies = replies.into_iter();
+        let mut requests = Vec::new();
+        let outcome = add_provider_results(response, ARTICLE, None, |body| {
+            requests.push(body);
+            std::future::ready(replies.next().expect("no reply left for the request"))
+        })
+        .await;
+        (outcome, requests)
+    }
+
+    #[tokio::test]
+    async fn malformed_replies_are_retried_once_then_fall_back() {
+        let mut response = local(AnalysisType::Keywords);
+        let replies = vec![Ok("Here are some ideas: sourdough starter".to_string()), Ok(r#"{"ideas": "none"}"#.to_string())];
+        let (outcome, requests) = with_replies(&mut response, replies).await;
+
+        assert_eq!(requests.len(), 2);
+        let retry = requests[1]["messages"].as_array().unwrap();
+        assert_eq!(retry.len(), 4);
+        assert_eq!(retry[2]["content"], "Here are some ideas: sourdough starter");
+        assert!(retry[3]["content"].as_str().unwrap().starts_with("That reply does not match the required JSON schema"));
+
+        let reason = outcome.unwrap_err();
+        assert!(reason.starts_with("unusable reply: "), "{}", reason);
+        assert!(matches!(response, SeoResponse::Keywords(ref r) if r.ideas.is_empty() && !r.keywords.density.is_empty()));
+
+        let body = encode(&response, false, Some(reason.clone())).unwrap();
+        assert_eq!(body["analysis_type"], "keywords");
+        assert_eq!(body["provider_fallback"], reason.as_str());
+        assert!(encode(&response, false, None).unwrap().get("provider_fallback").is_none());
+    }
+
+    #[tokio::test]
+    async fn a_valid_retry_is_used() {
+        let mut response = local(AnalysisType::Keywords);
+        let replies = vec![
+            Ok(r#"{"ideas": [{"keyword": "", "intent": "informational", "rationale": "empty"}]}"#.to_string()),
+            Ok(r#"{"ideas": [{"keyword": "sourdough starter", "intent": "informational", "rationale": "Covered in depth."}]}"#
+                .to_string()),
+        ];
+        let (outcome, requests) = with_replies(&mut response, replies).await;
+
+        assert_eq!(requests.len(), 2);
+        let retry = requests[1]["messages"].as_array().unwrap();
+        assert!(retry[3]["content"].as_str().unwrap().contains("no usable ideas: keyword '' must be 1 to 8 words"));
+        assert!(outcome.is_ok());
+        assert!(matches!(response, SeoResponse::Keywords(ref r) if r.ideas.len() == 1 && r.ideas[0].keyword == "sourdough starter"));
+    }
 
-    let content_str = data["choices"][0]["message"]["content"]
-        .as_str()
-        .unwrap_or("{}");
+    #[tokio::test]
+    async fn partly_valid_replies_keep_the_valid_items() {
+        let mut response = local(AnalysisType::Keywords);
+        let reply = json!({ "ideas": [
+            { "keyword": "sourdough starter feeding", "intent": "informational", "rationale": "The page explains it." },
+            { "keyword": "how to bake the best sourdough bread at home quickly", "intent": "informational", "rationale": "Too long." },
+            { "keyword": "dutch oven", "intent": "commercial", "rationale": " " },
+        ]});
+        let (outcome, requests) = with_replies(&mut response, vec![Ok(reply.to_string())]).await;
 
-    let mut report: serde_json::Value =
-        serde_json::from_str(content_str).unwrap_or_else(|_| json!({ "error": "Failed to parse SEO report" }));
+        assert_eq!(requests.len(), 1);
+        assert!(outcome.is_ok());
+        match response {
+            SeoResponse::Keywords(research) => {
+                let kept: Vec<&str> = research.ideas.iter().map(|i| i.keyword.as_str()).collect();
+                assert_eq!(kept, ["sourdough starter feeding"]);
+            }
+            _ => panic!("expected keyword research"),
+        }
+
+        // Rewrites must match a given sentence and be shorter than it.
+        let mut response = local(AnalysisType::Readability);
+        let SeoResponse::Readability(detail) = &response else { panic!("expected readability") };
+        assert_eq!(detail.long_sentences.len(), 1);
+        let original = detail.long_sentences[0].text.clone();
+        let reply = json!({ "rewrites": [
+            { "original": original, "rewritten": "Shape the risen dough, chill it overnight and bake it in a covered pot." },
+            { "original": original, "rewritten": format!("{} Really.", original) },
+            { "original": "A sentence that is not on the page.", "rewritten": "Not here." },
+        ]});
+        let (outcome, _) = with_replies(&mut response, vec![Ok(reply.to_string())]).await;
 
-    if let Some(obj) = report.as_object_mut() {
-        obj.insert("demo".to_string(), json!(false));
+        assert!(outcome.is_ok());
+        assert!(matches!(response, SeoResponse::Readability(ref d) if d.rewrites.len() == 1 && d.rewrites[0].rewritten.starts_with("Shape")));
     }
 
-    Ok(Json(report))
+    #[tokio::test]
+    async fn upstream_errors_fall_back_without_retrying() {
+        let mut response = local(AnalysisType::Full);
+        let SeoResponse::Full(report) = &response else { panic!("expected a full report") };
+        let recommendations = report.recommendations.len();
+
+        let replies = vec![Err(AppError::InternalError("OpenAI API returned error: 502 Bad Gateway".to_string()))];
+        let (outcome, requests) = with_replies(&mut response, replies).await;
+
+        assert_eq!(requests.len(), 1);
+        assert_eq!(outcome.unwrap_err(), "provider unavailable: OpenAI API returned error: 502 Bad Gateway");
+        assert!(matches!(
+            response,
+            SeoResponse::Full(ref r) if r.recommendations.len() == recommendations
+                && r.recommendations.iter().all(|s| s.source != "llm")
+        ));
+    }
+
+    #[test]
+    fn retain_valid_reports_the_first_rejection() {
+        let positive = |n: &i32| if *n > 0 { Ok(()) } else { Err(format!("{} is not positive", n)) };
+
+        let mut items = vec![3, -1, 2, -4];
+        assert!(retain_valid(&mut items, "numbers", positive).is_ok());
+        assert_eq!(items, [3, 2]);
+
+        let mut items = vec![-1, -4];
+        assert_eq!(retain_valid(&mut items, "numbers", positive).unwrap_err(), "no usable numbers: -1 is not positive");
+        assert!(items.is_empty());
+
+        assert_eq!(retain_valid(&mut Vec::new(), "numbers", positive).unwrap_err(), "no numbers returned");
+    }
 }
@@ -13,6 +13,7 @@ use tokio_stream::wrappers::ReceiverStream;
 use crate::error::AppError;
 use crate::mock;
 use crate::state::AppState;
+use crate::upstream::{send_with_retry, RetryPolicy};
 
 #[derive(Deserialize)]
 pub struct VideoRequest {
@@ -32,11 +33,11 @@ pub async fn handle_video_generate(
     state
         .rate_limiter
         .check_global_limit(&ip)
-        .map_err(|e| AppError::RateLimited(e))?;
+        .map_err(AppError::RateLimited)?;
     state
         .rate_limiter
         .check_tool_limits(&ip, tool)
-        .map_err(|e| AppError::RateLimited(e))?;
+        .map_err(AppError::RateLimited)?;
 
     if req.topic.trim().is_empty() {
         state.rate_limiter.record_error(&ip, tool);
@@ -88,17 +89,22 @@ pub async fn handle_video_generate(
         "max_tokens": 2048,
     });
 
-    let res = state
-        .http_client
-        .post("https://api.openai.com/v1/chat/completions")
-        .header(
-            "Authorization",
-            format!("Bearer {}", state.config.openai_api_key),
-        )
-        .json(&body)
-        .send()
-        .await
-        .map_err(|e| AppError::InternalError(format!("OpenAI request failed: {}", e)))?;
+    let res = send_with_retry(&state.openai_breaker, &RetryPolicy::OPENAI, || {
+        state
+            .http_client
+            .post("https://api.openai.com/v1/chat/completions")
+            .header(
+                "Authorization",
+                format!("Bearer {}", state.config.openai_api_key),
+            )
+            .json(&body)
+    })
+    .await?;
+
+    // `send_with_retry` hands back client errors (auth, quota) untouched.
+    if !res.status().is_success() {
+        return Err(AppError::InternalError(format!("OpenAI API returned error: {}", res.status())));
+    }
 
     let data: serde_json::Value = res
         .json()
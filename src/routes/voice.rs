@@ -1,40 +1,172 @@
 use axum::{
-    extract::{ConnectInfo, State},
+    body::{Body, Bytes},
+    extract::{
+        multipart::{Multipart, MultipartError, MultipartRejection},
+        ConnectInfo, Query, State,
+    },
+    http::{header, HeaderMap, Response, StatusCode},
+    response::{sse::Event, IntoResponse, Sse},
     Json,
 };
 use base64::Engine;
+use futures::StreamExt;
 use serde::Deserialize;
 use serde_json::json;
+use std::convert::Infallible;
 use std::net::SocketAddr;
-use tokio::time::sleep;
+use tokio::sync::mpsc;
+use tokio_stream::wrappers::ReceiverStream;
 
+use crate::audio::{self, AudioFormat, Encoding};
+use crate::captions::{self, Cue};
 use crate::error::AppError;
 use crate::mock;
+use crate::ssml;
 use crate::state::AppState;
+use crate::upstream::{send_with_retry, RetryPolicy};
+
+/// OpenAI TTS accepts speeds in this range.
+const MIN_RATE: f32 = 0.25;
+const MAX_RATE: f32 = 4.0;
+/// OpenAI's `pcm` response format: 24 kHz, 16-bit little-endian mono.
+const PROVIDER_PCM_RATE: u32 = 24_000;
+/// OpenAI TTS caps `input` at 4096 characters; chunks stay below that.
+pub const MAX_CHUNK_CHARS: usize = 4000;
+/// Longest text for `?stream=true`, which sends each chunk as it is synthesized.
+const MAX_TEXT_CHARS: usize = 100_000;
+/// Longest text for the JSON and progress responses, which hold the whole
+/// track (base64-encoded) in memory: about 15 minutes of speech.
+const MAX_BUFFERED_TEXT_CHARS: usize = 10_000;
+/// Chunks synthesized at the same time.
+const MAX_PARALLEL_CHUNKS: usize = 4;
+/// Silence between joined chunks (they always end at a sentence boundary).
+const CHUNK_GAP_MS: u32 = 350;
+/// Whisper's upload limit.
+pub const MAX_AUDIO_UPLOAD_BYTES: usize = 25 * 1024 * 1024;
+/// Longest cue the demo transcriber produces.
+const MAX_CUE_SECS: f64 = 6.0;
+/// Bitrate assumed when estimating the length of compressed demo uploads.
+const ESTIMATED_BITRATE: f64 = 128_000.0;
 
 #[derive(Deserialize)]
 pub struct VoiceRequest {
+    /// Plain text, or an SSML document starting with `<speak>`.
     pub text: String,
     pub voice: String,
     pub rate: Option<f32>,
+    /// mp3, ogg (opus), wav or pcm.
+    pub format: Option<String>,
+    /// wav / pcm only; defaults to the source rate.
+    pub sample_rate: Option<u32>,
+    /// wav / pcm only: linear16 (default) or mulaw.
+    pub encoding: Option<String>,
+}
+
+/// `?stream=true` (or `Accept: audio/*`) returns raw audio instead of a JSON data URL;
+/// `?progress=true` (or `Accept: text/event-stream`) reports chunk progress over SSE.
+#[derive(Deserialize)]
+pub struct VoiceQuery {
+    pub stream: Option<bool>,
+    pub progress: Option<bool>,
+}
+
+/// Validated output settings. `sample_rate` is `None` for provider-encoded formats.
+#[derive(Clone, Copy)]
+struct AudioOutput {
+    format: AudioFormat,
+    encoding: Encoding,
+    sample_rate: Option<u32>,
+}
+
+impl AudioOutput {
+    fn mime(&self) -> String {
+        self.format.mime(self.encoding, self.sample_rate.unwrap_or(0))
+    }
+}
+
+impl VoiceRequest {
+    fn output(&self, demo: bool) -> Result<AudioOutput, String> {
+        let format = match self.format.as_deref() {
+            Some(name) => AudioFormat::from_name(name).ok_or_else(|| {
+                format!("Unsupported format '{}' (available: mp3, ogg, wav, pcm)", name)
+            })?,
+            None if demo => AudioFormat::Wav,
+            None => AudioFormat::Mp3,
+        };
+
+        if format.is_compressed() {
+            if demo {
+                return Err(format!(
+                    "{} output needs the TTS provider; demo mode supports wav and pcm",
+                    format.name()
+                ));
+            }
+            if self.sample_rate.is_some() || self.encoding.is_some() {
+                return Err("sample_rate and encoding only apply to wav and pcm".to_string());
+            }
+            return Ok(AudioOutput { format, encoding: Encoding::Linear16, sample_rate: None });
+        }
+
+        let encoding = match self.encoding.as_deref() {
+            None => Encoding::Linear16,
+            Some(name) => Encoding::from_name(name)
+                .ok_or_else(|| format!("Unsupported encoding '{}' (available: linear16, mulaw)", name))?,
+        };
+        let native = if demo { mock::SYNTH_SAMPLE_RATE } else { PROVIDER_PCM_RATE };
+        let sample_rate = self.sample_rate.unwrap_or(native);
+        if !audio::SUPPORTED_SAMPLE_RATES.contains(&sample_rate) {
+            let rates: Vec<String> = audio::SUPPORTED_SAMPLE_RATES.iter().map(u32::to_string).collect();
+            return Err(format!("sample_rate must be one of {}", rates.join(", ")));
+        }
+
+        Ok(AudioOutput { format, encoding, sample_rate: Some(sample_rate) })
+    }
+}
+
+/// Resamples PCM to the requested rate and wraps it as wav or raw pcm.
+fn encode_local(samples: &[i16], source_rate: u32, output: AudioOutput) -> Vec<u8> {
+    let rate = output.sample_rate.unwrap_or(source_rate);
+    let samples = audio::resample(samples, source_rate, rate);
+    match output.format {
+        AudioFormat::Wav => audio::encode_wav(&samples, rate, output.encoding),
+        _ => audio::encode_samples(&samples, output.encoding),
+    }
+}
+
+/// Chunked audio response; no Content-Length so playback can start early.
+fn audio_stream_response<S>(stream: S, content_type: &str, voice: &str) -> Response<Body>
+where
+    S: futures::TryStream + Send + 'static,
+    S::Ok: Into<Bytes>,
+    S::Error: Into<axum::BoxError>,
+{
+    Response::builder()
+        .status(200)
+        .header(header::CONTENT_TYPE, content_type)
+        .header(header::CACHE_CONTROL, "no-store")
+        .header("X-Voice", voice)
+        .body(Body::from_stream(stream))
+        .unwrap()
 }
 
 pub async fn handle_voice_synthesize(
     State(state): State<AppState>,
     ConnectInfo(addr): ConnectInfo<SocketAddr>,
+    Query(query): Query<VoiceQuery>,
+    headers: HeaderMap,
     Json(req): Json<VoiceRequest>,
-) -> Result<Json<serde_json::Value>, AppError> {
+) -> Result<Response<Body>, AppError> {
     let ip = addr.ip().to_string();
     let tool = "voice";
 
     state
         .rate_limiter
         .check_global_limit(&ip)
-        .map_err(|e| AppError::RateLimited(e))?;
+        .map_err(AppError::RateLimited)?;
     state
         .rate_limiter
         .check_tool_limits(&ip, tool)
-        .map_err(|e| AppError::RateLimited(e))?;
+        .map_err(AppError::RateLimited)?;
 
     if req.text.trim().is_empty() {
         state.rate_limiter.record_error(&ip, tool);
@@ -43,47 +175,317 @@ pub async fn handle_voice_synthesize(
         ));
     }
 
+    let Some(voice) = mock::find_voice(&req.voice) else {
+        state.rate_limiter.record_error(&ip, tool);
+        let names: Vec<&str> = mock::VOICE_LIST.iter().map(|v| v.name.as_str()).collect();
+        return Err(AppError::InvalidInput(format!(
+            "Unknown voice '{}' (available: {})",
+            req.voice,
+            names.join(", ")
+        )));
+    };
+
+    let rate = req.rate.unwrap_or(1.0);
+    if !(MIN_RATE..=MAX_RATE).contains(&rate) {
+        state.rate_limiter.record_error(&ip, tool);
+        return Err(AppError::InvalidInput(format!(
+            "rate must be between {} and {}",
+            MIN_RATE, MAX_RATE
+        )));
+    }
+
+    let accept = headers
+        .get(header::ACCEPT)
+        .and_then(|v| v.to_str().ok())
+        .unwrap_or_default();
+    let progress = query.progress.unwrap_or_else(|| accept.contains("text/event-stream"));
+    let stream = !progress && query.stream.unwrap_or_else(|| accept.contains("audio/"));
+
+    let max_chars = if stream { MAX_TEXT_CHARS } else { MAX_BUFFERED_TEXT_CHARS };
+    if req.text.chars().count() > max_chars {
+        state.rate_limiter.record_error(&ip, tool);
+        let hint = if stream {
+            String::new()
+        } else {
+            format!(" (up to {} with ?stream=true)", MAX_TEXT_CHARS)
+        };
+        return Err(AppError::InvalidInput(format!(
+            "Text must be at most {} characters{}",
+            max_chars, hint
+        )));
+    }
+
+    let is_ssml = ssml::is_ssml(&req.text);
+    let speech = if is_ssml { ssml::parse(&req.text) } else { Ok(ssml::plain(&req.text)) };
+    let (speech, output) = match speech.and_then(|speech| Ok((speech, req.output(state.is_demo())?))) {
+        Ok(parsed) => parsed,
+        Err(e) => {
+            state.rate_limiter.record_error(&ip, tool);
+            return Err(AppError::InvalidInput(e));
+        }
+    };
+
     state.rate_limiter.increment_counters(&ip, tool);
 
-    if state.is_demo() {
-        sleep(mock::random_delay()).await;
-        let audio_bytes = mock::mock_voice_audio();
-        let base64_audio = base64::engine::general_purpose::STANDARD.encode(&audio_bytes);
-        return Ok(Json(json!({
-            "audio": format!("data:audio/mpeg;base64,{}", base64_audio),
-            "demo": true,
-        })));
-    }
-
-    // Real mode: call an external TTS API
-    let _rate = req.rate.unwrap_or(1.0);
-    let res = state
-        .http_client
-        .post("https://api.openai.com/v1/audio/speech")
-        .header(
-            "Authorization",
-            format!("Bearer {}", state.config.openai_api_key),
-        )
-        .json(&json!({
+    let job = SynthesisJob {
+        state: state.clone(),
+        chunks: ssml::chunk(&speech, MAX_CHUNK_CHARS),
+        voice,
+        rate,
+        output,
+        is_ssml,
+    };
+
+    if progress {
+        let (tx, rx) = mpsc::channel::<Result<Event, Infallible>>(32);
+        tokio::spawn(async move {
+            let event = match job.render(Some(&tx)).await {
+                Ok(audio) => Event::default().event("done").data(job.json_body(&audio).to_string()),
+                Err(e) => Event::default().event("error").data(json!({ "error": e.message() }).to_string()),
+            };
+            let _ = tx.send(Ok(event)).await;
+        });
+        return Ok(Sse::new(ReceiverStream::new(rx)).into_response());
+    }
+
+    // Compressed upstream chunks are piped straight through without buffering.
+    if stream && !state.is_demo() && output.format.is_compressed() && job.chunks.len() == 1 {
+        let res = job.provider_request(&job.chunks[0]).await?;
+        let upstream = res.bytes_stream().map(|chunk| {
+            chunk.inspect_err(|e| tracing::warn!("TTS stream interrupted: {}", e))
+        });
+        return Ok(audio_stream_response(upstream, &output.mime(), &voice.name));
+    }
+
+    if stream {
+        return Ok(audio_stream_response(job.stream(), &output.mime(), &voice.name));
+    }
+
+    let audio_bytes = job.render(None).await?;
+    Ok(Json(job.json_body(&audio_bytes)).into_response())
+}
+
+/// Audio of one chunk: PCM for local encoding, or provider-encoded bytes.
+enum ChunkAudio {
+    Pcm(Vec<i16>),
+    Encoded(Vec<u8>),
+}
+
+/// Validated synthesis request, split into provider-sized chunks.
+struct SynthesisJob {
+    state: AppState,
+    chunks: Vec<Vec<ssml::SpeechSegment>>,
+    voice: &'static mock::VoiceInfo,
+    rate: f32,
+    output: AudioOutput,
+    is_ssml: bool,
+}
+
+impl SynthesisJob {
+    /// OpenAI TTS has no SSML support, so each chunk goes out as its plain-text downgrade.
+    async fn provider_request(&self, chunk: &[ssml::SpeechSegment]) -> Result<reqwest::Response, AppError> {
+        let state = &self.state;
+        let response_format = match self.output.format {
+            AudioFormat::Mp3 => "mp3",
+            AudioFormat::OggOpus => "opus",
+            AudioFormat::Wav | AudioFormat::Pcm => "pcm",
+        };
+        let body = json!({
             "model": "tts-1",
-            "input": req.text,
-            "voice": "alloy",
-        }))
-        .send()
-        .await
-        .map_err(|e| AppError::InternalError(format!("TTS request failed: {}", e)))?;
+            "input": ssml::to_plain_text(chunk),
+            "voice": self.voice.provider_voice,
+            "speed": self.rate,
+            "response_format": response_format,
+        });
+        let res = send_with_retry(&state.openai_breaker, &RetryPolicy::OPENAI, || {
+            state
+                .http_client
+                .post("https://api.openai.com/v1/audio/speech")
+                .header(
+                    "Authorization",
+                    format!("Bearer {}", state.config.openai_api_key),
+                )
+                .json(&body)
+        })
+        .await?;
 
-    let bytes = res
-        .bytes()
-        .await
-        .map_err(|e| AppError::InternalError(format!("Failed to read audio bytes: {}", e)))?;
+        if !res.status().is_success() {
+            return Err(AppError::InternalError(format!("TTS API returned error: {}", res.status())));
+        }
+        Ok(res)
+    }
+
+    async fn synthesize_chunk(&self, chunk: Vec<ssml::SpeechSegment>) -> Result<ChunkAudio, AppError> {
+        if self.state.is_demo() {
+            // The built-in synthesizer renders speech without any network access and
+            // honours SSML prosody and breaks natively.
+            let (voice, rate) = (self.voice, self.rate);
+            return tokio::task::spawn_blocking(move || {
+                ChunkAudio::Pcm(mock::mock_voice_samples(&chunk, voice, rate))
+            })
+            .await
+            .map_err(|e| AppError::InternalError(format!("Speech synthesis failed: {}", e)));
+        }
+
+        let bytes = self
+            .provider_request(&chunk)
+            .await?
+            .bytes()
+            .await
+            .map_err(|e| AppError::InternalError(format!("Failed to read audio bytes: {}", e)))?;
+
+        Ok(if self.output.format.is_compressed() {
+            ChunkAudio::Encoded(bytes.to_vec())
+        } else {
+            ChunkAudio::Pcm(audio::pcm16_from_bytes(&bytes))
+        })
+    }
 
-    let base64_audio = base64::engine::general_purpose::STANDARD.encode(&bytes);
+    fn source_rate(&self) -> u32 {
+        if self.state.is_demo() { mock::SYNTH_SAMPLE_RATE } else { PROVIDER_PCM_RATE }
+    }
+
+    /// Silence after each chunk: its trailing break, at least [`CHUNK_GAP_MS`].
+    fn gaps(&self) -> Vec<u32> {
+        self.chunks
+            .iter()
+            .map(|chunk| chunk.last().map_or(0, |s| s.pause_after_ms).max(CHUNK_GAP_MS))
+            .collect()
+    }
+
+    /// Sends the audio chunk by chunk as synthesis completes (in order), so long
+    /// texts are never held in memory whole. WAV output starts with an
+    /// open-ended header. An error can only cut the stream short.
+    fn stream(self) -> ReceiverStream<Result<Bytes, std::io::Error>> {
+        let (tx, rx) = mpsc::channel(MAX_PARALLEL_CHUNKS);
+        tokio::spawn(async move {
+            let output = self.output;
+            let source_rate = self.source_rate();
+            let rate = output.sample_rate.unwrap_or(source_rate);
+            if output.format == AudioFormat::Wav {
+                let header = audio::wav_header(rate, output.encoding, None);
+                if tx.send(Ok(Bytes::from(header))).await.is_err() {
+                    return;
+                }
+            }
+
+            let gaps = self.gaps();
+            let count = self.chunks.len();
+            let mut results = futures::stream::iter(self.chunks.clone())
+                .map(|chunk| self.synthesize_chunk(chunk))
+                .buffered(MAX_PARALLEL_CHUNKS);
+
+            let mut index = 0;
+            while let Some(result) = results.next().await {
+                let bytes = match result {
+                    Ok(ChunkAudio::Encoded(bytes)) if index > 0 && output.format == AudioFormat::Mp3 => {
+                        audio::strip_id3(&bytes).to_vec()
+                    }
+                    Ok(ChunkAudio::Encoded(bytes)) => bytes,
+                    Ok(ChunkAudio::Pcm(samples)) => {
+                        let (gap, first, last) = (gaps[index], index == 0, index + 1 == count);
+                        let encoded = tokio::task::spawn_blocking(move || {
+                            let mut part = Vec::new();
+                            audio::join_part(&mut part, &samples, gap, first, last, source_rate);
+                            audio::encode_samples(&audio::resample(&part, source_rate, rate), output.encoding)
+                        })
+                        .await;
+                        match encoded {
+                            Ok(bytes) => bytes,
+                            Err(e) => {
+                                tracing::warn!("TTS stream interrupted: {}", e);
+                                return;
+                            }
+                        }
+                    }
+                    Err(e) => {
+                        tracing::warn!("TTS stream interrupted: {}", e.message());
+                        let _ = tx.send(Err(std::io::Error::other(e.message().to_string()))).await;
+                        return;
+                    }
+                };
+                index += 1;
+                // The client went away.
+                if tx.send(Ok(Bytes::from(bytes))).await.is_err() {
+                    return;
+                }
+            }
+        });
+        ReceiverStream::new(rx)
+    }
+
+    /// Synthesizes all chunks with bounded concurrency (results stay in order) and
+    /// joins them. Progress events are sent as chunks complete.
+    async fn render(&self, progress: Option<&mpsc::Sender<Result<Event, Infallible>>>) -> Result<Vec<u8>, AppError> {
+        let total = self.chunks.len();
+        let gaps = self.gaps();
+
+        let mut results = futures::stream::iter(self.chunks.clone())
+            .map(|chunk| self.synthesize_chunk(chunk))
+            .buffered(MAX_PARALLEL_CHUNKS);
 
-    Ok(Json(json!({
-        "audio": format!("data:audio/mpeg;base64,{}", base64_audio),
-        "demo": false,
-    })))
+        let mut parts = Vec::with_capacity(total);
+        while let Some(result) = results.next().await {
+            parts.push(result?);
+            if let Some(tx) = progress {
+                let data = json!({
+                    "completed": parts.len(),
+                    "total": total,
+                    "percent": parts.len() * 100 / total,
+                });
+                let _ = tx.send(Ok(Event::default().event("progress").data(data.to_string()))).await;
+            }
+        }
+
+        let source_rate = self.source_rate();
+        let output = self.output;
+        tokio::task::spawn_blocking(move || {
+            let mut encoded = Vec::new();
+            let mut pcm = Vec::new();
+            for (index, (part, gap)) in parts.into_iter().zip(gaps).enumerate() {
+                match part {
+                    // MP3 frames can simply be appended; Ogg pages form a chained stream.
+                    ChunkAudio::Encoded(bytes) if index > 0 && output.format == AudioFormat::Mp3 => {
+                        encoded.extend_from_slice(audio::strip_id3(&bytes))
+                    }
+                    ChunkAudio::Encoded(bytes) => encoded.extend_from_slice(&bytes),
+                    ChunkAudio::Pcm(samples) => pcm.push((samples, gap)),
+                }
+            }
+            if output.format.is_compressed() {
+                encoded
+            } else {
+                encode_local(&audio::join_pcm(pcm, source_rate), source_rate, output)
+            }
+        })
+        .await
+        .map_err(|e| AppError::InternalError(format!("Audio encoding failed: {}", e)))
+    }
+
+    fn json_body(&self, audio_bytes: &[u8]) -> serde_json::Value {
+        let output = self.output;
+        let demo = self.state.is_demo();
+        let base64_audio = base64::engine::general_purpose::STANDARD.encode(audio_bytes);
+        let mut body = json!({
+            "audio": format!("data:{};base64,{}", output.mime(), base64_audio),
+            "voice": self.voice.name,
+            "rate": self.rate,
+            "format": output.format.name(),
+            "chunks": self.chunks.len(),
+            "demo": demo,
+        });
+        if let Some(sample_rate) = output.sample_rate {
+            body["sample_rate"] = json!(sample_rate);
+            body["encoding"] = json!(match output.encoding {
+                Encoding::Linear16 => "linear16",
+                Encoding::Mulaw => "mulaw",
+            });
+        }
+        if self.is_ssml {
+            body["ssml"] = json!(if demo { "native" } else { "downgraded" });
+        }
+        body
+    }
 }
 
 pub async fn handle_voice_list(
@@ -95,3 +497,355 @@ pub async fn handle_voice_list(
         "demo": true,
     }))
 }
+
+/// `?format=json|srt|vtt|text`; a `format` form field works as well.
+#[derive(Deserialize)]
+pub struct TranscribeQuery {
+    pub format: Option<String>,
+}
+
+#[derive(Clone, Copy)]
+enum TranscriptFormat {
+    Json,
+    Srt,
+    Vtt,
+    Text,
+}
+
+impl TranscriptFormat {
+    fn from_name(name: &str) -> Option<Self> {
+        match name.trim().to_lowercase().as_str() {
+            "json" => Some(Self::Json),
+            "srt" => Some(Self::Srt),
+            "vtt" | "webvtt" => Some(Self::Vtt),
+            "text" | "txt" => Some(Self::Text),
+            _ => None,
+        }
+    }
+}
+
+struct Transcript {
+    language: String,
+    duration: f64,
+    segments: Vec<Cue>,
+}
+
+fn audio_upload_error(e: MultipartError) -> AppError {
+    if e.status() == StatusCode::PAYLOAD_TOO_LARGE {
+        AppError::PayloadTooLarge(format!(
+            "Upload exceeds {} MB",
+            MAX_AUDIO_UPLOAD_BYTES / (1024 * 1024)
+        ))
+    } else {
+        AppError::InvalidInput(e.body_text())
+    }
+}
+
+/// Reads the `file` (or `audio`) part plus optional `language` and `format` fields.
+async fn read_audio_upload(
+    mut multipart: Multipart,
+) -> Result<(Vec<u8>, Option<String>, Option<String>), AppError> {
+    let (mut audio, mut language, mut format) = (None, None, None);
+
+    while let Some(field) = multipart.next_field().await.map_err(audio_upload_error)? {
+        match field.name().unwrap_or_default() {
+            "file" | "audio" => audio = Some(field.bytes().await.map_err(audio_upload_error)?.to_vec()),
+            "language" => language = Some(field.text().await.map_err(audio_upload_error)?),
+            "format" => format = Some(field.text().await.map_err(audio_upload_error)?),
+            _ => {}
+        }
+    }
+
+    let audio = audio
+        .filter(|bytes| !bytes.is_empty())
+        .ok_or_else(|| AppError::InvalidInput("Missing audio file (form field 'file')".to_string()))?;
+    let language = language.map(|l| l.trim().to_lowercase()).filter(|l| !l.is_empty());
+    Ok((audio, language, format))
+}
+
+pub async fn handle_voice_transcribe(
+    State(state): State<AppState>,
+    ConnectInfo(addr): ConnectInfo<SocketAddr>,
+    Query(query): Query<TranscribeQuery>,
+    multipart: Result<Multipart, MultipartRejection>,
+) -> Result<Response<Body>, AppError> {
+    let ip = addr.ip().to_string();
+    let tool = "voice";
+
+    state.rate_limiter.check_global_limit(&ip).map_err(AppError::RateLimited)?;
+    state.rate_limiter.check_tool_limits(&ip, tool).map_err(AppError::RateLimited)?;
+
+    let upload = match multipart {
+        Ok(multipart) => read_audio_upload(multipart).await,
+        Err(e) => Err(AppError::InvalidInput(e.body_text())),
+    };
+    let validated = upload.and_then(|(audio, language, form_format)| {
+        let format = match query.format.or(form_format) {
+            Some(name) => TranscriptFormat::from_name(&name).ok_or_else(|| {
+                AppError::InvalidInput(format!(
+                    "Unsupported format '{}' (available: json, srt, vtt, text)",
+                    name
+                ))
+            })?,
+            None => TranscriptFormat::Json,
+        };
+        if let Some(language) = &language {
+            if !(2..=3).contains(&language.len()) || !language.bytes().all(|b| b.is_ascii_lowercase()) {
+                return Err(AppError::InvalidInput(
+                    "language must be an ISO-639-1 code such as 'en'".to_string(),
+                ));
+            }
+        }
+        let container = audio::sniff_container(&audio).ok_or_else(|| {
+            AppError::InvalidInput(
+                "Unsupported audio format (supported: wav, mp3, ogg, flac, webm, m4a)".to_string(),
+            )
+        })?;
+        Ok((audio, container, language, format))
+    });
+    let (audio_bytes, container, language, format) = match validated {
+        Ok(validated) => validated,
+        Err(e) => {
+            state.rate_limiter.record_error(&ip, tool);
+            return Err(e);
+        }
+    };
+
+    state.rate_limiter.increment_counters(&ip, tool);
+
+    let transcript = if state.is_demo() {
+        mock_transcribe(audio_bytes, language).await?
+    } else {
+        provider_transcribe(&state, audio_bytes, container, language).await?
+    };
+
+    let text = transcript
+        .segments
+        .iter()
+        .map(|cue| cue.text.as_str())
+        .collect::<Vec<_>>()
+        .join(" ");
+    let (content_type, body) = match format {
+        TranscriptFormat::Srt => ("application/x-subrip; charset=utf-8", captions::to_srt(&transcript.segments)),
+        TranscriptFormat::Vtt => ("text/vtt; charset=utf-8", captions::to_vtt(&transcript.segments)),
+        TranscriptFormat::Text => ("text/plain; charset=utf-8", text),
+        TranscriptFormat::Json => {
+            return Ok(Json(json!({
+                "text": text,
+                "language": transcript.language,
+                "duration": transcript.duration,
+                "segments": transcript.segments,
+                "srt": captions::to_srt(&transcript.segments),
+                "vtt": captions::to_vtt(&transcript.segments),
+                "demo": state.is_demo(),
+            }))
+            .into_response());
+        }
+    };
+
+    Ok(([(header::CONTENT_TYPE, content_type)], body).into_response())
+}
+
+/// Demo transcription: WAV uploads are segmented on their actual speech pauses;
+/// other containers get evenly spaced cues over an estimated duration.
+async fn mock_transcribe(audio_bytes: Vec<u8>, language: Option<String>) -> Result<Transcript, AppError> {
+    tokio::task::spawn_blocking(move || {
+        let (duration, spans) = match audio::decode_wav(&audio_bytes) {
+            Some((samples, rate)) => (
+                samples.len() as f64 / rate.max(1) as f64,
+                audio::speech_spans(&samples, rate, MAX_CUE_SECS),
+            ),
+            None => {
+                let duration = audio_bytes.len() as f64 * 8.0 / ESTIMATED_BITRATE;
+                let pieces = (duration / MAX_CUE_SECS).ceil() as usize;
+                let spans = (0..pieces)
+                    .map(|i| (i as f64 * MAX_CUE_SECS, ((i + 1) as f64 * MAX_CUE_SECS).min(duration)))
+                    .collect();
+                (duration, spans)
+            }
+        };
+
+        Transcript {
+            language: language.unwrap_or_else(|| "en".to_string()),
+            duration,
+            segments: mock::mock_transcript(&audio_bytes, &spans),
+        }
+    })
+    .await
+    .map_err(|e| AppError::InternalError(format!("Transcription failed: {}", e)))
+}
+
+async fn provider_transcribe(
+    state: &AppState,
+    audio_bytes: Vec<u8>,
+    container: &str,
+    language: Option<String>,
+) -> Result<Transcript, AppError> {
+    let res = send_with_retry(&state.openai_breaker, &RetryPolicy::OPENAI, || {
+        let file = reqwest::multipart::Part::bytes(audio_bytes.clone())
+            .file_name(format!("audio.{}", container));
+        let mut form = reqwest::multipart::Form::new()
+            .part("file", file)
+            .text("model", "whisper-1")
+            .text("response_format", "verbose_json")
+            .text("timestamp_granularities[]", "segment");
+        if let Some(language) = &language {
+            form = form.text("language", language.clone());
+        }
+        state
+            .http_client
+            .post("https://api.openai.com/v1/audio/transcriptions")
+            .header(
+                "Authorization",
+                format!("Bearer {}", state.config.openai_api_key),
+            )
+            .multipart(form)
+    })
+    .await?;
+
+    if !res.status().is_success() {
+        return Err(AppError::InternalError(format!(
+            "Transcription API returned error: {}",
+            res.status()
+        )));
+    }
+
+    let data: serde_json::Value = res
+        .json()
+        .await
+        .map_err(|e| AppError::InternalError(format!("Failed to parse response: {}", e)))?;
+
+    let segments = data["segments"]
+        .as_array()
+        .map(|segments| {
+            segments
+                .iter()
+                .map(|s| Cue {
+                    start: s["start"].as_f64().unwrap_or(0.0),
+                    end: s["end"].as_f64().unwrap_or(0.0),
+                    text: s["text"].as_str().unwrap_or_default().trim().to_string(),
+                })
+                .filter(|cue| !cue.text.is_empty())
+                .collect()
+        })
+        .unwrap_or_default();
+
+    Ok(Transcript {
+        language: data["language"].as_str().or(language.as_deref()).unwrap_or("unknown").to_string(),
+        duration: data["duration"].as_f64().unwrap_or(0.0),
+        segments,
+    })
+}
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+
+    async fn synthesize(
+        state: &AppState,
+        query: VoiceQuery,
+        body: serde_json::Value,
+    ) -> Result<Response<Body>, AppError> {
+        handle_voice_synthesize(
+            State(state.clone()),
+            ConnectInfo(SocketAddr::from(([203, 0, 113, 7], 40000))),
+            Query(query),
+            HeaderMap::new(),
+            Json(serde_json::from_value(body).unwrap()),
+        )
+        .await
+    }
+
+    fn buffered() -> VoiceQuery {
+        VoiceQuery { stream: None, progress: None }
+    }
+
+    async fn json(res: Response<Body>) -> serde_json::Value {
+        let bytes = axum::body::to_bytes(res.into_body(), usize::MAX).await.unwrap();
+        serde_json::from_slice(&bytes).unwrap()
+    }
+
+    #[test]
+    fn advertised_voices_map_to_provider_voices() {
+        const PROVIDER_VOICES: &[&str] = &["alloy", "echo", "fable", "onyx", "nova", "shimmer"];
+        for voice in mock::VOICE_LIST.iter() {
+            assert!(PROVIDER_VOICES.contains(&voice.provider_voice.as_str()), "{}", voice.name);
+            assert!(std::ptr::eq(mock::find_voice(&voice.name).unwrap(), voice), "{}", voice.name);
+        }
+    }
+
+    #[tokio::test]
+    async fn unknown_voices_are_rejected() {
+        let state = AppState::for_tests();
+        let err = synthesize(&state, buffered(), json!({ "text": "Hello.", "voice": "en-US-NobodyNeural" }))
+            .await
+            .unwrap_err();
+        let AppError::InvalidInput(message) = err else { panic!("unexpected error: {:?}", err) };
+        assert!(message.contains("Unknown voice 'en-US-NobodyNeural'"), "{}", message);
+        assert!(message.contains("en-US-AriaNeural"), "{}", message);
+    }
+
+    #[tokio::test]
+    async fn voices_resolve_case_insensitively_and_keep_the_rate() {
+        let state = AppState::for_tests();
+        let res = synthesize(&state, buffered(), json!({ "text": "Hello.", "voice": " en-us-guyneural ", "rate": 1.5 }))
+            .await
+            .unwrap();
+        let body = json(res).await;
+        assert_eq!(body["voice"], "en-US-GuyNeural");
+        assert_eq!(body["rate"], 1.5);
+
+        for rate in [MIN_RATE - 0.01, MAX_RATE + 0.01] {
+            let err = synthesize(&state, buffered(), json!({ "text": "Hello.", "voice": "en-US-GuyNeural", "rate": rate }))
+                .await
+                .unwrap_err();
+            assert!(matches!(err, AppError::InvalidInput(_)), "rate {}", rate);
+        }
+    }
+
+    async fn body_bytes(res: Response<Body>) -> Vec<u8> {
+        axum::body::to_bytes(res.into_body(), usize::MAX).await.unwrap().to_vec()
+    }
+
+    #[tokio::test]
+    async fn streamed_audio_matches_the_buffered_track() {
+        let state = AppState::for_tests();
+        let text = "The quick brown fox jumps over the lazy dog. ".repeat(92);
+        let body = json!({ "text": text, "voice": "en-US-AriaNeural", "format": "pcm" });
+
+        let res = synthesize(&state, VoiceQuery { stream: Some(true), progress: None }, body.clone()).await.unwrap();
+        assert_eq!(res.headers()[header::CONTENT_TYPE], "audio/L16; rate=16000; channels=1");
+        assert!(res.headers().get(header::CONTENT_LENGTH).is_none());
+        let streamed = body_bytes(res).await;
+
+        let buffered = json(synthesize(&state, buffered(), body).await.unwrap()).await;
+        assert_eq!(buffered["chunks"], 2);
+        let data_url = buffered["audio"].as_str().unwrap();
+        let encoded = data_url.split_once("base64,").unwrap().1;
+        assert_eq!(streamed, base64::engine::general_purpose::STANDARD.decode(encoded).unwrap());
+    }
+
+    #[tokio::test]
+    async fn streamed_wav_starts_with_an_open_ended_header() {
+        let state = AppState::for_tests();
+        let body = json!({ "text": "Hello there.", "voice": "en-US-GuyNeural", "sample_rate": 8000, "encoding": "mulaw" });
+        let res = synthesize(&state, VoiceQuery { stream: Some(true), progress: None }, body).await.unwrap();
+        let wav = body_bytes(res).await;
+        assert_eq!(wav[4..8], u32::MAX.to_le_bytes());
+        let (samples, rate) = audio::decode_wav(&wav).unwrap();
+        assert_eq!((samples.len() + 58, rate), (wav.len(), 8000));
+    }
+
+    #[tokio::test]
+    async fn progress_is_reported_over_sse() {
+        let state = AppState::for_tests();
+        let body = json!({ "text": "Hello there.", "voice": "en-US-GuyNeural" });
+        let res = synthesize(&state, VoiceQuery { stream: None, progress: Some(true) }, body).await.unwrap();
+        assert_eq!(res.headers()[header::CONTENT_TYPE], "text/event-stream");
+
+        let events = String::from_utf8(body_bytes(res).await).unwrap();
+        let progress = events.find("event: progress\ndata: {\"completed\":1,\"percent\":100,\"total\":1}").unwrap();
+        let done = events.find("event: done\ndata: {\"audio\":\"data:audio/wav;base64,").unwrap();
+        assert!(progress < done, "{}", events);
+    }
+}
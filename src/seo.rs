@@ -0,0 +1,1985 @@
+//! Deterministic on-page SEO analysis: readability formulas, keyword density
+//! and TF-IDF phrases, heading hierarchy, image alt coverage, links and meta
+//! lengths. No network or model calls.
+
+use std::collections::{BTreeMap, HashMap, HashSet};
+
+use serde::Serialize;
+
+use crate::fetch::{self, Fetched, Redirect};
+use crate::keywords;
+use crate::page::{self, Alternate, Heading, Page, TitleSource};
+
+/// Reading speed used for the reading-time estimate.
+const WORDS_PER_MINUTE: f64 = 200.0;
+
+/// Keyword density window most guides recommend.
+const DENSITY_RANGE: (f64, f64) = (1.0, 2.5);
+
+/// Density above which a repeated keyword reads as stuffing.
+const STUFFING_DENSITY: f64 = 3.0;
+
+/// Meta length bounds in characters, roughly what search results display.
+const TITLE_RANGE: (usize, usize) = (30, 60);
+const DESCRIPTION_RANGE: (usize, usize) = (70, 160);
+
+/// Responses slower than this are flagged.
+const SLOW_RESPONSE_MS: u64 = 2000;
+
+/// Open Graph properties link previews need.
+const REQUIRED_OPEN_GRAPH: [&str; 4] = ["og:title", "og:description", "og:image", "og:url"];
+
+/// Below this many words density figures are too noisy to judge.
+const MIN_DENSITY_WORDS: usize = 100;
+
+const MAX_KEYWORDS: usize = 10;
+const MAX_PHRASES: usize = 10;
+
+/// Anchor texts that say nothing about the target.
+const GENERIC_ANCHORS: &[&str] = &["click here", "here", "read more", "more", "link", "this", "learn more", "this page"];
+
+/// Abbreviations whose period does not end a sentence.
+const ABBREVIATIONS: &[&str] = &[
+    "mr", "mrs", "ms", "dr", "prof", "sr", "jr", "st", "vs", "etc", "e.g", "i.e", "inc", "ltd", "co", "no", "fig", "approx",
+];
+
+fn round(value: f64, places: i32) -> f64 {
+    let factor = 10f64.powi(places);
+    (value * factor).round() / factor
+}
+
+// ---------------------------------------------------------------------------
+// Readability
+// ---------------------------------------------------------------------------
+
+#[derive(Serialize)]
+pub struct Readability {
+    pub score: u32,
+    pub word_count: usize,
+    pub sentence_count: usize,
+    pub syllable_count: usize,
+    pub complex_word_count: usize,
+    pub avg_sentence_length: f64,
+    pub avg_syllables_per_word: f64,
+    pub flesch_reading_ease: f64,
+    pub flesch_kincaid_grade: f64,
+    pub gunning_fog: f64,
+    pub reading_time_minutes: u32,
+    /// Plain-language band of the reading ease score.
+    pub level: &'static str,
+}
+
+/// English syllable estimate: vowel groups, minus a silent final `e` and the
+/// unvoiced `-es` / `-ed` endings.
+pub fn syllables(word: &str) -> usize {
+    let word = word.to_lowercase();
+    let letters: Vec<char> = word.chars().filter(|c| c.is_alphabetic()).collect();
+    if letters.len() <= 3 {
+        return 1;
+    }
+    let is_vowel = |c: char| "aeiouyàáâäèéêëìíîïòóôöùúûü".contains(c);
+    let mut count = 0;
+    let mut previous_vowel = false;
+    for &c in &letters {
+        let vowel = is_vowel(c);
+        if vowel && !previous_vowel {
+            count += 1;
+        }
+        previous_vowel = vowel;
+    }
+
+    let n = letters.len();
+    let ends = |suffix: &str| word.ends_with(suffix);
+    let before = |back: usize| letters.get(n.wrapping_sub(back)).copied().unwrap_or(' ');
+    // "whale", "mile": a `le` after a vowel is not a syllable of its own.
+    let silent = (ends("e") && !ends("le") && !ends("ee") && !ends("ye"))
+        || (ends("le") && is_vowel(before(3)))
+        || (ends("es") && !matches!(before(3), 's' | 'x' | 'z' | 'c' | 'g' | 'h'))
+        || (ends("ed") && !matches!(before(3), 't' | 'd'));
+    if silent && count > 1 {
+        count -= 1;
+    }
+    count.max(1)
+}
+
+/// Gunning's "complex" words: three or more syllables, not counting proper
+/// nouns, hyphenated compounds or syllables added by `-es`, `-ed`, `-ing`.
+fn is_complex(word: &str, sentence_start: bool) -> bool {
+    if word.contains('-') || (!sentence_start && word.starts_with(char::is_uppercase)) {
+        return false;
+    }
+    let lower = word.to_lowercase();
+    let base = ["ing", "es", "ed"]
+        .iter()
+        .find_map(|suffix| lower.strip_suffix(suffix).filter(|b| b.chars().count() > 3))
+        .unwrap_or(&lower);
+    syllables(base) >= 3
+}
+
+/// Splits a text block into sentences at `.`, `!` and `?` followed by
+/// whitespace, skipping abbreviations, initials and decimals.
+pub fn sentences(block: &str) -> Vec<&str> {
+    let mut out = Vec::new();
+    let mut start = 0;
+    let chars: Vec<(usize, char)> = block.char_indices().collect();
+    for (i, &(at, c)) in chars.iter().enumerate() {
+        if !matches!(c, '.' | '!' | '?') {
+            continue;
+        }
+        let next = chars.get(i + 1).map(|&(_, c)| c);
+        let ends = next.is_none_or(|n| n.is_whitespace() || n == '"' || n == '”' || n == ')');
+        if !ends {
+            continue;
+        }
+        if c == '.' {
+            let word = block[start..at].rsplit(char::is_whitespace).next().unwrap_or_default();
+            let word = word.trim_start_matches(|c: char| !c.is_alphanumeric()).to_lowercase();
+            let initial = word.chars().count() == 1 && word.chars().all(char::is_alphabetic);
+            if initial || ABBREVIATIONS.contains(&word.as_str()) {
+                continue;
+            }
+        }
+        let end = at + c.len_utf8();
+        if block[start..end].chars().any(char::is_alphanumeric) {
+            out.push(block[start..end].trim());
+        }
+        start = end;
+    }
+    if block[start..].chars().any(char::is_alphanumeric) {
+        out.push(block[start..].trim());
+    }
+    out
+}
+
+fn reading_level(ease: f64) -> &'static str {
+    match ease {
+        e if e >= 90.0 => "very easy",
+        e if e >= 80.0 => "easy",
+        e if e >= 70.0 => "fairly easy",
+        e if e >= 60.0 => "plain English",
+        e if e >= 50.0 => "fairly difficult",
+        e if e >= 30.0 => "difficult",
+        _ => "very difficult",
+    }
+}
+
+/// Flesch Reading Ease, Flesch-Kincaid grade and Gunning Fog over the page's
+/// text blocks. Headings and list items count as sentences of their own.
+pub fn readability(page: &Page) -> Readability {
+    let (mut words, mut sentence_count, mut syllable_count, mut complex) = (0usize, 0usize, 0usize, 0usize);
+    for block in &page.blocks {
+        for sentence in sentences(block) {
+            let tokens: Vec<&str> =
+                keywords::words(sentence).into_iter().filter(|w| w.chars().any(char::is_alphabetic)).collect();
+            if tokens.is_empty() {
+                continue;
+            }
+            sentence_count += 1;
+            words += tokens.len();
+            for (i, word) in tokens.iter().enumerate() {
+                syllable_count += syllables(word);
+                if is_complex(word, i == 0) {
+                    complex += 1;
+                }
+            }
+        }
+    }
+
+    let wps = words as f64 / sentence_count.max(1) as f64;
+    let spw = syllable_count as f64 / words.max(1) as f64;
+    let (ease, grade, fog) = if words == 0 {
+        (0.0, 0.0, 0.0)
+    } else {
+        (
+            206.835 - 1.015 * wps - 84.6 * spw,
+            (0.39 * wps + 11.8 * spw - 15.59).max(0.0),
+            0.4 * (wps + 100.0 * complex as f64 / words as f64),
+        )
+    };
+    // Plain English (reading ease 60+) is the target for web copy.
+    let score = if words == 0 { 0 } else { (ease / 60.0 * 100.0).clamp(0.0, 100.0).round() as u32 };
+
+    Readability {
+        score,
+        word_count: words,
+        sentence_count,
+        syllable_count,
+        complex_word_count: complex,
+        avg_sentence_length: round(wps, 1),
+        avg_syllables_per_word: round(spw, 2),
+        flesch_reading_ease: round(ease, 1),
+        flesch_kincaid_grade: round(grade, 1),
+        gunning_fog: round(fog, 1),
+        reading_time_minutes: (words as f64 / WORDS_PER_MINUTE).ceil() as u32,
+        level: reading_level(ease),
+    }
+}
+
+// ---------------------------------------------------------------------------
+// Keywords
+// ---------------------------------------------------------------------------
+
+#[derive(Serialize)]
+pub struct KeywordDensity {
+    pub keyword: String,
+    pub count: usize,
+    /// Share of all words, in percent.
+    pub density: f64,
+}
+
+#[derive(Serialize)]
+pub struct Phrase {
+    pub phrase: String,
+    pub count: usize,
+    pub tf_idf: f64,
+}
+
+#[derive(Serialize)]
+pub struct KeywordReport {
+    pub score: u32,
+    pub density: Vec<KeywordDensity>,
+    pub phrases: Vec<Phrase>,
+    /// Keywords above the stuffing threshold.
+    pub overused: Vec<String>,
+    pub recommended_density_range: String,
+}
+
+fn is_keyword_word(word: &str) -> bool {
+    let lower = word.to_lowercase();
+    lower.chars().count() >= 3 && lower.chars().any(char::is_alphabetic) && !keywords::is_stopword(&lower)
+}
+
+/// Counts per stemmed key, remembering the most common spelling.
+#[derive(Default)]
+struct Counter {
+    counts: HashMap<String, usize>,
+    forms: HashMap<String, HashMap<String, usize>>,
+}
+
+impl Counter {
+    fn add(&mut self, key: String, form: String) {
+        *self.counts.entry(key.clone()).or_default() += 1;
+        *self.forms.entry(key).or_default().entry(form).or_default() += 1;
+    }
+
+    fn form(&self, key: &str) -> String {
+        self.forms
+            .get(key)
+            .and_then(|f| f.iter().max_by(|a, b| a.1.cmp(b.1).then_with(|| b.0.cmp(a.0))))
+            .map_or_else(|| key.to_string(), |(form, _)| form.clone())
+    }
+}
+
+/// Runs of words between punctuation, so phrases never span a clause break.
+fn clauses(text: &str) -> impl Iterator<Item = &str> {
+    text.split(|c: char| ".,;:!?()[]\"“”|/".contains(c))
+}
+
+/// Keyword density over non-stopwords, and two- and three-word phrases
+/// ranked by TF-IDF with each text block as a document: sublinear term
+/// frequency times smoothed inverse block frequency, so phrases that recur
+/// across a few sections outrank boilerplate repeated in every block.
+pub fn keyword_report(page: &Page, word_count: usize) -> KeywordReport {
+    let mut unigrams = Counter::default();
+    let mut ngrams = Counter::default();
+    let mut block_frequency: HashMap<String, usize> = HashMap::new();
+
+    for block in &page.blocks {
+        let mut seen: HashSet<String> = HashSet::new();
+        for clause in clauses(block) {
+            let words: Vec<&str> = keywords::words(clause);
+            let keys: Vec<String> = words.iter().map(|w| keywords::stem(&w.to_lowercase())).collect();
+            for (i, word) in words.iter().enumerate() {
+                if is_keyword_word(word) {
+                    unigrams.add(keys[i].clone(), word.to_lowercase());
+                }
+                for n in 2..=3 {
+                    let Some(window) = words.get(i..i + n) else { break };
+                    // Phrases start and end on content words; a stopword may sit inside.
+                    if !is_keyword_word(window[0]) || !is_keyword_word(window[n - 1]) {
+                        continue;
+                    }
+                    let key = keys[i..i + n].join(" ");
+                    let form = window.iter().map(|w| w.to_lowercase()).collect::<Vec<_>>().join(" ");
+                    ngrams.add(key.clone(), form);
+                    seen.insert(key);
+                }
+            }
+        }
+        for key in seen {
+            *block_frequency.entry(key).or_default() += 1;
+        }
+    }
+
+    let total = word_count.max(1) as f64;
+    let mut density: Vec<KeywordDensity> = unigrams
+        .counts
+        .iter()
+        .filter(|(_, &count)| count >= 2)
+        .map(|(key, &count)| KeywordDensity {
+            keyword: unigrams.form(key),
+            count,
+            density: round(count as f64 / total * 100.0, 2),
+        })
+        .collect();
+    density.sort_by(|a, b| b.count.cmp(&a.count).then_with(|| a.keyword.cmp(&b.keyword)));
+    density.truncate(MAX_KEYWORDS);
+
+    let documents = page.blocks.len().max(1) as f64;
+    let mut phrases: Vec<(String, usize, f64)> = ngrams
+        .counts
+        .iter()
+        .filter(|(_, &count)| count >= 2)
+        .map(|(key, &count)| {
+            let df = block_frequency.get(key).copied().unwrap_or(1) as f64;
+            let idf = ((1.0 + documents) / (1.0 + df)).ln() + 1.0;
+            (key.clone(), count, (1.0 + (count as f64).ln()) * idf)
+        })
+        .collect();
+    // A bigram that only ever appears inside one trigram is the same phrase.
+    let trigrams: Vec<(String, usize)> =
+        phrases.iter().filter(|(k, ..)| k.matches(' ').count() == 2).map(|(k, c, _)| (k.clone(), *c)).collect();
+    phrases.retain(|(key, count, _)| {
+        key.matches(' ').count() == 2
+            || !trigrams.iter().any(|(t, c)| c == count && (t.starts_with(&format!("{} ", key)) || t.ends_with(&format!(" {}", key))))
+    });
+    phrases.sort_by(|a, b| b.2.total_cmp(&a.2).then_with(|| b.1.cmp(&a.1)).then_with(|| a.0.cmp(&b.0)));
+    let phrases: Vec<Phrase> = phrases
+        .into_iter()
+        .take(MAX_PHRASES)
+        .map(|(key, count, score)| Phrase { phrase: ngrams.form(&key), count, tf_idf: round(score, 2) })
+        .collect();
+
+    let overused: Vec<String> = density
+        .iter()
+        .filter(|k| word_count >= MIN_DENSITY_WORDS && k.density > STUFFING_DENSITY && k.count >= 3)
+        .map(|k| k.keyword.clone())
+        .collect();
+    let top = density.first().map_or(0.0, |k| k.density);
+    let mut score: i64 = 100;
+    if word_count >= MIN_DENSITY_WORDS && top < DENSITY_RANGE.0 {
+        // No term stands out: the topic is unclear.
+        score -= 35;
+    } else if word_count >= MIN_DENSITY_WORDS && top > DENSITY_RANGE.1 && overused.is_empty() {
+        score -= 10;
+    }
+    score -= 25 * overused.len() as i64;
+    if word_count > 0 && density.is_empty() {
+        score = 40;
+    }
+
+    KeywordReport {
+        score: score.clamp(0, 100) as u32,
+        density,
+        phrases,
+        overused,
+        recommended_density_range: format!("{:.1}% - {:.1}%", DENSITY_RANGE.0, DENSITY_RANGE.1),
+    }
+}
+
+// ---------------------------------------------------------------------------
+// Headings
+// ---------------------------------------------------------------------------
+
+#[derive(Serialize)]
+pub struct HeadingReport {
+    pub score: u32,
+    pub outline: Vec<Heading>,
+    /// Count per level, H1 to H6.
+    pub counts: [usize; 6],
+    pub issues: Vec<String>,
+}
+
+pub fn heading_report(page: &Page, word_count: usize) -> HeadingReport {
+    let mut counts = [0; 6];
+    for heading in &page.headings {
+        counts[(heading.level.clamp(1, 6) - 1) as usize] += 1;
+    }
+    let mut issues = Vec::new();
+    match counts[0] {
+        0 => issues.push("No H1 heading".to_string()),
+        1 => {}
+        n => issues.push(format!("{} H1 headings; use exactly one", n)),
+    }
+    if let Some(first) = page.headings.first().filter(|h| h.level != 1) {
+        issues.push(format!("The first heading is an H{} (\"{}\"), not the H1", first.level, first.text));
+    }
+    for pair in page.headings.windows(2) {
+        if pair[1].level > pair[0].level + 1 {
+            issues.push(format!(
+                "H{} \"{}\" follows H{} \"{}\", skipping H{}",
+                pair[1].level,
+                pair[1].text,
+                pair[0].level,
+                pair[0].text,
+                pair[0].level + 1
+            ));
+        }
+    }
+    let empty = page.headings.iter().filter(|h| h.text.is_empty()).count();
+    if empty > 0 {
+        issues.push(format!("{} empty heading{}", empty, if empty == 1 { "" } else { "s" }));
+    }
+    for heading in page.headings.iter().filter(|h| h.text.chars().count() > 70) {
+        issues.push(format!("H{} is {} characters long: \"{}\"", heading.level, heading.text.chars().count(), heading.text));
+    }
+    let subheadings = counts[1..].iter().sum::<usize>();
+    if word_count > 300 && subheadings == 0 {
+        issues.push(format!("{} words without any H2-H6 subheadings", word_count));
+    }
+
+    HeadingReport {
+        score: 100u32.saturating_sub(20 * issues.len() as u32),
+        outline: page.headings.clone(),
+        counts,
+        issues,
+    }
+}
+
+// ---------------------------------------------------------------------------
+// Images, links, meta
+// ---------------------------------------------------------------------------
+
+#[derive(Serialize)]
+pub struct ImageReport {
+    pub score: u32,
+    pub total: usize,
+    pub with_alt: usize,
+    /// `alt=""`: correct for decorative images only.
+    pub decorative: usize,
+    pub missing_alt: usize,
+    /// Percentage of images with an `alt` attribute.
+    pub alt_coverage: f64,
+    /// Sources of images without `alt` (first 20).
+    pub missing_alt_sources: Vec<String>,
+}
+
+pub fn image_report(page: &Page) -> ImageReport {
+    let total = page.images.len();
+    let with_alt = page.images.iter().filter(|i| i.alt.as_deref().is_some_and(|a| !a.is_empty())).count();
+    let decorative = page.images.iter().filter(|i| i.alt.as_deref() == Some("")).count();
+    let missing: Vec<String> = page.images.iter().filter(|i| i.alt.is_none()).map(|i| i.src.clone()).collect();
+    let coverage = if total == 0 { 100.0 } else { (with_alt + decorative) as f64 / total as f64 * 100.0 };
+    ImageReport {
+        score: coverage.round() as u32,
+        total,
+        with_alt,
+        decorative,
+        missing_alt: missing.len(),
+        alt_coverage: round(coverage, 1),
+        missing_alt_sources: missing.into_iter().take(20).collect(),
+    }
+}
+
+#[derive(Serialize)]
+pub struct LinkReport {
+    pub score: u32,
+    pub total: usize,
+    pub internal: usize,
+    pub external: usize,
+    /// mailto:, tel: and similar.
+    pub other: usize,
+    pub nofollow: usize,
+    pub empty_anchor: usize,
+    pub generic_anchor: usize,
+}
+
+/// `host` is the page's own host when known; absolute links to it count as internal.
+pub fn link_report(page: &Page, host: Option<&str>, word_count: usize) -> LinkReport {
+    let (mut internal, mut external, mut other) = (0, 0, 0);
+    for link in &page.links {
+        let href = link.href.to_lowercase();
+        let absolute = href.starts_with("http://") || href.starts_with("https://") || href.starts_with("//");
+        if absolute {
+            let link_host = href.split("//").nth(1).and_then(|rest| rest.split(['/', '?', '#', ':']).next()).unwrap_or("");
+            let same = host.is_some_and(|h| {
+                let h = h.to_lowercase();
+                link_host == h || link_host.strip_prefix("www.") == Some(&h) || h.strip_prefix("www.") == Some(link_host)
+            });
+            if same {
+                internal += 1;
+            } else {
+                external += 1;
+            }
+        } else if href.contains(':') && !href.starts_with('/') && !href.starts_with('#') {
+            other += 1;
+        } else {
+            internal += 1;
+        }
+    }
+    let nofollow = page.links.iter().filter(|l| l.rel.split_whitespace().any(|r| r == "nofollow")).count();
+    let empty_anchor = page.links.iter().filter(|l| l.text.is_empty()).count();
+    let generic_anchor =
+        page.links.iter().filter(|l| GENERIC_ANCHORS.contains(&l.text.to_lowercase().trim_end_matches('.'))).count();
+
+    let mut score: i64 = 100;
+    if page.links.is_empty() && word_count > 300 {
+        score -= 40;
+    }
+    score -= (10 * (empty_anchor + generic_anchor) as i64).min(40);
+    LinkReport {
+        score: score.clamp(0, 100) as u32,
+        total: page.links.len(),
+        internal,
+        external,
+        other,
+        nofollow,
+        empty_anchor,
+        generic_anchor,
+    }
+}
+
+#[derive(Serialize)]
+pub struct LengthCheck {
+    pub text: Option<String>,
+    pub length: usize,
+    /// ok, missing, too_short or too_long.
+    pub status: &'static str,
+    pub recommended: String,
+}
+
+fn length_check(text: Option<&str>, (min, max): (usize, usize)) -> LengthCheck {
+    let length = text.map_or(0, |t| t.chars().count());
+    let status = match text {
+        None => "missing",
+        Some(_) if length < min => "too_short",
+        Some(_) if length > max => "too_long",
+        Some(_) => "ok",
+    };
+    LengthCheck { text: text.map(str::to_string), length, status, recommended: format!("{}-{} characters", min, max) }
+}
+
+#[derive(Serialize)]
+pub struct MetaReport {
+    pub score: u32,
+    pub title: LengthCheck,
+    pub title_source: Option<TitleSource>,
+    pub description: LengthCheck,
+}
+
+pub fn meta_report(page: &Page) -> MetaReport {
+    let title = length_check(page.title.as_ref().map(|(t, _)| t.as_str()), TITLE_RANGE);
+    let description = length_check(page.description.as_deref(), DESCRIPTION_RANGE);
+    let points = |check: &LengthCheck| match check.status {
+        "ok" => 50,
+        "missing" => 0,
+        _ => 30,
+    };
+    MetaReport {
+        score: points(&title) + points(&description),
+        title,
+        title_source: page.title.as_ref().map(|(_, source)| *source),
+        description,
+    }
+}
+
+// ---------------------------------------------------------------------------
+// Technical (fetched pages)
+// ---------------------------------------------------------------------------
+
+#[derive(Serialize)]
+pub struct Finding {
+    /// error, warning or info.
+    pub severity: &'static str,
+    pub check: &'static str,
+    pub message: String,
+}
+
+#[derive(Serialize)]
+pub struct JsonLdBlock {
+    pub valid: bool,
+    /// `@type` values, including those inside `@graph`.
+    pub types: Vec<String>,
+    #[serde(skip_serializing_if = "Option::is_none")]
+    pub error: Option<String>,
+}
+
+#[derive(Serialize)]
+pub struct TechnicalReport {
+    pub score: u32,
+    pub url: String,
+    pub final_url: String,
+    pub status: u16,
+    pub redirects: Vec<Redirect>,
+    pub response_ms: u64,
+    pub content_type: Option<String>,
+    pub bytes: usize,
+    pub truncated: bool,
+    pub lang: Option<String>,
+    /// Resolved against the page URL.
+    pub canonical: Option<String>,
+    /// Robots directives from `<meta>` tags and the `X-Robots-Tag` header.
+    pub robots: Vec<String>,
+    pub indexable: bool,
+    pub hreflang: Vec<Alternate>,
+    pub open_graph: BTreeMap<String, String>,
+    pub json_ld: Vec<JsonLdBlock>,
+    pub findings: Vec<Finding>,
+}
+
+/// Language tags such as `en`, `en-GB`, `zh-Hant-TW`, `es-419` or `x-default`.
+fn valid_hreflang(tag: &str) -> bool {
+    if tag.eq_ignore_ascii_case("x-default") {
+        return true;
+    }
+    let mut parts = tag.split('-');
+    let language = parts.next().unwrap_or_default();
+    (2..=3).contains(&language.len())
+        && language.chars().all(|c| c.is_ascii_alphabetic())
+        && parts.all(|p| {
+            (p.len() == 2 && p.chars().all(|c| c.is_ascii_alphabetic()))
+                || (p.len() == 4 && p.chars().all(|c| c.is_ascii_alphabetic()))
+                || (p.len() == 3 && p.chars().all(|c| c.is_ascii_digit()))
+        })
+}
+
+fn json_ld_types(value: &serde_json::Value, types: &mut Vec<String>) {
+    match value {
+        serde_json::Value::Array(items) => items.iter().for_each(|item| json_ld_types(item, types)),
+        serde_json::Value::Object(map) => {
+            match map.get("@type") {
+                Some(serde_json::Value::String(t)) => types.push(t.clone()),
+                Some(serde_json::Value::Array(ts)) => types.extend(ts.iter().filter_map(|t| t.as_str().map(str::to_string))),
+                _ => {}
+            }
+            if let Some(graph) = map.get("@graph") {
+                json_ld_types(graph, types);
+            }
+        }
+        _ => {}
+    }
+}
+
+/// The same URL without its fragment, for comparisons.
+fn without_fragment(url: &reqwest::Url) -> String {
+    let mut url = url.clone();
+    url.set_fragment(None);
+    url.to_string()
+}
+
+/// HTTP, indexing and head-markup findings for a fetched page.
+pub fn technical_report(page: &Page, fetched: &Fetched) -> TechnicalReport {
+    let mut findings = Vec::new();
+    let mut find = |severity, check, message: String| findings.push(Finding { severity, check, message });
+    let final_url = without_fragment(&fetched.url);
+
+    // HTTP
+    if fetched.status >= 400 {
+        find("error", "status", format!("The page returned HTTP {}", fetched.status));
+    }
+    match fetched.redirects.len() {
+        0 => {}
+        1 => {
+            let hop = &fetched.redirects[0];
+            let kind = if matches!(hop.status, 301 | 308) { "Permanent" } else { "Temporary" };
+            let severity = if kind == "Temporary" { "warning" } else { "info" };
+            find(severity, "redirects", format!("{} redirect ({}) from {} to {}", kind, hop.status, hop.from, hop.to));
+        }
+        n => find(
+            "warning",
+            "redirects",
+            format!("Redirect chain of {} hops from {} to {}; link to the final URL directly", n, fetched.requested, final_url),
+        ),
+    }
+    if fetched.response_ms > SLOW_RESPONSE_MS {
+        find("warning", "response_time", format!("The server took {} ms to respond", fetched.response_ms));
+    }
+    if fetched.truncated {
+        find(
+            "warning",
+            "size",
+            format!("The page is larger than {} MB; only the first {} MB were analysed", fetch::MAX_PAGE_BYTES >> 20, fetch::MAX_PAGE_BYTES >> 20),
+        );
+    }
+    if page.head.lang.is_none() {
+        find("warning", "lang", "The <html> element has no lang attribute".to_string());
+    }
+
+    // Canonical
+    let canonical = page.head.canonicals.first().and_then(|href| fetched.url.join(href).ok());
+    match page.head.canonicals.len() {
+        0 => find("warning", "canonical", "No canonical link; duplicate URLs may split ranking signals".to_string()),
+        1 => {}
+        n => find("error", "canonical", format!("{} canonical links; search engines may ignore all of them", n)),
+    }
+    if let Some(url) = &canonical {
+        if without_fragment(url) != final_url {
+            find("info", "canonical", format!("The canonical URL is {}, not this page", url));
+        }
+        if !page.head.canonicals[0].starts_with("http") {
+            find("info", "canonical", "The canonical link is relative; absolute URLs are safer".to_string());
+        }
+    } else if !page.head.canonicals.is_empty() {
+        find("error", "canonical", format!("Invalid canonical URL '{}'", page.head.canonicals[0]));
+    }
+
+    // Robots
+    let mut robots = page.head.robots.clone();
+    robots.extend(fetched.x_robots_tag.clone());
+    let directives: Vec<String> = robots
+        .iter()
+        .flat_map(|r| r.split(',').map(|d| d.trim().to_lowercase()).collect::<Vec<_>>())
+        .collect();
+    let has = |name: &str| directives.iter().any(|d| d == name || d == "none" || d.ends_with(&format!(": {}", name)));
+    let indexable = !has("noindex") && fetched.status < 400;
+    if has("noindex") {
+        find("error", "robots", "The page is marked noindex and will not appear in search results".to_string());
+    }
+    if has("nofollow") {
+        find("warning", "robots", "Robots nofollow: links on this page pass no ranking signals".to_string());
+    }
+
+    // hreflang
+    let alternates = &page.head.hreflang;
+    let mut seen: Vec<String> = Vec::new();
+    for alternate in alternates {
+        let lang = alternate.lang.to_lowercase();
+        if !valid_hreflang(&lang) {
+            find("error", "hreflang", format!("Invalid hreflang code '{}'", alternate.lang));
+        }
+        if seen.contains(&lang) {
+            find("warning", "hreflang", format!("hreflang '{}' is declared more than once", alternate.lang));
+        }
+        seen.push(lang);
+    }
+    if !alternates.is_empty() {
+        let self_url = canonical.as_ref().map(without_fragment).unwrap_or_else(|| final_url.clone());
+        let references_self = alternates
+            .iter()
+            .filter_map(|a| fetched.url.join(&a.href).ok())
+            .any(|url| without_fragment(&url) == self_url || without_fragment(&url) == final_url);
+        if !references_self {
+            find("warning", "hreflang", "The hreflang set does not include this page itself".to_string());
+        }
+        if alternates.len() >= 2 && !seen.iter().any(|l| l == "x-default") {
+            find("info", "hreflang", "No x-default alternate for unmatched languages".to_string());
+        }
+    }
+
+    // Open Graph
+    let mut open_graph = BTreeMap::new();
+    for (property, content) in &page.head.open_graph {
+        open_graph.entry(property.clone()).or_insert_with(|| content.clone());
+    }
+    let missing: Vec<&str> = REQUIRED_OPEN_GRAPH
+        .iter()
+        .copied()
+        .filter(|p| open_graph.get(*p).is_none_or(|c: &String| c.is_empty()))
+        .collect();
+    if !missing.is_empty() {
+        find("warning", "open_graph", format!("Missing Open Graph tags for link previews: {}", missing.join(", ")));
+    }
+
+    // JSON-LD
+    let json_ld: Vec<JsonLdBlock> = page
+        .head
+        .json_ld
+        .iter()
+        .map(|raw| match serde_json::from_str::<serde_json::Value>(raw) {
+            Ok(value) => {
+                let mut types = Vec::new();
+                json_ld_types(&value, &mut types);
+                JsonLdBlock { valid: true, types, error: None }
+            }
+            Err(e) => JsonLdBlock { valid: false, types: Vec::new(), error: Some(e.to_string()) },
+        })
+        .collect();
+    for (i, block) in json_ld.iter().enumerate() {
+        match &block.error {
+            Some(e) => find("error", "json_ld", format!("JSON-LD block {} is not valid JSON: {}", i + 1, e)),
+            None if block.types.is_empty() => find("warning", "json_ld", format!("JSON-LD block {} has no @type", i + 1)),
+            None => {}
+        }
+    }
+    if json_ld.is_empty() {
+        find("info", "json_ld", "No JSON-LD structured data; rich results need it".to_string());
+    }
+
+    let errors = findings.iter().filter(|f| f.severity == "error").count() as u32;
+    let warnings = findings.iter().filter(|f| f.severity == "warning").count() as u32;
+    TechnicalReport {
+        score: 100u32.saturating_sub(25 * errors + 10 * warnings),
+        url: fetched.requested.to_string(),
+        final_url,
+        status: fetched.status,
+        redirects: fetched.redirects.clone(),
+        response_ms: fetched.response_ms,
+        content_type: fetched.content_type.clone(),
+        bytes: fetched.bytes,
+        truncated: fetched.truncated,
+        lang: page.head.lang.clone(),
+        canonical: canonical.map(|u| u.to_string()),
+        robots,
+        indexable,
+        hreflang: alternates.clone(),
+        open_graph,
+        json_ld,
+        findings,
+    }
+}
+
+// ---------------------------------------------------------------------------
+// Report
+// ---------------------------------------------------------------------------
+
+#[derive(Clone, Serialize, serde::Deserialize)]
+pub struct Recommendation {
+    /// high, medium or low.
+    pub priority: String,
+    pub category: String,
+    pub title: String,
+    pub description: String,
+    /// analyzer for findings from the metrics, llm for provider suggestions.
+    #[serde(skip_deserializing, default = "analyzer_source")]
+    pub source: &'static str,
+}
+
+fn analyzer_source() -> &'static str {
+    "analyzer"
+}
+
+fn recommendation(priority: &str, category: &str, title: &str, description: String) -> Recommendation {
+    Recommendation {
+        priority: priority.to_string(),
+        category: category.to_string(),
+        title: title.to_string(),
+        description,
+        source: analyzer_source(),
+    }
+}
+
+#[derive(Serialize)]
+pub struct Scores {
+    pub readability: u32,
+    pub keywords: u32,
+    pub headings: u32,
+    pub images: u32,
+    pub links: u32,
+    pub meta: u32,
+    /// Only for fetched pages.
+    #[serde(skip_serializing_if = "Option::is_none")]
+    pub technical: Option<u32>,
+}
+
+#[derive(Serialize)]
+pub struct Analysis {
+    pub readability: Readability,
+    pub keywords: KeywordReport,
+    pub headings: HeadingReport,
+    pub images: ImageReport,
+    pub links: LinkReport,
+    pub meta: MetaReport,
+    #[serde(skip_serializing_if = "Option::is_none")]
+    pub technical: Option<TechnicalReport>,
+}
+
+#[derive(Serialize)]
+pub struct Report {
+    pub overall_score: u32,
+    /// html or markdown, as detected.
+    pub format: &'static str,
+    pub scores: Scores,
+    pub analysis: Analysis,
+    pub recommendations: Vec<Recommendation>,
+}
+
+fn recommendations(analysis: &Analysis) -> Vec<Recommendation> {
+    let mut out = Vec::new();
+    let r = &analysis.readability;
+    if r.word_count < 300 {
+        out.push(recommendation(
+            "high",
+            "content",
+            "Expand thin content",
+            format!("The page has {} words. Pages under 300 words rarely rank; cover the topic in more depth.", r.word_count),
+        ));
+    }
+    if r.word_count > 0 && r.flesch_reading_ease < 60.0 {
+        out.push(recommendation(
+            if r.flesch_reading_ease < 40.0 { "high" } else { "medium" },
+            "readability",
+            "Simplify sentences",
+            format!(
+                "Flesch reading ease is {} ({}), grade {} and Gunning Fog {}. Average sentence length is {} words; \
+                 aim for 15-20 and prefer shorter words ({} complex words found).",
+                r.flesch_reading_ease, r.level, r.flesch_kincaid_grade, r.gunning_fog, r.avg_sentence_length, r.complex_word_count
+            ),
+        ));
+    }
+
+    let k = &analysis.keywords;
+    if !k.overused.is_empty() {
+        out.push(recommendation(
+            "high",
+            "keywords",
+            "Reduce keyword repetition",
+            format!(
+                "{} {} above {}% of all words, which reads as keyword stuffing. Use synonyms and related phrases.",
+                k.overused.join(", "),
+                if k.overused.len() == 1 { "is" } else { "are" },
+                STUFFING_DENSITY
+            ),
+        ));
+    } else if let Some(top) = k.density.first().filter(|t| t.density < DENSITY_RANGE.0 && r.word_count >= MIN_DENSITY_WORDS) {
+        out.push(recommendation(
+            "medium",
+            "keywords",
+            "Focus on a primary keyword",
+            format!(
+                "The most frequent term, \"{}\", is only {}% of the text. Choose a primary keyword and use it in the \
+                 first paragraph, an H2 and the conclusion ({} target).",
+                top.keyword, top.density, k.recommended_density_range
+            ),
+        ));
+    }
+
+    let h = &analysis.headings;
+    if !h.issues.is_empty() {
+        out.push(recommendation(
+            if h.counts[0] == 0 { "high" } else { "medium" },
+            "structure",
+            "Fix the heading hierarchy",
+            format!("{}.", h.issues.join("; ")),
+        ));
+    }
+
+    let m = &analysis.meta;
+    for (check, name, element) in [(&m.title, "title", "<title>"), (&m.description, "meta description", "<meta name=\"description\">")] {
+        let advice = match check.status {
+            "missing" => format!("Add a {} ({}) of {}.", name, element, check.recommended),
+            "too_short" => format!("The {} is {} characters; use {} to describe the page fully.", name, check.length, check.recommended),
+            "too_long" => format!("The {} is {} characters and will be truncated in results; keep it to {}.", name, check.length, check.recommended),
+            _ => continue,
+        };
+        let priority = if check.status == "missing" { "high" } else { "medium" };
+        out.push(recommendation(priority, "meta", &format!("Adjust the {}", name), advice));
+    }
+    if m.title_source == Some(TitleSource::H1) {
+        out.push(recommendation(
+            "low",
+            "meta",
+            "Set an explicit title",
+            "The H1 stands in for the page title; add `title` front matter so the search title can differ from the heading."
+                .to_string(),
+        ));
+    }
+
+    let i = &analysis.images;
+    if i.missing_alt > 0 {
+        out.push(recommendation(
+            "medium",
+            "media",
+            "Add alt text to images",
+            format!(
+                "{} of {} images have no alt attribute ({}). Describe each image; use alt=\"\" only for decorative ones.",
+                i.missing_alt,
+                i.total,
+                i.missing_alt_sources.join(", ")
+            ),
+        ));
+    } else if i.total == 0 && r.word_count > 600 {
+        out.push(recommendation(
+            "low",
+            "media",
+            "Add supporting images",
+            "Long pages without images are harder to scan; add diagrams or screenshots with descriptive alt text.".to_string(),
+        ));
+    }
+
+    let l = &analysis.links;
+    if l.total == 0 && r.word_count > 300 {
+        out.push(recommendation(
+            "medium",
+            "links",
+            "Add internal and external links",
+            "Link to related pages on your site and to authoritative sources to build topical context.".to_string(),
+        ));
+    }
+    if l.empty_anchor + l.generic_anchor > 0 {
+        out.push(recommendation(
+            "low",
+            "links",
+            "Use descriptive anchor text",
+            format!(
+                "{} link{} empty or generic anchor text (\"click here\", \"read more\"). Say where the link goes.",
+                l.empty_anchor + l.generic_anchor,
+                if l.empty_anchor + l.generic_anchor == 1 { " has" } else { "s have" }
+            ),
+        ));
+    }
+
+    if let Some(t) = &analysis.technical {
+        for finding in t.findings.iter().filter(|f| f.severity != "info") {
+            let priority = if finding.severity == "error" { "high" } else { "medium" };
+            let title = match finding.check {
+                "status" => "Fix the HTTP status",
+                "redirects" => "Shorten redirects",
+                "response_time" => "Improve server response time",
+                "size" => "Reduce page weight",
+                "lang" => "Declare the page language",
+                "canonical" => "Fix the canonical link",
+                "robots" => "Review robots directives",
+                "hreflang" => "Fix hreflang annotations",
+                "open_graph" => "Complete Open Graph tags",
+                _ => "Fix structured data",
+            };
+            out.push(recommendation(priority, "technical", title, format!("{}.", finding.message)));
+        }
+    }
+
+    let rank = |p: &str| match p {
+        "high" => 0,
+        "medium" => 1,
+        _ => 2,
+    };
+    out.sort_by_key(|rec| rank(&rec.priority));
+    out
+}
+
+/// Full deterministic report for HTML or Markdown `content`.
+pub fn analyze(content: &str) -> Report {
+    analyze_page(&Page::parse(content), None, None)
+}
+
+/// Full report for a fetched page, including the technical checks.
+pub fn analyze_fetched(fetched: &Fetched) -> Report {
+    let page = Page::parse_html(&fetched.body);
+    let technical = technical_report(&page, fetched);
+    analyze_page(&page, fetched.url.host_str(), Some(technical))
+}
+
+/// `host` classifies absolute links as internal.
+fn analyze_page(page: &Page, host: Option<&str>, technical: Option<TechnicalReport>) -> Report {
+    let readability = readability(page);
+    let words = readability.word_count;
+    let analysis = Analysis {
+        keywords: keyword_report(page, words),
+        headings: heading_report(page, words),
+        images: image_report(page),
+        links: link_report(page, host, words),
+        meta: meta_report(page),
+        readability,
+        technical,
+    };
+    let scores = Scores {
+        readability: analysis.readability.score,
+        keywords: analysis.keywords.score,
+        headings: analysis.headings.score,
+        images: analysis.images.score,
+        links: analysis.links.score,
+        meta: analysis.meta.score,
+        technical: analysis.technical.as_ref().map(|t| t.score),
+    };
+    // Readability and structure matter most; images and links least.
+    let content = 0.25 * scores.readability as f64
+        + 0.20 * scores.keywords as f64
+        + 0.20 * scores.headings as f64
+        + 0.15 * scores.meta as f64
+        + 0.10 * scores.images as f64
+        + 0.10 * scores.links as f64;
+    let overall = match scores.technical {
+        Some(technical) => 0.75 * content + 0.25 * technical as f64,
+        None => content,
+    };
+    Report {
+        overall_score: overall.round() as u32,
+        format: page.markup.name(),
+        scores,
+        recommendations: recommendations(&analysis),
+        analysis,
+    }
+}
+
+// ---------------------------------------------------------------------------
+// Analysis modes
+// ---------------------------------------------------------------------------
+
+#[derive(Clone, Copy, PartialEq)]
+pub enum AnalysisType {
+    /// Every on-page metric with prioritised recommendations.
+    Full,
+    /// Keyword density, phrases and where each term is placed.
+    Keywords,
+    /// Readability formulas plus the sentences and words that drag them down.
+    Readability,
+    /// HTTP, indexing and head-markup checks of a fetched page.
+    Technical,
+    /// Side-by-side comparison with a competitor's text.
+    Competitor,
+    /// How the page would look as a search result.
+    Serp,
+}
+
+pub const ANALYSIS_TYPES: &[&str] = &["full", "keywords", "readability", "technical", "competitor", "serp"];
+
+impl AnalysisType {
+    pub fn from_name(name: &str) -> Option<Self> {
+        match name.trim().to_lowercase().replace('-', "_").as_str() {
+            "full" | "seo" => Some(Self::Full),
+            "keywords" | "keyword" | "keyword_research" => Some(Self::Keywords),
+            "readability" => Some(Self::Readability),
+            "technical" => Some(Self::Technical),
+            "competitor" | "comparison" | "competitor_comparison" => Some(Self::Competitor),
+            "serp" | "serp_preview" | "snippet" => Some(Self::Serp),
+            _ => None,
+        }
+    }
+}
+
+/// Search intent of a keyword idea.
+#[derive(Clone, Copy, Serialize, serde::Deserialize)]
+#[serde(rename_all = "lowercase")]
+pub enum SearchIntent {
+    Informational,
+    Navigational,
+    Commercial,
+    Transactional,
+}
+
+#[derive(Serialize, serde::Deserialize)]
+pub struct KeywordIdea {
+    pub keyword: String,
+    pub intent: SearchIntent,
+    pub rationale: String,
+}
+
+#[derive(Serialize)]
+pub struct Placement {
+    pub term: String,
+    pub in_title: bool,
+    pub in_description: bool,
+    pub in_h1: bool,
+    pub in_subheadings: bool,
+    pub in_first_paragraph: bool,
+}
+
+#[derive(Serialize)]
+pub struct KeywordResearch {
+    pub word_count: usize,
+    /// The top phrase, else the top keyword: what the page appears to target.
+    pub focus: Option<String>,
+    pub keywords: KeywordReport,
+    pub placements: Vec<Placement>,
+    /// Related keywords to target (provider; empty in demo mode).
+    pub ideas: Vec<KeywordIdea>,
+}
+
+/// Density, phrases and where the leading terms appear.
+pub fn keyword_research(page: &Page) -> KeywordResearch {
+    let word_count = readability(page).word_count;
+    let keywords = keyword_report(page, word_count);
+    let field = |text: Option<&str>| text.map(keywords::normalized).unwrap_or_default();
+    let title = field(page.title.as_ref().map(|(t, _)| t.as_str()));
+    let description = field(page.description.as_deref());
+    let h1: Vec<String> = page.headings.iter().filter(|h| h.level == 1).flat_map(|h| keywords::normalized(&h.text)).collect();
+    let subheadings: Vec<String> =
+        page.headings.iter().filter(|h| h.level > 1).flat_map(|h| keywords::normalized(&h.text)).collect();
+    // The first block that is not a heading.
+    let first_paragraph = field(
+        page.blocks
+            .iter()
+            .find(|b| !page.headings.iter().any(|h| h.text == **b))
+            .map(String::as_str),
+    );
+
+    let terms = keywords
+        .phrases
+        .iter()
+        .take(5)
+        .map(|p| p.phrase.clone())
+        .chain(keywords.density.iter().take(5).map(|k| k.keyword.clone()));
+    let placements = terms
+        .map(|term| Placement {
+            in_title: keywords::contains(&title, &term),
+            in_description: keywords::contains(&description, &term),
+            in_h1: keywords::contains(&h1, &term),
+            in_subheadings: keywords::contains(&subheadings, &term),
+            in_first_paragraph: keywords::contains(&first_paragraph, &term),
+            term,
+        })
+        .collect();
+
+    KeywordResearch {
+        word_count,
+        focus: keywords
+            .phrases
+            .first()
+            .map(|p| p.phrase.clone())
+            .or_else(|| keywords.density.first().map(|k| k.keyword.clone())),
+        keywords,
+        placements,
+        ideas: Vec::new(),
+    }
+}
+
+/// Sentences longer than this are hard to follow on screen.
+const LONG_SENTENCE_WORDS: usize = 25;
+const LONG_PARAGRAPH_WORDS: usize = 150;
+const MAX_EXAMPLES: usize = 5;
+
+#[derive(Serialize)]
+pub struct LongSentence {
+    pub text: String,
+    pub words: usize,
+}
+
+#[derive(Serialize, serde::Deserialize)]
+pub struct SentenceRewrite {
+    pub original: String,
+    pub rewritten: String,
+}
+
+#[derive(Serialize)]
+pub struct ReadabilityDetail {
+    pub readability: Readability,
+    /// Longest sentences over 25 words, longest first.
+    pub long_sentences: Vec<LongSentence>,
+    pub long_sentence_count: usize,
+    /// Most frequent words of three or more syllables.
+    pub complex_words: Vec<String>,
+    pub long_paragraph_count: usize,
+    /// Plainer versions of the long sentences (provider; empty in demo mode).
+    pub rewrites: Vec<SentenceRewrite>,
+}
+
+pub fn readability_detail(page: &Page) -> ReadabilityDetail {
+    let mut long_sentences = Vec::new();
+    let mut complex = Counter::default();
+    let mut long_paragraph_count = 0;
+    for block in &page.blocks {
+        let mut block_words = 0;
+        for sentence in sentences(block) {
+            let tokens: Vec<&str> =
+                keywords::words(sentence).into_iter().filter(|w| w.chars().any(char::is_alphabetic)).collect();
+            block_words += tokens.len();
+            if tokens.len() > LONG_SENTENCE_WORDS {
+                long_sentences.push(LongSentence { text: sentence.to_string(), words: tokens.len() });
+            }
+            for (i, word) in tokens.iter().enumerate() {
+                if is_complex(word, i == 0) {
+                    complex.add(word.to_lowercase(), word.to_lowercase());
+                }
+            }
+        }
+        if block_words > LONG_PARAGRAPH_WORDS {
+            long_paragraph_count += 1;
+        }
+    }
+    long_sentences.sort_by_key(|s| std::cmp::Reverse(s.words));
+    let long_sentence_count = long_sentences.len();
+    long_sentences.truncate(MAX_EXAMPLES);
+
+    let mut complex_words: Vec<(String, usize)> = complex.counts.into_iter().collect();
+    complex_words.sort_by(|a, b| b.1.cmp(&a.1).then_with(|| a.0.cmp(&b.0)));
+
+    ReadabilityDetail {
+        readability: readability(page),
+        long_sentences,
+        long_sentence_count,
+        complex_words: complex_words.into_iter().take(MAX_KEYWORDS).map(|(w, _)| w).collect(),
+        long_paragraph_count,
+        rewrites: Vec::new(),
+    }
+}
+
+#[derive(Serialize)]
+pub struct TechnicalAnalysis {
+    pub technical: TechnicalReport,
+    pub meta: MetaReport,
+    pub headings: HeadingReport,
+}
+
+pub fn technical_analysis(fetched: &Fetched) -> TechnicalAnalysis {
+    let page = Page::parse_html(&fetched.body);
+    let words = readability(&page).word_count;
+    TechnicalAnalysis {
+        technical: technical_report(&page, fetched),
+        meta: meta_report(&page),
+        headings: heading_report(&page, words),
+    }
+}
+
+#[derive(Serialize)]
+pub struct ComparisonSide {
+    pub overall_score: u32,
+    pub scores: Scores,
+    pub word_count: usize,
+    pub flesch_reading_ease: f64,
+    pub headings: usize,
+    pub images: usize,
+    pub links: usize,
+    pub top_keywords: Vec<String>,
+}
+
+#[derive(Serialize)]
+pub struct AreaComparison {
+    pub area: &'static str,
+    pub yours: u32,
+    pub competitor: u32,
+    /// yours, competitor or tie.
+    pub leader: &'static str,
+}
+
+#[derive(Serialize, serde::Deserialize)]
+pub struct ContentGap {
+    pub topic: String,
+    pub reason: String,
+    pub suggestion: String,
+}
+
+#[derive(Serialize)]
+pub struct Comparison {
+    pub yours: ComparisonSide,
+    pub competitor: ComparisonSide,
+    pub areas: Vec<AreaComparison>,
+    /// Leading terms both texts use.
+    pub shared_keywords: Vec<String>,
+    /// The competitor's leading terms that never appear in yours.
+    pub missing_keywords: Vec<String>,
+    /// Your leading terms the competitor never uses.
+    pub unique_keywords: Vec<String>,
+    /// Topics the competitor covers better (provider; empty in demo mode).
+    pub gaps: Vec<ContentGap>,
+}
+
+/// Top keywords and phrases of a report, phrases first.
+fn leading_terms(report: &Report) -> Vec<String> {
+    let k = &report.analysis.keywords;
+    let mut terms: Vec<String> = k.phrases.iter().take(5).map(|p| p.phrase.clone()).collect();
+    terms.extend(k.density.iter().take(MAX_KEYWORDS).map(|d| d.keyword.clone()));
+    terms
+}
+
+fn comparison_side(report: Report) -> ComparisonSide {
+    let a = &report.analysis;
+    ComparisonSide {
+        overall_score: report.overall_score,
+        word_count: a.readability.word_count,
+        flesch_reading_ease: a.readability.flesch_reading_ease,
+        headings: a.headings.outline.len(),
+        images: a.images.total,
+        links: a.links.total,
+        top_keywords: leading_terms(&report),
+        scores: report.scores,
+    }
+}
+
+/// Compares `content` with `competitor` metric by metric and term by term.
+pub fn compare(content: &str, competitor: &str) -> Comparison {
+    let (page, other_page) = (Page::parse(content), Page::parse(competitor));
+    let text = |page: &Page| keywords::normalized(&page.blocks.join("\n"));
+    let (tokens, other_tokens) = (text(&page), text(&other_page));
+    let (yours, theirs) = (analyze_page(&page, None, None), analyze_page(&other_page, None, None));
+    let (your_terms, their_terms) = (leading_terms(&yours), leading_terms(&theirs));
+
+    let shared_keywords = your_terms.iter().filter(|t| keywords::contains(&other_tokens, t)).cloned().collect();
+    let missing_keywords = their_terms.iter().filter(|t| !keywords::contains(&tokens, t)).cloned().collect();
+    let unique_keywords = your_terms.iter().filter(|t| !keywords::contains(&other_tokens, t)).cloned().collect();
+
+    let pairs = [
+        ("overall", yours.overall_score, theirs.overall_score),
+        ("readability", yours.scores.readability, theirs.scores.readability),
+        ("keywords", yours.scores.keywords, theirs.scores.keywords),
+        ("headings", yours.scores.headings, theirs.scores.headings),
+        ("meta", yours.scores.meta, theirs.scores.meta),
+        ("images", yours.scores.images, theirs.scores.images),
+        ("links", yours.scores.links, theirs.scores.links),
+    ];
+    let areas = pairs
+        .into_iter()
+        .map(|(area, mine, other)| AreaComparison {
+            area,
+            yours: mine,
+            competitor: other,
+            leader: match mine.cmp(&other) {
+                std::cmp::Ordering::Greater => "yours",
+                std::cmp::Ordering::Less => "competitor",
+                std::cmp::Ordering::Equal => "tie",
+            },
+        })
+        .collect();
+
+    Comparison {
+        yours: comparison_side(yours),
+        competitor: comparison_side(theirs),
+        areas,
+        shared_keywords,
+        missing_keywords,
+        unique_keywords,
+        gaps: Vec::new(),
+    }
+}
+
+/// Desktop result widths: titles at 20px Arial up to ~600px, descriptions
+/// at 14px up to ~920px (about two lines).
+const SERP_TITLE: (f64, f64) = (20.0, 600.0);
+const SERP_DESCRIPTION: (f64, f64) = (14.0, 920.0);
+
+/// Approximate Arial advance widths in em.
+fn char_em(c: char) -> f64 {
+    match c {
+        'i' | 'j' | 'l' | '\'' | '|' | '!' | '.' | ',' | ':' | ';' => 0.25,
+        'f' | 't' | 'r' | 'I' | ' ' | '(' | ')' | '[' | ']' | '-' | '/' => 0.31,
+        'm' | 'w' => 0.83,
+        'M' | 'W' => 0.92,
+        '\u{2E80}'.. => 1.0,
+        c if c.is_ascii_digit() => 0.56,
+        c if c.is_uppercase() => 0.68,
+        _ => 0.52,
+    }
+}
+
+fn text_width(text: &str, font_px: f64) -> f64 {
+    text.chars().map(char_em).sum::<f64>() * font_px
+}
+
+#[derive(Serialize)]
+pub struct SnippetLine {
+    pub text: String,
+    /// As shown, cut at a word boundary with "..." when too wide.
+    pub display: String,
+    pub characters: usize,
+    pub pixels: u32,
+    pub max_pixels: u32,
+    pub truncated: bool,
+}
+
+fn snippet_line(text: &str, (font_px, max): (f64, f64)) -> SnippetLine {
+    let text = page::squash(text);
+    let width = text_width(&text, font_px);
+    let truncated = width > max;
+    let display = if truncated {
+        let budget = max - text_width(" ...", font_px);
+        // The longest prefix that fits, then back to a word boundary.
+        let mut end = 0;
+        let mut used = 0.0;
+        for (at, c) in text.char_indices() {
+            used += char_em(c) * font_px;
+            if used > budget {
+                break;
+            }
+            end = at + c.len_utf8();
+        }
+        let cut = match text[..end].rfind(' ') {
+            Some(space) if !text[end..].starts_with(' ') => space,
+            _ => end,
+        };
+        format!("{} ...", text[..cut].trim_end())
+    } else {
+        text.clone()
+    };
+    SnippetLine {
+        characters: text.chars().count(),
+        pixels: width.round() as u32,
+        max_pixels: max as u32,
+        truncated,
+        text,
+        display,
+    }
+}
+
+pub fn title_fits(title: &str) -> bool {
+    !title.trim().is_empty() && text_width(title.trim(), SERP_TITLE.0) <= SERP_TITLE.1
+}
+
+pub fn description_fits(description: &str) -> bool {
+    !description.trim().is_empty() && text_width(description.trim(), SERP_DESCRIPTION.0) <= SERP_DESCRIPTION.1
+}
+
+#[derive(Serialize, serde::Deserialize)]
+pub struct SnippetVariant {
+    pub title: String,
+    pub description: String,
+}
+
+#[derive(Serialize)]
+pub struct SerpPreview {
+    /// `example.com › blog › post`, when the page has a URL.
+    pub breadcrumb: Option<String>,
+    pub title: SnippetLine,
+    pub title_source: Option<TitleSource>,
+    pub description: SnippetLine,
+    /// No meta description: search engines pick page text instead, as here.
+    pub description_generated: bool,
+    pub issues: Vec<String>,
+    /// Alternative titles and descriptions that fit (provider; empty in demo mode).
+    pub variants: Vec<SnippetVariant>,
+}
+
+fn breadcrumb(url: &reqwest::Url) -> String {
+    let host = url.host_str().unwrap_or_default();
+    let host = host.strip_prefix("www.").unwrap_or(host);
+    std::iter::once(host)
+        .chain(url.path_segments().into_iter().flatten().filter(|s| !s.is_empty()))
+        .collect::<Vec<_>>()
+        .join(" › ")
+}
+
+/// The search result snippet for `page`, with pixel-based truncation.
+pub fn serp_preview(page: &Page, url: Option<&reqwest::Url>) -> SerpPreview {
+    let (title_text, title_source) = match &page.title {
+        Some((title, source)) => (title.clone(), Some(*source)),
+        None => (String::new(), None),
+    };
+    let description_generated = page.description.as_deref().is_none_or(|d| d.trim().is_empty());
+    let description_text = if description_generated {
+        page.blocks
+            .iter()
+            .filter(|b| !page.headings.iter().any(|h| h.text == **b))
+            .flat_map(|b| sentences(b))
+            .collect::<Vec<_>>()
+            .join(" ")
+    } else {
+        page.description.clone().unwrap_or_default()
+    };
+    let title = snippet_line(&title_text, SERP_TITLE);
+    let description = snippet_line(&description_text, SERP_DESCRIPTION);
+
+    let mut issues = Vec::new();
+    match title_source {
+        None => issues.push("No title: search engines will make one up".to_string()),
+        Some(TitleSource::H1) => issues.push("No <title>; the H1 stands in for it".to_string()),
+        _ => {}
+    }
+    if title.truncated {
+        issues.push(format!("The title is cut off at about {} of {} pixels", title.max_pixels, title.pixels));
+    }
+    if description_generated {
+        issues.push("No meta description: the snippet is taken from page text".to_string());
+    } else if description.truncated {
+        issues.push(format!("The description is cut off at about {} of {} pixels", description.max_pixels, description.pixels));
+    } else if description.characters < DESCRIPTION_RANGE.0 {
+        issues.push(format!("The description is short ({} characters); use the space to sell the click", description.characters));
+    }
+
+    SerpPreview {
+        breadcrumb: url.map(breadcrumb),
+        title,
+        title_source,
+        description,
+        description_generated,
+        issues,
+        variants: Vec::new(),
+    }
+}
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+
+    /// `n` distinct made-up words that no stemmer or stopword list touches.
+    fn filler(n: usize) -> Vec<String> {
+        const LETTERS: &[u8] = b"bcdfghjklmnpqrtvwxz";
+        (0..n)
+            .map(|i| {
+                let (a, b) = (LETTERS[i % LETTERS.len()], LETTERS[i / LETTERS.len() % LETTERS.len()]);
+                format!("zo{}{}", a as char, b as char)
+            })
+            .collect()
+    }
+
+    fn text_with(keyword: &str, count: usize, total: usize) -> String {
+        let mut words = filler(total - count);
+        words.extend(std::iter::repeat_n(keyword.to_string(), count));
+        words.join(" ")
+    }
+
+    fn keywords_of(text: &str) -> KeywordReport {
+        let page = Page::parse(text);
+        keyword_report(&page, readability(&page).word_count)
+    }
+
+    #[test]
+    fn syllable_estimates() {
+        for (word, count) in [
+            ("the", 1),
+            ("rhythm", 1),
+            ("table", 2),
+            ("whale", 1),
+            ("makes", 1),
+            ("boxes", 2),
+            ("jumped", 1),
+            ("wanted", 2),
+            ("today", 2),
+            ("committee", 3),
+            ("complicated", 4),
+        ] {
+            assert_eq!(syllables(word), count, "{}", word);
+        }
+        assert!(is_complex("beautiful", false));
+        assert!(!is_complex("computing", false), "-ing adds no syllable of its own");
+        assert!(!is_complex("well-organised", false), "hyphenated compound");
+        assert!(!is_complex("Microsoft", false), "proper noun");
+        assert!(is_complex("Microsoft", true), "capitalised only by the sentence start");
+    }
+
+    #[test]
+    fn sentences_skip_abbreviations_initials_and_decimals() {
+        assert_eq!(
+            sentences("Dr. Smith met J. Doe 3.5 km from here. It was fun! Really? Yes"),
+            ["Dr. Smith met J. Doe 3.5 km from here.", "It was fun!", "Really?", "Yes"]
+        );
+        assert!(sentences(" ... !? ").is_empty());
+    }
+
+    #[test]
+    fn readability_formulas_on_known_text() {
+        // 9 words, 2 sentences, 18 syllables; committee, complicated and
+        // proposal are complex ("discussed" is two syllables without -ed).
+        let page = Page::parse("The committee discussed the complicated proposal today. We left.");
+        let r = readability(&page);
+        assert_eq!((r.word_count, r.sentence_count, r.syllable_count, r.complex_word_count), (9, 2, 18, 3));
+        assert_eq!((r.avg_sentence_length, r.avg_syllables_per_word), (4.5, 2.0));
+        // 206.835 - 1.015 * 4.5 - 84.6 * 2.0
+        assert_eq!(r.flesch_reading_ease, 33.1);
+        // 0.39 * 4.5 + 11.8 * 2.0 - 15.59
+        assert_eq!(r.flesch_kincaid_grade, 9.8);
+        // 0.4 * (4.5 + 100 * 3 / 9)
+        assert_eq!(r.gunning_fog, 15.1);
+        assert_eq!((r.score, r.level, r.reading_time_minutes), (55, "difficult", 1));
+
+        // Short words and sentences: the grade floors at zero, the score caps at 100.
+        let r = readability(&Page::parse("The cat sat on the mat. The dog ran."));
+        assert_eq!((r.flesch_reading_ease, r.flesch_kincaid_grade, r.gunning_fog), (117.7, 0.0, 1.8));
+        assert_eq!((r.score, r.level), (100, "very easy"));
+
+        for text in ["", "123 4.5 -- !!!"] {
+            let r = readability(&Page::parse(text));
+            assert_eq!((r.word_count, r.sentence_count, r.score, r.reading_time_minutes), (0, 0, 0, 0));
+            assert_eq!((r.flesch_reading_ease, r.flesch_kincaid_grade, r.gunning_fog), (0.0, 0.0, 0.0));
+        }
+    }
+
+    #[test]
+    fn reading_level_bands() {
+        for (ease, level) in [
+            (90.0, "very easy"),
+            (89.9, "easy"),
+            (80.0, "easy"),
+            (79.9, "fairly easy"),
+            (70.0, "fairly easy"),
+            (60.0, "plain English"),
+            (50.0, "fairly difficult"),
+            (49.9, "difficult"),
+            (30.0, "difficult"),
+            (29.9, "very difficult"),
+            (-20.0, "very difficult"),
+        ] {
+            assert_eq!(reading_level(ease), level, "{}", ease);
+        }
+    }
+
+    #[test]
+    fn keyword_density_without_words() {
+        for text in ["", "123 456 7.5 -- ?!"] {
+            let report = keywords_of(text);
+            assert!(report.density.is_empty() && report.phrases.is_empty() && report.overused.is_empty(), "{:?}", text);
+            assert_eq!(report.score, 100, "{:?}", text);
+        }
+        // Words, but none that repeat: no topic at all.
+        let report = keywords_of(&filler(150).join(" "));
+        assert!(report.density.is_empty());
+        assert_eq!(report.score, 40);
+    }
+
+    #[test]
+    fn keyword_density_thresholds() {
+        // (occurrences, total words) -> density, overused, score
+        for (count, total, density, overused, score) in [
+            (10, 100, 10.0, true, 75),
+            (6, 200, 3.0, false, 90),
+            (4, 200, 2.0, false, 100),
+            (2, 250, 0.8, false, 65),
+            // Too short to judge.
+            (10, 20, 50.0, false, 100),
+        ] {
+            let report = keywords_of(&text_with("rust", count, total));
+            let top = &report.density[0];
+            assert_eq!((top.keyword.as_str(), top.count, top.density), ("rust", count, density), "{}/{}", count, total);
+            assert_eq!(report.overused == ["rust"], overused, "{}/{}", count, total);
+            assert_eq!(report.score, score, "{}/{}", count, total);
+        }
+    }
+
+    #[test]
+    fn keyword_forms_fold_plurals_and_phrases_stay_in_clauses() {
+        let report = keywords_of(
+            "Data pipelines need care. A data pipeline fails quietly. Build data pipelines, test them.\n\n\
+             Data pipelines again.",
+        );
+        assert_eq!(report.density[0].keyword, "data");
+        assert_eq!((report.density[1].keyword.as_str(), report.density[1].count), ("pipelines", 4));
+        assert_eq!(report.phrases[0].phrase, "data pipelines");
+        assert!(report.phrases.iter().all(|p| !p.phrase.contains("test")), "phrases must not span clauses");
+    }
+
+    #[test]
+    fn heading_checks() {
+        let report = |html: &str, words: usize| heading_report(&Page::parse(html), words);
+
+        let clean = report("<h1>Guide</h1><h2>Setup</h2><h3>Linux</h3><h2>Usage</h2>", 400);
+        assert!(clean.issues.is_empty(), "{:?}", clean.issues);
+        assert_eq!((clean.score, clean.counts), (100, [1, 2, 1, 0, 0, 0]));
+
+        let skipped = report("<h2>Intro</h2><h4>Deep</h4>", 100);
+        assert_eq!(
+            skipped.issues,
+            [
+                "No H1 heading",
+                "The first heading is an H2 (\"Intro\"), not the H1",
+                "H4 \"Deep\" follows H2 \"Intro\", skipping H3",
+            ]
+        );
+        assert_eq!(skipped.score, 40);
+
+        let long = "A heading that keeps going well past the seventy characters a title needs";
+        let doubled = report(&format!("<h1>One</h1><h1>{}</h1>", long), 301);
+        assert_eq!(doubled.issues.len(), 3, "{:?}", doubled.issues);
+        assert_eq!(doubled.issues[0], "2 H1 headings; use exactly one");
+        assert!(doubled.issues[1].starts_with("H1 is 73 characters long"), "{:?}", doubled.issues);
+        assert_eq!(doubled.issues[2], "301 words without any H2-H6 subheadings");
+        assert_eq!(doubled.score, 40);
+        assert!(report("<h1>One</h1>", 300).issues.is_empty());
+
+        let many = report("<h3>a</h3><h5>b</h5><h1>c</h1><h1>d</h1><h3>e</h3><h2></h2><h2> </h2>", 0);
+        assert_eq!(many.issues.last().map(String::as_str), Some("2 empty headings"));
+        assert_eq!(many.score, 0, "{:?}", many.issues);
+    }
+
+    #[test]
+    fn image_alt_coverage() {
+        let page = Page::parse(r#"<p>x</p><img src="a.png" alt="A cat"><img src="b.png" alt=""><img src="c.png"><img src="d.png">"#);
+        let report = image_report(&page);
+        assert_eq!((report.total, report.with_alt, report.decorative, report.missing_alt), (4, 1, 1, 2));
+        assert_eq!((report.alt_coverage, report.score), (50.0, 50));
+        assert_eq!(report.missing_alt_sources, ["c.png", "d.png"]);
+
+        let none = image_report(&Page::parse("<p>No pictures.</p>"));
+        assert_eq!((none.total, none.alt_coverage, none.score), (0, 100.0, 100));
+    }
+
+    #[test]
+    fn link_classification_and_anchor_penalties() {
+        let page = Page::parse(
+            r##"<p><a href="/about">About us</a> <a href="https://www.example.com/x">Ours</a>
+            <a href="https://other.org/">Other site</a> <a href="mailto:a@example.com">Mail</a>
+            <a href="#top" rel="nofollow noopener"></a> <a href="/more">Read more.</a></p>"##,
+        );
+        let report = link_report(&page, Some("example.com"), 100);
+        assert_eq!((report.total, report.internal, report.external, report.other), (6, 4, 1, 1));
+        assert_eq!((report.nofollow, report.empty_anchor, report.generic_anchor), (1, 1, 1));
+        assert_eq!(report.score, 80);
+
+        // Without the page's host every absolute link is external.
+        assert_eq!(link_report(&page, None, 100).external, 2);
+
+        let generic = Page::parse(&format!("<p>{}</p>", r#"<a href="/a">here</a> "#.repeat(6)));
+        assert_eq!(link_report(&generic, None, 100).score, 60, "anchor penalties are capped");
+
+        let unlinked = Page::parse("<p>Plain text.</p>");
+        assert_eq!(link_report(&unlinked, None, 300).score, 100);
+        assert_eq!(link_report(&unlinked, None, 301).score, 60);
+    }
+
+    #[test]
+    fn meta_length_thresholds() {
+        let check = |len: usize, range| length_check(Some(&"x".repeat(len)), range).status;
+        assert_eq!(length_check(None, TITLE_RANGE).status, "missing");
+        assert_eq!(
+            [check(29, TITLE_RANGE), check(30, TITLE_RANGE), check(60, TITLE_RANGE), check(61, TITLE_RANGE)],
+            ["too_short", "ok", "ok", "too_long"]
+        );
+        assert_eq!(
+            [check(69, DESCRIPTION_RANGE), check(70, DESCRIPTION_RANGE), check(160, DESCRIPTION_RANGE), check(161, DESCRIPTION_RANGE)],
+            ["too_short", "ok", "ok", "too_long"]
+        );
+
+        let meta = |title: usize, description: Option<usize>| {
+            let description = description.map_or(String::new(), |n| format!(r#"<meta name="description" content="{}">"#, "d".repeat(n)));
+            meta_report(&Page::parse(&format!("<html><head><title>{}</title>{}</head><body><p>x</p></body></html>", "t".repeat(title), description))).score
+        };
+        assert_eq!(meta(45, Some(120)), 100);
+        assert_eq!(meta(45, None), 50);
+        assert_eq!(meta(80, Some(120)), 80);
+        assert_eq!(meta(10, Some(200)), 60);
+    }
+
+    const URL: &str = "https://example.com/page";
+
+    fn clean_head() -> String {
+        format!(
+            r#"<link rel="canonical" href="{URL}">
+            <meta property="og:title" content="Page"><meta property="og:description" content="About the page">
+            <meta property="og:image" content="https://example.com/page.png"><meta property="og:url" content="{URL}">
+            <script type="application/ld+json">{{"@context":"https://schema.org","@type":"Article"}}</script>"#
+        )
+    }
+
+    fn fetched(html_attrs: &str, head: &str) -> Fetched {
+        let url = reqwest::Url::parse(URL).unwrap();
+        let body = format!("<html{}><head>{}</head><body><h1>Page</h1><p>Hello there.</p></body></html>", html_attrs, head);
+        Fetched {
+            requested: url.clone(),
+            url,
+            status: 200,
+            redirects: Vec::new(),
+            content_type: Some("text/html".to_string()),
+            x_robots_tag: None,
+            bytes: body.len(),
+            body,
+            truncated: false,
+            response_ms: 120,
+        }
+    }
+
+    fn clean() -> Fetched {
+        fetched(r#" lang="en""#, &clean_head())
+    }
+
+    fn findings(fetched: &Fetched) -> Vec<(&'static str, &'static str)> {
+        let report = technical_report(&Page::parse_html(&fetched.body), fetched);
+        report.findings.iter().map(|f| (f.severity, f.check)).collect()
+    }
+
+    fn hop(status: u16) -> Redirect {
+        Redirect { from: "http://example.com/page".to_string(), to: URL.to_string(), status }
+    }
+
+    #[test]
+    fn technical_http_checks() {
+        let page = clean();
+        let report = technical_report(&Page::parse_html(&page.body), &page);
+        assert!(report.findings.is_empty(), "{:?}", findings(&page));
+        assert_eq!((report.score, report.indexable), (100, true));
+        assert_eq!(report.canonical.as_deref(), Some(URL));
+
+        let with = |change: fn(&mut Fetched)| {
+            let mut page = clean();
+            change(&mut page);
+            findings(&page)
+        };
+        assert_eq!(with(|f| f.status = 404), [("error", "status")]);
+        assert_eq!(with(|f| f.redirects = vec![hop(301)]), [("info", "redirects")]);
+        assert_eq!(with(|f| f.redirects = vec![hop(302)]), [("warning", "redirects")]);
+        assert_eq!(with(|f| f.redirects = vec![hop(301), hop(301)]), [("warning", "redirects")]);
+        assert!(with(|f| f.response_ms = SLOW_RESPONSE_MS).is_empty());
+        assert_eq!(with(|f| f.response_ms = SLOW_RESPONSE_MS + 1), [("warning", "response_time")]);
+        assert_eq!(with(|f| f.truncated = true), [("warning", "size")]);
+        assert_eq!(with(|f| f.x_robots_tag = Some("noindex".to_string())), [("error", "robots")]);
+        assert_eq!(with(|f| f.x_robots_tag = Some("googlebot: nofollow".to_string())), [("warning", "robots")]);
+        assert_eq!(with(|f| f.x_robots_tag = Some("none".to_string())), [("error", "robots"), ("warning", "robots")]);
+
+        let mut gone = clean();
+        gone.status = 410;
+        let report = technical_report(&Page::parse_html(&gone.body), &gone);
+        assert!(!report.indexable);
+    }
+
+    #[test]
+    fn technical_markup_checks() {
+        let with_head = |head: String| fetched(r#" lang="en""#, &head);
+        let canonical = |href: &str| clean_head().replace(&format!(r#"href="{URL}""#), &format!(r#"href="{}""#, href));
+
+        assert_eq!(findings(&fetched("", &clean_head())), [("warning", "lang")]);
+        assert_eq!(findings(&with_head(canonical("/page"))), [("info", "canonical")]);
+        assert_eq!(findings(&with_head(canonical("https://example.com/other"))), [("info", "canonical")]);
+        assert_eq!(findings(&with_head(canonical("http://[bad"))), [("error", "canonical")]);
+        assert_eq!(findings(&with_head(clean_head().replace("canonical", "nothing"))), [("warning", "canonical")]);
+        let twice = format!(r#"{}<link rel="canonical" href="{URL}">"#, clean_head());
+        assert_eq!(findings(&with_head(twice)), [("error", "canonical")]);
+
+        let noindex = format!(r#"{}<meta name="robots" content="noindex, follow">"#, clean_head());
+        assert_eq!(findings(&with_head(noindex)), [("error", "robots")]);
+
+        assert_eq!(findings(&with_head(clean_head().replace("og:image", "og:picture"))), [("warning", "open_graph")]);
+
+        let ld = |body: &str| clean_head().replace(r#"{"@context":"https://schema.org","@type":"Article"}"#, body);
+        assert_eq!(findings(&with_head(ld("{ not json"))), [("error", "json_ld")]);
+        assert_eq!(findings(&with_head(ld(r#"{"name":"x"}"#))), [("warning", "json_ld")]);
+        assert_eq!(findings(&with_head(clean_head().replace("ld+json", "json"))), [("info", "json_ld")]);
+
+        // Errors cost 25 points and warnings 10; info is free.
+        let broken = fetched("", &ld("{"));
+        let report = technical_report(&Page::parse_html(&broken.body), &broken);
+        assert_eq!(report.score, 100 - 25 - 10);
+    }
+
+    #[test]
+    fn hreflang_checks() {
+        let with_alternates = |alternates: &[(&str, &str)]| {
+            let links: String = alternates
+                .iter()
+                .map(|(lang, href)| format!(r#"<link rel="alternate" hreflang="{}" href="{}">"#, lang, href))
+                .collect();
+            findings(&fetched(r#" lang="en""#, &format!("{}{}", clean_head(), links)))
+        };
+
+        assert!(with_alternates(&[("en", URL), ("de", "/de/page"), ("x-default", URL)]).is_empty());
+        assert_eq!(with_alternates(&[("en", URL), ("de", "/de/page")]), [("info", "hreflang")]);
+        assert_eq!(with_alternates(&[("english", URL)]), [("error", "hreflang")]);
+        assert_eq!(with_alternates(&[("en", URL), ("EN", "/en")]), [("warning", "hreflang"), ("info", "hreflang")]);
+        assert_eq!(with_alternates(&[("de", "/de/page")]), [("warning", "hreflang")]);
+
+        for tag in ["en", "en-GB", "zh-Hant-TW", "es-419", "x-default", "fil"] {
+            assert!(valid_hreflang(&tag.to_lowercase()), "{}", tag);
+        }
+        for tag in ["english", "e", "en_gb", "en-g", "en-12", ""] {
+            assert!(!valid_hreflang(tag), "{}", tag);
+        }
+    }
+
+    #[test]
+    fn json_ld_types_include_graph_members() {
+        let value = serde_json::json!({
+            "@context": "https://schema.org",
+            "@graph": [{ "@type": "WebSite" }, { "@type": ["Organization", "Brand"] }, { "name": "untyped" }]
+        });
+        let mut types = Vec::new();
+        json_ld_types(&value, &mut types);
+        assert_eq!(types, ["WebSite", "Organization", "Brand"]);
+    }
+
+    #[test]
+    fn overall_score_weights_each_area() {
+        let report = analyze("# Title\n\nA short page about nothing much. It has two sentences.\n");
+        let s = &report.scores;
+        let expected = 0.25 * s.readability as f64
+            + 0.20 * s.keywords as f64
+            + 0.20 * s.headings as f64
+            + 0.15 * s.meta as f64
+            + 0.10 * s.images as f64
+            + 0.10 * s.links as f64;
+        assert_eq!(report.overall_score, expected.round() as u32);
+        assert!(s.technical.is_none());
+        assert_eq!(report.recommendations[0].priority, "high");
+        assert!(report.recommendations.iter().any(|r| r.title == "Expand thin content"));
+
+        let page = clean();
+        let report = analyze_fetched(&page);
+        let s = &report.scores;
+        let content = 0.25 * s.readability as f64
+            + 0.20 * s.keywords as f64
+            + 0.20 * s.headings as f64
+            + 0.15 * s.meta as f64
+            + 0.10 * s.images as f64
+            + 0.10 * s.links as f64;
+        assert_eq!(s.technical, Some(100));
+        assert_eq!(report.overall_score, (0.75 * content + 25.0).round() as u32);
+    }
+
+    #[test]
+    fn serp_snippets_truncate_by_pixel_width() {
+        assert!(title_fits("A short title"));
+        assert!(!title_fits("   "));
+        assert!(!title_fits(&"W".repeat(40)));
+        assert!(description_fits(&"i".repeat(200)), "narrow letters fit far more characters");
+
+        let line = snippet_line(&"word ".repeat(40), SERP_TITLE);
+        assert!(line.truncated && line.display.ends_with(" ..."));
+        assert!(line.display.trim_end_matches(" ...").ends_with("word"));
+        assert!(text_width(&line.display, SERP_TITLE.0) <= SERP_TITLE.1);
+
+        let url = reqwest::Url::parse("https://www.example.com/blog/post/").unwrap();
+        assert_eq!(breadcrumb(&url), "example.com › blog › post");
+    }
+}
@@ -0,0 +1,469 @@
+/// Upper bound for a single `<break>`.
+const MAX_BREAK_MS: u32 = 10_000;
+
+/// A run of text sharing the same prosody, followed by an optional pause.
+#[derive(Clone, Debug)]
+pub struct SpeechSegment {
+    pub text: String,
+    /// Tempo multiplier (1.0 = normal).
+    pub rate: f32,
+    /// Pitch multiplier (1.0 = voice default).
+    pub pitch: f32,
+    pub pause_after_ms: u32,
+}
+
+#[derive(Clone, Copy)]
+struct Prosody {
+    rate: f32,
+    pitch: f32,
+}
+
+pub fn is_ssml(text: &str) -> bool {
+    text.trim_start().starts_with("<speak")
+}
+
+/// Plain text as a single segment.
+pub fn plain(text: &str) -> Vec<SpeechSegment> {
+    vec![SpeechSegment {
+        text: text.trim().to_string(),
+        rate: 1.0,
+        pitch: 1.0,
+        pause_after_ms: 0,
+    }]
+}
+
+/// Validates an SSML document and flattens it into speech segments.
+///
+/// Supported: `<speak>`, `<p>`, `<s>`, `<break>`, `<emphasis>`, `<prosody>`,
+/// `<say-as>` and `<sub>`. Anything else is rejected.
+pub fn parse(ssml: &str) -> Result<Vec<SpeechSegment>, String> {
+    let doc = roxmltree::Document::parse(ssml).map_err(|e| format!("Invalid SSML: {}", e))?;
+    let root = doc.root_element();
+    if root.tag_name().name() != "speak" {
+        return Err("SSML must have a <speak> root element".to_string());
+    }
+
+    let mut segments = Vec::new();
+    walk(root, Prosody { rate: 1.0, pitch: 1.0 }, &mut segments)?;
+    segments.retain(|s| !s.text.is_empty() || s.pause_after_ms > 0);
+    if segments.iter().all(|s| s.text.is_empty()) {
+        return Err("SSML contains no text to speak".to_string());
+    }
+    Ok(segments)
+}
+
+/// Downgrade for providers without SSML: pauses become punctuation.
+pub fn to_plain_text(segments: &[SpeechSegment]) -> String {
+    let mut out = String::new();
+    for segment in segments {
+        if !segment.text.is_empty() {
+            if !out.is_empty() && !out.ends_with(' ') {
+                out.push(' ');
+            }
+            out.push_str(&segment.text);
+        }
+        let ends_sentence = out.ends_with(['.', '!', '?', ',', ';', ':']);
+        if segment.pause_after_ms >= 600 && !out.is_empty() {
+            out.push_str(if ends_sentence { " ..." } else { "..." });
+        } else if segment.pause_after_ms >= 200 && !ends_sentence && !out.is_empty() {
+            out.push(',');
+        }
+    }
+    out.trim().to_string()
+}
+
+fn push_text(segments: &mut Vec<SpeechSegment>, text: &str, prosody: Prosody) {
+    let text = text.split_whitespace().collect::<Vec<_>>().join(" ");
+    if text.is_empty() {
+        return;
+    }
+    match segments.last_mut() {
+        Some(last)
+            if last.pause_after_ms == 0 && last.rate == prosody.rate && last.pitch == prosody.pitch =>
+        {
+            last.text.push(' ');
+            last.text.push_str(&text);
+        }
+        _ => segments.push(SpeechSegment {
+            text,
+            rate: prosody.rate,
+            pitch: prosody.pitch,
+            pause_after_ms: 0,
+        }),
+    }
+}
+
+fn push_pause(segments: &mut Vec<SpeechSegment>, ms: u32, prosody: Prosody) {
+    match segments.last_mut() {
+        Some(last) => last.pause_after_ms = (last.pause_after_ms + ms).min(MAX_BREAK_MS),
+        None => segments.push(SpeechSegment {
+            text: String::new(),
+            rate: prosody.rate,
+            pitch: prosody.pitch,
+            pause_after_ms: ms,
+        }),
+    }
+}
+
+fn walk(node: roxmltree::Node, prosody: Prosody, segments: &mut Vec<SpeechSegment>) -> Result<(), String> {
+    for child in node.children() {
+        if child.is_text() {
+            push_text(segments, child.text().unwrap_or_default(), prosody);
+            continue;
+        }
+        if !child.is_element() {
+            continue;
+        }
+
+        match child.tag_name().name() {
+            "p" | "s" => {
+                walk(child, prosody, segments)?;
+                push_pause(segments, if child.tag_name().name() == "p" { 500 } else { 250 }, prosody);
+            }
+            "break" => push_pause(segments, break_duration(child)?, prosody),
+            "emphasis" => {
+                let (rate, pitch) = match child.attribute("level").unwrap_or("moderate") {
+                    "strong" => (0.85, 1.12),
+                    "moderate" => (0.92, 1.06),
+                    "reduced" => (1.1, 0.95),
+                    "none" => (1.0, 1.0),
+                    other => return Err(format!("Invalid emphasis level '{}'", other)),
+                };
+                let inner = Prosody { rate: prosody.rate * rate, pitch: prosody.pitch * pitch };
+                walk(child, inner, segments)?;
+            }
+            "prosody" => {
+                let mut inner = prosody;
+                if let Some(rate) = child.attribute("rate") {
+                    inner.rate *= prosody_rate(rate)?;
+                }
+                if let Some(pitch) = child.attribute("pitch") {
+                    inner.pitch *= prosody_pitch(pitch)?;
+                }
+                if let Some(volume) = child.attribute("volume") {
+                    validate_volume(volume)?;
+                }
+                walk(child, inner, segments)?;
+            }
+            "say-as" => {
+                let text = child.text().unwrap_or_default();
+                if child.children().any(|c| c.is_element()) {
+                    return Err("<say-as> may only contain text".to_string());
+                }
+                let spoken = say_as(child.attribute("interpret-as").unwrap_or_default(), text)?;
+                push_text(segments, &spoken, prosody);
+            }
+            "sub" => {
+                let alias = child
+                    .attribute("alias")
+                    .ok_or_else(|| "<sub> requires an alias attribute".to_string())?;
+                push_text(segments, alias, prosody);
+            }
+            other => return Err(format!("Unsupported SSML element <{}>", other)),
+        }
+    }
+    Ok(())
+}
+
+fn break_duration(node: roxmltree::Node) -> Result<u32, String> {
+    if let Some(time) = node.attribute("time") {
+        let time = time.trim();
+        let ms = if let Some(ms) = time.strip_suffix("ms") {
+            ms.parse::<f32>().ok()
+        } else if let Some(s) = time.strip_suffix('s') {
+            s.parse::<f32>().ok().map(|s| s * 1000.0)
+        } else {
+            None
+        };
+        return match ms {
+            Some(ms) if (0.0..=MAX_BREAK_MS as f32).contains(&ms) => Ok(ms as u32),
+            _ => Err(format!("Invalid break time '{}' (use e.g. 500ms or 1.5s, max 10s)", time)),
+        };
+    }
+
+    match node.attribute("strength").unwrap_or("medium") {
+        "none" => Ok(0),
+        "x-weak" => Ok(100),
+        "weak" => Ok(200),
+        "medium" => Ok(400),
+        "strong" => Ok(700),
+        "x-strong" => Ok(1200),
+        other => Err(format!("Invalid break strength '{}'", other)),
+    }
+}
+
+/// Parses "+20%", "-10%" (relative) or "120%" (absolute).
+fn percentage(value: &str) -> Option<f32> {
+    let number = value.strip_suffix('%')?;
+    let parsed: f32 = number.parse().ok()?;
+    Some(if number.starts_with(['+', '-']) { 1.0 + parsed / 100.0 } else { parsed / 100.0 })
+}
+
+fn prosody_rate(value: &str) -> Result<f32, String> {
+    let rate = match value {
+        "x-slow" => Some(0.6),
+        "slow" => Some(0.8),
+        "medium" | "default" => Some(1.0),
+        "fast" => Some(1.25),
+        "x-fast" => Some(1.5),
+        other => percentage(other),
+    };
+    rate.filter(|r| (0.25..=4.0).contains(r))
+        .ok_or_else(|| format!("Invalid prosody rate '{}'", value))
+}
+
+fn prosody_pitch(value: &str) -> Result<f32, String> {
+    let pitch = match value {
+        "x-low" => Some(0.7),
+        "low" => Some(0.85),
+        "medium" | "default" => Some(1.0),
+        "high" => Some(1.15),
+        "x-high" => Some(1.3),
+        other => match other.strip_suffix("st") {
+            // Semitones relative to the voice default.
+            Some(st) => st.parse::<f32>().ok().map(|st| 2f32.powf(st / 12.0)),
+            None => percentage(other),
+        },
+    };
+    pitch
+        .filter(|p| (0.5..=2.0).contains(p))
+        .ok_or_else(|| format!("Invalid prosody pitch '{}'", value))
+}
+
+fn validate_volume(value: &str) -> Result<(), String> {
+    let named = ["silent", "x-soft", "soft", "medium", "loud", "x-loud", "default"];
+    let decibels = value
+        .strip_suffix("dB")
+        .is_some_and(|db| db.parse::<f32>().is_ok());
+    if named.contains(&value) || decibels {
+        Ok(())
+    } else {
+        Err(format!("Invalid prosody volume '{}'", value))
+    }
+}
+
+fn say_as(interpret_as: &str, text: &str) -> Result<String, String> {
+    let text = text.trim();
+    let spaced = |filter: fn(&char) -> bool| {
+        text.chars()
+            .filter(filter)
+            .map(|c| c.to_uppercase().to_string())
+            .collect::<Vec<_>>()
+            .join(" ")
+    };
+    match interpret_as {
+        "characters" | "spell-out" => Ok(spaced(|c| !c.is_whitespace())),
+        "digits" => Ok(spaced(char::is_ascii_digit)),
+        "cardinal" | "number" | "ordinal" | "date" | "time" => Ok(text.to_string()),
+        // Digit groups are read one by one with a short pause between groups.
+        "telephone" => Ok(text
+            .split(|c: char| !c.is_ascii_digit() && c != '+')
+            .filter(|group| !group.is_empty())
+            .map(|group| group.chars().filter(char::is_ascii_digit).map(String::from).collect::<Vec<_>>().join(" "))
+            .collect::<Vec<_>>()
+            .join(", ")),
+        "" => Err("<say-as> requires an interpret-as attribute".to_string()),
+        other => Err(format!("Unsupported say-as interpret-as '{}'", other)),
+    }
+}
+
+/// Splits speech into provider-sized chunks of at most `max_chars` characters.
+///
+/// Breaks fall on sentence ends where possible, then on commas, then on spaces.
+/// Prosody is kept per piece and a segment's pause stays after its last piece.
+pub fn chunk(segments: &[SpeechSegment], max_chars: usize) -> Vec<Vec<SpeechSegment>> {
+    let mut pieces = Vec::new();
+    for segment in segments {
+        let parts: Vec<String> = split_sentences(&segment.text)
+            .into_iter()
+            .flat_map(|sentence| split_long(sentence, max_chars))
+            .collect();
+        let count = parts.len();
+        if count == 0 {
+            pieces.push(segment.clone());
+            continue;
+        }
+        for (i, text) in parts.into_iter().enumerate() {
+            pieces.push(SpeechSegment {
+                text,
+                rate: segment.rate,
+                pitch: segment.pitch,
+                pause_after_ms: if i + 1 == count { segment.pause_after_ms } else { 0 },
+            });
+        }
+    }
+
+    let mut chunks: Vec<Vec<SpeechSegment>> = Vec::new();
+    let mut current: Vec<SpeechSegment> = Vec::new();
+    let mut current_len = 0;
+    for piece in pieces {
+        let len = piece.text.chars().count() + 1;
+        if !current.is_empty() && current_len + len > max_chars {
+            chunks.push(std::mem::take(&mut current));
+            current_len = 0;
+        }
+        current_len += len;
+        current.push(piece);
+    }
+    if !current.is_empty() {
+        chunks.push(current);
+    }
+    chunks
+}
+
+fn split_sentences(text: &str) -> Vec<&str> {
+    let mut sentences = Vec::new();
+    let mut start = 0;
+    let mut chars = text.char_indices().peekable();
+    while let Some((i, c)) = chars.next() {
+        let boundary = match c {
+            '\n' => true,
+            '.' | '!' | '?' => chars.peek().is_none_or(|(_, next)| next.is_whitespace()),
+            _ => false,
+        };
+        if boundary {
+            let end = i + c.len_utf8();
+            sentences.push(text[start..end].trim());
+            start = end;
+        }
+    }
+    sentences.push(text[start..].trim());
+    sentences.retain(|s| !s.is_empty());
+    sentences
+}
+
+/// Breaks an over-long sentence at the last comma (or space) that fits.
+fn split_long(sentence: &str, max_chars: usize) -> Vec<String> {
+    let mut parts = Vec::new();
+    let mut rest = sentence.trim();
+    while rest.chars().count() > max_chars {
+        let limit = rest.char_indices().nth(max_chars).map_or(rest.len(), |(i, _)| i);
+        let head = &rest[..limit];
+        let cut = head
+            .rfind(", ")
+            .map(|i| i + 1)
+            .or_else(|| head.rfind(char::is_whitespace))
+            .filter(|&i| i > 0)
+            .unwrap_or(limit);
+        parts.push(rest[..cut].trim().to_string());
+        rest = rest[cut..].trim_start();
+    }
+    if !rest.is_empty() {
+        parts.push(rest.to_string());
+    }
+    parts
+}
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+    use crate::routes::voice::MAX_CHUNK_CHARS;
+
+    fn texts(segments: &[SpeechSegment]) -> Vec<(&str, u32)> {
+        segments.iter().map(|s| (s.text.as_str(), s.pause_after_ms)).collect()
+    }
+
+    fn close(a: f32, b: f32) -> bool {
+        (a - b).abs() < 1e-4
+    }
+
+    #[test]
+    fn parse_flattens_nested_prosody_and_breaks() {
+        let segments = parse(
+            r#"<speak>Hello <prosody rate="slow" pitch="+10%">slow <emphasis level="strong">very<break time="300ms"/></emphasis> done</prosody><break strength="strong"/>end</speak>"#,
+        )
+        .unwrap();
+        assert_eq!(texts(&segments), [("Hello", 0), ("slow", 0), ("very", 300), ("done", 700), ("end", 0)]);
+
+        let prosody: Vec<(f32, f32)> = segments.iter().map(|s| (s.rate, s.pitch)).collect();
+        let expected = [(1.0, 1.0), (0.8, 1.1), (0.8 * 0.85, 1.1 * 1.12), (0.8, 1.1), (1.0, 1.0)];
+        for ((rate, pitch), (want_rate, want_pitch)) in prosody.iter().zip(expected) {
+            assert!(close(*rate, want_rate) && close(*pitch, want_pitch), "{:?}", prosody);
+        }
+    }
+
+    #[test]
+    fn parse_merges_breaks_and_keeps_a_leading_pause() {
+        let segments = parse(r#"<speak><break time="1s"/>Hi<break time="8s"/><break time="5s"/><s>there</s></speak>"#).unwrap();
+        assert_eq!(texts(&segments), [("", 1000), ("Hi", MAX_BREAK_MS), ("there", 250)]);
+
+        let segments = parse(r#"<speak><p>One <sub alias="two">2</sub></p><say-as interpret-as="characters">ab c</say-as></speak>"#).unwrap();
+        assert_eq!(texts(&segments), [("One two", 500), ("A B C", 0)]);
+    }
+
+    #[test]
+    fn parse_rejects_invalid_documents() {
+        let cases = [
+            ("<speak>Hello", "Invalid SSML"),
+            ("<speak>a <b>c</speak>", "Invalid SSML"),
+            ("<p>Hello</p>", "<speak> root"),
+            (r#"<speak><audio src="x.mp3"/>Hi</speak>"#, "Unsupported SSML element <audio>"),
+            (r#"<speak>Hi<break time="20s"/></speak>"#, "Invalid break time"),
+            (r#"<speak>Hi<break time="fast"/></speak>"#, "Invalid break time"),
+            (r#"<speak><prosody rate="900%">Hi</prosody></speak>"#, "Invalid prosody rate"),
+            (r#"<speak><prosody pitch="+24st">Hi</prosody></speak>"#, "Invalid prosody pitch"),
+            (r#"<speak><emphasis level="loud">Hi</emphasis></speak>"#, "Invalid emphasis level"),
+            (r#"<speak><sub>WHO</sub></speak>"#, "requires an alias"),
+            (r#"<speak><break time="1s"/></speak>"#, "no text"),
+        ];
+        for (ssml, error) in cases {
+            let message = parse(ssml).unwrap_err();
+            assert!(message.contains(error), "{}: {}", ssml, message);
+        }
+    }
+
+    #[test]
+    fn plain_text_downgrade_turns_pauses_into_punctuation() {
+        let segment = |text: &str, pause_after_ms| SpeechSegment { text: text.to_string(), rate: 1.0, pitch: 1.0, pause_after_ms };
+        let segments = [segment("Hello", 300), segment("world", 700), segment("Done.", 250), segment("Stop.", 1000), segment("", 0)];
+        assert_eq!(to_plain_text(&segments), "Hello, world... Done. Stop. ...");
+        assert_eq!(to_plain_text(&[segment("", 1000), segment("Hi", 100)]), "Hi");
+    }
+
+    #[test]
+    fn sentences_split_at_terminal_punctuation_and_newlines() {
+        assert_eq!(
+            split_sentences("Hi there. Version 2.5 is out!\nNew line? yes...  "),
+            ["Hi there.", "Version 2.5 is out!", "New line?", "yes..."]
+        );
+        assert!(split_sentences("  \n ").is_empty());
+    }
+
+    #[test]
+    fn long_sentences_break_at_commas_then_spaces() {
+        assert_eq!(split_long("aaaa, bbbb cccc", 8), ["aaaa,", "bbbb", "cccc"]);
+        assert_eq!(split_long("abcdefghij", 4), ["abcd", "efgh", "ij"]);
+        assert_eq!(split_long("ééééé", 2), ["éé", "éé", "é"]);
+        assert_eq!(split_long("short", 8), ["short"]);
+    }
+
+    #[test]
+    fn chunks_fill_up_to_the_limit_at_sentence_ends() {
+        let sentence = "This sentence is one of many that fill a chunk.";
+        let per_chunk = MAX_CHUNK_CHARS / (sentence.chars().count() + 1);
+        let text = vec![sentence; per_chunk * 2 + 3].join(" ");
+
+        let chunks = chunk(&plain(&text), MAX_CHUNK_CHARS);
+        let sizes: Vec<usize> = chunks.iter().map(Vec::len).collect();
+        assert_eq!(sizes, [per_chunk, per_chunk, 3]);
+        for chunk in &chunks {
+            assert!(to_plain_text(chunk).chars().count() <= MAX_CHUNK_CHARS);
+        }
+        let rejoined: Vec<String> = chunks.iter().map(|c| to_plain_text(c)).collect();
+        assert_eq!(rejoined.join(" "), text);
+    }
+
+    #[test]
+    fn a_sentence_over_the_limit_is_split_and_keeps_its_pause() {
+        let text = "word ".repeat(1000);
+        let segments = [SpeechSegment { text: text.trim().to_string(), rate: 1.2, pitch: 1.0, pause_after_ms: 500 }];
+
+        let chunks = chunk(&segments, MAX_CHUNK_CHARS);
+        assert_eq!(chunks.len(), 2);
+        let first = &chunks[0][0];
+        assert!(first.text.chars().count() <= MAX_CHUNK_CHARS && first.text.ends_with("word"));
+        assert_eq!((first.pause_after_ms, chunks[1][0].pause_after_ms), (0, 500));
+        assert!(chunks.iter().flatten().all(|s| s.rate == 1.2));
+        assert_eq!(format!("{} {}", first.text, chunks[1][0].text), text.trim());
+    }
+}
@@ -1,27 +1,61 @@
+use crate::bot_actions;
+use crate::bot_stats::BotAnalytics;
 use crate::config::{Config, RateLimitConfig};
+use crate::fetch;
+use crate::gallery::{GalleryLimits, ImageGallery};
 use crate::rate_limiter::RateLimiter;
+use crate::transcripts::TranscriptStore;
+use crate::upstream::CircuitBreaker;
 use dashmap::DashMap;
 use std::sync::Arc;
+use std::time::Duration;
 
 #[derive(Clone)]
 pub struct AppState {
     pub config: Config,
     pub rate_limit_config: RateLimitConfig,
     pub http_client: reqwest::Client,
+    /// Third-party page fetches (SEO); does not follow redirects by itself and
+    /// only connects to public addresses unless private URLs are allowed.
+    pub page_client: reqwest::Client,
+    /// Bot action calls; same connection policy as `page_client`.
+    pub action_client: reqwest::Client,
     pub rate_limiter: RateLimiter,
     pub bots: Arc<DashMap<String, serde_json::Value>>,
+    pub transcripts: TranscriptStore,
+    pub bot_stats: BotAnalytics,
+    pub gallery: ImageGallery,
+    pub hf_breaker: CircuitBreaker,
+    pub openai_breaker: CircuitBreaker,
 }
 
 impl AppState {
     pub fn new(config: Config, rate_limit_config: RateLimitConfig) -> Self {
         let rate_limiter = RateLimiter::new(&rate_limit_config);
+        let transcripts = TranscriptStore::new(config.bot_transcript_retention_days);
+        let gallery = ImageGallery::open(
+            &config.image_gallery_dir,
+            GalleryLimits {
+                max_per_owner: config.image_gallery_max_per_client,
+                max_total_bytes: config.image_gallery_max_mb * 1024 * 1024,
+            },
+        );
+        let page_client = fetch::client(config.allow_private_urls);
+        let action_client = bot_actions::client(config.allow_private_urls);
 
         Self {
             config,
             rate_limit_config,
             http_client: reqwest::Client::new(),
+            page_client,
+            action_client,
             rate_limiter,
             bots: Arc::new(DashMap::new()),
+            transcripts,
+            bot_stats: BotAnalytics::default(),
+            gallery,
+            hf_breaker: CircuitBreaker::new("HuggingFace", 5, Duration::from_secs(60)),
+            openai_breaker: CircuitBreaker::new("OpenAI", 5, Duration::from_secs(30)),
         }
     }
 
@@ -29,3 +63,34 @@ impl AppState {
         self.config.demo_mode
     }
 }
+
+#[cfg(test)]
+impl AppState {
+    /// Demo-mode state for handler tests: a throwaway gallery directory,
+    /// private URLs allowed (test servers listen on loopback) and no limits
+    /// that a test could run into.
+    pub fn for_tests() -> Self {
+        let gallery_dir = std::env::temp_dir().join(format!("nexus-test-{:016x}", rand::random::<u64>()));
+        let config = Config {
+            openai_api_key: String::new(),
+            hf_api_token: String::new(),
+            port: 0,
+            domain: "localhost".to_string(),
+            demo_mode: true,
+            bot_transcript_retention_days: 30,
+            image_gallery_dir: gallery_dir.to_string_lossy().into_owned(),
+            image_gallery_max_per_client: 100,
+            image_gallery_max_mb: 1024,
+            allow_private_urls: true,
+        };
+        let rate_limit_config = RateLimitConfig {
+            global_daily_limit: usize::MAX,
+            tool_daily_limit: usize::MAX,
+            tool_minute_limit: usize::MAX,
+            error_ban_threshold: usize::MAX,
+            error_ban_duration_secs: 0,
+            cleanup_interval_secs: 300,
+        };
+        Self::new(config, rate_limit_config)
+    }
+}
@@ -0,0 +1,214 @@
+use serde::Serialize;
+
+use crate::json_resume::Section;
+use crate::keywords::{self, Keyword};
+
+/// Job-description keywords that are scored for coverage.
+pub const MAX_KEYWORDS: usize = 25;
+
+/// One bullet of an experience or projects section; `start..end` are its
+/// line indices in the Markdown (wrapped bullets span several lines).
+pub struct Bullet {
+    pub section: String,
+    pub start: usize,
+    pub end: usize,
+    pub text: String,
+}
+
+/// Bullets under `##` sections that hold achievements (experience, projects).
+pub fn bullets(markdown: &str) -> Vec<Bullet> {
+    let lines: Vec<&str> = markdown.lines().collect();
+    let mut bullets: Vec<Bullet> = Vec::new();
+    let mut section: Option<(Section, String)> = None;
+
+    let mut i = 0;
+    while i < lines.len() {
+        let line = lines[i].trim_end();
+        if let Some(title) = line.strip_prefix("## ") {
+            section = Some((Section::detect(title), title.trim().to_string()));
+        } else if let (Some(text), Some((Section::Experience | Section::Projects, title))) =
+            (line.strip_prefix("- ").or_else(|| line.strip_prefix("* ")), &section)
+        {
+            let mut text = text.trim().to_string();
+            let start = i;
+            // Indented lines continue the bullet.
+            while i + 1 < lines.len() && lines[i + 1].starts_with("  ") && !lines[i + 1].trim().is_empty() {
+                i += 1;
+                text.push(' ');
+                text.push_str(lines[i].trim());
+            }
+            bullets.push(Bullet { section: title.clone(), start, end: i + 1, text });
+        }
+        i += 1;
+    }
+    bullets
+}
+
+/// Phrases that start weak bullets, with a stronger opener. Only applied
+/// when a noun phrase follows, so the sentence stays grammatical.
+const WEAK_OPENERS: [(&str, &str); 6] = [
+    ("was responsible for ", "Owned "),
+    ("responsible for ", "Owned "),
+    ("worked on ", "Delivered "),
+    ("participated in ", "Contributed to "),
+    ("involved in ", "Contributed to "),
+    ("assisted with ", "Supported "),
+];
+
+/// Common alternative spellings; the job description's spelling wins.
+const SYNONYMS: [(&str, &str); 14] = [
+    ("k8s", "kubernetes"),
+    ("postgres", "postgresql"),
+    ("golang", "go"),
+    ("js", "javascript"),
+    ("ts", "typescript"),
+    ("reactjs", "react"),
+    ("react.js", "react"),
+    ("nodejs", "node.js"),
+    ("ml", "machine learning"),
+    ("gcp", "google cloud"),
+    ("amazon web services", "aws"),
+    ("ci/cd", "continuous integration"),
+    ("rest api", "restful api"),
+    ("scrum", "agile"),
+];
+
+#[derive(Serialize)]
+pub struct Change {
+    pub section: String,
+    pub before: String,
+    pub after: String,
+    pub reasons: Vec<String>,
+}
+
+/// Byte ranges of case-insensitive whole-word matches of `needle` in `text`.
+fn find_word(text: &str, needle: &str) -> Vec<(usize, usize)> {
+    let hay = text.to_ascii_lowercase();
+    let needle = needle.to_ascii_lowercase();
+    let is_word = |c: Option<char>| c.is_some_and(|c| c.is_alphanumeric() || c == '+' || c == '#');
+    let mut found = Vec::new();
+    let mut from = 0;
+    while let Some(at) = hay[from..].find(&needle) {
+        let (start, end) = (from + at, from + at + needle.len());
+        if !is_word(hay[..start].chars().next_back()) && !is_word(hay[end..].chars().next()) {
+            found.push((start, end));
+        }
+        from = end;
+    }
+    found
+}
+
+fn replace_ranges(text: &str, ranges: &[(usize, usize)], with: &str) -> String {
+    let mut out = String::with_capacity(text.len());
+    let mut last = 0;
+    for &(start, end) in ranges {
+        out.push_str(&text[last..start]);
+        out.push_str(with);
+        last = end;
+    }
+    out.push_str(&text[last..]);
+    out
+}
+
+/// Deterministic bullet rewrite: stronger openers, and the job description's
+/// spelling for terms the bullet already mentions (`k8s` → `Kubernetes`,
+/// `kafka` → `Kafka`). Nothing the candidate has not claimed is added.
+pub fn rewrite_locally(text: &str, keywords: &[Keyword]) -> (String, Vec<String>) {
+    let mut text = text.to_string();
+    let mut reasons = Vec::new();
+
+    let lower = text.to_lowercase();
+    for (weak, strong) in WEAK_OPENERS {
+        if let Some(rest) = lower.strip_prefix(weak) {
+            let next = rest.split_whitespace().next().unwrap_or_default();
+            if !next.ends_with("ing") {
+                text = format!("{}{}", strong, &text[weak.len()..]);
+                reasons.push(format!("stronger opener than \"{}\"", weak.trim()));
+            }
+            break;
+        }
+    }
+
+    for keyword in keywords {
+        let term = keyword.term.as_str();
+        let lower_term = term.to_lowercase();
+        // Alternative spellings of the keyword.
+        for (a, b) in SYNONYMS {
+            let variant = if lower_term == b { a } else if lower_term == a { b } else { continue };
+            let ranges = find_word(&text, variant);
+            if !ranges.is_empty() {
+                text = replace_ranges(&text, &ranges, term);
+                reasons.push(format!("uses the job's term \"{}\"", term));
+            }
+        }
+        // Same term, different capitalisation.
+        let ranges: Vec<(usize, usize)> =
+            find_word(&text, term).into_iter().filter(|&(s, e)| &text[s..e] != term).collect();
+        // Two-letter terms (`Go`) are too often ordinary words to recapitalise.
+        if !ranges.is_empty() && term.chars().any(char::is_uppercase) && term.chars().count() > 2 {
+            text = replace_ranges(&text, &ranges, term);
+            reasons.push(format!("matches the job's spelling \"{}\"", term));
+        }
+    }
+    (text, reasons)
+}
+
+/// Number of job keywords a bullet mentions.
+pub fn hits(text: &str, keywords: &[Keyword]) -> usize {
+    let tokens = keywords::normalized(text);
+    keywords.iter().filter(|k| keywords::contains(&tokens, &k.term)).count()
+}
+
+/// Writes rewritten bullets back into the Markdown and moves the bullets with
+/// the most keyword hits to the top of each list. Returns the Markdown and
+/// how many bullets moved.
+pub fn apply(markdown: &str, bullets: &[Bullet], rewritten: &[String], keywords: &[Keyword]) -> (String, usize) {
+    let lines: Vec<&str> = markdown.lines().collect();
+    let mut out: Vec<String> = Vec::with_capacity(lines.len());
+    let mut moved = 0;
+    let mut line = 0;
+    let mut b = 0;
+
+    while line < lines.len() {
+        if b < bullets.len() && bullets[b].start == line {
+            // A run of adjacent bullets forms one list.
+            let first = b;
+            while b + 1 < bullets.len() && bullets[b + 1].start == bullets[b].end {
+                b += 1;
+            }
+            let mut run: Vec<usize> = (first..=b).collect();
+            let original = run.clone();
+            run.sort_by_key(|&i| std::cmp::Reverse(hits(&rewritten[i], keywords)));
+            moved += run.iter().zip(&original).filter(|(a, b)| a != b).count();
+            out.extend(run.iter().map(|&i| format!("- {}", rewritten[i])));
+            line = bullets[b].end;
+            b += 1;
+        } else {
+            out.push(lines[line].to_string());
+            line += 1;
+        }
+    }
+
+    let mut md = out.join("\n");
+    md.push('\n');
+    (md, moved)
+}
+
+#[derive(Serialize)]
+pub struct KeywordCoverage {
+    pub term: String,
+    pub weight: f64,
+    pub matched_before: bool,
+    pub matched_after: bool,
+}
+
+/// Weighted share of `keywords` present in `markdown`, as a percentage,
+/// and which keywords matched.
+pub fn coverage(keywords: &[Keyword], markdown: &str) -> (f64, Vec<bool>) {
+    let tokens = keywords::normalized(markdown);
+    let matched: Vec<bool> = keywords.iter().map(|k| keywords::contains(&tokens, &k.term)).collect();
+    let total: f64 = keywords.iter().map(|k| k.weight).sum();
+    let hit: f64 = keywords.iter().zip(&matched).filter(|(_, m)| **m).map(|(k, _)| k.weight).sum();
+    let percent = if total > 0.0 { (hit / total * 1000.0).round() / 10.0 } else { 0.0 };
+    (percent, matched)
+}
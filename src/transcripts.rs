@@ -0,0 +1,229 @@
+use dashmap::DashMap;
+use serde::Serialize;
+use std::sync::Arc;
+
+/// Open sessions kept in memory; the least recently active one is dropped
+/// when a new session would exceed this.
+pub const MAX_SESSIONS: usize = 10_000;
+/// Messages kept per session; older ones are dropped first.
+pub const MAX_SESSION_MESSAGES: usize = 200;
+/// Most recent messages sent to the model with each turn.
+pub const MAX_PROMPT_MESSAGES: usize = 20;
+
+/// A single message in a bot conversation (visitor or bot side).
+#[derive(Clone, Serialize)]
+pub struct TranscriptMessage {
+    pub role: String,
+    pub content: String,
+    pub timestamp: String,
+}
+
+impl TranscriptMessage {
+    pub fn now(role: &str, content: &str) -> Self {
+        Self {
+            role: role.to_string(),
+            content: content.to_string(),
+            timestamp: chrono::Local::now().to_rfc3339(),
+        }
+    }
+}
+
+/// Per-visitor bot session. The same record serves as chat history and transcript.
+#[derive(Clone, Serialize)]
+pub struct BotSession {
+    pub session_id: String,
+    pub bot_id: String,
+    pub visitor: String,
+    pub created_at: String,
+    pub messages: Vec<TranscriptMessage>,
+    #[serde(skip)]
+    last_activity: i64,
+}
+
+#[derive(Clone)]
+pub struct TranscriptStore {
+    sessions: Arc<DashMap<String, BotSession>>,
+    retention_days: u32,
+}
+
+impl TranscriptStore {
+    pub fn new(retention_days: u32) -> Self {
+        Self {
+            sessions: Arc::new(DashMap::new()),
+            retention_days,
+        }
+    }
+
+    /// Returns the requested session if it belongs to this bot and visitor,
+    /// otherwise opens a new one, so a leaked session id cannot be used to
+    /// write into someone else's transcript.
+    pub fn resolve_session(&self, bot_id: &str, visitor: &str, requested: Option<&str>) -> String {
+        if let Some(id) = requested {
+            if let Some(session) = self.sessions.get(id) {
+                if session.bot_id == bot_id && session.visitor == visitor {
+                    return id.to_string();
+                }
+            }
+        }
+
+        if self.sessions.len() >= MAX_SESSIONS {
+            self.evict_idlest();
+        }
+
+        let now = chrono::Local::now();
+        let session_id = format!(
+            "sess_{}_{:08x}",
+            now.timestamp_millis(),
+            rand::random::<u32>()
+        );
+        self.sessions.insert(
+            session_id.clone(),
+            BotSession {
+                session_id: session_id.clone(),
+                bot_id: bot_id.to_string(),
+                visitor: visitor.to_string(),
+                created_at: now.to_rfc3339(),
+                messages: Vec::new(),
+                last_activity: now.timestamp(),
+            },
+        );
+        session_id
+    }
+
+    pub fn history(&self, session_id: &str) -> Vec<TranscriptMessage> {
+        self.sessions
+            .get(session_id)
+            .map(|s| s.messages.clone())
+            .unwrap_or_default()
+    }
+
+    /// The last [`MAX_PROMPT_MESSAGES`] messages of a session.
+    pub fn prompt_history(&self, session_id: &str) -> Vec<TranscriptMessage> {
+        let mut messages = self.history(session_id);
+        let excess = messages.len().saturating_sub(MAX_PROMPT_MESSAGES);
+        messages.drain(..excess);
+        messages
+    }
+
+    pub fn append(&self, session_id: &str, role: &str, content: &str) {
+        self.push(session_id, TranscriptMessage::now(role, content));
+    }
+
+    pub fn push(&self, session_id: &str, message: TranscriptMessage) {
+        if let Some(mut session) = self.sessions.get_mut(session_id) {
+            session.messages.push(message);
+            let excess = session.messages.len().saturating_sub(MAX_SESSION_MESSAGES);
+            session.messages.drain(..excess);
+            session.last_activity = chrono::Local::now().timestamp();
+        }
+    }
+
+    fn evict_idlest(&self) {
+        let idlest = self
+            .sessions
+            .iter()
+            .min_by_key(|s| s.last_activity)
+            .map(|s| s.key().clone());
+        if let Some(id) = idlest {
+            self.sessions.remove(&id);
+        }
+    }
+
+    /// All sessions of a bot, oldest first.
+    pub fn sessions_for_bot(&self, bot_id: &str) -> Vec<BotSession> {
+        let mut sessions: Vec<BotSession> = self
+            .sessions
+            .iter()
+            .filter(|s| s.bot_id == bot_id)
+            .map(|s| s.value().clone())
+            .collect();
+        sessions.sort_by(|a, b| a.created_at.cmp(&b.created_at));
+        sessions
+    }
+
+    /// CSV export with one row per message.
+    pub fn export_csv(&self, bot_id: &str) -> String {
+        let mut out = String::from("session_id,visitor,timestamp,role,content\n");
+        for session in self.sessions_for_bot(bot_id) {
+            for msg in &session.messages {
+                out.push_str(&format!(
+                    "{},{},{},{},{}\n",
+                    csv_field(&session.session_id),
+                    csv_field(&session.visitor),
+                    csv_field(&msg.timestamp),
+                    csv_field(&msg.role),
+                    csv_field(&msg.content),
+                ));
+            }
+        }
+        out
+    }
+
+    /// Drops sessions idle for longer than the retention period and returns how many were removed.
+    pub fn purge_expired(&self) -> usize {
+        let cutoff = chrono::Local::now().timestamp() - i64::from(self.retention_days) * 86400;
+        let before = self.sessions.len();
+        self.sessions.retain(|_, s| s.last_activity >= cutoff);
+        before - self.sessions.len()
+    }
+}
+
+fn csv_field(value: &str) -> String {
+    if value.contains(',') || value.contains('"') || value.contains('\n') || value.contains('\r') {
+        format!("\"{}\"", value.replace('"', "\"\""))
+    } else {
+        value.to_string()
+    }
+}
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+
+    #[test]
+    fn resumes_only_own_session() {
+        let store = TranscriptStore::new(30);
+        let id = store.resolve_session("bot_1", "10.0.0.1", None);
+        assert_eq!(store.resolve_session("bot_1", "10.0.0.1", Some(&id)), id);
+        assert_ne!(store.resolve_session("bot_1", "10.0.0.2", Some(&id)), id);
+        assert_ne!(store.resolve_session("bot_2", "10.0.0.1", Some(&id)), id);
+    }
+
+    #[test]
+    fn caps_messages_and_prompt_history() {
+        let store = TranscriptStore::new(30);
+        let id = store.resolve_session("bot_1", "10.0.0.1", None);
+        for i in 0..MAX_SESSION_MESSAGES + 5 {
+            store.append(&id, "user", &i.to_string());
+        }
+        let history = store.history(&id);
+        assert_eq!(history.len(), MAX_SESSION_MESSAGES);
+        assert_eq!(history[0].content, "5");
+
+        let prompt = store.prompt_history(&id);
+        assert_eq!(prompt.len(), MAX_PROMPT_MESSAGES);
+        assert_eq!(prompt.last().unwrap().content, (MAX_SESSION_MESSAGES + 4).to_string());
+    }
+
+    #[test]
+    fn evicts_idlest_session_at_capacity() {
+        let store = TranscriptStore::new(30);
+        let first = store.resolve_session("bot_1", "10.0.0.1", None);
+        store.sessions.get_mut(&first).unwrap().last_activity = 0;
+        for _ in 1..MAX_SESSIONS {
+            store.resolve_session("bot_1", "10.0.0.2", None);
+        }
+        assert_eq!(store.sessions.len(), MAX_SESSIONS);
+
+        store.resolve_session("bot_1", "10.0.0.3", None);
+        assert_eq!(store.sessions.len(), MAX_SESSIONS);
+        assert!(store.sessions.get(&first).is_none());
+    }
+
+    #[test]
+    fn csv_quotes_fields() {
+        assert_eq!(csv_field("plain"), "plain");
+        assert_eq!(csv_field("a,b"), "\"a,b\"");
+        assert_eq!(csv_field("say \"hi\""), "\"say \"\"hi\"\"\"");
+    }
+}
@@ -0,0 +1,212 @@
+use rand::Rng;
+use std::sync::{Arc, Mutex};
+use std::time::{Duration, Instant};
+
+use crate::error::AppError;
+
+/// Retry budget for a single upstream request.
+pub struct RetryPolicy {
+    pub max_attempts: u32,
+    /// Total time allowed across all attempts, including waits.
+    pub deadline: Duration,
+    pub base_backoff: Duration,
+    /// Cap on a single wait, also applied to HF's `estimated_time`.
+    pub max_wait: Duration,
+}
+
+impl RetryPolicy {
+    /// HuggingFace cold-starts models and answers 503 with an `estimated_time` meanwhile.
+    pub const HUGGINGFACE: RetryPolicy = RetryPolicy {
+        max_attempts: 6,
+        deadline: Duration::from_secs(90),
+        base_backoff: Duration::from_secs(1),
+        max_wait: Duration::from_secs(30),
+    };
+
+    pub const OPENAI: RetryPolicy = RetryPolicy {
+        max_attempts: 3,
+        deadline: Duration::from_secs(60),
+        base_backoff: Duration::from_millis(500),
+        max_wait: Duration::from_secs(5),
+    };
+}
+
+struct BreakerState {
+    consecutive_failures: u32,
+    open_until: Option<Instant>,
+    /// Start of the half-open trial request, if one is in flight.
+    trial_since: Option<Instant>,
+}
+
+/// Fails fast while an upstream is down.
+///
+/// After `failure_threshold` consecutive failed requests the breaker opens for
+/// `cooldown`. Once the cooldown passes a single trial request goes through
+/// while others keep failing fast; its failure re-opens the breaker and its
+/// success closes it. A trial that never reports back is replaced after
+/// another `cooldown`.
+#[derive(Clone)]
+pub struct CircuitBreaker {
+    name: &'static str,
+    state: Arc<Mutex<BreakerState>>,
+    failure_threshold: u32,
+    cooldown: Duration,
+}
+
+impl CircuitBreaker {
+    pub fn new(name: &'static str, failure_threshold: u32, cooldown: Duration) -> Self {
+        Self {
+            name,
+            state: Arc::new(Mutex::new(BreakerState {
+                consecutive_failures: 0,
+                open_until: None,
+                trial_since: None,
+            })),
+            failure_threshold,
+            cooldown,
+        }
+    }
+
+    pub fn check(&self) -> Result<(), AppError> {
+        let mut state = self.state.lock().unwrap();
+        let now = Instant::now();
+        match state.open_until {
+            Some(until) if now < until => Err(AppError::Unavailable(format!(
+                "{} is temporarily unavailable, retry in {}s",
+                self.name,
+                until.saturating_duration_since(now).as_secs() + 1
+            ))),
+            Some(_) => {
+                if state.trial_since.is_some_and(|since| now < since + self.cooldown) {
+                    return Err(AppError::Unavailable(format!(
+                        "{} is recovering, retry in a few seconds",
+                        self.name
+                    )));
+                }
+                state.trial_since = Some(now);
+                Ok(())
+            }
+            None => Ok(()),
+        }
+    }
+
+    pub fn record_success(&self) {
+        let mut state = self.state.lock().unwrap();
+        state.consecutive_failures = 0;
+        state.open_until = None;
+        state.trial_since = None;
+    }
+
+    pub fn record_failure(&self) {
+        let mut state = self.state.lock().unwrap();
+        state.consecutive_failures += 1;
+        if state.consecutive_failures >= self.failure_threshold {
+            state.open_until = Some(Instant::now() + self.cooldown);
+            state.trial_since = None;
+            tracing::warn!("Circuit breaker for {} opened", self.name);
+        }
+    }
+}
+
+/// Sends the request built by `build`, retrying 429/5xx responses and network errors
+/// with exponential backoff until the policy runs out.
+///
+/// A 503 body carrying `estimated_time` (HuggingFace model loading) sets the wait.
+/// Other 4xx responses are returned to the caller untouched.
+pub async fn send_with_retry<F>(
+    breaker: &CircuitBreaker,
+    policy: &RetryPolicy,
+    build: F,
+) -> Result<reqwest::Response, AppError>
+where
+    F: Fn() -> reqwest::RequestBuilder,
+{
+    breaker.check()?;
+
+    let deadline = Instant::now() + policy.deadline;
+    let mut attempt = 0;
+
+    loop {
+        attempt += 1;
+        let remaining = deadline.saturating_duration_since(Instant::now());
+        let backoff = policy.base_backoff * 2u32.pow(attempt - 1)
+            + Duration::from_millis(rand::thread_rng().gen_range(0..250));
+
+        let (wait, last_error) = match build().timeout(remaining).send().await {
+            Ok(res) => {
+                let status = res.status();
+                if status.is_success() || (status.is_client_error() && status.as_u16() != 429) {
+                    breaker.record_success();
+                    return Ok(res);
+                }
+                let body = res.text().await.unwrap_or_default();
+                let wait = if status.as_u16() == 503 {
+                    estimated_time(&body, policy.max_wait).unwrap_or(backoff)
+                } else {
+                    backoff
+                };
+                (wait, format!("{} returned {}", breaker.name, status))
+            }
+            Err(e) => (backoff, format!("{} request failed: {}", breaker.name, e)),
+        };
+
+        let wait = wait.min(policy.max_wait);
+        if attempt >= policy.max_attempts || Instant::now() + wait >= deadline {
+            breaker.record_failure();
+            return Err(AppError::Unavailable(last_error));
+        }
+
+        tracing::warn!("{} (attempt {}), retrying in {:?}", last_error, attempt, wait);
+        tokio::time::sleep(wait).await;
+    }
+}
+
+/// Reads `{"error": "... is currently loading", "estimated_time": 20.5}`,
+/// capped at `max` before it becomes a `Duration` (huge values would panic).
+fn estimated_time(body: &str, max: Duration) -> Option<Duration> {
+    serde_json::from_str::<serde_json::Value>(body)
+        .ok()?
+        .get("estimated_time")?
+        .as_f64()
+        .filter(|secs| secs.is_finite() && *secs > 0.0)
+        .map(|secs| Duration::from_secs_f64(secs.min(max.as_secs_f64())))
+}
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+
+    #[test]
+    fn estimated_time_is_capped() {
+        let max = Duration::from_secs(30);
+        assert_eq!(
+            estimated_time(r#"{"estimated_time": 20.5}"#, max),
+            Some(Duration::from_millis(20500))
+        );
+        assert_eq!(estimated_time(r#"{"estimated_time": 1e300}"#, max), Some(max));
+        assert_eq!(estimated_time(r#"{"estimated_time": -1}"#, max), None);
+        assert_eq!(estimated_time("loading", max), None);
+    }
+
+    #[test]
+    fn half_open_breaker_admits_one_trial() {
+        let breaker = CircuitBreaker::new("Test", 2, Duration::from_millis(50));
+        breaker.record_failure();
+        assert!(breaker.check().is_ok());
+        breaker.record_failure();
+        assert!(breaker.check().is_err());
+
+        std::thread::sleep(Duration::from_millis(60));
+        assert!(breaker.check().is_ok());
+        assert!(breaker.check().is_err(), "only one trial while half-open");
+
+        // A failed trial re-opens the breaker, a successful one closes it.
+        breaker.record_failure();
+        assert!(breaker.check().is_err());
+        std::thread::sleep(Duration::from_millis(60));
+        assert!(breaker.check().is_ok());
+        breaker.record_success();
+        assert!(breaker.check().is_ok());
+        assert!(breaker.check().is_ok());
+    }
+}